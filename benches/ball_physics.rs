@@ -0,0 +1,28 @@
+//! How long a step of the balls physics takes on the cpu, without a window or uploading anything.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use michaels_screensaver::BallPhysics;
+use std::time::Duration;
+
+fn ball_physics(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ball_physics");
+    for ball_count in [10_000, 50_000] {
+        //the balls still need a gpu to be set up, machines without one (like most ci runners) skip the benchmark
+        let mut balls = match pollster::block_on(BallPhysics::new(ball_count)) {
+            Ok(balls) => balls,
+            Err(e) => {
+                eprintln!("Skipping the ball physics benchmark: {}", e);
+                break;
+            }
+        };
+        group.bench_with_input(
+            BenchmarkId::from_parameter(ball_count),
+            &ball_count,
+            |b, _| b.iter(|| balls.step(Duration::from_secs_f32(1.0 / 60.0))),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, ball_physics);
+criterion_main!(benches);
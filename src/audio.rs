@@ -0,0 +1,144 @@
+use crate::particle::{Axis, ParticleEvent, ParticleSystem};
+use cgmath::InnerSpace;
+use rodio::{OutputStream, OutputStreamHandle, Sink, Source};
+use std::time::Duration;
+
+/// how many sounds can play at once, a pile of balls would otherwise start thousands of them
+const MAX_VOICES: usize = 8;
+const SAMPLE_RATE: u32 = 44100;
+const CLICK_LENGTH: Duration = Duration::from_millis(80);
+/// the pitch of the softest hit, harder ones go up to 4 times higher
+const BASE_FREQUENCY: f32 = 220.0;
+
+/// plays a click for the hardest hits of every frame, higher and louder the faster the balls hit
+pub struct BallSounds {
+    //the sound stops once the stream is dropped
+    _stream: OutputStream,
+    handle: OutputStreamHandle,
+    voices: Vec<Sink>,
+    //the hits since the last `play`, as how hard they were relative to the ball speed
+    pending: Vec<f32>,
+}
+
+impl BallSounds {
+    /// `None` if there is no audio device
+    pub fn new() -> Option<Self> {
+        match OutputStream::try_default() {
+            Ok((stream, handle)) => Some(Self {
+                _stream: stream,
+                handle,
+                voices: Vec::new(),
+                pending: Vec::new(),
+            }),
+            Err(e) => {
+                log::warn!(
+                    "Failed to open the audio device, there won't be any sound: {}",
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    /// remembers how hard the particles of `events` hit, `play` plays the hardest ones
+    pub fn queue(
+        &mut self,
+        events: &[ParticleEvent],
+        particle_system: &ParticleSystem,
+        ball_speed: f32,
+    ) {
+        let data = &particle_system.particle_data;
+        let speed = ball_speed.max(f32::EPSILON);
+        for event in events {
+            let impact = match *event {
+                ParticleEvent::WallBounce { index, axis } => match (data.get(index), axis) {
+                    (Some(data), Axis::X) => data.velocity.x.abs(),
+                    (Some(data), Axis::Y) => data.velocity.y.abs(),
+                    (Some(data), Axis::Z) => data.velocity.z.abs(),
+                    (None, _) => continue,
+                },
+                ParticleEvent::Collision { a, b } => match (data.get(a), data.get(b)) {
+                    (Some(a), Some(b)) => (a.velocity - b.velocity).magnitude(),
+                    _ => continue,
+                },
+            };
+            self.pending.push(impact / speed);
+        }
+        //only the hardest hits get played anyway
+        if self.pending.len() > MAX_VOICES * 4 {
+            self.keep_hardest();
+        }
+    }
+
+    /// plays the hardest hits since the last call, as many as there are free voices
+    pub fn play(&mut self) {
+        self.voices.retain(|voice| !voice.empty());
+        self.keep_hardest();
+        let free = MAX_VOICES - self.voices.len();
+        for impact in self.pending.drain(..).take(free) {
+            let Ok(voice) = Sink::try_new(&self.handle) else {
+                break;
+            };
+            voice.append(Click::new(impact));
+            self.voices.push(voice);
+        }
+    }
+
+    fn keep_hardest(&mut self) {
+        self.pending.sort_by(|a, b| b.total_cmp(a));
+        self.pending.truncate(MAX_VOICES);
+    }
+}
+
+/// a short sine wave that dies out quickly
+struct Click {
+    frequency: f32,
+    volume: f32,
+    sample: u32,
+    length: u32,
+}
+
+impl Click {
+    /// `impact` is 1 for a hit at the ball speed
+    fn new(impact: f32) -> Self {
+        let impact = impact.clamp(0.0, 3.0);
+        Self {
+            frequency: BASE_FREQUENCY * (1.0 + impact),
+            volume: 0.05 + 0.1 * impact,
+            sample: 0,
+            length: (CLICK_LENGTH.as_secs_f32() * SAMPLE_RATE as f32) as u32,
+        }
+    }
+}
+
+impl Iterator for Click {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.sample >= self.length {
+            return None;
+        }
+        let t = self.sample as f32 / SAMPLE_RATE as f32;
+        let decay = (-t * 60.0).exp();
+        self.sample += 1;
+        Some((t * self.frequency * std::f32::consts::TAU).sin() * decay * self.volume)
+    }
+}
+
+impl Source for Click {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(CLICK_LENGTH)
+    }
+}
@@ -0,0 +1,349 @@
+use crate::shaders::ShaderType;
+use crate::texture;
+use crate::texture::TextureQuality;
+use crate::util::color::{color_from_hex, lerp_color, ColorSpace};
+use wgpu::util::DeviceExt;
+
+#[derive(Debug, Clone, PartialEq, Copy)]
+pub enum BackgroundFit {
+    /// fills the whole screen, cutting off the parts of the image that don't fit
+    Cover,
+    /// shows the whole image, leaving bars at the sides
+    Contain,
+    /// fills the whole screen, ignoring the image's aspect ratio
+    Stretch,
+}
+
+impl ToString for BackgroundFit {
+    fn to_string(&self) -> String {
+        match self {
+            BackgroundFit::Cover => "cover".to_string(),
+            BackgroundFit::Contain => "contain".to_string(),
+            BackgroundFit::Stretch => "stretch".to_string(),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct BackgroundUniform {
+    uv_offset: [f32; 2],
+    uv_scale: [f32; 2],
+}
+
+impl BackgroundUniform {
+    /// maps the screen onto the image, so that it keeps its aspect ratio (unless stretched)
+    fn new(fit: BackgroundFit, image_ratio: f32, surface_ratio: f32) -> Self {
+        //how much wider the image is than the screen
+        let relative_width = image_ratio / surface_ratio;
+        let uv_scale = match fit {
+            BackgroundFit::Stretch => [1.0, 1.0],
+            BackgroundFit::Cover => {
+                if relative_width > 1.0 {
+                    [1.0 / relative_width, 1.0]
+                } else {
+                    [1.0, relative_width]
+                }
+            }
+            BackgroundFit::Contain => {
+                if relative_width > 1.0 {
+                    [1.0, relative_width]
+                } else {
+                    [1.0 / relative_width, 1.0]
+                }
+            }
+        };
+        Self {
+            //center the image
+            uv_offset: [(1.0 - uv_scale[0]) / 2.0, (1.0 - uv_scale[1]) / 2.0],
+            uv_scale,
+        }
+    }
+}
+
+/// how many rows the background gradient is made of
+const GRADIENT_STEPS: u32 = 256;
+
+/// an image drawn over the whole screen, behind the screensaver
+pub(crate) struct Background {
+    image_ratio: f32,
+    fit: BackgroundFit,
+    uniform_buffer: wgpu::Buffer,
+    texture_bind_group: wgpu::BindGroup,
+    uniform_bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl Background {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bytes: &[u8],
+        fit: BackgroundFit,
+        quality: TextureQuality,
+        surface_ratio: f32,
+        texture_bind_group_layout: &wgpu::BindGroupLayout,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        sample_count: u32,
+    ) -> anyhow::Result<Self> {
+        let mut img = quality.downsample(image::load_from_memory(bytes)?);
+        //big photos can be larger than what the gpu supports (especially with webgl)
+        let max_size = device.limits().max_texture_dimension_2d;
+        if img.width() > max_size || img.height() > max_size {
+            img = img.resize(max_size, max_size, image::imageops::FilterType::Triangle);
+        }
+        Self::from_image(
+            device,
+            queue,
+            &img,
+            fit,
+            surface_ratio,
+            texture_bind_group_layout,
+            color_format,
+            depth_format,
+            sample_count,
+        )
+    }
+
+    /// a vertical gradient from `top` to `bottom`, stretched over the whole screen
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn gradient(
+        top: egui::Color32,
+        bottom: egui::Color32,
+        space: ColorSpace,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        texture_bind_group_layout: &wgpu::BindGroupLayout,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        sample_count: u32,
+    ) -> anyhow::Result<Self> {
+        let top = color_from_hex(top.to_hex())?;
+        let bottom = color_from_hex(bottom.to_hex())?;
+        let img = image::RgbaImage::from_fn(1, GRADIENT_STEPS, |_, y| {
+            let color = lerp_color(top, bottom, y as f64 / (GRADIENT_STEPS - 1) as f64, space);
+            image::Rgba([
+                (color.r * 255.0).round() as u8,
+                (color.g * 255.0).round() as u8,
+                (color.b * 255.0).round() as u8,
+                (color.a * 255.0).round() as u8,
+            ])
+        });
+        Self::from_image(
+            device,
+            queue,
+            &image::DynamicImage::ImageRgba8(img),
+            BackgroundFit::Stretch,
+            1.0,
+            texture_bind_group_layout,
+            color_format,
+            depth_format,
+            sample_count,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn from_image(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        img: &image::DynamicImage,
+        fit: BackgroundFit,
+        surface_ratio: f32,
+        texture_bind_group_layout: &wgpu::BindGroupLayout,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        sample_count: u32,
+    ) -> anyhow::Result<Self> {
+        let image_ratio = img.width() as f32 / img.height() as f32;
+        let texture = texture::Texture::from_image(device, queue, img, Some("background"))?;
+
+        let texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: texture_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&texture.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&texture.sampler),
+                },
+            ],
+            label: Some("background_texture_bind_group"),
+        });
+
+        let uniform_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("background_uniform_bind_group_layout"),
+            });
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Background Buffer"),
+            contents: bytemuck::cast_slice(&[BackgroundUniform::new(
+                fit,
+                image_ratio,
+                surface_ratio,
+            )]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &uniform_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+            label: Some("background_uniform_bind_group"),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Background Pipeline Layout"),
+            bind_group_layouts: &[texture_bind_group_layout, &uniform_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Background Shader"),
+            source: ShaderType::BackgroundShader.get_source(),
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Background Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Option::from("vs_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Option::from("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                cull_mode: None,
+                ..Default::default()
+            },
+            //the background is drawn first and must never hide anything
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        Ok(Self {
+            image_ratio,
+            fit,
+            uniform_buffer,
+            texture_bind_group,
+            uniform_bind_group,
+            pipeline,
+        })
+    }
+
+    /// loads the image from the config. on failure the error is logged and there is no background
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn load(
+        source: &str,
+        fit: BackgroundFit,
+        quality: TextureQuality,
+        surface_ratio: f32,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        texture_bind_group_layout: &wgpu::BindGroupLayout,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        sample_count: u32,
+    ) -> Option<Self> {
+        if source.is_empty() {
+            return None;
+        }
+        let background = match load_image_bytes(source).await {
+            Ok(bytes) => Self::new(
+                device,
+                queue,
+                &bytes,
+                fit,
+                quality,
+                surface_ratio,
+                texture_bind_group_layout,
+                color_format,
+                depth_format,
+                sample_count,
+            ),
+            Err(e) => Err(e),
+        };
+        match background {
+            Ok(background) => Some(background),
+            Err(e) => {
+                log::error!("Failed to load the background image \"{}\": {}", source, e);
+                None
+            }
+        }
+    }
+
+    pub(crate) fn fit(&self) -> BackgroundFit {
+        self.fit
+    }
+
+    /// recalculates the texture coordinates, should be called when the window is resized or the fit mode changes
+    pub(crate) fn update_fit(
+        &mut self,
+        queue: &wgpu::Queue,
+        fit: BackgroundFit,
+        surface_ratio: f32,
+    ) {
+        self.fit = fit;
+        queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[BackgroundUniform::new(fit, self.image_ratio, surface_ratio)]),
+        );
+    }
+
+    pub(crate) fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.texture_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.uniform_bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+/// reads the background image from a file, or on the web, downloads it
+pub(crate) async fn load_image_bytes(source: &str) -> anyhow::Result<Vec<u8>> {
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            Ok(reqwest::get(source).await?.bytes().await?.to_vec())
+        } else {
+            Ok(std::fs::read(source)?)
+        }
+    }
+}
@@ -0,0 +1,857 @@
+//! the layout of the config file. `Configurator` keeps its settings flat, this mirrors the
+//! `[general]`/`[snow]`/`[balls]`/... tables so the whole config is read and written by serde.
+//! the conversions list every field, so a setting that is added to one side but not the other
+//! doesn't compile
+
+use crate::background::BackgroundFit;
+use crate::configurator::Configurator;
+use crate::particle::ResizeBehavior;
+use crate::screensaver::{
+    BallColorMode, BallRenderMode, ClockFormat, MouseMode, PlasmaPalette, ScreenSaverType,
+};
+use crate::texture::TextureQuality;
+use crate::util::color::ColorSpace;
+use crate::util::model::DDDModel;
+use crate::{Backend, PowerPreference, PresentMode, Projection};
+use serde::{Deserialize, Serialize};
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ConfigFile {
+    #[serde(with = "config_name")]
+    screensaver: ScreenSaverType,
+    fullscreen: bool,
+    #[serde(with = "config_name")]
+    present_mode: PresentMode,
+    #[serde(with = "config_name")]
+    backend: Backend,
+    #[serde(with = "config_name")]
+    power_preference: PowerPreference,
+    //-1 means the primary monitor
+    #[serde(with = "negative_is_none")]
+    monitor_index: Option<usize>,
+    general: General,
+    snow: Snow,
+    balls: Balls,
+    #[serde(rename = "3d_model")]
+    ddd_model: Model,
+    clock: Clock,
+    harmonograph: Harmonograph,
+    packing: Packing,
+    comet: Comet,
+    starfield: Starfield,
+    matrix: Matrix,
+    fireworks: Fireworks,
+    boids: Boids,
+    plasma: Plasma,
+    /// not written back, the `[[monitor]]` sections are left in the file as they are
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(rename = "monitor", default, skip_serializing)]
+    monitor_overrides: Vec<toml::Table>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct General {
+    fixed_timestep: f32,
+    time_scale: f32,
+    motion_smoothing: bool,
+    freeze_age: bool,
+    opacity: f32,
+    adaptive_count: bool,
+    adaptive_target_fps: f32,
+    adaptive_min_count: usize,
+    adaptive_max_count: usize,
+    //0 means no limit
+    #[serde(with = "zero_is_none")]
+    max_fps: Option<u32>,
+    //0 means no trails
+    #[serde(with = "zero_is_none")]
+    trail_fade: Option<f32>,
+    //0 means a different scene every time
+    #[serde(with = "zero_is_none")]
+    seed: Option<u64>,
+    #[serde(with = "config_name")]
+    color_interpolation: ColorSpace,
+    //empty means the screensaver's own color
+    #[serde(with = "optional_hex_color")]
+    background_color: Option<egui::Color32>,
+    //empty means a solid color
+    #[serde(with = "optional_hex_color")]
+    background_gradient: Option<egui::Color32>,
+    background_image: String,
+    #[serde(with = "config_name")]
+    background_fit: BackgroundFit,
+    #[serde(with = "config_name")]
+    texture_quality: TextureQuality,
+    msaa: u32,
+    #[serde(with = "config_name")]
+    resize_behavior: ResizeBehavior,
+    windows_preview: bool,
+    dismiss_mouse_threshold: f32,
+    free_camera: bool,
+    #[serde(with = "config_name")]
+    projection: Projection,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Snow {
+    snowflake_count: usize,
+    depth: f32,
+    z_drift: f32,
+    buildup_secs: f32,
+    wind_strength: f32,
+    wind_variability: f32,
+    max_accumulation: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Balls {
+    count: usize,
+    speed: f32,
+    size: f32,
+    //0 makes every ball the same size
+    #[serde(with = "zero_is_none")]
+    size_max: Option<f32>,
+    #[serde(with = "config_name")]
+    color_mode: BallColorMode,
+    #[serde(with = "hex_color")]
+    color: egui::Color32,
+    hue_min: f32,
+    hue_max: f32,
+    saturation: f32,
+    value: f32,
+    show_density: bool,
+    target_display_density: f64,
+    density_power: f32,
+    region_size: f32,
+    correct_ball_velocity: bool,
+    gravity: f32,
+    restitution: f32,
+    friction: f32,
+    edge_softness: f32,
+    #[serde(with = "config_name")]
+    render_mode: BallRenderMode,
+    shadow: bool,
+    shadow_offset: f32,
+    debug_velocities: bool,
+    gpu_physics: bool,
+    sound: bool,
+    auto_retoss: bool,
+    retoss_energy: f32,
+    retoss_delay: f32,
+    #[serde(with = "config_name")]
+    mouse_mode: MouseMode,
+    brush_size: f32,
+    //-1 follows the slowest and fastest ball
+    #[serde(with = "negative_f32_is_none")]
+    temperature_min_speed: Option<f32>,
+    #[serde(with = "negative_f32_is_none")]
+    temperature_max_speed: Option<f32>,
+    infection_restart: bool,
+    reinfection_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Model {
+    #[serde(with = "config_name")]
+    model: DDDModel,
+    model_scale: f32,
+    model_count: usize,
+    trail_count: usize,
+    spin_speed: f32,
+    bounce_speed: f32,
+    bounce_height: f32,
+    bounce_floor: f32,
+    orbit_camera: bool,
+    orbit_speed: f32,
+    orbit_radius: f32,
+    orbit_height: f32,
+    fov: f32,
+    znear: f32,
+    zfar: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Clock {
+    #[serde(with = "config_name")]
+    format: ClockFormat,
+    #[serde(with = "hex_color")]
+    color: egui::Color32,
+    font_size: f32,
+    show_seconds: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Harmonograph {
+    frequency_x: f32,
+    frequency_y: f32,
+    phase_x: f32,
+    phase_y: f32,
+    damping: f32,
+    speed: f32,
+    trail_length: usize,
+    line_width: f32,
+    #[serde(with = "hex_color")]
+    color: egui::Color32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Packing {
+    max_circles: usize,
+    growth_rate: f32,
+    palette: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Comet {
+    count: usize,
+    speed: f32,
+    tail_length: f32,
+    #[serde(with = "hex_color")]
+    color: egui::Color32,
+    glow: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Starfield {
+    count: usize,
+    speed: f32,
+    streak_length: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Matrix {
+    column_density: f32,
+    fall_speed: f32,
+    #[serde(with = "hex_color")]
+    glyph_color: egui::Color32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Fireworks {
+    launch_rate: f32,
+    gravity: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Boids {
+    count: usize,
+    separation: f32,
+    alignment: f32,
+    cohesion: f32,
+    max_speed: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Plasma {
+    #[serde(with = "config_name")]
+    palette: PlasmaPalette,
+    speed: f32,
+}
+
+impl From<ConfigFile> for Configurator {
+    fn from(file: ConfigFile) -> Self {
+        let ConfigFile {
+            screensaver,
+            fullscreen,
+            present_mode,
+            backend,
+            power_preference,
+            monitor_index,
+            general,
+            snow,
+            balls,
+            ddd_model,
+            clock,
+            harmonograph,
+            packing,
+            comet,
+            starfield,
+            matrix,
+            fireworks,
+            boids,
+            plasma,
+            #[cfg(not(target_arch = "wasm32"))]
+            monitor_overrides,
+        } = file;
+        Self {
+            screensaver,
+            fullscreen,
+            present_mode,
+            backend,
+            power_preference,
+            monitor_index,
+            //General
+            fixed_timestep: general.fixed_timestep,
+            time_scale: general.time_scale,
+            motion_smoothing: general.motion_smoothing,
+            freeze_age: general.freeze_age,
+            opacity: general.opacity,
+            adaptive_count: general.adaptive_count,
+            adaptive_target_fps: general.adaptive_target_fps,
+            adaptive_min_count: general.adaptive_min_count,
+            adaptive_max_count: general.adaptive_max_count,
+            max_fps: general.max_fps,
+            trail_fade: general.trail_fade,
+            seed: general.seed,
+            color_interpolation: general.color_interpolation,
+            background_color: general.background_color,
+            background_gradient: general.background_gradient,
+            background_image: general.background_image,
+            background_fit: general.background_fit,
+            texture_quality: general.texture_quality,
+            msaa: general.msaa,
+            resize_behavior: general.resize_behavior,
+            windows_preview: general.windows_preview,
+            dismiss_mouse_threshold: general.dismiss_mouse_threshold,
+            free_camera: general.free_camera,
+            projection: general.projection,
+            //Snow
+            snowflake_count: snow.snowflake_count,
+            snow_depth: snow.depth,
+            snow_z_drift: snow.z_drift,
+            snow_buildup_secs: snow.buildup_secs,
+            snow_wind_strength: snow.wind_strength,
+            snow_wind_variability: snow.wind_variability,
+            snow_max_accumulation: snow.max_accumulation,
+            //Balls
+            ball_count: balls.count,
+            ball_speed: balls.speed,
+            ball_size: balls.size,
+            ball_size_max: balls.size_max,
+            color_mode: balls.color_mode,
+            color: balls.color,
+            hue_min: balls.hue_min,
+            hue_max: balls.hue_max,
+            saturation: balls.saturation,
+            value: balls.value,
+            show_density: balls.show_density,
+            target_display_density: balls.target_display_density,
+            density_power: balls.density_power,
+            region_size: balls.region_size,
+            correct_ball_velocity: balls.correct_ball_velocity,
+            gravity: balls.gravity,
+            restitution: balls.restitution,
+            friction: balls.friction,
+            edge_softness: balls.edge_softness,
+            ball_render_mode: balls.render_mode,
+            shadow: balls.shadow,
+            shadow_offset: balls.shadow_offset,
+            debug_velocities: balls.debug_velocities,
+            gpu_physics: balls.gpu_physics,
+            sound: balls.sound,
+            auto_retoss: balls.auto_retoss,
+            retoss_energy: balls.retoss_energy,
+            retoss_delay: balls.retoss_delay,
+            mouse_mode: balls.mouse_mode,
+            brush_size: balls.brush_size,
+            temperature_min_speed: balls.temperature_min_speed,
+            temperature_max_speed: balls.temperature_max_speed,
+            infection_restart: balls.infection_restart,
+            reinfection_count: balls.reinfection_count,
+            //3D Model
+            ddd_model: ddd_model.model,
+            model_scale: ddd_model.model_scale,
+            model_count: ddd_model.model_count,
+            model_trail_count: ddd_model.trail_count,
+            spin_speed: ddd_model.spin_speed,
+            bounce_speed: ddd_model.bounce_speed,
+            bounce_height: ddd_model.bounce_height,
+            bounce_floor: ddd_model.bounce_floor,
+            orbit_camera: ddd_model.orbit_camera,
+            orbit_speed: ddd_model.orbit_speed,
+            orbit_radius: ddd_model.orbit_radius,
+            orbit_height: ddd_model.orbit_height,
+            fov: ddd_model.fov,
+            znear: ddd_model.znear,
+            zfar: ddd_model.zfar,
+            //Clock
+            clock_format: clock.format,
+            clock_color: clock.color,
+            clock_font_size: clock.font_size,
+            clock_show_seconds: clock.show_seconds,
+            //Harmonograph
+            harmonograph_frequency_x: harmonograph.frequency_x,
+            harmonograph_frequency_y: harmonograph.frequency_y,
+            harmonograph_phase_x: harmonograph.phase_x,
+            harmonograph_phase_y: harmonograph.phase_y,
+            harmonograph_damping: harmonograph.damping,
+            harmonograph_speed: harmonograph.speed,
+            harmonograph_trail_length: harmonograph.trail_length,
+            harmonograph_line_width: harmonograph.line_width,
+            harmonograph_color: harmonograph.color,
+            //Circle Packing
+            packing_max_circles: packing.max_circles,
+            packing_growth_rate: packing.growth_rate,
+            packing_palette: packing.palette,
+            //Comet
+            comet_count: comet.count,
+            comet_speed: comet.speed,
+            comet_tail_length: comet.tail_length,
+            comet_color: comet.color,
+            comet_glow: comet.glow,
+            //Starfield
+            star_count: starfield.count,
+            star_speed: starfield.speed,
+            warp_streak_length: starfield.streak_length,
+            //Matrix
+            column_density: matrix.column_density,
+            fall_speed: matrix.fall_speed,
+            glyph_color: matrix.glyph_color,
+            //Fireworks
+            fireworks_launch_rate: fireworks.launch_rate,
+            fireworks_gravity: fireworks.gravity,
+            //Boids
+            boid_count: boids.count,
+            boid_separation: boids.separation,
+            boid_alignment: boids.alignment,
+            boid_cohesion: boids.cohesion,
+            boid_max_speed: boids.max_speed,
+            //Plasma
+            plasma_palette: plasma.palette,
+            plasma_speed: plasma.speed,
+            //Per Monitor
+            #[cfg(not(target_arch = "wasm32"))]
+            monitor_overrides,
+            //Internal Use - Not Configurable
+            preview_window: false,
+            #[cfg(target_os = "windows")]
+            parent_window: None,
+            should_reload: false,
+        }
+    }
+}
+
+impl From<&Configurator> for ConfigFile {
+    fn from(config: &Configurator) -> Self {
+        let config = config.clone();
+        Self {
+            screensaver: config.screensaver,
+            fullscreen: config.fullscreen,
+            present_mode: config.present_mode,
+            backend: config.backend,
+            power_preference: config.power_preference,
+            monitor_index: config.monitor_index,
+            general: General {
+                fixed_timestep: config.fixed_timestep,
+                time_scale: config.time_scale,
+                motion_smoothing: config.motion_smoothing,
+                freeze_age: config.freeze_age,
+                opacity: config.opacity,
+                adaptive_count: config.adaptive_count,
+                adaptive_target_fps: config.adaptive_target_fps,
+                adaptive_min_count: config.adaptive_min_count,
+                adaptive_max_count: config.adaptive_max_count,
+                max_fps: config.max_fps,
+                trail_fade: config.trail_fade,
+                seed: config.seed,
+                color_interpolation: config.color_interpolation,
+                background_color: config.background_color,
+                background_gradient: config.background_gradient,
+                background_image: config.background_image,
+                background_fit: config.background_fit,
+                texture_quality: config.texture_quality,
+                msaa: config.msaa,
+                resize_behavior: config.resize_behavior,
+                windows_preview: config.windows_preview,
+                dismiss_mouse_threshold: config.dismiss_mouse_threshold,
+                free_camera: config.free_camera,
+                projection: config.projection,
+            },
+            snow: Snow {
+                snowflake_count: config.snowflake_count,
+                depth: config.snow_depth,
+                z_drift: config.snow_z_drift,
+                buildup_secs: config.snow_buildup_secs,
+                wind_strength: config.snow_wind_strength,
+                wind_variability: config.snow_wind_variability,
+                max_accumulation: config.snow_max_accumulation,
+            },
+            balls: Balls {
+                count: config.ball_count,
+                speed: config.ball_speed,
+                size: config.ball_size,
+                size_max: config.ball_size_max,
+                color_mode: config.color_mode,
+                color: config.color,
+                hue_min: config.hue_min,
+                hue_max: config.hue_max,
+                saturation: config.saturation,
+                value: config.value,
+                show_density: config.show_density,
+                target_display_density: config.target_display_density,
+                density_power: config.density_power,
+                region_size: config.region_size,
+                correct_ball_velocity: config.correct_ball_velocity,
+                gravity: config.gravity,
+                restitution: config.restitution,
+                friction: config.friction,
+                edge_softness: config.edge_softness,
+                render_mode: config.ball_render_mode,
+                shadow: config.shadow,
+                shadow_offset: config.shadow_offset,
+                debug_velocities: config.debug_velocities,
+                gpu_physics: config.gpu_physics,
+                sound: config.sound,
+                auto_retoss: config.auto_retoss,
+                retoss_energy: config.retoss_energy,
+                retoss_delay: config.retoss_delay,
+                mouse_mode: config.mouse_mode,
+                brush_size: config.brush_size,
+                temperature_min_speed: config.temperature_min_speed,
+                temperature_max_speed: config.temperature_max_speed,
+                infection_restart: config.infection_restart,
+                reinfection_count: config.reinfection_count,
+            },
+            ddd_model: Model {
+                model: config.ddd_model,
+                model_scale: config.model_scale,
+                model_count: config.model_count,
+                trail_count: config.model_trail_count,
+                spin_speed: config.spin_speed,
+                bounce_speed: config.bounce_speed,
+                bounce_height: config.bounce_height,
+                bounce_floor: config.bounce_floor,
+                orbit_camera: config.orbit_camera,
+                orbit_speed: config.orbit_speed,
+                orbit_radius: config.orbit_radius,
+                orbit_height: config.orbit_height,
+                fov: config.fov,
+                znear: config.znear,
+                zfar: config.zfar,
+            },
+            clock: Clock {
+                format: config.clock_format,
+                color: config.clock_color,
+                font_size: config.clock_font_size,
+                show_seconds: config.clock_show_seconds,
+            },
+            harmonograph: Harmonograph {
+                frequency_x: config.harmonograph_frequency_x,
+                frequency_y: config.harmonograph_frequency_y,
+                phase_x: config.harmonograph_phase_x,
+                phase_y: config.harmonograph_phase_y,
+                damping: config.harmonograph_damping,
+                speed: config.harmonograph_speed,
+                trail_length: config.harmonograph_trail_length,
+                line_width: config.harmonograph_line_width,
+                color: config.harmonograph_color,
+            },
+            packing: Packing {
+                max_circles: config.packing_max_circles,
+                growth_rate: config.packing_growth_rate,
+                palette: config.packing_palette,
+            },
+            comet: Comet {
+                count: config.comet_count,
+                speed: config.comet_speed,
+                tail_length: config.comet_tail_length,
+                color: config.comet_color,
+                glow: config.comet_glow,
+            },
+            starfield: Starfield {
+                count: config.star_count,
+                speed: config.star_speed,
+                streak_length: config.warp_streak_length,
+            },
+            matrix: Matrix {
+                column_density: config.column_density,
+                fall_speed: config.fall_speed,
+                glyph_color: config.glyph_color,
+            },
+            fireworks: Fireworks {
+                launch_rate: config.fireworks_launch_rate,
+                gravity: config.fireworks_gravity,
+            },
+            boids: Boids {
+                count: config.boid_count,
+                separation: config.boid_separation,
+                alignment: config.boid_alignment,
+                cohesion: config.boid_cohesion,
+                max_speed: config.boid_max_speed,
+            },
+            plasma: Plasma {
+                palette: config.plasma_palette,
+                speed: config.plasma_speed,
+            },
+            #[cfg(not(target_arch = "wasm32"))]
+            monitor_overrides: config.monitor_overrides,
+        }
+    }
+}
+
+/// the settings that are written as a name, like `screensaver = "balls"`. the names are the ones from `to_string`
+pub(crate) trait ConfigName: ToString + Sized {
+    /// unknown names fall back to the default of the setting instead of failing the whole config
+    fn from_name(name: &str) -> Self;
+}
+
+impl ConfigName for ScreenSaverType {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "snow" => ScreenSaverType::Snow,
+            "balls" => ScreenSaverType::Balls,
+            "3d_model" => ScreenSaverType::DDDModel,
+            "clock" => ScreenSaverType::Clock,
+            "harmonograph" => ScreenSaverType::Harmonograph,
+            "packing" => ScreenSaverType::CirclePacking,
+            "comet" => ScreenSaverType::Comet,
+            "starfield" => ScreenSaverType::Starfield,
+            "matrix" => ScreenSaverType::Matrix,
+            "fireworks" => ScreenSaverType::Fireworks,
+            "boids" => ScreenSaverType::Boids,
+            "plasma" => ScreenSaverType::Plasma,
+            _ => {
+                log::error!("Unknown screensaver: \"{}\", defaulting to \"snow\"", name);
+                ScreenSaverType::Snow
+            }
+        }
+    }
+}
+
+impl ConfigName for Backend {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "vulkan" => Backend::Vulkan,
+            "dx12" => Backend::Dx12,
+            "metal" => Backend::Metal,
+            "gl" => Backend::Gl,
+            _ => Backend::All,
+        }
+    }
+}
+
+impl ConfigName for PowerPreference {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "high" => PowerPreference::High,
+            _ => PowerPreference::Low,
+        }
+    }
+}
+
+impl ConfigName for PresentMode {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "no_vsync" => PresentMode::NoVsync,
+            "mailbox" => PresentMode::Mailbox,
+            "fifo" => PresentMode::Fifo,
+            _ => PresentMode::Vsync,
+        }
+    }
+}
+
+impl ConfigName for Projection {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "orthographic" => Projection::Orthographic,
+            _ => Projection::Perspective,
+        }
+    }
+}
+
+impl ConfigName for ColorSpace {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "rgb" => ColorSpace::Rgb,
+            _ => ColorSpace::Hsv,
+        }
+    }
+}
+
+impl ConfigName for BackgroundFit {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "contain" => BackgroundFit::Contain,
+            "stretch" => BackgroundFit::Stretch,
+            _ => BackgroundFit::Cover,
+        }
+    }
+}
+
+impl ConfigName for TextureQuality {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "half" => TextureQuality::Half,
+            "quarter" => TextureQuality::Quarter,
+            _ => TextureQuality::Full,
+        }
+    }
+}
+
+impl ConfigName for ResizeBehavior {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "respawn" => ResizeBehavior::Respawn,
+            _ => ResizeBehavior::Preserve,
+        }
+    }
+}
+
+impl ConfigName for BallColorMode {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "infection" => BallColorMode::Infection,
+            "color" => BallColorMode::Color,
+            "temperature" => BallColorMode::Temperature,
+            "dvd_bounce" => BallColorMode::DvdBounce,
+            _ => BallColorMode::Random,
+        }
+    }
+}
+
+impl ConfigName for BallRenderMode {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "sphere" => BallRenderMode::Sphere,
+            _ => BallRenderMode::Sprite,
+        }
+    }
+}
+
+impl ConfigName for MouseMode {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "attract" => MouseMode::Attract,
+            "repel" => MouseMode::Repel,
+            _ => MouseMode::Push,
+        }
+    }
+}
+
+impl ConfigName for ClockFormat {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "12h" => ClockFormat::TwelveHour,
+            _ => ClockFormat::TwentyFourHour,
+        }
+    }
+}
+
+impl ConfigName for PlasmaPalette {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "fire" => PlasmaPalette::Fire,
+            "ocean" => PlasmaPalette::Ocean,
+            "neon" => PlasmaPalette::Neon,
+            _ => PlasmaPalette::Rainbow,
+        }
+    }
+}
+
+impl ConfigName for DDDModel {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "shark" => DDDModel::Shark,
+            "kim_kitsuragi" => DDDModel::KimKitsuragi,
+            #[cfg(not(target_arch = "wasm32"))]
+            path if DDDModel::is_model_file(Path::new(path)) => DDDModel::Custom {
+                path: PathBuf::from(path),
+            },
+            _ => DDDModel::Apple,
+        }
+    }
+}
+
+mod config_name {
+    use super::ConfigName;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<T: ConfigName, S: Serializer>(value: &T, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&value.to_string())
+    }
+
+    pub fn deserialize<'de, T: ConfigName, D: Deserializer<'de>>(d: D) -> Result<T, D::Error> {
+        Ok(T::from_name(&String::deserialize(d)?))
+    }
+}
+
+/// colors are written as "#rrggbb"
+mod hex_color {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(color: &egui::Color32, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&color.to_hex()[0..7])
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<egui::Color32, D::Error> {
+        let hex = String::deserialize(d)?;
+        egui::Color32::from_hex(&hex)
+            .map_err(|_| serde::de::Error::custom(format!("\"{}\" is not a hex color", hex)))
+    }
+}
+
+/// for the optional colors, where an empty string means "none"
+mod optional_hex_color {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        color: &Option<egui::Color32>,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        match color {
+            Some(color) => super::hex_color::serialize(color, s),
+            None => s.serialize_str(""),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<egui::Color32>, D::Error> {
+        let hex = String::deserialize(d)?;
+        if hex.is_empty() {
+            return Ok(None);
+        }
+        egui::Color32::from_hex(&hex)
+            .map(Some)
+            .map_err(|_| serde::de::Error::custom(format!("\"{}\" is not a hex color", hex)))
+    }
+}
+
+/// for the optional settings where 0 means "off"
+mod zero_is_none {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<T, S>(value: &Option<T>, s: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize + Default + Copy,
+        S: Serializer,
+    {
+        value.unwrap_or_default().serialize(s)
+    }
+
+    pub fn deserialize<'de, T, D>(d: D) -> Result<Option<T>, D::Error>
+    where
+        T: Deserialize<'de> + Default + PartialEq,
+        D: Deserializer<'de>,
+    {
+        let value = T::deserialize(d)?;
+        Ok((value != T::default()).then_some(value))
+    }
+}
+
+/// for the indices where -1 means "none"
+mod negative_is_none {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Option<usize>, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_i64(value.map_or(-1, |value| value as i64))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<usize>, D::Error> {
+        Ok(usize::try_from(i64::deserialize(d)?).ok())
+    }
+}
+
+/// for the numbers where a negative value means "automatic"
+mod negative_f32_is_none {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Option<f32>, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_f32(value.unwrap_or(-1.0))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<f32>, D::Error> {
+        let value = f32::deserialize(d)?;
+        Ok((value >= 0.0).then_some(value))
+    }
+}
@@ -1,12 +1,25 @@
-use crate::screensaver::{BallColorMode, ScreenSaverType};
-use crate::util::model::DDDModel;
+//the types of the settings, so the builder can be used from outside the crate
+pub use crate::background::BackgroundFit;
+use crate::config_file::ConfigFile;
+pub use crate::particle::ResizeBehavior;
+pub use crate::screensaver::{
+    BallColorMode, BallRenderMode, ClockFormat, MouseMode, PlasmaPalette, ScreenSaverType,
+};
+pub use crate::texture::TextureQuality;
+pub use crate::util::color::ColorSpace;
+pub use crate::util::model::DDDModel;
 use crate::{run_with_config, screensaver};
+pub use crate::{Backend, PowerPreference, PresentMode, Projection};
 use config::Config;
-use std::fs::File;
-use std::io::{Read, Write};
+#[cfg(not(target_arch = "wasm32"))]
+use config::FileFormat;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use std::ops::{Add, AddAssign};
 use std::process::exit;
 use std::str::FromStr;
+#[cfg(target_arch = "wasm32")]
+use std::sync::TryLockError;
 use std::sync::{Arc, Mutex};
 use std::thread;
 #[cfg(not(target_arch = "wasm32"))]
@@ -24,76 +37,435 @@ pub enum ConfigPresets {
     Colors,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// why the config couldn't be read
+#[derive(Debug)]
+pub enum ConfigError {
+    /// a key or a table that has to be there isn't
+    Missing(String),
+    /// a key has a value of the wrong type
+    Invalid { key: String, message: String },
+    /// the config couldn't be read at all
+    Other(config::ConfigError),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Missing(key) => write!(f, "missing {} in the config", key),
+            ConfigError::Invalid { key, message } => {
+                write!(f, "invalid value for {}: {}", key, message)
+            }
+            ConfigError::Other(e) => write!(f, "failed to read the config: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<config::ConfigError> for ConfigError {
+    fn from(e: config::ConfigError) -> Self {
+        match e {
+            config::ConfigError::NotFound(key) => ConfigError::Missing(key),
+            config::ConfigError::Type {
+                key: Some(ref key), ..
+            } => ConfigError::Invalid {
+                key: key.clone(),
+                message: e.to_string(),
+            },
+            e => ConfigError::Other(e),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Configurator {
     pub(crate) screensaver: screensaver::ScreenSaverType,
     pub(crate) fullscreen: bool,
+    pub(crate) present_mode: PresentMode,
+    /// the graphics api, only applied when the screensaver starts
+    pub(crate) backend: Backend,
+    /// which graphics card is used, only applied when the screensaver starts
+    pub(crate) power_preference: PowerPreference,
+    /// the monitor to go fullscreen on, `None` for the primary one
+    pub(crate) monitor_index: Option<usize>,
+
+    //General
+    pub(crate) fixed_timestep: f32,
+    pub(crate) time_scale: f32,
+    pub(crate) motion_smoothing: bool,
+    pub(crate) freeze_age: bool,
+    pub(crate) opacity: f32,
+    pub(crate) adaptive_count: bool,
+    pub(crate) adaptive_target_fps: f32,
+    pub(crate) adaptive_min_count: usize,
+    pub(crate) adaptive_max_count: usize,
+    pub(crate) max_fps: Option<u32>,
+    /// how much of the previous frame is left under the current one, for motion trails. `None` clears the screen every frame
+    pub(crate) trail_fade: Option<f32>,
+    pub(crate) seed: Option<u64>,
+    pub(crate) color_interpolation: ColorSpace,
+    /// replaces the screensaver's own background color
+    pub(crate) background_color: Option<egui::Color32>,
+    /// the bottom color of a vertical gradient, the top one being `background_color`
+    pub(crate) background_gradient: Option<egui::Color32>,
+    pub(crate) background_image: String,
+    pub(crate) background_fit: BackgroundFit,
+    pub(crate) texture_quality: TextureQuality,
+    /// how many samples per pixel are used for antialiasing: 1 (off), 2, 4 or 8
+    pub(crate) msaa: u32,
+    pub(crate) resize_behavior: ResizeBehavior,
+    pub(crate) windows_preview: bool,
+    /// how many pixels the cursor has to move in total before the screensaver closes
+    pub(crate) dismiss_mouse_threshold: f32,
+    /// move the camera with wasd (and q/e), instead of closing the screensaver when a key is pressed
+    pub(crate) free_camera: bool,
+    /// lets the 3D screensavers be viewed flat, without perspective
+    pub(crate) projection: Projection,
 
     //Snow
     pub(crate) snowflake_count: usize,
+    pub(crate) snow_depth: f32,
+    pub(crate) snow_z_drift: f32,
+    pub(crate) snow_buildup_secs: f32,
+    pub(crate) snow_wind_strength: f32,
+    pub(crate) snow_wind_variability: f32,
+    /// how many flakes can pile up on the ground, 0 turns it off
+    pub(crate) snow_max_accumulation: usize,
 
     //Balls
     pub(crate) ball_count: usize,
     pub(crate) ball_speed: f32,
+    /// the size of the smallest balls
     pub(crate) ball_size: f32,
+    /// the balls get random sizes between `ball_size` and this, and are as heavy as they are big. `None` makes them all the same
+    pub(crate) ball_size_max: Option<f32>,
     pub(crate) color_mode: screensaver::BallColorMode,
     pub(crate) color: egui::Color32,
+    pub(crate) hue_min: f32,
+    pub(crate) hue_max: f32,
+    pub(crate) saturation: f32,
+    pub(crate) value: f32,
     pub(crate) show_density: bool,
     pub(crate) target_display_density: f64,
+    /// the opacity is the density to the power of this
+    pub(crate) density_power: f32,
     pub(crate) region_size: f32,
     pub(crate) correct_ball_velocity: bool,
+    pub(crate) gravity: f32,
+    pub(crate) restitution: f32,
+    /// how much of their speed the balls lose every second (0 to 1)
+    pub(crate) friction: f32,
+    pub(crate) edge_softness: f32,
+    pub(crate) ball_render_mode: BallRenderMode,
+    pub(crate) shadow: bool,
+    pub(crate) shadow_offset: f32,
+    pub(crate) debug_velocities: bool,
+    pub(crate) gpu_physics: bool,
+    pub(crate) sound: bool,
+    pub(crate) auto_retoss: bool,
+    pub(crate) retoss_energy: f32,
+    pub(crate) retoss_delay: f32,
+    pub(crate) mouse_mode: MouseMode,
+    pub(crate) brush_size: f32,
+    /// the speeds that get the coldest and hottest color in the temperature color mode, `None` follows the balls
+    pub(crate) temperature_min_speed: Option<f32>,
+    pub(crate) temperature_max_speed: Option<f32>,
+    /// start a new infection once every ball is infected, instead of staying that color
+    pub(crate) infection_restart: bool,
+    /// how many balls start the next infection
+    pub(crate) reinfection_count: usize,
 
     //3D Model
     pub ddd_model: DDDModel,
     pub model_scale: f32,
+    /// how many copies of the model are scattered around, 1 puts it in the middle
+    pub model_count: usize,
+    /// how many fading copies of the model's past poses follow it around, 0 turns them off
+    pub model_trail_count: usize,
     pub spin_speed: f32,
     pub bounce_speed: f32,
     pub bounce_height: f32,
+    /// the lowest the model goes while bouncing, it goes up to `bounce_floor + bounce_height`
+    pub bounce_floor: f32,
+    /// hold the model still and move the camera around it instead
+    pub orbit_camera: bool,
+    /// in degrees per second
+    pub orbit_speed: f32,
+    pub orbit_radius: f32,
+    pub orbit_height: f32,
+    /// in degrees
+    pub fov: f32,
+    pub znear: f32,
+    pub zfar: f32,
+
+    //Clock
+    pub(crate) clock_format: ClockFormat,
+    pub(crate) clock_color: egui::Color32,
+    pub(crate) clock_font_size: f32,
+    pub(crate) clock_show_seconds: bool,
+
+    //Harmonograph
+    pub(crate) harmonograph_frequency_x: f32,
+    pub(crate) harmonograph_frequency_y: f32,
+    pub(crate) harmonograph_phase_x: f32,
+    pub(crate) harmonograph_phase_y: f32,
+    pub(crate) harmonograph_damping: f32,
+    pub(crate) harmonograph_speed: f32,
+    pub(crate) harmonograph_trail_length: usize,
+    pub(crate) harmonograph_line_width: f32,
+    pub(crate) harmonograph_color: egui::Color32,
+
+    //Circle Packing
+    pub(crate) packing_max_circles: usize,
+    pub(crate) packing_growth_rate: f32,
+    pub(crate) packing_palette: String,
+
+    //Comet
+    pub(crate) comet_count: usize,
+    pub(crate) comet_speed: f32,
+    pub(crate) comet_tail_length: f32,
+    pub(crate) comet_color: egui::Color32,
+    pub(crate) comet_glow: f32,
+
+    //Starfield
+    pub(crate) star_count: usize,
+    pub(crate) star_speed: f32,
+    pub(crate) warp_streak_length: f32,
+
+    //Matrix
+    pub(crate) column_density: f32,
+    pub(crate) fall_speed: f32,
+    pub(crate) glyph_color: egui::Color32,
+
+    //Fireworks
+    pub(crate) fireworks_launch_rate: f32,
+    pub(crate) fireworks_gravity: f32,
+
+    //Boids
+    pub(crate) boid_count: usize,
+    pub(crate) boid_separation: f32,
+    pub(crate) boid_alignment: f32,
+    pub(crate) boid_cohesion: f32,
+    pub(crate) boid_max_speed: f32,
+
+    //Plasma
+    pub(crate) plasma_palette: PlasmaPalette,
+    pub(crate) plasma_speed: f32,
+
+    //Per Monitor
+    /// the `[[monitor]]` sections, each with `index` or `name` and the settings to change on that monitor
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) monitor_overrides: Vec<toml::Table>,
 
     //Internal Use - Not Configurable
     pub(crate) preview_window: bool,
+    /// the window to draw into instead of opening a new one (the windows screensaver preview)
+    #[cfg(target_os = "windows")]
+    pub(crate) parent_window: Option<isize>,
     pub should_reload: bool,
 }
 
+/// percent-encodes everything except the characters that are safe in a url parameter
+fn encode_url_param(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// undoes `encode_url_param`, a `+` is a space like in a form
+fn decode_url_param(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+            (b'+', _) => decoded.push(b' '),
+            (byte, _) => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// the `key=value` pairs of the part of a url after the `?`
+pub fn parse_url_query(query: &str) -> Vec<(String, String)> {
+    query
+        .trim_start_matches('?')
+        .split('&')
+        .filter(|param| !param.is_empty())
+        .map(|param| {
+            let (key, value) = param.split_once('=').unwrap_or((param, ""));
+            (decode_url_param(key), decode_url_param(value))
+        })
+        .collect()
+}
+
+/// the whole config as a toml table, with the same tables as the config file
+fn config_table(configurator: &Configurator) -> toml::Table {
+    let toml::Value::Table(values) = toml::Value::try_from(ConfigFile::from(configurator)).unwrap()
+    else {
+        unreachable!("the config is always a table")
+    };
+    values
+}
+
+/// how a value is written in a url, before it is encoded
+fn url_param_value(value: &toml::Value) -> String {
+    let value = match value {
+        toml::Value::String(string) => string.clone(),
+        //most of the floats are f32, as an f64 they would get a lot of extra digits
+        toml::Value::Float(float) if (*float as f32) as f64 == *float => {
+            (*float as f32).to_string()
+        }
+        value => value.to_string(),
+    };
+    encode_url_param(&value)
+}
+
+/// the tables a url parameter can be in without saying so, after the picked screensaver's table
+const URL_FALLBACK_TABLES: [&str; 4] = ["general", "snow", "balls", "3d_model"];
+
+/// the table a url parameter without a table name goes in. that is the picked screensaver's table if it has that key,
+/// otherwise the first fallback table that does
+fn url_param_table<'a>(defaults: &toml::Table, screensaver: &'a str, key: &str) -> Option<&'a str> {
+    let has_key = |table: &str| defaults.get(table).is_some_and(|t| t.get(key).is_some());
+    [screensaver]
+        .into_iter()
+        .chain(URL_FALLBACK_TABLES)
+        .find(|table| has_key(table))
+}
+
+/// turns url parameters into a toml config. a parameter can say which table it goes in with `table.key`,
+/// the others are found with `url_param_table`
+pub fn url_params_to_toml(params: &[(String, String)]) -> String {
+    let defaults = config_table(&Configurator::default());
+    let screensaver = params
+        .iter()
+        .find(|(key, _)| key == "screensaver")
+        .map(|(_, value)| value.clone())
+        .unwrap_or_else(|| Configurator::default().screensaver.to_string());
+
+    let mut config = toml::Table::new();
+    for (key, value) in params {
+        //the values are all strings, the config crate parses them into whatever the field is
+        let value = toml::Value::String(value.clone());
+        if defaults.get(key).is_some_and(|default| !default.is_table()) {
+            config.insert(key.clone(), value);
+            continue;
+        }
+        let (table, key) = match key.split_once('.') {
+            Some((table, key)) if defaults.get(table).is_some_and(|t| t.get(key).is_some()) => {
+                (Some(table), key)
+            }
+            _ => (url_param_table(&defaults, &screensaver, key), key.as_str()),
+        };
+        match table {
+            Some(table) => {
+                config
+                    .entry(table)
+                    .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+                    .as_table_mut()
+                    .unwrap()
+                    .insert(key.to_string(), value);
+            }
+            None => log::warn!("Ignoring the unknown url parameter \"{}\"", key),
+        }
+    }
+    toml::to_string(&config).unwrap()
+}
+
 impl Configurator {
+    /// the random number generator the screensavers are set up with. with a seed the scene is the same every time
+    pub(crate) fn rng(&self) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        }
+    }
+
+    /// writes the config back in the format it was loaded from
     #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_config(&self) -> std::io::Result<()> {
+        let (config_path, format) = crate::config_file();
+        let existing = match std::fs::read_to_string(&config_path) {
+            Ok(existing) => Some(existing),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+        let contents = match format {
+            //without a config file the comments come from the default one
+            FileFormat::Toml => self
+                .to_toml(&existing.unwrap_or_else(|| {
+                    String::from_utf8_lossy(crate::DEFAULT_CONFIG).into_owned()
+                })),
+            //these can't keep the comments anyway, but the `monitor` sections aren't in `ConfigFile` and are
+            //copied over from the file as they were
+            FileFormat::Json => {
+                let mut json = serde_json::to_value(ConfigFile::from(self)).unwrap();
+                let monitor = existing
+                    .and_then(|existing| serde_json::from_str::<serde_json::Value>(&existing).ok())
+                    .and_then(|mut existing| {
+                        existing.get_mut("monitor").map(serde_json::Value::take)
+                    });
+                if let (Some(monitor), Some(json)) = (monitor, json.as_object_mut()) {
+                    json.insert("monitor".to_string(), monitor);
+                }
+                serde_json::to_string_pretty(&json).unwrap()
+            }
+            _ => {
+                let mut yaml = serde_yaml::to_value(ConfigFile::from(self)).unwrap();
+                let monitor = existing
+                    .and_then(|existing| serde_yaml::from_str::<serde_yaml::Value>(&existing).ok())
+                    .and_then(|mut existing| existing.get_mut("monitor").map(std::mem::take));
+                if let (Some(monitor), Some(yaml)) = (monitor, yaml.as_mapping_mut()) {
+                    yaml.insert("monitor".into(), monitor);
+                }
+                serde_yaml::to_string(&yaml).unwrap()
+            }
+        };
+
+        crate::write_config_file(&config_path, &contents)
+    }
+
+    /// there is no config file on the web, the config is kept in the browser's local storage instead
+    #[cfg(target_arch = "wasm32")]
     pub fn save_config(&self) {
-        let mut config_path = dirs::config_dir().unwrap().to_path_buf();
-        config_path.push("michaels-screensaver.toml");
-        let mut toml = File::open(&config_path).unwrap();
-        let mut toml_string = String::new();
-        toml.read_to_string(&mut toml_string).unwrap();
-        use toml_edit::value;
-        let mut doc = toml_edit::DocumentMut::from_str(toml_string.as_str()).unwrap();
-
-        doc["screensaver"] = value(self.screensaver.to_string());
-        doc["fullscreen"] = value(self.fullscreen);
-        //Snow
-        doc["snow"]["snowflake_count"] = value(self.snowflake_count as i64);
-        //Balls
-        doc["balls"]["speed"] = value(self.ball_speed as f64);
-        doc["balls"]["count"] = value(self.ball_count as i64);
-        doc["balls"]["size"] = value(self.ball_size as f64);
-        doc["balls"]["color_mode"] = value(self.color_mode.to_string());
-        doc["balls"]["show_density"] = value(self.show_density);
-        doc["balls"]["target_display_density"] = value(self.target_display_density);
-        doc["balls"]["color"] = value(self.color.to_hex()[0..7].to_string());
-        doc["balls"]["region_size"] = value(self.region_size as f64);
-        doc["balls"]["correct_ball_velocity"] = value(self.correct_ball_velocity);
-
-        doc["3d_model"]["model"] = value(self.ddd_model.to_string());
-        doc["3d_model"]["model_scale"] = value(self.model_scale as f64);
-        doc["3d_model"]["spin_speed"] = value(self.spin_speed as f64);
-        doc["3d_model"]["bounce_speed"] = value(self.bounce_speed as f64);
-        doc["3d_model"]["bounce_height"] = value(self.bounce_height as f64);
-
-        let mut toml = File::create(config_path).unwrap();
-        toml.write_all(doc.to_string().as_bytes()).unwrap();
+        let Some(storage) = crate::local_storage() else {
+            return;
+        };
+        let json = serde_json::to_string(&ConfigFile::from(self)).unwrap();
+        if let Err(e) = storage.set_item(crate::LOCAL_STORAGE_KEY, &json) {
+            log::warn!("Failed to save the config to the local storage: {:?}", e);
+        }
     }
 
-    pub fn generate_url(&self) -> String {
-        let dc = Self::default();
+    /// writes the config into an existing toml document, keeping its comments and formatting
+    pub fn to_toml(&self, toml_string: &str) -> String {
+        let mut doc = toml_edit::DocumentMut::from_str(toml_string).unwrap();
+        let toml::Value::Table(values) = toml::Value::try_from(ConfigFile::from(self)).unwrap()
+        else {
+            unreachable!("the config is always a table")
+        };
+        merge_into_toml(doc.as_table_mut(), values);
+        doc.to_string()
+    }
 
+    /// a link to the web version with every setting that isn't the default, `from_url` reads it back
+    pub fn generate_url(&self) -> String {
         #[cfg(not(target_arch = "wasm32"))]
         let mut url = String::from("https://mhanak.net/screensaver/");
         #[cfg(target_arch = "wasm32")]
@@ -104,200 +476,340 @@ impl Configurator {
             .as_string()
             .unwrap();
 
-        if url.contains("?") {
-            url.truncate(url.find("?").unwrap());
-            log::error!("{}", url);
+        if let Some(query_start) = url.find("?") {
+            url.truncate(query_start);
         }
 
-        url += format!("?screensaver={}", self.screensaver.to_string()).as_str();
-
-        if dc.snowflake_count != self.snowflake_count {
-            url += format!("&snowflake_count={}", self.snowflake_count).as_str()
+        let values = config_table(self);
+        let defaults = config_table(&Self::default());
+        let screensaver = self.screensaver.to_string();
+        let mut params = vec![format!("screensaver={}", screensaver)];
+        for (key, value) in &values {
+            let toml::Value::Table(table) = value else {
+                if key != "screensaver" && defaults.get(key) != Some(value) {
+                    params.push(format!("{}={}", key, url_param_value(value)));
+                }
+                continue;
+            };
+            for (table_key, value) in table {
+                if defaults.get(key).and_then(|t| t.get(table_key)) == Some(value) {
+                    continue;
+                }
+                //the keys that would end up in another table say which table they are from
+                let name =
+                    if url_param_table(&defaults, &screensaver, table_key) == Some(key.as_str()) {
+                        table_key.clone()
+                    } else {
+                        format!("{}.{}", key, table_key)
+                    };
+                params.push(format!("{}={}", name, url_param_value(value)));
+            }
         }
 
-        if dc.ball_count != self.ball_count {
-            url += format!("&count={}", self.ball_count).as_str()
-        }
-        if dc.ball_speed != self.ball_speed {
-            url += format!("&speed={}", self.ball_speed).as_str()
-        }
-        if dc.ball_size != self.ball_size {
-            url += format!("&size={}", self.ball_size).as_str()
-        }
-        if dc.color_mode != self.color_mode {
-            url += format!("&color_mode={}", self.color_mode.to_string()).as_str()
-        }
-        if dc.color != self.color {
-            url += format!("&color={}", self.color.to_hex()[0..7].replace("#", "%23")).as_str()
-        }
-        if dc.show_density != self.show_density {
-            url += format!("&show_density={}", self.show_density).as_str()
-        }
-        if dc.target_display_density != self.target_display_density {
-            url += format!("&target_display_density={}", self.target_display_density).as_str()
+        url + "?" + &params.join("&")
+    }
+
+    /// the config a link made by `generate_url` describes, read the same way the web version reads its url
+    pub fn from_url(url: &str) -> Result<Self, ConfigError> {
+        let query = url.split_once('?').map_or("", |(_, query)| query);
+        let params_toml = url_params_to_toml(&parse_url_query(query));
+        Self::from_config(
+            Config::builder()
+                .add_source(config::File::from_str(&params_toml, FileFormat::Toml))
+                .build()?,
+        )
+    }
+
+    /// for setting the config up in code, starting from the default one
+    pub fn builder() -> ConfiguratorBuilder {
+        ConfiguratorBuilder::default()
+    }
+
+    pub fn from_config(config: Config) -> Result<Self, ConfigError> {
+        //keys the config doesn't have, like ones added in a newer version, get their default value
+        let config = Config::builder()
+            .add_source(config::File::from_str(
+                std::str::from_utf8(crate::DEFAULT_CONFIG)
+                    .expect("Failed to read the default config"),
+                config::FileFormat::Toml,
+            ))
+            .add_source(config)
+            .build()?;
+        let mut configurator: Self = config.try_deserialize::<ConfigFile>()?.into();
+        configurator.validate();
+        Ok(configurator)
+    }
+
+    /// clamps the values the config ui can't set, but the config file can, to the ranges of their sliders in the
+    /// config ui, logging every change
+    pub fn validate(&mut self) {
+        for (key, count, min, max) in [
+            ("snowflake_count", &mut self.snowflake_count, 200, 20000),
+            (
+                "snow_max_accumulation",
+                &mut self.snow_max_accumulation,
+                0,
+                10000,
+            ),
+            ("ball_count", &mut self.ball_count, 1, 100000),
+            ("reinfection_count", &mut self.reinfection_count, 1, 20),
+            ("adaptive_min_count", &mut self.adaptive_min_count, 1, 10000),
+            (
+                "harmonograph_trail_length",
+                &mut self.harmonograph_trail_length,
+                100,
+                20000,
+            ),
+            (
+                "packing_max_circles",
+                &mut self.packing_max_circles,
+                10,
+                3000,
+            ),
+            ("comet_count", &mut self.comet_count, 1, 20),
+            ("star_count", &mut self.star_count, 100, 20000),
+            ("boid_count", &mut self.boid_count, 10, 3000),
+            ("model_count", &mut self.model_count, 1, 50),
+            ("trail_count", &mut self.model_trail_count, 0, 10),
+        ] {
+            clamp_value(key, count, min, max);
         }
-        if dc.region_size != self.region_size {
-            url += format!("&region_size={}", self.region_size).as_str()
+        let adaptive_min_count = self.adaptive_min_count;
+        clamp_value(
+            "adaptive_max_count",
+            &mut self.adaptive_max_count,
+            adaptive_min_count,
+            50000,
+        );
+
+        for (key, value, min, max) in [
+            //the snow's depth is divided by, so it can't be 0
+            ("snow_depth", &mut self.snow_depth, 0.2, 3.0),
+            ("snow_z_drift", &mut self.snow_z_drift, 0.0, 0.5),
+            ("snow_buildup_secs", &mut self.snow_buildup_secs, 0.0, 300.0),
+            (
+                "snow_wind_strength",
+                &mut self.snow_wind_strength,
+                -0.5,
+                0.5,
+            ),
+            (
+                "snow_wind_variability",
+                &mut self.snow_wind_variability,
+                0.0,
+                1.0,
+            ),
+            ("ball_speed", &mut self.ball_speed, 0.01, 1.0),
+            //the balls have to fit in the regions, and tiny regions glitch
+            ("ball_size", &mut self.ball_size, 0.02, 1.0),
+            ("hue_min", &mut self.hue_min, 0.0, 1.0),
+            ("hue_max", &mut self.hue_max, 0.0, 1.0),
+            ("saturation", &mut self.saturation, 0.0, 1.0),
+            ("value", &mut self.value, 0.0, 1.0),
+            ("density_power", &mut self.density_power, 0.5, 5.0),
+            ("region_size", &mut self.region_size, 0.5, 5.0),
+            ("gravity", &mut self.gravity, 0.0, 2.0),
+            ("restitution", &mut self.restitution, 0.0, 1.0),
+            ("friction", &mut self.friction, 0.0, 1.0),
+            ("edge_softness", &mut self.edge_softness, 0.0, 1.0),
+            ("shadow_offset", &mut self.shadow_offset, 0.0, 0.05),
+            ("retoss_energy", &mut self.retoss_energy, 0.0, 0.2),
+            ("retoss_delay", &mut self.retoss_delay, 0.0, 10.0),
+            ("brush_size", &mut self.brush_size, 0.05, 1.0),
+            ("model_scale", &mut self.model_scale, 0.1, 3.0),
+            ("spin_speed", &mut self.spin_speed, 0.0, 5.0),
+            ("bounce_speed", &mut self.bounce_speed, 0.0, 5.0),
+            ("bounce_height", &mut self.bounce_height, 0.0, 1.0),
+            ("bounce_floor", &mut self.bounce_floor, -2.0, 2.0),
+            ("orbit_speed", &mut self.orbit_speed, -90.0, 90.0),
+            ("orbit_radius", &mut self.orbit_radius, 1.0, 10.0),
+            ("orbit_height", &mut self.orbit_height, -5.0, 5.0),
+            ("fov", &mut self.fov, 10.0, 120.0),
+            //with these ranges znear is always in front of zfar
+            ("znear", &mut self.znear, 0.01, 1.0),
+            ("zfar", &mut self.zfar, 10.0, 1000.0),
+            ("clock_font_size", &mut self.clock_font_size, 0.1, 1.0),
+            (
+                "harmonograph_frequency_x",
+                &mut self.harmonograph_frequency_x,
+                0.1,
+                10.0,
+            ),
+            (
+                "harmonograph_frequency_y",
+                &mut self.harmonograph_frequency_y,
+                0.1,
+                10.0,
+            ),
+            (
+                "harmonograph_phase_x",
+                &mut self.harmonograph_phase_x,
+                0.0,
+                1.0,
+            ),
+            (
+                "harmonograph_phase_y",
+                &mut self.harmonograph_phase_y,
+                0.0,
+                1.0,
+            ),
+            (
+                "harmonograph_damping",
+                &mut self.harmonograph_damping,
+                0.0,
+                1.0,
+            ),
+            (
+                "harmonograph_speed",
+                &mut self.harmonograph_speed,
+                0.01,
+                2.0,
+            ),
+            (
+                "harmonograph_line_width",
+                &mut self.harmonograph_line_width,
+                0.002,
+                0.05,
+            ),
+            (
+                "packing_growth_rate",
+                &mut self.packing_growth_rate,
+                0.005,
+                0.5,
+            ),
+            ("comet_speed", &mut self.comet_speed, 0.05, 2.0),
+            ("comet_tail_length", &mut self.comet_tail_length, 0.1, 5.0),
+            ("comet_glow", &mut self.comet_glow, 0.0, 1.0),
+            ("star_speed", &mut self.star_speed, 0.5, 50.0),
+            ("warp_streak_length", &mut self.warp_streak_length, 0.0, 0.5),
+            ("column_density", &mut self.column_density, 0.05, 1.0),
+            ("fall_speed", &mut self.fall_speed, 0.05, 2.0),
+            (
+                "fireworks_launch_rate",
+                &mut self.fireworks_launch_rate,
+                0.1,
+                10.0,
+            ),
+            ("fireworks_gravity", &mut self.fireworks_gravity, 0.1, 2.0),
+            ("boid_separation", &mut self.boid_separation, 0.0, 5.0),
+            ("boid_alignment", &mut self.boid_alignment, 0.0, 5.0),
+            ("boid_cohesion", &mut self.boid_cohesion, 0.0, 5.0),
+            ("boid_max_speed", &mut self.boid_max_speed, 0.05, 2.0),
+            ("plasma_speed", &mut self.plasma_speed, 0.0, 3.0),
+            ("time_scale", &mut self.time_scale, -2.0, 4.0),
+            ("fixed_timestep", &mut self.fixed_timestep, 0.0, 240.0),
+            (
+                "adaptive_target_fps",
+                &mut self.adaptive_target_fps,
+                15.0,
+                240.0,
+            ),
+            ("opacity", &mut self.opacity, 0.0, 1.0),
+            (
+                "dismiss_mouse_threshold",
+                &mut self.dismiss_mouse_threshold,
+                0.0,
+                500.0,
+            ),
+        ] {
+            clamp_value(key, value, min, max);
         }
-        if dc.correct_ball_velocity != self.correct_ball_velocity {
-            url += format!("&correct_ball_velocity={}", self.correct_ball_velocity).as_str()
+
+        let ball_size = self.ball_size;
+        if let Some(ball_size_max) = &mut self.ball_size_max {
+            clamp_value("ball_size_max", ball_size_max, ball_size, 1.0);
         }
-        if dc.ddd_model != self.ddd_model {
-            url += format!("&model={}", self.ddd_model.to_string()).as_str()
+        for (key, speed) in [
+            ("temperature_min_speed", &mut self.temperature_min_speed),
+            ("temperature_max_speed", &mut self.temperature_max_speed),
+        ] {
+            if let Some(speed) = speed {
+                clamp_value(key, speed, 0.0, 2.0);
+            }
         }
-        if dc.model_scale != self.model_scale {
-            url += format!("&model_scale={}", self.model_scale.to_string()).as_str()
+        clamp_value(
+            "target_display_density",
+            &mut self.target_display_density,
+            1.0,
+            100.0,
+        );
+        if let Some(max_fps) = &mut self.max_fps {
+            clamp_value("max_fps", max_fps, 5, 240);
         }
-        if dc.spin_speed != self.spin_speed {
-            url += format!("&spin_speed={}", self.spin_speed).as_str()
+        //a trail that never fades would never clear the screen
+        if let Some(trail_fade) = &mut self.trail_fade {
+            clamp_value("trail_fade", trail_fade, 0.1, 0.99);
         }
-        if dc.bounce_speed != self.bounce_speed {
-            url += format!("&bounce_speed={}", self.bounce_speed).as_str()
+        if ![1, 2, 4, 8].contains(&self.msaa) {
+            log::warn!(
+                "msaa has to be 1, 2, 4 or 8, not {}, using 1 instead",
+                self.msaa
+            );
+            self.msaa = 1;
         }
-        if dc.bounce_height != self.bounce_height {
-            url += format!("&bounce_height={}", self.bounce_height).as_str()
+    }
+
+    /// like `from_config`, but a broken config only gets logged and the default one is used instead
+    pub fn from_config_or_default(config: Config) -> Self {
+        match Self::from_config(config) {
+            Ok(configurator) => configurator,
+            Err(e) => {
+                log::warn!("{}, using the default config instead", e);
+                Self::default()
+            }
         }
+    }
+
+    /// the config with the settings from the `[[monitor]]` section matching the monitor applied.
+    /// monitors are matched by their index (starting at 0), or their name
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn for_monitor(&self, index: usize, name: Option<&str>) -> Self {
+        let overrides = self.monitor_overrides.iter().find(|monitor| {
+            match (monitor.get("index"), monitor.get("name")) {
+                (Some(toml::Value::Integer(i)), _) => *i == index as i64,
+                (_, Some(toml::Value::String(n))) => Some(n.as_str()) == name,
+                _ => false,
+            }
+        });
+        let Some(overrides) = overrides else {
+            return self.clone();
+        };
+        let mut overrides = overrides.clone();
+        overrides.remove("index");
+        overrides.remove("name");
 
-        url
+        let base = self.to_toml(std::str::from_utf8(crate::DEFAULT_CONFIG).unwrap());
+        let config = Config::builder()
+            .add_source(config::File::from_str(&base, config::FileFormat::Toml))
+            .add_source(config::File::from_str(
+                &toml::to_string(&overrides).unwrap(),
+                config::FileFormat::Toml,
+            ))
+            .build();
+        match config
+            .map_err(ConfigError::from)
+            .and_then(Self::from_config)
+        {
+            Ok(mut configurator) => {
+                configurator.monitor_overrides = self.monitor_overrides.clone();
+                configurator.preview_window = self.preview_window;
+                configurator
+            }
+            Err(e) => {
+                log::error!("Failed to apply the monitor config: {}", e);
+                self.clone()
+            }
+        }
     }
 
-    pub fn from_config(config: Config) -> Self {
-        let screensaver_name: String = config.get("screensaver").unwrap();
-        let snow = config.get_table("snow").unwrap();
-        let balls = config.get_table("balls").unwrap();
-        let ddd_model = config.get_table("3d_model").unwrap();
-        Self {
-            screensaver: match screensaver_name.as_str() {
-                "snow" => ScreenSaverType::Snow,
-                "balls" => ScreenSaverType::Balls,
-                "3d_model" => ScreenSaverType::DDDModel,
-                _ => {
-                    log::error!(
-                        "Unknown screensaver: \"{}\", defaulting to \"snow\"",
-                        screensaver_name
-                    );
-                    ScreenSaverType::Snow
-                }
-            },
-            fullscreen: config.get("fullscreen").unwrap(),
-            //Snow
-            snowflake_count: snow
-                .get("snowflake_count")
-                .unwrap()
-                .clone()
-                .try_deserialize()
-                .unwrap(),
-            //Balls
-            ball_count: balls
-                .get("count")
-                .unwrap()
-                .clone()
-                .try_deserialize()
-                .unwrap(),
-            ball_speed: config
-                .get_table("balls")
-                .unwrap()
-                .get("speed")
-                .unwrap()
-                .clone()
-                .try_deserialize()
-                .unwrap(),
-            ball_size: balls
-                .get("size")
-                .unwrap()
-                .clone()
-                .try_deserialize()
-                .unwrap(),
-            region_size: balls
-                .get("region_size")
-                .unwrap()
-                .clone()
-                .try_deserialize()
-                .unwrap(),
-            correct_ball_velocity: balls
-                .get("correct_ball_velocity")
-                .unwrap()
-                .clone()
-                .try_deserialize()
-                .unwrap(),
-            color_mode: match balls
-                .get("color_mode")
-                .unwrap()
-                .clone()
-                .try_deserialize::<Option<String>>()
-                .unwrap()
-            {
-                Some(a) => match a.as_str() {
-                    "random" => BallColorMode::Random,
-                    "infection" => BallColorMode::Infection,
-                    "color" => BallColorMode::Color,
-                    "temperature" => BallColorMode::Temperature,
-                    _ => BallColorMode::Random,
-                },
-                None => BallColorMode::Color,
-            },
-            color: {
-                let color_hex: String = balls
-                    .get("color")
-                    .unwrap()
-                    .clone()
-                    .try_deserialize()
-                    .unwrap();
-                egui::Color32::from_hex(&color_hex).unwrap_or(egui::Color32::WHITE)
-            },
-            show_density: balls
-                .get("show_density")
-                .unwrap()
-                .clone()
-                .try_deserialize()
-                .unwrap(),
-            target_display_density: balls
-                .get("target_display_density")
-                .unwrap()
-                .clone()
-                .try_deserialize()
-                .unwrap(),
-            ddd_model: match ddd_model
-                .get("model")
-                .unwrap()
-                .clone()
-                .try_deserialize::<Option<String>>()
-                .unwrap()
-            {
-                None => DDDModel::Apple,
-                Some(a) => match a.as_str() {
-                    "apple" => DDDModel::Apple,
-                    "shark" => DDDModel::Shark,
-                    "kim_kitsuragi" => DDDModel::KimKitsuragi,
-                    _ => DDDModel::Apple,
-                },
-            },
-            model_scale: ddd_model
-                .get("model_scale")
-                .unwrap()
-                .clone()
-                .try_deserialize()
-                .unwrap(),
-            spin_speed: ddd_model
-                .get("spin_speed")
-                .unwrap()
-                .clone()
-                .try_deserialize()
-                .unwrap(),
-            bounce_speed: ddd_model
-                .get("bounce_speed")
-                .unwrap()
-                .clone()
-                .try_deserialize()
-                .unwrap(),
-            bounce_height: ddd_model
-                .get("bounce_height")
-                .unwrap()
-                .clone()
-                .try_deserialize()
-                .unwrap(),
-            preview_window: false,
-            should_reload: false,
+    /// the particle count of the selected screensaver, if it has one that can be changed while running
+    pub(crate) fn instance_count_mut(&mut self) -> Option<&mut usize> {
+        match self.screensaver {
+            ScreenSaverType::Balls => Some(&mut self.ball_count),
+            ScreenSaverType::Snow => Some(&mut self.snowflake_count),
+            ScreenSaverType::Starfield => Some(&mut self.star_count),
+            ScreenSaverType::Boids => Some(&mut self.boid_count),
+            _ => None,
         }
     }
 
@@ -337,7 +849,7 @@ impl Configurator {
                 ball_count: 1,
                 ball_speed: 0.3,
                 ball_size: 0.5,
-                color_mode: BallColorMode::Random,
+                color_mode: BallColorMode::DvdBounce,
                 ..Default::default()
             },
             ConfigPresets::Colors => Self {
@@ -352,20 +864,400 @@ impl Configurator {
     }
 }
 
+/// the names of the connected monitors, in the order `monitor_index` counts them. winit only lets a program
+/// create one event loop, and eframe already has it, so they are listed by another copy of this program
+#[cfg(not(target_arch = "wasm32"))]
+fn detect_monitors() -> Vec<String> {
+    let output = std::env::current_exe().and_then(|exe| {
+        std::process::Command::new(exe)
+            .arg("--list-monitors")
+            .output()
+    });
+    match output {
+        Ok(output) => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::to_string)
+            .collect(),
+        Err(e) => {
+            log::error!("Failed to list the monitors: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// sets the values in `doc` without touching the comments around them. tables are merged key by key
+fn merge_into_toml(doc: &mut toml_edit::Table, values: toml::Table) {
+    for (key, new_value) in values {
+        match new_value {
+            toml::Value::Table(table) => {
+                if !doc.contains_table(&key) {
+                    doc.insert(&key, toml_edit::table());
+                }
+                if let Some(doc_table) = doc[key.as_str()].as_table_mut() {
+                    merge_into_toml(doc_table, table);
+                }
+            }
+            new_value => {
+                let new_value: toml_edit::Value = match new_value {
+                    toml::Value::String(s) => s.into(),
+                    toml::Value::Integer(i) => i.into(),
+                    toml::Value::Float(f) => f.into(),
+                    toml::Value::Boolean(b) => b.into(),
+                    other => other.to_string().parse().unwrap(),
+                };
+                doc[key.as_str()] = toml_edit::value(new_value);
+            }
+        }
+    }
+}
+
 impl Default for Configurator {
     fn default() -> Configurator {
         Configurator::from_config(crate::get_default_config())
+            .expect("The default config is invalid")
     }
 }
 
+/// chainable setters for a `Configurator`, for using the screensaver as a library without a config file.
+/// the settings that aren't set keep their default value
+///
+/// ```no_run
+/// use michaels_screensaver::configurator::{Configurator, ScreenSaverType};
+///
+/// let configurator = Configurator::builder()
+///     .screensaver(ScreenSaverType::Balls)
+///     .ball_count(500)
+///     .ball_speed(0.3)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ConfiguratorBuilder {
+    configurator: Configurator,
+}
+
+/// a `fn field(mut self, value) -> Self` for every listed field
+macro_rules! setters {
+    ($($field:ident: $type:ty),* $(,)?) => {
+        $(
+            pub fn $field(mut self, $field: $type) -> Self {
+                self.configurator.$field = $field;
+                self
+            }
+        )*
+    };
+}
+
+impl ConfiguratorBuilder {
+    setters! {
+        screensaver: ScreenSaverType,
+        fullscreen: bool,
+        present_mode: PresentMode,
+        backend: Backend,
+        power_preference: PowerPreference,
+        monitor_index: Option<usize>,
+
+        //General
+        fixed_timestep: f32,
+        time_scale: f32,
+        motion_smoothing: bool,
+        freeze_age: bool,
+        opacity: f32,
+        adaptive_count: bool,
+        adaptive_target_fps: f32,
+        adaptive_min_count: usize,
+        adaptive_max_count: usize,
+        max_fps: Option<u32>,
+        trail_fade: Option<f32>,
+        seed: Option<u64>,
+        color_interpolation: ColorSpace,
+        background_color: Option<egui::Color32>,
+        background_gradient: Option<egui::Color32>,
+        background_image: String,
+        background_fit: BackgroundFit,
+        texture_quality: TextureQuality,
+        msaa: u32,
+        resize_behavior: ResizeBehavior,
+        windows_preview: bool,
+        dismiss_mouse_threshold: f32,
+        free_camera: bool,
+        projection: Projection,
+
+        //Snow
+        snowflake_count: usize,
+        snow_depth: f32,
+        snow_z_drift: f32,
+        snow_buildup_secs: f32,
+        snow_wind_strength: f32,
+        snow_wind_variability: f32,
+        snow_max_accumulation: usize,
+
+        //Balls
+        ball_count: usize,
+        ball_speed: f32,
+        ball_size: f32,
+        ball_size_max: Option<f32>,
+        color_mode: BallColorMode,
+        color: egui::Color32,
+        hue_min: f32,
+        hue_max: f32,
+        saturation: f32,
+        value: f32,
+        show_density: bool,
+        target_display_density: f64,
+        density_power: f32,
+        region_size: f32,
+        correct_ball_velocity: bool,
+        gravity: f32,
+        restitution: f32,
+        friction: f32,
+        edge_softness: f32,
+        ball_render_mode: BallRenderMode,
+        shadow: bool,
+        shadow_offset: f32,
+        debug_velocities: bool,
+        gpu_physics: bool,
+        sound: bool,
+        auto_retoss: bool,
+        retoss_energy: f32,
+        retoss_delay: f32,
+        mouse_mode: MouseMode,
+        brush_size: f32,
+        temperature_min_speed: Option<f32>,
+        temperature_max_speed: Option<f32>,
+        infection_restart: bool,
+        reinfection_count: usize,
+
+        //3D Model
+        ddd_model: DDDModel,
+        model_scale: f32,
+        model_count: usize,
+        model_trail_count: usize,
+        spin_speed: f32,
+        bounce_speed: f32,
+        bounce_height: f32,
+        bounce_floor: f32,
+        orbit_camera: bool,
+        orbit_speed: f32,
+        orbit_radius: f32,
+        orbit_height: f32,
+        fov: f32,
+        znear: f32,
+        zfar: f32,
+
+        //Clock
+        clock_format: ClockFormat,
+        clock_color: egui::Color32,
+        clock_font_size: f32,
+        clock_show_seconds: bool,
+
+        //Harmonograph
+        harmonograph_frequency_x: f32,
+        harmonograph_frequency_y: f32,
+        harmonograph_phase_x: f32,
+        harmonograph_phase_y: f32,
+        harmonograph_damping: f32,
+        harmonograph_speed: f32,
+        harmonograph_trail_length: usize,
+        harmonograph_line_width: f32,
+        harmonograph_color: egui::Color32,
+
+        //Circle Packing
+        packing_max_circles: usize,
+        packing_growth_rate: f32,
+        packing_palette: String,
+
+        //Comet
+        comet_count: usize,
+        comet_speed: f32,
+        comet_tail_length: f32,
+        comet_color: egui::Color32,
+        comet_glow: f32,
+
+        //Starfield
+        star_count: usize,
+        star_speed: f32,
+        warp_streak_length: f32,
+
+        //Matrix
+        column_density: f32,
+        fall_speed: f32,
+        glyph_color: egui::Color32,
+
+        //Fireworks
+        fireworks_launch_rate: f32,
+        fireworks_gravity: f32,
+
+        //Boids
+        boid_count: usize,
+        boid_separation: f32,
+        boid_alignment: f32,
+        boid_cohesion: f32,
+        boid_max_speed: f32,
+
+        //Plasma
+        plasma_palette: PlasmaPalette,
+        plasma_speed: f32,
+    }
+
+    /// checks the settings that would break the screensavers. unlike the config file, nothing is
+    /// fixed up quietly, a bad value is an error
+    pub fn build(self) -> Result<Configurator, ConfigError> {
+        let c = self.configurator;
+        let invalid = |key: &str, message: &str| ConfigError::Invalid {
+            key: key.to_string(),
+            message: message.to_string(),
+        };
+
+        let counts = [
+            ("snowflake_count", c.snowflake_count),
+            ("ball_count", c.ball_count),
+            ("reinfection_count", c.reinfection_count),
+            ("adaptive_min_count", c.adaptive_min_count),
+            ("harmonograph_trail_length", c.harmonograph_trail_length),
+            ("packing_max_circles", c.packing_max_circles),
+            ("comet_count", c.comet_count),
+            ("star_count", c.star_count),
+            ("boid_count", c.boid_count),
+            ("model_count", c.model_count),
+        ];
+        if let Some((key, _)) = counts.iter().find(|(_, count)| *count < 1) {
+            return Err(invalid(key, "has to be at least 1"));
+        }
+        if c.adaptive_max_count < c.adaptive_min_count {
+            return Err(invalid(
+                "adaptive_max_count",
+                "can't be less than adaptive_min_count",
+            ));
+        }
+
+        let speeds = [
+            ("ball_speed", c.ball_speed),
+            ("spin_speed", c.spin_speed),
+            ("bounce_speed", c.bounce_speed),
+            ("harmonograph_speed", c.harmonograph_speed),
+            ("comet_speed", c.comet_speed),
+            ("star_speed", c.star_speed),
+            ("fall_speed", c.fall_speed),
+            ("boid_max_speed", c.boid_max_speed),
+            ("plasma_speed", c.plasma_speed),
+        ];
+        if let Some((key, _)) = speeds
+            .iter()
+            .find(|(_, speed)| speed.is_nan() || *speed < 0.0)
+        {
+            return Err(invalid(key, "can't be negative"));
+        }
+
+        if c.snow_depth.is_nan() || c.snow_depth <= 0.0 {
+            return Err(invalid("snow_depth", "has to be above 0"));
+        }
+        if c.ball_size.is_nan() || c.ball_size <= 0.0 {
+            return Err(invalid("ball_size", "has to be above 0"));
+        }
+        if c.ball_size_max
+            .is_some_and(|size_max| size_max < c.ball_size)
+        {
+            return Err(invalid("ball_size_max", "can't be less than ball_size"));
+        }
+        if c.region_size.is_nan() || c.region_size <= 0.0 {
+            return Err(invalid("region_size", "has to be above 0"));
+        }
+        if ![1, 2, 4, 8].contains(&c.msaa) {
+            return Err(invalid("msaa", "has to be 1, 2, 4 or 8"));
+        }
+        if !(0.0..=1.0).contains(&c.opacity) {
+            return Err(invalid("opacity", "has to be between 0 and 1"));
+        }
+        if !(10.0..=120.0).contains(&c.fov) {
+            return Err(invalid("fov", "has to be between 10 and 120 degrees"));
+        }
+        if c.znear <= 0.0 || c.znear >= c.zfar {
+            return Err(invalid("znear", "has to be above 0 and less than zfar"));
+        }
+
+        Ok(c)
+    }
+}
+
+/// puts `value` between `min` and `max` (a NaN ends up at `min`), with a warning if it wasn't
+fn clamp_value<T: PartialOrd + Copy + std::fmt::Display>(key: &str, value: &mut T, min: T, max: T) {
+    //a NaN fails both comparisons, so it ends up at min
+    let clamped = if *value >= min {
+        if *value <= max {
+            return;
+        }
+        max
+    } else {
+        min
+    };
+    log::warn!("{} can't be {}, using {} instead", key, value, clamped);
+    *value = clamped;
+}
+
+/// locks the config and runs `f` with it. if something panicked while holding the lock, the config is used
+/// as it was left (with a warning) instead of taking the config ui or the render loop down with it.
+/// returns `None` only on the web, if the config is already locked, because waiting for it would freeze the page
+pub fn with_config<R>(
+    configurator: &Mutex<Configurator>,
+    f: impl FnOnce(&mut Configurator) -> R,
+) -> Option<R> {
+    #[cfg(target_arch = "wasm32")]
+    let result = match configurator.try_lock() {
+        Ok(guard) => Ok(guard),
+        Err(TryLockError::Poisoned(poisoned)) => Err(poisoned),
+        Err(TryLockError::WouldBlock) => return None,
+    };
+    #[cfg(not(target_arch = "wasm32"))]
+    let result = configurator.lock();
+
+    let mut guard = result.unwrap_or_else(|poisoned| {
+        log::warn!("Something crashed while using the config, continuing with it as it was left");
+        //so the warning is only logged once
+        configurator.clear_poison();
+        poisoned.into_inner()
+    });
+    Some(f(&mut guard))
+}
+
+/// whether the background image can be switched to while its path is still being typed
+fn is_usable_image_path(path: &str) -> bool {
+    //urls are only loaded once they're committed, there is no telling if they're complete
+    #[cfg(target_arch = "wasm32")]
+    let exists = false;
+    #[cfg(not(target_arch = "wasm32"))]
+    let exists = std::path::Path::new(path).is_file();
+    path.is_empty() || exists
+}
+
 pub struct ConfigUI {
     pub configurator: Arc<Mutex<Configurator>>,
     color_picker_color: [f32; 3],
     clicked_gen_url: Instant,
     old_model: DDDModel,
+    /// the built-in models and the ones found in the models directory at startup
+    models: Vec<DDDModel>,
+    /// the names of the connected monitors, looked up the first time the config ui is drawn
+    #[cfg(not(target_arch = "wasm32"))]
+    monitors: Option<Vec<String>>,
+    /// the background image path being typed, it only goes in the config once it's done
+    background_image: String,
+    editing_background_image: bool,
+    /// the config as it was last saved to the local storage
+    #[cfg(target_arch = "wasm32")]
+    saved_config: Configurator,
 }
 impl ConfigUI {
     pub fn new(configurator: Arc<Mutex<Configurator>>) -> Self {
+        let mut models = DDDModel::available();
+        //a model set in the config file doesn't have to be in the models directory
+        let current_model = configurator.lock().unwrap().ddd_model.clone();
+        if !models.contains(&current_model) {
+            models.push(current_model);
+        }
+        #[cfg(target_arch = "wasm32")]
+        let saved_config = configurator.lock().unwrap().clone();
+        let background_image = configurator.lock().unwrap().background_image.clone();
         Self {
             configurator,
             color_picker_color: [f32::NAN, f32::NAN, f32::NAN],
@@ -373,166 +1265,638 @@ impl ConfigUI {
                 .checked_sub(Duration::from_secs(10))
                 .unwrap_or(Instant::now()), //for some reason subtracting from an instant doesn't work on WASM
             old_model: DDDModel::Apple,
+            models,
+            #[cfg(not(target_arch = "wasm32"))]
+            monitors: None,
+            background_image,
+            editing_background_image: false,
+            #[cfg(target_arch = "wasm32")]
+            saved_config,
         }
     }
 }
 
 impl eframe::App for ConfigUI {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        #[cfg(target_arch = "wasm32")]
-        let result = self.configurator.try_lock();
-        #[cfg(not(target_arch = "wasm32"))]
-        let result = self.configurator.lock();
-        match result {
-            Ok(mut configurator) => {
-                if self.color_picker_color[0].is_nan() {
-                    self.color_picker_color = [
-                        configurator.color.r() as f32 / 255.0,
-                        configurator.color.g() as f32 / 255.0,
-                        configurator.color.b() as f32 / 255.0,
-                    ];
-                }
-                egui::CentralPanel::default().show(ctx, |ui| {
-                    ui.heading("Config");
-                    egui::ComboBox::from_label("Screensaver")
-                        .selected_text(format!("{:?}", configurator.screensaver))
-                        .show_ui(ui, |ui| {
-                            ui.selectable_value(&mut configurator.screensaver, ScreenSaverType::Snow, "Snow");
-                            ui.selectable_value(&mut configurator.screensaver, ScreenSaverType::Balls, "Balls");
-                            ui.selectable_value(&mut configurator.screensaver, ScreenSaverType::DDDModel, "3D Model");
-                        });
-                    ui.end_row();
-                    ui.separator();
+        with_config(&self.configurator, |configurator| {
+            if self.color_picker_color[0].is_nan() {
+                self.color_picker_color = [
+                    configurator.color.r() as f32 / 255.0,
+                    configurator.color.g() as f32 / 255.0,
+                    configurator.color.b() as f32 / 255.0,
+                ];
+            }
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.heading("Config");
+                egui::ComboBox::from_label("Screensaver")
+                    .selected_text(format!("{:?}", configurator.screensaver))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut configurator.screensaver, ScreenSaverType::Snow, "Snow");
+                        ui.selectable_value(&mut configurator.screensaver, ScreenSaverType::Balls, "Balls");
+                        ui.selectable_value(&mut configurator.screensaver, ScreenSaverType::DDDModel, "3D Model");
+                        ui.selectable_value(&mut configurator.screensaver, ScreenSaverType::Clock, "Clock");
+                        ui.selectable_value(&mut configurator.screensaver, ScreenSaverType::Harmonograph, "Harmonograph");
+                        ui.selectable_value(&mut configurator.screensaver, ScreenSaverType::CirclePacking, "Circle Packing");
+                        ui.selectable_value(&mut configurator.screensaver, ScreenSaverType::Comet, "Comet");
+                        ui.selectable_value(&mut configurator.screensaver, ScreenSaverType::Starfield, "Starfield");
+                        ui.selectable_value(&mut configurator.screensaver, ScreenSaverType::Matrix, "Matrix");
+                        ui.selectable_value(&mut configurator.screensaver, ScreenSaverType::Fireworks, "Fireworks");
+                        ui.selectable_value(&mut configurator.screensaver, ScreenSaverType::Boids, "Boids");
+                        ui.selectable_value(&mut configurator.screensaver, ScreenSaverType::Plasma, "Plasma");
+                    });
+                ui.end_row();
+                ui.separator();
 
-                    egui::ScrollArea::vertical().show(ui, |ui| {
-                        match configurator.screensaver {
-                            ScreenSaverType::Snow => {
-                                ui.add(egui::Slider::new(&mut configurator.snowflake_count, 200..=20000).text("Snowflakes"));
-                            }
-                            ScreenSaverType::Balls => {
-                                ui.add(egui::Slider::new(&mut configurator.ball_speed, 0.01..=1.0).text("Ball Speed"));
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    match configurator.screensaver {
+                        ScreenSaverType::Snow => {
+                            ui.add(egui::Slider::new(&mut configurator.snowflake_count, 200..=20000).text("Snowflakes"));
+                            ui.add(egui::Slider::new(&mut configurator.snow_depth, 0.2..=3.0).text("Depth")).on_hover_text("how deep the volume the snow falls in is. deeper snow has more variation in flake size");
+                            ui.add(egui::Slider::new(&mut configurator.snow_z_drift, 0.0..=0.5).text("Depth Drift")).on_hover_text("how fast the flakes drift towards and away from the camera");
+                            ui.add(egui::Slider::new(&mut configurator.snow_buildup_secs, 0.0..=300.0).text("Buildup Time")).on_hover_text("the snow starts light and gets heavier over this many seconds. 0 starts with all the flakes");
+                            ui.add(egui::Slider::new(&mut configurator.snow_wind_strength, -0.5..=0.5).text("Wind")).on_hover_text("how hard the wind blows the snow sideways. negative blows it the other way");
+                            ui.add(egui::Slider::new(&mut configurator.snow_wind_variability, 0.0..=1.0).text("Gustiness")).on_hover_text("how much the wind changes over time. 0 is a steady breeze, 1 goes from calm to gusts twice as strong");
+                            ui.add(egui::Slider::new(&mut configurator.snow_max_accumulation, 0..=10000).text("Snow on the Ground")).on_hover_text("how many flakes can pile up on the ground. 0 turns it off");
+                        }
+                        ScreenSaverType::Balls => {
+                            ui.add(egui::Slider::new(&mut configurator.ball_speed, 0.01..=1.0).text("Ball Speed"));
+                            ui.end_row();
+                            ui.horizontal(|ui| {
+                                let label = ui.label("Ball Count: ");
+                                ui.add(egui::DragValue::new(&mut configurator.ball_count).range(1..=100000)).labelled_by(label.id);
+                            });
+                            ui.end_row();
+                            ui.add(egui::Slider::new(&mut configurator.ball_size, 0.02..=1.0).text("Ball Size"));
+                            let mut random_sizes = configurator.ball_size_max.is_some();
+                            ui.add(egui::Checkbox::new(&mut random_sizes, "Random Sizes")).on_hover_text("give the balls random sizes. big balls are heavier and shove the small ones around");
+                            configurator.ball_size_max = match random_sizes {
+                                true => configurator.ball_size_max.or(Some((configurator.ball_size * 3.0).min(1.0))),
+                                false => None,
+                            };
+                            ui.end_row();
+                            if let Some(ball_size_max) = &mut configurator.ball_size_max {
+                                ui.add(egui::Slider::new(ball_size_max, configurator.ball_size..=1.0).text("Max Ball Size")).on_hover_text("the size of the biggest balls");
                                 ui.end_row();
-                                ui.horizontal(|ui| {
-                                    let label = ui.label("Ball Count: ");
-                                    ui.add(egui::DragValue::new(&mut configurator.ball_count).range(1..=100000)).labelled_by(label.id);
+                            }
+                            egui::ComboBox::from_label("Color Mode")
+                                .selected_text(format!("{:?}", configurator.color_mode))
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut configurator.color_mode, BallColorMode::Random, "Random");
+                                    ui.selectable_value(&mut configurator.color_mode, BallColorMode::Color, "Color");
+                                    ui.selectable_value(&mut configurator.color_mode, BallColorMode::Infection, "Infection");
+                                    ui.selectable_value(&mut configurator.color_mode, BallColorMode::Temperature, "Temperature");
+                                    ui.selectable_value(&mut configurator.color_mode, BallColorMode::DvdBounce, "DVD Bounce");
                                 });
+                            ui.end_row();
+                            //don't ask me why it has to be this way
+                            if configurator.color_mode == BallColorMode::Color {
+                                ui.color_edit_button_rgb(&mut self.color_picker_color);
+                                configurator.color = egui::Color32::from_rgb((self.color_picker_color[0] * 255.0) as u8, (self.color_picker_color[1] * 255.0) as u8, (self.color_picker_color[2] * 255.0) as u8);
                                 ui.end_row();
-                                ui.add(egui::Slider::new(&mut configurator.ball_size, 0.02..=1.0).text("Ball Size"));
-                                egui::ComboBox::from_label("Color Mode")
-                                    .selected_text(format!("{:?}", configurator.color_mode))
-                                    .show_ui(ui, |ui| {
-                                        ui.selectable_value(&mut configurator.color_mode, BallColorMode::Random, "Random");
-                                        ui.selectable_value(&mut configurator.color_mode, BallColorMode::Color, "Color");
-                                        ui.selectable_value(&mut configurator.color_mode, BallColorMode::Infection, "Infection");
-                                        ui.selectable_value(&mut configurator.color_mode, BallColorMode::Temperature, "Temperature");
-                                    });
-                                ui.end_row();
-                                //don't ask me why it has to be this way
-                                if configurator.color_mode == BallColorMode::Color {
-                                    ui.color_edit_button_rgb(&mut self.color_picker_color);
-                                    configurator.color = egui::Color32::from_rgb((self.color_picker_color[0] * 255.0) as u8, (self.color_picker_color[1] * 255.0) as u8, (self.color_picker_color[2] * 255.0) as u8);
-                                    ui.end_row();
+                            };
+                            if configurator.color_mode == BallColorMode::Temperature {
+                                let mut fixed_min = configurator.temperature_min_speed.is_some();
+                                ui.add(egui::Checkbox::new(&mut fixed_min, "Fixed Min Speed")).on_hover_text("the speed that is the coldest color. otherwise it follows the slowest ball");
+                                configurator.temperature_min_speed = match fixed_min {
+                                    true => configurator.temperature_min_speed.or(Some(0.0)),
+                                    false => None,
                                 };
-                                ui.add(egui::Checkbox::new(&mut configurator.show_density, "Show Density")).on_hover_text("change the opacity based on how many balls are in the surrounding regions and is influenced by their size.");
+                                if let Some(min_speed) = &mut configurator.temperature_min_speed {
+                                    ui.add(egui::Slider::new(min_speed, 0.0..=2.0).text("Min Speed"));
+                                }
+                                let mut fixed_max = configurator.temperature_max_speed.is_some();
+                                ui.add(egui::Checkbox::new(&mut fixed_max, "Fixed Max Speed")).on_hover_text("the speed that is the hottest color. otherwise it follows the fastest ball");
+                                configurator.temperature_max_speed = match fixed_max {
+                                    true => configurator.temperature_max_speed.or(Some(2.0 * configurator.ball_speed)),
+                                    false => None,
+                                };
+                                if let Some(max_speed) = &mut configurator.temperature_max_speed {
+                                    ui.add(egui::Slider::new(max_speed, 0.0..=2.0).text("Max Speed"));
+                                }
                                 ui.end_row();
-                                if configurator.show_density {
-                                    ui.horizontal(|ui| {
-                                        let label = ui.label("Target Density: ");
-                                        ui.add(egui::DragValue::new(&mut configurator.target_display_density).range(1..=100)).labelled_by(label.id).on_hover_text("how many balls surrounding a given ball is needed for full opacity. if density display is all white, lower it. if it's too dark, make it higher");
-                                        ui.end_row();
-                                    });
+                            }
+                            if configurator.color_mode == BallColorMode::Infection {
+                                ui.add(egui::Checkbox::new(&mut configurator.infection_restart, "Restart Infection")).on_hover_text("once every ball is infected, start again with a new color. otherwise they stay that color");
+                                if configurator.infection_restart {
+                                    ui.add(egui::Slider::new(&mut configurator.reinfection_count, 1..=20).text("Reinfected Balls")).on_hover_text("how many balls start the next infection");
                                 }
-                                ui.add(egui::Slider::new(&mut configurator.region_size, 0.5..=5.0).text("Region Size")).on_hover_text("For optimisation the space is split into chunks, and balls check for collisions in their chunk and those surrounding it. if you have a dense simulation, set it to 0.5, if you have a very sparse one set it to a higher value. if you don't know what this does keep it at 1.0.");
                                 ui.end_row();
-                                ui.add(egui::Checkbox::new(&mut configurator.correct_ball_velocity, "Correct Ball Velocity")).on_hover_text("Whether the speed of the balls should be adjusted if the average ball velocity is off");
+                            }
+                            if configurator.color_mode == BallColorMode::Random || configurator.color_mode == BallColorMode::DvdBounce {
+                                ui.add(egui::Slider::new(&mut configurator.hue_min, 0.0..=1.0).text("Hue From")).on_hover_text("the range of hues the random colors are picked from. if \"from\" is bigger than \"to\", the range wraps around through red");
+                                ui.add(egui::Slider::new(&mut configurator.hue_max, 0.0..=1.0).text("Hue To"));
+                                ui.add(egui::Slider::new(&mut configurator.saturation, 0.0..=1.0).text("Saturation")).on_hover_text("lower it for pastel colors");
+                                ui.add(egui::Slider::new(&mut configurator.value, 0.0..=1.0).text("Brightness"));
                                 ui.end_row();
-                                ui.heading("Presets");
-                                egui::ScrollArea::horizontal().show(ui, |ui| {
-                                    ui.horizontal(|ui| {
-                                        if ui.button("Infection").clicked() {
-                                            *configurator = Configurator::from_preset(ConfigPresets::BallsInfection);
-                                        };
-                                        if ui.button("Lava").clicked() {
-                                            *configurator = Configurator::from_preset(ConfigPresets::BallsLava);
-                                        };
-                                        if ui.button("Gas Simulation").clicked() {
-                                            *configurator = Configurator::from_preset(ConfigPresets::BallsGasSimulation);
-                                        }
-                                        if ui.button("Just like the DVD logo").clicked() {
-                                            *configurator = Configurator::from_preset(ConfigPresets::BallsDVD);
-                                        }
-                                        if ui.button("Colors!").clicked() {
-                                            *configurator = Configurator::from_preset(ConfigPresets::Colors);
-                                        }
-                                    });
-                                    ui.add_space(10.0);
+                            }
+                            ui.add(egui::Checkbox::new(&mut configurator.show_density, "Show Density")).on_hover_text("change the opacity based on how many balls are in the surrounding regions and is influenced by their size.");
+                            ui.end_row();
+                            if configurator.show_density {
+                                ui.horizontal(|ui| {
+                                    let label = ui.label("Target Density: ");
+                                    ui.add(egui::DragValue::new(&mut configurator.target_display_density).range(1..=100)).labelled_by(label.id).on_hover_text("how many balls surrounding a given ball is needed for full opacity. if density display is all white, lower it. if it's too dark, make it higher");
+                                    ui.end_row();
                                 });
+                                ui.add(egui::Slider::new(&mut configurator.density_power, 0.5..=5.0).text("Density Contrast")).on_hover_text("higher makes the sparse areas fade out more");
                                 ui.end_row();
                             }
-                            ScreenSaverType::DDDModel => {
-                                egui::ComboBox::from_label("Model")
-                                    .selected_text(format!("{:?}", configurator.ddd_model))
-                                    .show_ui(ui, |ui| {
-                                        ui.selectable_value(&mut configurator.ddd_model, DDDModel::Apple, "Apple");
-                                        ui.selectable_value(&mut configurator.ddd_model, DDDModel::Shark, "Shark");
-                                        ui.selectable_value(&mut configurator.ddd_model, DDDModel::KimKitsuragi, "Kim Kitsuragi");
-                                        //ui.selectable_value(&mut configurator.ddd_model, DDDModel::Custom, "Custom");
-                                    });
-                                if self.old_model != configurator.ddd_model {
-                                    configurator.should_reload = true;
-                                }
-                                ui.add(egui::Slider::new(&mut configurator.model_scale, 0.1..=3.0).text("Model Size"));
-                                ui.add(egui::Slider::new(&mut configurator.spin_speed, 0.0..=5.0).text("Spin Speed"));
-                                ui.add(egui::Slider::new(&mut configurator.bounce_speed, 0.0..=5.0).text("Bounce Speed"));
-                                ui.add(egui::Slider::new(&mut configurator.bounce_height, 0.0..=1.0).text("Bounce Height"));
-
-                                self.old_model = configurator.ddd_model;
+                            ui.add(egui::Slider::new(&mut configurator.region_size, 0.5..=5.0).text("Region Size")).on_hover_text("For optimisation the space is split into chunks, and balls check for collisions in their chunk and those surrounding it. if you have a dense simulation, set it to 0.5, if you have a very sparse one set it to a higher value. if you don't know what this does keep it at 1.0.");
+                            ui.end_row();
+                            ui.add_enabled(configurator.gravity == 0.0 && configurator.friction == 0.0, egui::Checkbox::new(&mut configurator.correct_ball_velocity, "Correct Ball Velocity")).on_hover_text("Whether the speed of the balls should be adjusted if the average ball velocity is off").on_disabled_hover_text("turned off while there is gravity or friction");
+                            ui.add(egui::Slider::new(&mut configurator.gravity, 0.0..=2.0).text("Gravity")).on_hover_text("how fast the balls fall, in screen heights per second squared");
+                            ui.add(egui::Slider::new(&mut configurator.restitution, 0.0..=1.0).text("Restitution")).on_hover_text("how much of their speed the balls keep when they bounce off the edges of the screen");
+                            ui.end_row();
+                            ui.add(egui::Slider::new(&mut configurator.friction, 0.0..=1.0).text("Friction")).on_hover_text("how much of their speed the balls lose every second, so they slow down and settle");
+                            ui.end_row();
+                            let old_render_mode = configurator.ball_render_mode;
+                            egui::ComboBox::from_label("Render Mode")
+                                .selected_text(format!("{:?}", configurator.ball_render_mode))
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut configurator.ball_render_mode, BallRenderMode::Sprite, "Sprite");
+                                    ui.selectable_value(&mut configurator.ball_render_mode, BallRenderMode::Sphere, "Sphere");
+                                })
+                                .response
+                                .on_hover_text("flat circles, or lit 3d spheres. the spheres need the balls on the cpu, so they turn off the gpu physics");
+                            //the spheres have their own pipeline, so the balls have to be set up again
+                            if configurator.ball_render_mode != old_render_mode {
+                                configurator.should_reload = true;
                             }
-                        }
-                        ui.separator();
-                        ui.horizontal(|ui| {
-                            #[cfg(not(target_arch = "wasm32"))]
-                            if ui.add(egui::Button::new("Save and Exit")).clicked() {
-                                configurator.save_config();
-                                exit(0);
+                            if configurator.ball_render_mode == BallRenderMode::Sprite {
+                                ui.add(egui::Slider::new(&mut configurator.edge_softness, 0.0..=1.0).text("Edge Softness")).on_hover_text("1 makes the balls fade out from the center, 0 draws the ball texture instead");
                             }
-                            #[cfg(not(target_arch = "wasm32"))]
-                            if ui.add(egui::Button::new("Exit without Saving")).clicked() {
-                                exit(0);
+                            ui.end_row();
+                            ui.add(egui::Checkbox::new(&mut configurator.shadow, "Shadow")).on_hover_text("draw a soft shadow under every ball, like they're on a table");
+                            if configurator.shadow {
+                                ui.add(egui::Slider::new(&mut configurator.shadow_offset, 0.0..=0.05).text("Shadow Offset"));
                             }
-                            if ui.add(egui::Button::new("Reset Settings")).clicked() {
-                                *configurator = Configurator::default();
-                                self.color_picker_color = [f32::INFINITY, f32::INFINITY, f32::INFINITY];
+                            ui.end_row();
+                            ui.add(egui::Checkbox::new(&mut configurator.auto_retoss, "Auto Re-toss")).on_hover_text("when the balls have (almost) stopped for a while, throw them all back up");
+                            if configurator.auto_retoss {
+                                ui.add(egui::Slider::new(&mut configurator.retoss_energy, 0.0..=0.2).text("Re-toss Energy")).on_hover_text("how slow the balls have to be, compared to the ball speed. 0.01 means they have a hundredth of the energy they would have at full speed");
+                                ui.add(egui::Slider::new(&mut configurator.retoss_delay, 0.0..=10.0).text("Re-toss Delay")).on_hover_text("how many seconds the balls have to stay that slow");
                             }
-                            #[cfg(not(target_arch = "wasm32"))]
-                            ui.separator();
-                            #[cfg(not(target_arch = "wasm32"))]
-                            if ui.add(egui::Button::new("Test")).clicked() {
-                                configurator.preview_window = true;
-                                let config = Arc::clone(&self.configurator);
-                                thread::spawn(move || {
-                                    pollster::block_on(run_with_config(config));
+                            ui.end_row();
+                            egui::ComboBox::from_label("Mouse Mode")
+                                .selected_text(format!("{:?}", configurator.mouse_mode))
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut configurator.mouse_mode, MouseMode::Push, "Push");
+                                    ui.selectable_value(&mut configurator.mouse_mode, MouseMode::Attract, "Attract");
+                                    ui.selectable_value(&mut configurator.mouse_mode, MouseMode::Repel, "Repel");
+                                })
+                                .response
+                                .on_hover_text("what happens to the balls under the cursor when it moves");
+                            ui.add(egui::Slider::new(&mut configurator.brush_size, 0.05..=1.0).text("Brush Size")).on_hover_text("how far from the cursor the balls are affected");
+                            ui.end_row();
+                            ui.add(egui::Checkbox::new(&mut configurator.debug_velocities, "Show Velocities")).on_hover_text("draw a line from every ball in the direction it's moving, longer the faster it goes. can also be toggled with F6");
+                            ui.add(egui::Checkbox::new(&mut configurator.gpu_physics, "GPU Physics")).on_hover_text("simulate the balls on the graphics card, for huge ball counts. the mouse, velocity correction, density and the infection, random and temperature colors don't react to collisions there. the dvd bounce colors and the sound need the cpu. falls back to the cpu if the graphics card can't do it");
+                            #[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+                            ui.add(egui::Checkbox::new(&mut configurator.sound, "Sound")).on_hover_text("click when the balls hit the walls and each other, higher the harder they hit");
+                            ui.end_row();
+                            ui.heading("Presets");
+                            egui::ScrollArea::horizontal().show(ui, |ui| {
+                                ui.horizontal(|ui| {
+                                    if ui.button("Infection").clicked() {
+                                        *configurator = Configurator::from_preset(ConfigPresets::BallsInfection);
+                                    };
+                                    if ui.button("Lava").clicked() {
+                                        *configurator = Configurator::from_preset(ConfigPresets::BallsLava);
+                                    };
+                                    if ui.button("Gas Simulation").clicked() {
+                                        *configurator = Configurator::from_preset(ConfigPresets::BallsGasSimulation);
+                                    }
+                                    if ui.button("Just like the DVD logo").clicked() {
+                                        *configurator = Configurator::from_preset(ConfigPresets::BallsDVD);
+                                    }
+                                    if ui.button("Colors!").clicked() {
+                                        *configurator = Configurator::from_preset(ConfigPresets::Colors);
+                                    }
+                                });
+                                ui.add_space(10.0);
+                            });
+                            ui.end_row();
+                        }
+                        ScreenSaverType::DDDModel => {
+                            egui::ComboBox::from_label("Model")
+                                .selected_text(configurator.ddd_model.display_name())
+                                .show_ui(ui, |ui| {
+                                    for model in &self.models {
+                                        ui.selectable_value(&mut configurator.ddd_model, model.clone(), model.display_name());
+                                    }
                                 });
+                            if self.old_model != configurator.ddd_model {
+                                configurator.should_reload = true;
                             }
-                            #[cfg(target_arch = "wasm32")]
-                            {
-                                let url_btn = ui.add(egui::Button::new("Generate URL"));
-                                if url_btn.clicked() {
-                                    self.clicked_gen_url = Instant::now();
-                                    ctx.copy_text(configurator.generate_url());
-                                }
-                                if Instant::now().duration_since(self.clicked_gen_url) < Duration::from_secs(1) {
-                                    url_btn.show_tooltip_text("Copied Link");
+                            ui.add(egui::Slider::new(&mut configurator.model_scale, 0.1..=3.0).text("Model Size"));
+                            ui.add(egui::Slider::new(&mut configurator.model_count, 1..=50).text("Model Count")).on_hover_text("scatter this many copies of the model around, each bouncing and spinning on its own. 1 puts the model in the middle");
+                            ui.add(egui::Slider::new(&mut configurator.model_trail_count, 0..=10).text("Afterimages")).on_hover_text("fading copies of where the model just was, trailing behind it as it moves");
+                            ui.add(egui::Slider::new(&mut configurator.spin_speed, 0.0..=5.0).text("Spin Speed"));
+                            ui.add(egui::Slider::new(&mut configurator.bounce_speed, 0.0..=5.0).text("Bounce Speed"));
+                            ui.add(egui::Slider::new(&mut configurator.bounce_height, 0.0..=1.0).text("Bounce Height"));
+                            ui.add(egui::Slider::new(&mut configurator.bounce_floor, -2.0..=2.0).text("Bounce Floor")).on_hover_text("the lowest the model goes while bouncing");
+                            ui.add(egui::Checkbox::new(&mut configurator.orbit_camera, "Orbit Camera")).on_hover_text("hold the model still and fly the camera around it instead");
+                            if configurator.orbit_camera {
+                                ui.add(egui::Slider::new(&mut configurator.orbit_speed, -90.0..=90.0).text("Orbit Speed")).on_hover_text("how many degrees the camera goes around the model per second. negative goes the other way");
+                                ui.add(egui::Slider::new(&mut configurator.orbit_radius, 1.0..=10.0).text("Orbit Distance"));
+                                ui.add(egui::Slider::new(&mut configurator.orbit_height, -5.0..=5.0).text("Orbit Height")).on_hover_text("how far above the model the camera is");
+                            }
+                            ui.add(egui::Slider::new(&mut configurator.fov, 10.0..=120.0).text("Field of View")).on_hover_text("how wide the camera's lens is, in degrees. lower zooms in");
+                            ui.add(egui::Slider::new(&mut configurator.znear, 0.01..=1.0).logarithmic(true).text("Near Clip")).on_hover_text("anything closer to the camera than this isn't drawn");
+                            ui.add(egui::Slider::new(&mut configurator.zfar, 10.0..=1000.0).logarithmic(true).text("Far Clip")).on_hover_text("anything further from the camera than this isn't drawn");
+
+                            self.old_model = configurator.ddd_model.clone();
+                        }
+                        ScreenSaverType::Clock => {
+                            egui::ComboBox::from_label("Format")
+                                .selected_text(format!("{:?}", configurator.clock_format))
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut configurator.clock_format, ClockFormat::TwentyFourHour, "24 Hour");
+                                    ui.selectable_value(&mut configurator.clock_format, ClockFormat::TwelveHour, "12 Hour");
+                                });
+                            ui.horizontal(|ui| {
+                                ui.label("Color: ");
+                                ui.color_edit_button_srgba(&mut configurator.clock_color);
+                            });
+                            ui.add(egui::Slider::new(&mut configurator.clock_font_size, 0.1..=1.0).text("Font Size"));
+                            ui.add(egui::Checkbox::new(&mut configurator.clock_show_seconds, "Show Seconds"));
+                        }
+                        ScreenSaverType::Harmonograph => {
+                            ui.add(egui::Slider::new(&mut configurator.harmonograph_frequency_x, 0.1..=10.0).text("Horizontal Frequency"));
+                            ui.add(egui::Slider::new(&mut configurator.harmonograph_frequency_y, 0.1..=10.0).text("Vertical Frequency")).on_hover_text("frequencies that are close to a simple ratio (like 2 and 3.01) make slowly evolving curves");
+                            ui.add(egui::Slider::new(&mut configurator.harmonograph_phase_x, 0.0..=1.0).text("Horizontal Phase"));
+                            ui.add(egui::Slider::new(&mut configurator.harmonograph_phase_y, 0.0..=1.0).text("Vertical Phase"));
+                            ui.add(egui::Slider::new(&mut configurator.harmonograph_damping, 0.0..=1.0).text("Damping")).on_hover_text("how quickly the swing dies down. once it's small enough the pen starts over");
+                            ui.add(egui::Slider::new(&mut configurator.harmonograph_speed, 0.01..=2.0).text("Speed"));
+                            ui.add(egui::Slider::new(&mut configurator.harmonograph_trail_length, 100..=20000).text("Trail Length"));
+                            ui.add(egui::Slider::new(&mut configurator.harmonograph_line_width, 0.002..=0.05).text("Line Width"));
+                            ui.horizontal(|ui| {
+                                ui.label("Color: ");
+                                ui.color_edit_button_srgba(&mut configurator.harmonograph_color);
+                            });
+                        }
+                        ScreenSaverType::CirclePacking => {
+                            ui.add(egui::Slider::new(&mut configurator.packing_max_circles, 10..=3000).text("Max Circles"));
+                            ui.add(egui::Slider::new(&mut configurator.packing_growth_rate, 0.005..=0.5).text("Growth Rate")).on_hover_text("how fast the circles grow until they touch something");
+                            ui.horizontal(|ui| {
+                                let label = ui.label("Palette: ");
+                                ui.text_edit_singleline(&mut configurator.packing_palette).labelled_by(label.id).on_hover_text("hex colors separated by commas. leave empty for random colors");
+                            });
+                        }
+                        ScreenSaverType::Comet => {
+                            ui.add(egui::Slider::new(&mut configurator.comet_count, 1..=20).text("Comet Count"));
+                            ui.add(egui::Slider::new(&mut configurator.comet_speed, 0.05..=2.0).text("Speed"));
+                            ui.add(egui::Slider::new(&mut configurator.comet_tail_length, 0.1..=5.0).text("Tail Length")).on_hover_text("how long the tail is, in seconds of the comet's path");
+                            ui.add(egui::Slider::new(&mut configurator.comet_glow, 0.0..=1.0).text("Glow")).on_hover_text("how wide and bright the faint glow around the tail is");
+                            ui.horizontal(|ui| {
+                                ui.label("Color: ");
+                                ui.color_edit_button_srgba(&mut configurator.comet_color);
+                            });
+                        }
+                        ScreenSaverType::Starfield => {
+                            ui.add(egui::Slider::new(&mut configurator.star_count, 100..=20000).text("Stars"));
+                            ui.add(egui::Slider::new(&mut configurator.star_speed, 0.5..=50.0).text("Speed"));
+                            ui.add(egui::Slider::new(&mut configurator.warp_streak_length, 0.0..=0.5).text("Streak Length")).on_hover_text("how long the streaks behind the stars are, in seconds of their path. 0 turns them off");
+                        }
+                        ScreenSaverType::Matrix => {
+                            ui.add(egui::Slider::new(&mut configurator.column_density, 0.05..=1.0).text("Column Density")).on_hover_text("how many of the columns have falling glyphs in them");
+                            ui.add(egui::Slider::new(&mut configurator.fall_speed, 0.05..=2.0).text("Fall Speed"));
+                            ui.horizontal(|ui| {
+                                ui.label("Color: ");
+                                ui.color_edit_button_srgba(&mut configurator.glyph_color);
+                            });
+                        }
+                        ScreenSaverType::Fireworks => {
+                            ui.add(egui::Slider::new(&mut configurator.fireworks_launch_rate, 0.1..=10.0).text("Launch Rate")).on_hover_text("how many rockets are launched every second, on average");
+                            ui.add(egui::Slider::new(&mut configurator.fireworks_gravity, 0.1..=2.0).text("Gravity")).on_hover_text("how fast the rockets and sparks fall, in screen heights per second squared. the rockets are launched so they still burst in the upper half");
+                        }
+                        ScreenSaverType::Boids => {
+                            ui.add(egui::Slider::new(&mut configurator.boid_count, 10..=3000).text("Boids"));
+                            ui.add(egui::Slider::new(&mut configurator.boid_separation, 0.0..=5.0).text("Separation")).on_hover_text("how strongly the boids avoid the ones that are too close");
+                            ui.add(egui::Slider::new(&mut configurator.boid_alignment, 0.0..=5.0).text("Alignment")).on_hover_text("how strongly the boids turn to fly the same way as the ones around them");
+                            ui.add(egui::Slider::new(&mut configurator.boid_cohesion, 0.0..=5.0).text("Cohesion")).on_hover_text("how strongly the boids fly towards the middle of the ones around them");
+                            ui.add(egui::Slider::new(&mut configurator.boid_max_speed, 0.05..=2.0).text("Max Speed")).on_hover_text("in screen heights per second");
+                        }
+                        ScreenSaverType::Plasma => {
+                            egui::ComboBox::from_label("Palette")
+                                .selected_text(format!("{:?}", configurator.plasma_palette))
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut configurator.plasma_palette, PlasmaPalette::Rainbow, "Rainbow");
+                                    ui.selectable_value(&mut configurator.plasma_palette, PlasmaPalette::Fire, "Fire");
+                                    ui.selectable_value(&mut configurator.plasma_palette, PlasmaPalette::Ocean, "Ocean");
+                                    ui.selectable_value(&mut configurator.plasma_palette, PlasmaPalette::Neon, "Neon");
+                                });
+                            ui.add(egui::Slider::new(&mut configurator.plasma_speed, 0.0..=3.0).text("Speed")).on_hover_text("how fast the plasma swirls. 0 freezes it");
+                        }
+                    }
+                    ui.separator();
+                    egui::CollapsingHeader::new("General").show(ui, |ui| {
+                        ui.add(egui::Slider::new(&mut configurator.time_scale, -2.0..=4.0).text("Time Scale")).on_hover_text("how fast time passes. below 1 is slow motion, negative runs the motion backwards (collisions can't be undone though)");
+                        ui.add(egui::Slider::new(&mut configurator.fixed_timestep, 0.0..=240.0).text("Physics Rate")).on_hover_text("how many times per second the simulation is updated. 0 means once per frame");
+                        if configurator.fixed_timestep > 0.0 {
+                            ui.add(egui::Checkbox::new(&mut configurator.motion_smoothing, "Motion Smoothing")).on_hover_text("interpolate between physics updates, so the motion looks smooth even if the physics rate is lower than the frame rate");
+                        }
+                        let mut limit_fps = configurator.max_fps.is_some();
+                        ui.add(egui::Checkbox::new(&mut limit_fps, "Limit FPS")).on_hover_text("draw less often to save battery. the motion stays at the same speed");
+                        configurator.max_fps = match limit_fps {
+                            true => configurator.max_fps.or(Some(30)),
+                            false => None,
+                        };
+                        if let Some(max_fps) = &mut configurator.max_fps {
+                            ui.add(egui::Slider::new(max_fps, 5..=240).text("Max FPS"));
+                        }
+                        let mut trails = configurator.trail_fade.is_some();
+                        ui.add(egui::Checkbox::new(&mut trails, "Trails")).on_hover_text("instead of clearing the screen, fade out the previous frame, so everything that moves leaves a trail");
+                        configurator.trail_fade = match trails {
+                            true => configurator.trail_fade.or(Some(0.8)),
+                            false => None,
+                        };
+                        if let Some(trail_fade) = &mut configurator.trail_fade {
+                            ui.add(egui::Slider::new(trail_fade, 0.1..=0.99).text("Trail Length")).on_hover_text("how much of the previous frame is left every frame");
+                        }
+                        let old_seed = configurator.seed;
+                        let mut fixed_seed = configurator.seed.is_some();
+                        ui.add(egui::Checkbox::new(&mut fixed_seed, "Fixed Seed")).on_hover_text("set the scene up the same way every time, for example to reproduce a bug");
+                        configurator.seed = match fixed_seed {
+                            true => configurator.seed.or(Some(1)),
+                            false => None,
+                        };
+                        if let Some(seed) = &mut configurator.seed {
+                            ui.add(egui::DragValue::new(seed).range(1..=i64::MAX as u64).prefix("Seed: "));
+                        }
+                        if configurator.seed != old_seed {
+                            configurator.should_reload = true;
+                        }
+                        egui::ComboBox::from_label("Color Blending")
+                            .selected_text(format!("{:?}", configurator.color_interpolation))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut configurator.color_interpolation, ColorSpace::Hsv, "HSV");
+                                ui.selectable_value(&mut configurator.color_interpolation, ColorSpace::Rgb, "RGB");
+                            })
+                            .response
+                            .on_hover_text("RGB blends colors directly, which can look muddy. HSV goes around the color wheel, so it stays vivid");
+                        ui.add(egui::Checkbox::new(&mut configurator.freeze_age, "Freeze Age")).on_hover_text("stop things from aging while they keep moving. useful for looking at age-based effects in isolation");
+                        ui.add(egui::Checkbox::new(&mut configurator.adaptive_count, "Adaptive Count")).on_hover_text("lower the number of balls or snowflakes when the frame rate drops, and raise it again when there's room");
+                        if configurator.adaptive_count {
+                            ui.add(egui::Slider::new(&mut configurator.adaptive_target_fps, 15.0..=240.0).text("Target FPS"));
+                            ui.add(egui::Slider::new(&mut configurator.adaptive_min_count, 1..=10000).text("Min Count"));
+                            ui.add(egui::Slider::new(&mut configurator.adaptive_max_count, 1..=50000).text("Max Count"));
+                        }
+                        ui.add(egui::Slider::new(&mut configurator.opacity, 0.0..=1.0).text("Opacity")).on_hover_text("below 1 the window becomes see-through, so the screensaver can be a faint overlay over other windows. not every platform supports it");
+                        egui::ComboBox::from_label("Texture Quality")
+                            .selected_text(format!("{:?}", configurator.texture_quality))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut configurator.texture_quality, TextureQuality::Full, "Full");
+                                ui.selectable_value(&mut configurator.texture_quality, TextureQuality::Half, "Half");
+                                ui.selectable_value(&mut configurator.texture_quality, TextureQuality::Quarter, "Quarter");
+                            })
+                            .response
+                            .on_hover_text("lower quality textures use less video memory, at the cost of sharpness. useful on old phones");
+                        egui::ComboBox::from_label("Antialiasing")
+                            .selected_text(match configurator.msaa {
+                                1 => "Off".to_string(),
+                                samples => format!("{}x MSAA", samples),
+                            })
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut configurator.msaa, 1, "Off");
+                                ui.selectable_value(&mut configurator.msaa, 2, "2x MSAA");
+                                ui.selectable_value(&mut configurator.msaa, 4, "4x MSAA");
+                                ui.selectable_value(&mut configurator.msaa, 8, "8x MSAA");
+                            })
+                            .response
+                            .on_hover_text("smooths out jagged edges, at the cost of some performance. if the graphics card can't do the chosen one, it's turned off");
+                        egui::ComboBox::from_label("On Resize")
+                            .selected_text(format!("{:?}", configurator.resize_behavior))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut configurator.resize_behavior, ResizeBehavior::Preserve, "Preserve");
+                                ui.selectable_value(&mut configurator.resize_behavior, ResizeBehavior::Respawn, "Respawn");
+                            })
+                            .response
+                            .on_hover_text("what happens to the particles when the window is resized. preserve stretches them to the new size, respawn scatters them randomly");
+                        #[cfg(not(target_arch = "wasm32"))]
+                        egui::ComboBox::from_label("Present Mode")
+                            .selected_text(format!("{:?}", configurator.present_mode))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut configurator.present_mode, PresentMode::Vsync, "Vsync");
+                                ui.selectable_value(&mut configurator.present_mode, PresentMode::NoVsync, "No Vsync");
+                                ui.selectable_value(&mut configurator.present_mode, PresentMode::Mailbox, "Mailbox");
+                                ui.selectable_value(&mut configurator.present_mode, PresentMode::Fifo, "Fifo");
+                            })
+                            .response
+                            .on_hover_text("vsync caps the frame rate to the display's, no vsync draws as fast as possible (may tear), mailbox doesn't wait but doesn't tear either. falls back to a supported mode");
+                        #[cfg(not(target_arch = "wasm32"))]
+                        egui::ComboBox::from_label("Backend")
+                            .selected_text(format!("{:?}", configurator.backend))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut configurator.backend, Backend::All, "All");
+                                ui.selectable_value(&mut configurator.backend, Backend::Vulkan, "Vulkan");
+                                ui.selectable_value(&mut configurator.backend, Backend::Dx12, "DX12");
+                                ui.selectable_value(&mut configurator.backend, Backend::Metal, "Metal");
+                                ui.selectable_value(&mut configurator.backend, Backend::Gl, "OpenGL");
+                            })
+                            .response
+                            .on_hover_text("the graphics api to draw with. forcing one can work around driver bugs. if it doesn't work, all of them are tried. applied the next time the screensaver starts");
+                        #[cfg(not(target_arch = "wasm32"))]
+                        egui::ComboBox::from_label("Graphics Card")
+                            .selected_text(match configurator.power_preference {
+                                PowerPreference::Low => "Low Power",
+                                PowerPreference::High => "High Performance",
+                            })
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut configurator.power_preference, PowerPreference::Low, "Low Power");
+                                ui.selectable_value(&mut configurator.power_preference, PowerPreference::High, "High Performance");
+                            })
+                            .response
+                            .on_hover_text("on laptops with two graphics cards, high performance uses the faster one, for huge ball counts. applied the next time the screensaver starts");
+                        #[cfg(not(target_arch = "wasm32"))]
+                        {
+                            let monitors = self.monitors.get_or_insert_with(detect_monitors);
+                            egui::ComboBox::from_label("Monitor")
+                                .selected_text(match configurator.monitor_index {
+                                    None => "Primary".to_string(),
+                                    Some(index) => match monitors.get(index) {
+                                        Some(name) => format!("{}: {}", index, name),
+                                        None => format!("Monitor {}", index),
+                                    },
+                                })
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut configurator.monitor_index, None, "Primary");
+                                    for (index, name) in monitors.iter().enumerate() {
+                                        ui.selectable_value(&mut configurator.monitor_index, Some(index), format!("{}: {}", index, name));
+                                    }
+                                })
+                                .response
+                                .on_hover_text("the monitor the screensaver goes fullscreen on");
+                        }
+                        #[cfg(not(target_arch = "wasm32"))]
+                        ui.add(egui::Slider::new(&mut configurator.dismiss_mouse_threshold, 0.0..=500.0).text("Mouse Movement to Exit")).on_hover_text("how many pixels the mouse has to move before the screensaver closes, so a bumped desk doesn't close it");
+                        egui::ComboBox::from_label("Projection")
+                            .selected_text(format!("{:?}", configurator.projection))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut configurator.projection, Projection::Perspective, "Perspective");
+                                ui.selectable_value(&mut configurator.projection, Projection::Orthographic, "Orthographic");
+                            })
+                            .response
+                            .on_hover_text("orthographic shows the 3D screensavers flat, without things getting smaller in the distance. the 2D ones are always flat");
+                        ui.add(egui::Checkbox::new(&mut configurator.free_camera, "Free Camera")).on_hover_text("for debugging. move the camera with w, a, s, d (and q, e in 3D). keys don't close the screensaver while this is on");
+                        #[cfg(target_os = "windows")]
+                        ui.add(egui::Checkbox::new(&mut configurator.windows_preview, "Show Preview")).on_hover_text("draw the screensaver in the little preview in the windows screensaver settings");
+                        let mut custom_background = configurator.background_color.is_some();
+                        ui.add(egui::Checkbox::new(&mut custom_background, "Background Color")).on_hover_text("use your own background color instead of the screensaver's");
+                        configurator.background_color = match custom_background {
+                            true => configurator.background_color.or(Some(egui::Color32::from_rgb(10, 10, 25))),
+                            false => None,
+                        };
+                        if let Some(background_color) = &mut configurator.background_color {
+                            ui.horizontal(|ui| {
+                                ui.color_edit_button_srgba(background_color);
+                                let mut gradient = configurator.background_gradient.is_some();
+                                ui.add(egui::Checkbox::new(&mut gradient, "Gradient")).on_hover_text("fade from the background color at the top to another color at the bottom");
+                                configurator.background_gradient = match gradient {
+                                    true => configurator.background_gradient.or(Some(egui::Color32::BLACK)),
+                                    false => None,
+                                };
+                                if let Some(background_gradient) = &mut configurator.background_gradient {
+                                    ui.color_edit_button_srgba(background_gradient);
                                 }
+                            });
+                        }
+                        //the image is loaded again every time the path changes, so a half typed path is only used once
+                        //it's committed (enter or clicking away) or points at an image that exists
+                        if !self.editing_background_image {
+                            self.background_image.clone_from(&configurator.background_image);
+                        }
+                        ui.horizontal(|ui| {
+                            let label = ui.label("Background Image: ");
+                            let response = ui.text_edit_singleline(&mut self.background_image).labelled_by(label.id).on_hover_text("path to an image (or a url on the web) shown behind the screensaver. leave empty for no image");
+                            self.editing_background_image = response.has_focus();
+                            if response.lost_focus() || (response.changed() && is_usable_image_path(&self.background_image)) {
+                                configurator.background_image.clone_from(&self.background_image);
                             }
                         });
-                        ui.end_row();
+                        if !configurator.background_image.is_empty() {
+                            egui::ComboBox::from_label("Background Fit")
+                                .selected_text(format!("{:?}", configurator.background_fit))
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut configurator.background_fit, BackgroundFit::Cover, "Cover");
+                                    ui.selectable_value(&mut configurator.background_fit, BackgroundFit::Contain, "Contain");
+                                    ui.selectable_value(&mut configurator.background_fit, BackgroundFit::Stretch, "Stretch");
+                                })
+                                .response
+                                .on_hover_text("cover fills the screen and cuts off the edges of the image, contain shows the whole image, stretch ignores the image's proportions");
+                        }
+                    });
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        #[cfg(not(target_arch = "wasm32"))]
+                        if ui.add(egui::Button::new("Save and Exit")).clicked() {
+                            //stay open on failure, so the settings aren't lost
+                            match configurator.save_config() {
+                                Ok(()) => exit(0),
+                                Err(e) => log::error!("Failed to save the config: {}", e),
+                            }
+                        }
+                        #[cfg(not(target_arch = "wasm32"))]
+                        if ui.add(egui::Button::new("Exit without Saving")).clicked() {
+                            exit(0);
+                        }
+                        if ui.add(egui::Button::new("Reset Settings")).clicked() {
+                            *configurator = Configurator::default();
+                            self.color_picker_color = [f32::INFINITY, f32::INFINITY, f32::INFINITY];
+                        }
+                        #[cfg(not(target_arch = "wasm32"))]
+                        ui.separator();
+                        #[cfg(not(target_arch = "wasm32"))]
+                        if ui.add(egui::Button::new("Test")).clicked() {
+                            configurator.preview_window = true;
+                            let config = Arc::clone(&self.configurator);
+                            thread::spawn(move || {
+                                pollster::block_on(run_with_config(config));
+                            });
+                        }
+                        #[cfg(target_arch = "wasm32")]
+                        {
+                            let url_btn = ui.add(egui::Button::new("Generate URL"));
+                            if url_btn.clicked() {
+                                self.clicked_gen_url = Instant::now();
+                                ctx.copy_text(configurator.generate_url());
+                            }
+                            if Instant::now().duration_since(self.clicked_gen_url) < Duration::from_secs(1) {
+                                url_btn.show_tooltip_text("Copied Link");
+                            }
+                        }
                     });
+                    ui.end_row();
                 });
+            });
+            //so the settings are still there after reloading the page
+            #[cfg(target_arch = "wasm32")]
+            if *configurator != self.saved_config {
+                configurator.save_config();
+                self.saved_config = configurator.clone();
             }
-            Err(e) => {
-                log::error!("{}", e);
-            }
-        }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(toml: &str) -> Configurator {
+        Configurator::from_config(
+            Config::builder()
+                .add_source(config::File::from_str(toml, FileFormat::Toml))
+                .build()
+                .unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn out_of_range_values_are_clamped() {
+        let configurator = load(
+            r#"
+            [general]
+            time_scale = 10.0
+            max_fps = 1000
+            msaa = 3
+            [snow]
+            depth = 0.0
+            snowflake_count = 0
+            [balls]
+            count = 1000000
+            size = 0.01
+            size_max = 0.001
+            hue_max = 2.0
+            retoss_delay = -1.0
+            temperature_max_speed = 100.0
+            [3d_model]
+            znear = 5.0
+            zfar = 1.0
+            [harmonograph]
+            damping = 3.0
+            "#,
+        );
+        assert_eq!(configurator.time_scale, 4.0);
+        assert_eq!(configurator.max_fps, Some(240));
+        assert_eq!(configurator.msaa, 1);
+        assert_eq!(configurator.snow_depth, 0.2);
+        assert_eq!(configurator.snowflake_count, 200);
+        assert_eq!(configurator.ball_count, 100000);
+        assert_eq!(configurator.ball_size, 0.02);
+        assert_eq!(configurator.ball_size_max, Some(0.02));
+        assert_eq!(configurator.hue_max, 1.0);
+        assert_eq!(configurator.retoss_delay, 0.0);
+        assert_eq!(configurator.temperature_max_speed, Some(2.0));
+        assert_eq!(configurator.znear, 1.0);
+        assert_eq!(configurator.zfar, 10.0);
+        assert_eq!(configurator.harmonograph_damping, 1.0);
+    }
+
+    #[test]
+    fn default_config_is_left_alone() {
+        let mut configurator = Configurator::default();
+        let before = configurator.clone();
+        configurator.validate();
+        assert_eq!(configurator, before);
     }
 }
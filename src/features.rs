@@ -0,0 +1,88 @@
+use crate::configurator::Configurator;
+
+/// the optional wgpu features the settings in `config` need, each with the setting that needs it,
+/// so the user can be told what to turn off when their gpu doesn't have it
+pub(crate) fn feature_requirements(_config: &Configurator) -> Vec<(wgpu::Features, &'static str)> {
+    //nothing needs an optional feature yet. settings that do get added here, for example:
+    //if config.wireframe {
+    //    requirements.push((wgpu::Features::POLYGON_MODE_LINE, "[balls] wireframe"));
+    //}
+    vec![]
+}
+
+/// every optional feature the config needs
+pub(crate) fn required_features(config: &Configurator) -> wgpu::Features {
+    feature_requirements(config)
+        .iter()
+        .fold(wgpu::Features::empty(), |features, (feature, _)| {
+            features | *feature
+        })
+}
+
+/// the features the config needs that the adapter actually has. the missing ones are reported,
+/// and the settings that need them have to check `device.features()` before using them
+pub(crate) fn supported_features(config: &Configurator, adapter: &wgpu::Adapter) -> wgpu::Features {
+    let available = adapter.features();
+    let missing = feature_requirements(config)
+        .into_iter()
+        .filter(|(feature, _)| !available.contains(*feature))
+        .map(|(feature, option)| format!("{:?} (needed by {}, turn it off)", feature, option))
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        crate::show_error(&format!(
+            "Your graphics card ({}) doesn't support: {}",
+            adapter.get_info().name,
+            missing.join(", ")
+        ));
+    }
+    (required_features(config) | msaa_features(config)) & available
+}
+
+/// every gpu can do 1x and 4x msaa, other sample counts need `TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES`.
+/// it isn't an error when it's missing though, the msaa just falls back to 1x
+fn msaa_features(config: &Configurator) -> wgpu::Features {
+    match config.msaa {
+        1 | 4 => wgpu::Features::empty(),
+        _ => wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES,
+    }
+}
+
+/// the msaa sample counts that work with `color_format` and the depth buffer on this device
+pub(crate) fn supported_sample_counts(
+    adapter: &wgpu::Adapter,
+    device: &wgpu::Device,
+    color_format: wgpu::TextureFormat,
+) -> Vec<u32> {
+    let adapter_specific = device
+        .features()
+        .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES);
+    let color_flags = adapter.get_texture_format_features(color_format).flags;
+    let depth_flags = adapter
+        .get_texture_format_features(crate::texture::Texture::DEPTH_FORMAT)
+        .flags;
+    [1, 2, 4, 8]
+        .into_iter()
+        .filter(|&count| {
+            count == 1
+                || ((count == 4 || adapter_specific)
+                    && color_flags.sample_count_supported(count)
+                    && depth_flags.sample_count_supported(count))
+        })
+        .collect()
+}
+
+/// whether the adapter can run compute shaders, which `[balls] gpu_physics` needs. that's a downlevel
+/// capability rather than a feature, WebGL is the usual case that doesn't have it
+pub(crate) fn compute_supported(config: &Configurator, adapter: &wgpu::Adapter) -> bool {
+    let supported = adapter
+        .get_downlevel_capabilities()
+        .flags
+        .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS);
+    if config.gpu_physics && !supported {
+        log::warn!(
+            "Your graphics card ({}) can't run compute shaders, the balls are simulated on the cpu",
+            adapter.get_info().name
+        );
+    }
+    supported
+}
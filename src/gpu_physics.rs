@@ -0,0 +1,346 @@
+use crate::model::Mesh;
+use crate::particle::ParticleSystem;
+use crate::shaders::ShaderType;
+use bytemuck::Zeroable;
+use cgmath::{Vector2, Zero};
+use std::mem::size_of;
+use wgpu::util::DeviceExt;
+
+/// must match CELL_CAPACITY in ball_physics.wgsl
+const CELL_CAPACITY: usize = 32;
+const WORKGROUP_SIZE: u32 = 64;
+//the instances, the bodies and the two grid buffers
+const STORAGE_BUFFERS: u32 = 4;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct PhysicsParams {
+    domain_min: [f32; 2],
+    domain_max: [f32; 2],
+    gravity: [f32; 2],
+    dt: f32,
+    restitution: f32,
+    //the diameter of a ball with a scale of 1
+    collider: f32,
+    count: u32,
+    cells_x: u32,
+    cells_y: u32,
+    //the part of the velocity that is left after friction
+    kept_speed: f32,
+    //uniforms are padded to 16 bytes
+    _padding: [f32; 3],
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct BodyRaw {
+    velocity: [f32; 4],
+    resolved: [f32; 4],
+    correction: [f32; 4],
+}
+
+/// moves and collides the balls of a particle system in a compute shader. the instance buffer
+/// is simulated in place and drawn directly, so nothing has to be read back every frame
+pub(crate) struct GpuPhysics {
+    params_buffer: wgpu::Buffer,
+    bodies: wgpu::Buffer,
+    cell_counts: wgpu::Buffer,
+    cell_entries: wgpu::Buffer,
+    //how many cells the grid buffers have room for
+    cell_capacity: usize,
+    count: usize,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bin_pipeline: wgpu::ComputePipeline,
+    collide_pipeline: wgpu::ComputePipeline,
+    integrate_pipeline: wgpu::ComputePipeline,
+}
+
+impl GpuPhysics {
+    /// whether the device can run compute shaders with enough storage buffers. WebGL can't
+    pub(crate) fn supported(device: &wgpu::Device) -> bool {
+        let limits = device.limits();
+        limits.max_storage_buffers_per_shader_stage >= STORAGE_BUFFERS
+            && limits.max_compute_invocations_per_workgroup >= WORKGROUP_SIZE
+            && limits.max_compute_workgroup_size_x >= WORKGROUP_SIZE
+    }
+
+    pub(crate) fn new(device: &wgpu::Device) -> Self {
+        let storage_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: false },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage_entry(1),
+                storage_entry(2),
+                storage_entry(3),
+                storage_entry(4),
+            ],
+            label: Some("physics_bind_group_layout"),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Physics Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Physics Shader"),
+            source: ShaderType::BallPhysicsShader.get_source(),
+        });
+        let pipeline = |entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(entry_point),
+                layout: Some(&pipeline_layout),
+                module: &shader,
+                entry_point: Some(entry_point),
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+
+        Self {
+            params_buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Physics Params Buffer"),
+                size: size_of::<PhysicsParams>() as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }),
+            bodies: Self::storage_buffer(device, "Physics Bodies Buffer", 1),
+            cell_counts: Self::storage_buffer(device, "Physics Cell Count Buffer", 1),
+            cell_entries: Self::storage_buffer(device, "Physics Cell Buffer", CELL_CAPACITY),
+            cell_capacity: 1,
+            count: 0,
+            bin_pipeline: pipeline("bin"),
+            collide_pipeline: pipeline("collide"),
+            integrate_pipeline: pipeline("integrate"),
+            bind_group_layout,
+        }
+    }
+
+    /// an empty buffer of `len` u32s
+    fn storage_buffer(device: &wgpu::Device, label: &str, len: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: (len.max(1) * size_of::<u32>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// hands the particles over to the gpu. has to be called again whenever the cpu side changes them,
+    /// which moves them back to where they were on the cpu (see `download`)
+    pub(crate) fn upload(&mut self, device: &wgpu::Device, particle_system: &mut ParticleSystem) {
+        particle_system.gpu_owned = true;
+        particle_system.rebuild_instance_buffer(device);
+
+        let bodies = particle_system
+            .particle_data
+            .iter()
+            .take(particle_system.instances.len())
+            .map(|data| BodyRaw {
+                //the mass rides along in the unused w
+                velocity: data.velocity.extend(data.mass).into(),
+                resolved: [0.0; 4],
+                correction: [0.0; 4],
+            })
+            .collect::<Vec<_>>();
+        self.count = bodies.len();
+        self.bodies = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Physics Bodies Buffer"),
+            //an empty buffer can't be bound
+            contents: if bodies.is_empty() {
+                bytemuck::cast_slice(&[BodyRaw::zeroed()])
+            } else {
+                bytemuck::cast_slice(&bodies)
+            },
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        });
+    }
+
+    /// gives the particles back to the cpu, which moves them from now on
+    pub(crate) fn release(&mut self, device: &wgpu::Device, particle_system: &mut ParticleSystem) {
+        particle_system.gpu_owned = false;
+        particle_system.rebuild_instance_buffer(device);
+        self.count = 0;
+    }
+
+    /// runs one step of the simulation. `dt` is negative when time runs backwards
+    pub(crate) fn step(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        particle_system: &ParticleSystem,
+        dt: f32,
+        gravity: f32,
+        kept_speed: f32,
+    ) {
+        let cells_x = particle_system.instances.regions_x;
+        let cells_y = particle_system.instances.regions_y;
+        if self.count == 0 || cells_x == 0 || cells_y == 0 {
+            return;
+        }
+        let cells = cells_x * cells_y;
+        if cells > self.cell_capacity {
+            self.cell_capacity = cells.next_power_of_two();
+            self.cell_counts =
+                Self::storage_buffer(device, "Physics Cell Count Buffer", self.cell_capacity);
+            self.cell_entries = Self::storage_buffer(
+                device,
+                "Physics Cell Buffer",
+                self.cell_capacity * CELL_CAPACITY,
+            );
+        }
+
+        let domain = particle_system.particle_system_data.domain;
+        let collider = particle_system.particle_data[0]
+            .collider
+            .unwrap_or_else(Vector2::zero);
+        let params = PhysicsParams {
+            domain_min: [domain.min_pos.x, domain.min_pos.y],
+            domain_max: [domain.max_pos.x, domain.max_pos.y],
+            //the camera is mirrored, so positive y is down
+            gravity: [0.0, gravity * dt],
+            dt,
+            restitution: particle_system.particle_system_data.restitution,
+            collider: collider.x,
+            count: self.count as u32,
+            cells_x: cells_x as u32,
+            cells_y: cells_y as u32,
+            kept_speed,
+            _padding: [0.0; 3],
+        };
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::cast_slice(&[params]));
+
+        //the instance buffer gets replaced when the particles change, so the bind group can't be kept
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.params_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: particle_system.instance_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.bodies.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: self.cell_counts.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: self.cell_entries.as_entire_binding(),
+                },
+            ],
+            label: Some("physics_bind_group"),
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Physics Encoder"),
+        });
+        encoder.clear_buffer(&self.cell_counts, 0, None);
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Physics Pass"),
+                timestamp_writes: None,
+            });
+            pass.set_bind_group(0, &bind_group, &[]);
+            let workgroups = (self.count as u32).div_ceil(WORKGROUP_SIZE);
+            for pipeline in [
+                &self.bin_pipeline,
+                &self.collide_pipeline,
+                &self.integrate_pipeline,
+            ] {
+                pass.set_pipeline(pipeline);
+                pass.dispatch_workgroups(workgroups, 1, 1);
+            }
+        }
+        queue.submit(Some(encoder.finish()));
+    }
+
+    /// copies the positions and velocities back to the cpu side, so changing the config doesn't undo
+    /// the simulation. this waits for the gpu, so it's only done when something changes.
+    /// the web can't wait, so there the particles jump back to where they were on the cpu instead
+    pub(crate) fn download(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        particle_system: &mut ParticleSystem,
+    ) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let count = self.count.min(particle_system.instances.len());
+            if count == 0 {
+                return;
+            }
+            let instance_size = size_of::<crate::particle::ParticleInstanceRaw>() * count;
+            let body_size = size_of::<BodyRaw>() * count;
+            let staging = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Physics Staging Buffer"),
+                size: (instance_size + body_size) as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Physics Download Encoder"),
+            });
+            encoder.copy_buffer_to_buffer(
+                &particle_system.instance_buffer,
+                0,
+                &staging,
+                0,
+                instance_size as wgpu::BufferAddress,
+            );
+            encoder.copy_buffer_to_buffer(
+                &self.bodies,
+                0,
+                &staging,
+                instance_size as wgpu::BufferAddress,
+                body_size as wgpu::BufferAddress,
+            );
+            queue.submit(Some(encoder.finish()));
+
+            let slice = staging.slice(..);
+            slice.map_async(wgpu::MapMode::Read, |_| {});
+            device.poll(wgpu::Maintain::Wait);
+            {
+                let data = slice.get_mapped_range();
+                let instances: &[crate::particle::ParticleInstanceRaw] =
+                    bytemuck::cast_slice(&data[..instance_size]);
+                let bodies: &[BodyRaw] = bytemuck::cast_slice(&data[instance_size..]);
+                for i in 0..count {
+                    let instance = &mut particle_system.instances[i];
+                    instance.position = instances[i].position.into();
+                    instance.previous_position = instance.position;
+                    let velocity = bodies[i].velocity;
+                    particle_system.particle_data[i].velocity =
+                        cgmath::Vector3::new(velocity[0], velocity[1], velocity[2]);
+                }
+            }
+            staging.unmap();
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = (device, queue, particle_system);
+    }
+}
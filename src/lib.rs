@@ -1,14 +1,24 @@
+#[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+mod audio;
+mod background;
+mod config_file;
 pub mod configurator;
+mod features;
+mod gpu_physics;
 mod instance;
 mod model;
 mod particle;
+#[cfg(all(feature = "recording", not(target_arch = "wasm32")))]
+mod recording;
 mod screensaver;
 mod shaders;
+mod snapshot;
 mod texture;
+mod trails;
 mod util;
 
 #[cfg(not(target_arch = "wasm32"))]
-use std::time::Instant;
+use std::time::{Duration, Instant};
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 #[cfg(target_arch = "wasm32")]
@@ -20,13 +30,25 @@ use winit::platform::web::WindowBuilderExtWebSys;
 #[cfg(target_arch = "wasm32")]
 use winit::platform::web::WindowExtWebSys;
 
+use crate::background::Background;
 use crate::instance::LayoutDescriptor;
 use crate::screensaver::{ScreenSaver, ScreenSaverType};
+use crate::snapshot::Snapshot;
+use crate::texture::TextureQuality;
+use crate::trails::Trails;
+use crate::util::color::ColorSpace;
 use cgmath::prelude::*;
 use cgmath::Matrix4;
 use config::{Config, FileFormat};
 use model::Vertex;
 use std::collections::HashSet;
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs::{self, File};
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::Write;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+use std::path::PathBuf;
 use std::process;
 use std::sync::{Arc, Mutex};
 use wgpu::util::DeviceExt;
@@ -40,7 +62,7 @@ use winit::error::EventLoopError;
 use winit::event::MouseButton;
 use winit::event::{ElementState, Event, TouchPhase, WindowEvent};
 
-use crate::configurator::{ConfigUI, Configurator};
+use crate::configurator::{with_config, ConfigUI, Configurator};
 use crate::model::ModelInstanceRaw;
 use particle::ParticleInstanceRaw;
 use util::render;
@@ -52,58 +74,158 @@ use winit::platform::windows::EventLoopBuilderExtWindows;
 use winit::platform::x11::EventLoopBuilderExtX11;
 use winit::window::{Fullscreen, Window, WindowBuilder};
 
+#[cfg(all(feature = "recording", not(target_arch = "wasm32")))]
+pub use recording::record;
+
 pub const DEFAULT_CONFIG: &[u8] = include_bytes!("resources/default_config.toml");
+const MAX_PHYSICS_STEPS: u32 = 8;
+//how far a single step goes while paused, when there is no fixed timestep
+const PAUSED_STEP: Duration = Duration::from_micros(16_667);
+//how often the adaptive count is adjusted
+const ADAPTIVE_COUNT_INTERVAL: Duration = Duration::from_millis(500);
+const MIN_TIME_SCALE: f32 = -2.0;
+const MAX_TIME_SCALE: f32 = 4.0;
+const SHADOW_SOFTNESS: f32 = 0.6;
+const SHADOW_OPACITY: f32 = 0.4;
+//how far behind the particles shadows are, so they never cover them
+const SHADOW_DEPTH: f32 = 0.05;
 
 #[cfg(target_arch = "wasm32")]
 pub fn get_config() -> Config {
     //yes I am converting request parameters into a .toml file and passing it as a config what about it
-    let url_params = web_sys::UrlSearchParams::new_with_str(
-        web_sys::window()
-            .unwrap()
-            .location()
-            .search()
-            .unwrap()
-            .as_str(),
-    )
-    .unwrap();
-    let mut params_toml = String::new();
-    for param in url_params.keys() {
-        //log::error!("{}", param.unwrap().as_string().unwrap());
-        let key_str = param.clone().unwrap().as_string().unwrap();
-        params_toml.push_str(&*format!(
-            "{} = \"{}\"\n",
-            key_str,
-            url_params.get(key_str.as_str()).unwrap()
-        ));
-        if key_str == "screensaver" {
-            params_toml.push_str(&*format!(
-                "[{}]\n",
-                url_params.get(key_str.as_str()).unwrap()
-            ));
-        }
+    let query = web_sys::window().unwrap().location().search().unwrap();
+    let params_toml = configurator::url_params_to_toml(&configurator::parse_url_query(&query));
+
+    let mut builder = Config::builder().add_source(config::File::from_str(
+        std::str::from_utf8(DEFAULT_CONFIG).expect("Failed to read the default config"),
+        FileFormat::Toml,
+    ));
+    if let Some(json) = stored_config() {
+        builder = builder.add_source(config::File::from_str(&json, FileFormat::Json));
     }
-
-    Config::builder()
-        .add_source(config::File::from_str(
-            std::str::from_utf8(DEFAULT_CONFIG).expect("Failed to read the default config"),
-            FileFormat::Toml,
-        ))
+    //the url parameters come last so a link always shows what it was made with
+    builder
         .add_source(config::File::from_str(&*params_toml, FileFormat::Toml))
         .build()
         .unwrap()
 }
 
+/// the extensions the config file can have, the first one that exists is used
+#[cfg(not(target_arch = "wasm32"))]
+const CONFIG_FORMATS: [(&str, FileFormat); 4] = [
+    ("toml", FileFormat::Toml),
+    ("yaml", FileFormat::Yaml),
+    ("yml", FileFormat::Yaml),
+    ("json", FileFormat::Json),
+];
+
+/// the config file in the config directory and its format. if there isn't one yet, this is where
+/// the toml one goes
+#[cfg(not(target_arch = "wasm32"))]
+pub fn config_file() -> (PathBuf, FileFormat) {
+    let config_dir = dirs::config_dir().unwrap();
+    CONFIG_FORMATS
+        .iter()
+        .map(|(extension, format)| {
+            (
+                config_dir.join(format!("michaels-screensaver.{}", extension)),
+                *format,
+            )
+        })
+        .find(|(path, _)| path.exists())
+        .unwrap_or_else(|| {
+            (
+                config_dir.join("michaels-screensaver.toml"),
+                FileFormat::Toml,
+            )
+        })
+}
+
+/// writes the config next to the old one and then moves it over it, so being killed halfway through
+/// can't leave a broken config behind
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn write_config_file(path: &Path, contents: &str) -> std::io::Result<()> {
+    //has to be in the same directory, renaming is only atomic within one filesystem
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+    let written = File::create(&temp_path).and_then(|mut file| {
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|_| fs::rename(&temp_path, path)) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// adds the keys of the default config that the toml config file doesn't have yet (usually settings added in
+/// a newer version), together with their comments. returns the keys that were added
+#[cfg(not(target_arch = "wasm32"))]
+pub fn migrate_config_file() -> std::io::Result<Vec<String>> {
+    let (config_path, format) = config_file();
+    //only toml can be edited without losing the comments, the others just use the defaults when loading
+    if !matches!(format, FileFormat::Toml) || !config_path.exists() {
+        return Ok(Vec::new());
+    }
+    let toml_string = fs::read_to_string(&config_path)?;
+    let Ok(mut doc) = toml_string.parse::<toml_edit::DocumentMut>() else {
+        //a broken config is reported when it's loaded
+        return Ok(Vec::new());
+    };
+    let default_doc = std::str::from_utf8(DEFAULT_CONFIG)
+        .expect("Failed to read the default config")
+        .parse::<toml_edit::DocumentMut>()
+        .expect("The default config is invalid");
+
+    let mut added = Vec::new();
+    add_missing_keys(doc.as_table_mut(), default_doc.as_table(), "", &mut added);
+    if !added.is_empty() {
+        write_config_file(&config_path, &doc.to_string())?;
+    }
+    Ok(added)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn add_missing_keys(
+    table: &mut toml_edit::Table,
+    defaults: &toml_edit::Table,
+    path: &str,
+    added: &mut Vec<String>,
+) {
+    for (key, default_item) in defaults.iter() {
+        let full_key = match path {
+            "" => key.to_string(),
+            path => format!("{}.{}", path, key),
+        };
+        if let (Some(toml_edit::Item::Table(sub_table)), toml_edit::Item::Table(default_table)) =
+            (table.get_mut(key), default_item)
+        {
+            add_missing_keys(sub_table, default_table, &full_key, added);
+            continue;
+        }
+        if table.contains_key(key) {
+            continue;
+        }
+        //the comments above a key are part of the key
+        if let Some((default_key, _)) = defaults.get_key_value(key) {
+            table.insert_formatted(default_key, default_item.clone());
+        }
+        added.push(full_key);
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 pub fn get_config() -> Config {
-    let mut config_path = dirs::config_dir().unwrap().to_path_buf();
-    config_path.push("michaels-screensaver.toml");
+    let (config_path, format) = config_file();
 
     Config::builder()
         .add_source(config::File::from_str(
             std::str::from_utf8(DEFAULT_CONFIG).expect("Failed to read the default config"),
             FileFormat::Toml,
         ))
-        .add_source(config::File::with_name(config_path.to_str().unwrap()))
+        .add_source(config::File::new(config_path.to_str().unwrap(), format))
         .add_source(config::Environment::with_prefix("APP"))
         .build()
         .unwrap()
@@ -155,18 +277,127 @@ impl CameraUniform {
     }
 }
 
+/// values available to every shader, bound at group 2
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct GlobalUniform {
+    // how soft the edges of procedurally drawn particles are. negative means the particle texture is used as is
+    edge_softness: f32,
+    // multiplied with the alpha of everything that is drawn
+    opacity: f32,
+    // vec3s are aligned to 16 bytes
+    _padding: [f32; 2],
+    // added to the position of every particle
+    offset: [f32; 3],
+    _padding2: f32,
+    // multiplied with the color of every particle
+    tint: [f32; 4],
+}
+
+impl GlobalUniform {
+    fn new() -> Self {
+        Self {
+            edge_softness: -1.0,
+            opacity: 1.0,
+            _padding: [0.0; 2],
+            offset: [0.0; 3],
+            _padding2: 0.0,
+            tint: [1.0; 4],
+        }
+    }
+
+    /// a dark, soft, see-through copy of the particles, drawn slightly behind them
+    fn shadow(edge_softness: f32, opacity: f32, offset: [f32; 2]) -> Self {
+        Self {
+            edge_softness: edge_softness.max(SHADOW_SOFTNESS),
+            opacity,
+            offset: [offset[0], offset[1], SHADOW_DEPTH],
+            tint: [0.0, 0.0, 0.0, SHADOW_OPACITY],
+            ..Self::new()
+        }
+    }
+}
+
+/// values for animated shader effects, bound at group 3
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct TimeUniform {
+    // seconds since the start, scaled by the time scale. goes down when time runs backwards
+    time: f32,
+    // seconds since the last update, also scaled but never negative
+    dt: f32,
+    // the size of the screen in pixels
+    resolution: [f32; 2],
+}
+
+impl TimeUniform {
+    fn new(size: winit::dpi::PhysicalSize<u32>) -> Self {
+        Self {
+            time: 0.0,
+            dt: 0.0,
+            resolution: [size.width as f32, size.height as f32],
+        }
+    }
+
+    /// screensavers with their own pipeline layout can make the same layout to use the time bind group
+    pub(crate) fn bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("time_bind_group_layout"),
+        })
+    }
+}
+
+/// maps opengl's -1 to 1 depth to wgpu's 0 to 1. cgmath takes the columns, not the rows
 #[rustfmt::skip]
 pub const OPENGL_TO_WGPU_MATRIX: cgmath::Matrix4<f32> = cgmath::Matrix4::new(
     1.0, 0.0, 0.0, 0.0,
     0.0, 1.0, 0.0, 0.0,
-    0.0, 0.0, 0.5, 0.5,
-    0.0, 0.0, 0.0, 1.0,
+    0.0, 0.0, 0.5, 0.0,
+    0.0, 0.0, 0.5, 1.0,
 );
 
 #[allow(dead_code)]
 enum CameraType {
     Perspective(f32),
     Orthographic(),
+    /// a flat view from the eye to the target, as big as the perspective one with this fov would be at the target
+    OrthographicLookAt(f32),
+}
+
+/// how the 3D screensavers are viewed. the flat ones are always orthographic
+#[derive(Debug, Clone, PartialEq, Copy)]
+pub enum Projection {
+    Perspective,
+    Orthographic,
+}
+
+impl ToString for Projection {
+    fn to_string(&self) -> String {
+        match self {
+            Projection::Perspective => "perspective".to_string(),
+            Projection::Orthographic => "orthographic".to_string(),
+        }
+    }
+}
+
+/// the screensaver's camera, switched to the configured projection
+fn camera_type(screensaver: &dyn ScreenSaver, projection: Projection) -> CameraType {
+    match screensaver.get_camera_type() {
+        CameraType::Perspective(fov) if projection == Projection::Orthographic => {
+            CameraType::OrthographicLookAt(fov)
+        }
+        camera_type => camera_type,
+    }
 }
 
 struct Camera {
@@ -179,17 +410,27 @@ struct Camera {
     camera_type: CameraType,
 }
 
+/// moves the camera with the keyboard when `free_camera` is on, for debugging screensavers
 struct CameraController {
     pressed_keys: HashSet<Key>,
+    /// how far the camera was moved from where the screensaver puts it
+    offset: cgmath::Vector3<f32>,
 }
 
 impl CameraController {
     fn new() -> Self {
         Self {
             pressed_keys: HashSet::new(),
+            offset: cgmath::Vector3::new(0.0, 0.0, 0.0),
         }
     }
 
+    /// puts the camera back where the screensaver wants it
+    fn reset(&mut self) {
+        self.pressed_keys.clear();
+        self.offset = cgmath::Vector3::new(0.0, 0.0, 0.0);
+    }
+
     fn process_events(&mut self, event: &WindowEvent) -> bool {
         match event {
             //#[cfg(debug_assertions)]
@@ -205,37 +446,42 @@ impl CameraController {
                 } else {
                     self.pressed_keys.remove(logical_key);
                 }
-                false
+                //the keys are for moving now, not for closing the screensaver
+                true
             }
             _ => false,
         }
     }
 
-    fn update_camera(&self, camera: &mut Camera) {
+    /// moves the camera by the held keys, on top of where the screensaver put it
+    fn update_camera(&mut self, camera: &mut Camera) {
         let move_delta = 0.1;
 
         for key in self.pressed_keys.iter() {
             if let Key::Character(char) = key {
                 match camera.camera_type {
                     CameraType::Orthographic() => match char.to_ascii_lowercase().as_str() {
-                        "w" => camera.eye.y -= move_delta,
-                        "s" => camera.eye.y += move_delta,
-                        "d" => camera.eye.x -= move_delta,
-                        "a" => camera.eye.x += move_delta,
-                        _ => {}
-                    },
-                    CameraType::Perspective(_) => match char.to_ascii_lowercase().as_str() {
-                        "s" => camera.eye.z += move_delta,
-                        "w" => camera.eye.z -= move_delta,
-                        "e" => camera.eye.y += move_delta,
-                        "q" => camera.eye.y -= move_delta,
-                        "a" => camera.eye.x -= move_delta,
-                        "d" => camera.eye.x += move_delta,
+                        "w" => self.offset.y -= move_delta,
+                        "s" => self.offset.y += move_delta,
+                        "d" => self.offset.x -= move_delta,
+                        "a" => self.offset.x += move_delta,
                         _ => {}
                     },
+                    CameraType::Perspective(_) | CameraType::OrthographicLookAt(_) => {
+                        match char.to_ascii_lowercase().as_str() {
+                            "s" => self.offset.z += move_delta,
+                            "w" => self.offset.z -= move_delta,
+                            "e" => self.offset.y += move_delta,
+                            "q" => self.offset.y -= move_delta,
+                            "a" => self.offset.x -= move_delta,
+                            "d" => self.offset.x += move_delta,
+                            _ => {}
+                        }
+                    }
                 }
             }
         }
+        camera.eye += self.offset;
     }
 }
 
@@ -247,6 +493,14 @@ impl Camera {
                 cgmath::Matrix4::look_at_rh(self.eye, self.target, self.up),
                 cgmath::perspective(cgmath::Deg(fov), self.ratio, self.znear, self.zfar),
             ),
+            CameraType::OrthographicLookAt(fov) => {
+                //the target ends up in the middle of the screen, same as with the perspective camera
+                let half_height = self.eye.distance(self.target) * (fov.to_radians() / 2.0).tan();
+                (
+                    cgmath::Matrix4::look_at_rh(self.eye, self.target, self.up),
+                    cgmath::ortho(-half_height * self.ratio, half_height * self.ratio, -half_height, half_height, self.znear, self.zfar),
+                )
+            }
             CameraType::Orthographic() => {
                 (
                     cgmath::Matrix4::from_translation(self.eye.to_vec()),
@@ -273,30 +527,368 @@ impl Camera {
     }
 }
 
-struct State<'a> {
-    surface: wgpu::Surface<'a>,
+/// what a State draws into
+enum RenderTarget<'a> {
+    Surface(wgpu::Surface<'a>),
+    /// an off-screen texture, for rendering without a window
+    Texture(wgpu::Texture),
+}
+
+impl RenderTarget<'_> {
+    const TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+    fn create_texture(device: &wgpu::Device, width: u32, height: u32) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Render Target"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::TEXTURE_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        })
+    }
+
+    fn configure(&mut self, device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) {
+        match self {
+            RenderTarget::Surface(surface) => surface.configure(device, config),
+            RenderTarget::Texture(texture) => {
+                if texture.width() != config.width || texture.height() != config.height {
+                    *texture = Self::create_texture(device, config.width, config.height);
+                }
+            }
+        }
+    }
+
+    /// the view to draw the next frame into. the surface texture has to be presented afterwards
+    fn current_view(
+        &self,
+    ) -> Result<(wgpu::TextureView, Option<wgpu::SurfaceTexture>), wgpu::SurfaceError> {
+        Ok(match self {
+            RenderTarget::Surface(surface) => {
+                let output = surface.get_current_texture()?;
+                let view = output
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default());
+                (view, Some(output))
+            }
+            RenderTarget::Texture(texture) => (
+                texture.create_view(&wgpu::TextureViewDescriptor::default()),
+                None,
+            ),
+        })
+    }
+}
+
+/// everything needed to draw the screensaver into a window. `run_with` drives it with its own event loop,
+/// but a program with its own window and event loop can drive it instead: `resize` it with the window,
+/// pass it the window events with `input`, and call `update` and then `render` for every frame
+pub struct State<'a> {
+    target: RenderTarget<'a>,
     device: wgpu::Device,
     queue: wgpu::Queue,
     config: wgpu::SurfaceConfiguration,
     size: winit::dpi::PhysicalSize<u32>,
-    window: &'a Window,
+    //None when rendering off-screen
+    window: Option<&'a Window>,
     background_color: wgpu::Color,
     camera: Camera,
     camera_controller: CameraController,
     camera_uniform: CameraUniform,
     camera_buffer: wgpu::Buffer,
     camera_bind_group: wgpu::BindGroup,
+    global_uniform: GlobalUniform,
+    global_buffer: wgpu::Buffer,
+    global_bind_group: wgpu::BindGroup,
+    //same layout as the global bind group, used for drawing shadows
+    shadow_buffer: wgpu::Buffer,
+    shadow_bind_group: wgpu::BindGroup,
+    time_uniform: TimeUniform,
+    time_buffer: wgpu::Buffer,
+    time_bind_group: wgpu::BindGroup,
     depth_texture: texture::Texture,
+    //with msaa the frame is drawn into this first, and then resolved to the screen
+    msaa_view: Option<wgpu::TextureView>,
+    //what every pipeline is made with, 1 without msaa
+    sample_count: u32,
+    //what the gpu supports
+    sample_counts: Vec<u32>,
+    //the configured sample count, the gpu might not support it
+    msaa: u32,
+    background: Option<Background>,
+    //drawn under the background image when a gradient background color is set
+    gradient: Option<Background>,
+    //the colors the gradient was made from, to know when it has to be made again
+    gradient_colors: (Option<egui::Color32>, Option<egui::Color32>, ColorSpace),
+    //the path or url of the loaded background image, to know when it has to be reloaded
+    background_image: String,
+    //textures are loaded at this quality, if it changes they have to be loaded again
+    texture_quality: TextureQuality,
+    //set while the motion trails are on
+    trails: Option<Trails>,
     screensaver: Box<dyn ScreenSaver>,
     screensaver_type: ScreenSaverType,
     last_updated: Instant,
+    //set while the window is minimized, the time it spends like that is skipped instead of simulated all at once
+    minimized: bool,
+    //frozen with space in a window that doesn't close on key presses
+    paused: bool,
+    //set by the step key, moves the simulation forward on the next update while paused
+    step: bool,
+    //when the last frame was drawn, for the frame limiter
+    last_rendered: Instant,
+    //leftover time that wasn't simulated yet when using a fixed timestep
+    physics_accumulator: Duration,
+    //rolling average of the real time between frames, in seconds
+    average_frame_time: f32,
+    //time since the adaptive count was last adjusted
+    adaptive_count_timer: Duration,
     texture_bind_group_layout: BindGroupLayout,
     render_pipeline_layout: wgpu::PipelineLayout,
+    //what the surface supports, for switching to a see-through one when the opacity is lowered
+    alpha_modes: Vec<wgpu::CompositeAlphaMode>,
+    present_modes: Vec<wgpu::PresentMode>,
+    //the configured present mode, the surface might be using a different one if it's not supported
+    present_mode: PresentMode,
+}
+
+/// a see-through alpha mode if the screensaver isn't fully opaque (and the platform supports one)
+fn alpha_mode(alpha_modes: &[wgpu::CompositeAlphaMode], opacity: f32) -> wgpu::CompositeAlphaMode {
+    if opacity < 1.0 {
+        for mode in [
+            wgpu::CompositeAlphaMode::PreMultiplied,
+            wgpu::CompositeAlphaMode::PostMultiplied,
+        ] {
+            if alpha_modes.contains(&mode) {
+                return mode;
+            }
+        }
+    }
+    alpha_modes[0]
+}
+
+/// the background gradient, if both of its colors are set
+fn load_gradient(
+    configurator: &Configurator,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture_bind_group_layout: &wgpu::BindGroupLayout,
+    color_format: wgpu::TextureFormat,
+    sample_count: u32,
+) -> Option<Background> {
+    let (Some(top), Some(bottom)) = (
+        configurator.background_color,
+        configurator.background_gradient,
+    ) else {
+        return None;
+    };
+    match Background::gradient(
+        top,
+        bottom,
+        configurator.color_interpolation,
+        device,
+        queue,
+        texture_bind_group_layout,
+        color_format,
+        Some(texture::Texture::DEPTH_FORMAT),
+        sample_count,
+    ) {
+        Ok(gradient) => Some(gradient),
+        Err(e) => {
+            log::error!("Failed to create the background gradient: {}", e);
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Copy)]
+pub enum PresentMode {
+    /// waits for the display, falls back to whatever vsync mode is supported
+    Vsync,
+    /// doesn't wait for the display, falls back to vsync if that's not supported
+    NoVsync,
+    /// doesn't wait, but only shows whole frames (no tearing)
+    Mailbox,
+    /// classic vsync, always supported
+    Fifo,
+}
+
+impl ToString for PresentMode {
+    fn to_string(&self) -> String {
+        match self {
+            PresentMode::Vsync => "vsync".to_string(),
+            PresentMode::NoVsync => "no_vsync".to_string(),
+            PresentMode::Mailbox => "mailbox".to_string(),
+            PresentMode::Fifo => "fifo".to_string(),
+        }
+    }
+}
+
+/// the graphics api the screensaver draws with
+#[derive(Debug, Clone, PartialEq, Copy)]
+pub enum Backend {
+    /// whichever one works
+    All,
+    Vulkan,
+    Dx12,
+    Metal,
+    Gl,
+}
+
+impl ToString for Backend {
+    fn to_string(&self) -> String {
+        match self {
+            Backend::All => "all".to_string(),
+            Backend::Vulkan => "vulkan".to_string(),
+            Backend::Dx12 => "dx12".to_string(),
+            Backend::Metal => "metal".to_string(),
+            Backend::Gl => "gl".to_string(),
+        }
+    }
+}
+
+impl Backend {
+    fn backends(&self) -> wgpu::Backends {
+        match self {
+            Backend::All => wgpu::Backends::all(),
+            Backend::Vulkan => wgpu::Backends::VULKAN,
+            Backend::Dx12 => wgpu::Backends::DX12,
+            Backend::Metal => wgpu::Backends::METAL,
+            Backend::Gl => wgpu::Backends::GL,
+        }
+    }
+}
+
+/// which graphics card is picked on computers with more than one
+#[derive(Debug, Clone, PartialEq, Copy)]
+pub enum PowerPreference {
+    /// usually the integrated one, which is plenty for most screensavers
+    Low,
+    /// usually the dedicated one, for huge particle counts
+    High,
+}
+
+impl ToString for PowerPreference {
+    fn to_string(&self) -> String {
+        match self {
+            PowerPreference::Low => "low".to_string(),
+            PowerPreference::High => "high".to_string(),
+        }
+    }
+}
+
+impl From<PowerPreference> for wgpu::PowerPreference {
+    fn from(preference: PowerPreference) -> Self {
+        match preference {
+            PowerPreference::Low => wgpu::PowerPreference::LowPower,
+            PowerPreference::High => wgpu::PowerPreference::HighPerformance,
+        }
+    }
+}
+
+/// runs `request` with the configured backend. if that doesn't find a graphics card, it's run again with all of them
+async fn request_with_fallback<T, F>(
+    backend: Backend,
+    request: impl Fn(wgpu::Backends) -> F,
+) -> Option<T>
+where
+    F: std::future::Future<Output = Option<T>>,
+{
+    if let Some(result) = request(backend.backends()).await {
+        return Some(result);
+    }
+    if backend == Backend::All {
+        return None;
+    }
+    log::warn!(
+        "Couldn't find a graphics card for the {} backend, trying all of them instead",
+        backend.to_string()
+    );
+    request(wgpu::Backends::all()).await
+}
+
+/// the configured present mode, or the first supported one if the surface can't do it
+fn present_mode(present_modes: &[wgpu::PresentMode], mode: PresentMode) -> wgpu::PresentMode {
+    let wanted = match mode {
+        PresentMode::Vsync => wgpu::PresentMode::AutoVsync,
+        PresentMode::NoVsync => wgpu::PresentMode::AutoNoVsync,
+        PresentMode::Mailbox => wgpu::PresentMode::Mailbox,
+        PresentMode::Fifo => wgpu::PresentMode::Fifo,
+    };
+    match wanted {
+        //the auto modes pick a supported mode by themselves
+        wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync => wanted,
+        _ if present_modes.contains(&wanted) => wanted,
+        _ => {
+            log::warn!(
+                "The present mode {:?} isn't supported, using {:?} instead",
+                wanted,
+                present_modes[0]
+            );
+            present_modes[0]
+        }
+    }
+}
+
+/// the configured msaa sample count, or 1 (no msaa) if the gpu can't do it
+fn msaa_sample_count(sample_counts: &[u32], msaa: u32) -> u32 {
+    if sample_counts.contains(&msaa) {
+        msaa
+    } else {
+        log::warn!(
+            "{}x msaa isn't supported (only {:?}), turning it off",
+            msaa,
+            sample_counts
+        );
+        1
+    }
+}
+
+/// the multisampled texture a frame is drawn into before it's resolved to the screen. none without msaa
+fn create_msaa_view(
+    device: &wgpu::Device,
+    config: &wgpu::SurfaceConfiguration,
+    sample_count: u32,
+) -> Option<wgpu::TextureView> {
+    if sample_count <= 1 {
+        return None;
+    }
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("MSAA Texture"),
+        size: wgpu::Extent3d {
+            width: config.width.max(1),
+            height: config.height.max(1),
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count,
+        dimension: wgpu::TextureDimension::D2,
+        format: config.format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    Some(texture.create_view(&wgpu::TextureViewDescriptor::default()))
 }
 
 impl<'a> State<'a> {
+    /// sets the screensaver up to draw into `window`. panics (after showing an error) if there is no
+    /// graphics card that can draw to it
     // Creating some of the wgpu types requires async code
-    async fn new(window: &'a Window, configurator: &Configurator) -> State<'a> {
+    pub async fn new(window: &'a Window, configurator: &Configurator) -> State<'a> {
+        Self::with_snapshot(window, configurator, None).await
+    }
+
+    /// like `new`, with the particles of the screensaver restored from `snapshot`
+    async fn with_snapshot(
+        window: &'a Window,
+        configurator: &Configurator,
+        snapshot: Option<&Snapshot>,
+    ) -> State<'a> {
         let size = window.inner_size();
 
         // The instance is a handle to our GPU
@@ -310,270 +902,667 @@ impl<'a> State<'a> {
             ..Default::default()
         });
         */
-        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::all(), //fuck it anything will do
-            ..Default::default()
-        });
-
-        let surface = instance.create_surface(window).unwrap();
+        let adapter = request_with_fallback(configurator.backend, |backends| async move {
+            let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+                backends,
+                ..Default::default()
+            });
 
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::LowPower, //we don't need the highest performance for a screen saver
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await;
+            let surface = instance.create_surface(window).ok()?;
+
+            let adapter = instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    //low by default, we don't need the highest performance for a screen saver
+                    power_preference: configurator.power_preference.into(),
+                    compatible_surface: Some(&surface),
+                    force_fallback_adapter: false,
+                })
+                .await?;
+            Some((surface, adapter))
+        })
+        .await;
 
         match adapter {
-            Some(adapter) => {
-                let (device, queue) = adapter
-                    .request_device(
-                        &wgpu::DeviceDescriptor {
-                            required_features: wgpu::Features::empty(),
-                            // WebGL doesn't support all of wgpu's features, so if
-                            // we're building for the web, we'll have to disable some.
-                            required_limits: if cfg!(target_arch = "wasm32") {
-                                //wgpu::Limits::downlevel_webgl2_defaults()
-                                Limits::downlevel_webgl2_defaults()
-                                    .using_resolution(adapter.limits())
-                            } else {
-                                wgpu::Limits::default()
-                            },
-                            label: None,
-                            memory_hints: Default::default(),
-                        },
-                        None, // Trace path
-                    )
+            Some((surface, adapter)) => {
+                Self::with_adapter(
+                    adapter,
+                    Some(surface),
+                    Some(window),
+                    size,
+                    configurator,
+                    snapshot,
+                )
+                .await
+            }
+            None => {
+                show_error("Couldn't find a graphics card that can run the screensaver");
+                panic!("Unable to find an appropriate graphics adapter");
+            }
+        }
+    }
+
+    /// a state that draws into a texture of the given size instead of a window
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn new_headless(
+        configurator: &Configurator,
+        width: u32,
+        height: u32,
+    ) -> anyhow::Result<State<'a>> {
+        let adapter = request_with_fallback(configurator.backend, |backends| async move {
+            let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+                backends,
+                ..Default::default()
+            });
+            instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: configurator.power_preference.into(),
+                    compatible_surface: None,
+                    force_fallback_adapter: false,
+                })
+                .await
+        })
+        .await
+        .ok_or_else(|| anyhow::anyhow!("Unable to find an appropriate graphics adapter"))?;
+
+        Ok(Self::with_adapter(
+            adapter,
+            None,
+            None,
+            winit::dpi::PhysicalSize::new(width.max(1), height.max(1)),
+            configurator,
+            None,
+        )
+        .await)
+    }
+
+    /// sets everything up on the adapter. without a surface, the frames are drawn into a texture instead
+    async fn with_adapter(
+        adapter: wgpu::Adapter,
+        surface: Option<wgpu::Surface<'a>>,
+        window: Option<&'a Window>,
+        size: winit::dpi::PhysicalSize<u32>,
+        configurator: &Configurator,
+        snapshot: Option<&Snapshot>,
+    ) -> State<'a> {
+        let gpu_physics =
+            configurator.gpu_physics && features::compute_supported(configurator, &adapter);
+        let device_descriptor = |required_features| wgpu::DeviceDescriptor {
+            required_features,
+            // WebGL doesn't support all of wgpu's features, so if
+            // we're building for the web, we'll have to disable some.
+            required_limits: if cfg!(target_arch = "wasm32") {
+                //wgpu::Limits::downlevel_webgl2_defaults()
+                if gpu_physics {
+                    //webgpu, which has the storage buffers the compute shader needs
+                    Limits::downlevel_defaults().using_resolution(adapter.limits())
+                } else {
+                    Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits())
+                }
+            } else {
+                wgpu::Limits::default()
+            },
+            label: None,
+            memory_hints: Default::default(),
+        };
+        let required_features = features::supported_features(configurator, &adapter);
+        let device = match adapter
+            .request_device(&device_descriptor(required_features), None)
+            .await
+        {
+            //the settings that need the features check if they're there, so try without them
+            Err(e) if !required_features.is_empty() => {
+                log::warn!(
+                    "Failed to create the device with {:?}: {}, trying without them",
+                    required_features,
+                    e
+                );
+                adapter
+                    .request_device(&device_descriptor(wgpu::Features::empty()), None)
                     .await
-                    .unwrap();
+            }
+            device => device,
+        };
+        let (device, queue) = match device {
+            Ok(device) => device,
+            Err(e) => {
+                show_error(&format!(
+                    "Your graphics card ({}) can't run the screensaver: {}",
+                    adapter.get_info().name,
+                    e
+                ));
+                panic!("Failed to create the device: {}", e);
+            }
+        };
 
+        let (mut target, surface_format, alpha_modes, present_modes) = match surface {
+            Some(surface) => {
                 let surface_caps = surface.get_capabilities(&adapter);
                 // Shader code in this tutorial assumes an sRGB surface texture. Using a different
                 // one will result in all the colors coming out darker. If you want to support non
                 // sRGB surfaces, you'll need to account for that when drawing to the frame.
-                let surface_format = surface_caps
-                    .formats
-                    .iter()
-                    .find(|f| f.is_srgb())
-                    .copied()
-                    .unwrap_or(surface_caps.formats[0]);
-                let config = wgpu::SurfaceConfiguration {
-                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT, /*| wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST*/
-                    format: surface_format,
-                    width: size.width.max(1),
-                    height: size.height.max(1),
-                    //present_mode: surface_caps.present_modes[0],
-                    present_mode: wgpu::PresentMode::AutoVsync,
-                    alpha_mode: surface_caps.alpha_modes[0],
-                    view_formats: vec![],
-                    desired_maximum_frame_latency: 2,
+                let surface_format = match surface_caps.formats.iter().find(|f| f.is_srgb()) {
+                    Some(format) => *format,
+                    None => {
+                        log::warn!(
+                            "The surface doesn't support any sRGB formats, using {:?}. the colors won't look right",
+                            surface_caps.formats[0]
+                        );
+                        surface_caps.formats[0]
+                    }
                 };
+                (
+                    RenderTarget::Surface(surface),
+                    surface_format,
+                    surface_caps.alpha_modes,
+                    surface_caps.present_modes,
+                )
+            }
+            None => (
+                RenderTarget::Texture(RenderTarget::create_texture(
+                    &device,
+                    size.width.max(1),
+                    size.height.max(1),
+                )),
+                RenderTarget::TEXTURE_FORMAT,
+                vec![wgpu::CompositeAlphaMode::Opaque],
+                vec![wgpu::PresentMode::Fifo],
+            ),
+        };
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT, /*| wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST*/
+            format: surface_format,
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode: present_mode(&present_modes, configurator.present_mode),
+            alpha_mode: alpha_mode(&alpha_modes, configurator.opacity),
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
 
-                surface.configure(&device, &config);
-
-                let texture_bind_group_layout =
-                    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                        entries: &[
-                            wgpu::BindGroupLayoutEntry {
-                                binding: 0,
-                                visibility: wgpu::ShaderStages::FRAGMENT,
-                                ty: wgpu::BindingType::Texture {
-                                    multisampled: false,
-                                    view_dimension: wgpu::TextureViewDimension::D2,
-                                    sample_type: wgpu::TextureSampleType::Float {
-                                        filterable: true,
-                                    },
-                                },
-                                count: None,
-                            },
-                            wgpu::BindGroupLayoutEntry {
-                                binding: 1,
-                                visibility: wgpu::ShaderStages::FRAGMENT,
-                                // This should match the filterable field of the
-                                // corresponding Texture entry above.
-                                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                                count: None,
-                            },
-                        ],
-                        label: Some("texture_bind_group_layout"),
-                    });
+        target.configure(&device, &config);
 
-                let screensaver_type = &configurator.screensaver;
+        let info = adapter.get_info();
+        log::info!(
+            "Using {} ({:?}, {:?}) with the {:?} format and the {:?} present mode",
+            info.name,
+            info.backend,
+            info.device_type,
+            config.format,
+            config.present_mode
+        );
 
-                let mut screensaver: Box<dyn ScreenSaver> = match screensaver_type {
-                    ScreenSaverType::Snow => {
-                        Box::new(screensaver::SnowScreenSaver::new(*configurator))
-                    }
-                    ScreenSaverType::Balls => {
-                        Box::new(screensaver::BallScreenSaver::new(*configurator))
-                    }
-                    ScreenSaverType::DDDModel => {
-                        Box::new(screensaver::DDDModelScreensaver::new(*configurator))
-                    }
-                };
+        let sample_counts = features::supported_sample_counts(&adapter, &device, config.format);
+        let sample_count = msaa_sample_count(&sample_counts, configurator.msaa);
+        let msaa_view = create_msaa_view(&device, &config, sample_count);
 
-                let campos = screensaver.get_camera_position();
+        let texture_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        // This should match the filterable field of the
+                        // corresponding Texture entry above.
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+                label: Some("texture_bind_group_layout"),
+            });
 
-                let camera = Camera {
-                    eye: campos.0,
-                    target: campos.1,
-                    up: cgmath::Vector3::unit_y(),
-                    znear: 0.1,
-                    zfar: 100.0,
-                    ratio: config.width as f32 / config.height as f32,
-                    camera_type: screensaver.get_camera_type(),
-                };
+        let screensaver_type = &configurator.screensaver;
 
-                let camera_controller = CameraController::new();
-
-                let camera_bind_group_layout =
-                    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                        entries: &[wgpu::BindGroupLayoutEntry {
-                            binding: 0,
-                            visibility: wgpu::ShaderStages::VERTEX,
-                            ty: wgpu::BindingType::Buffer {
-                                ty: wgpu::BufferBindingType::Uniform,
-                                has_dynamic_offset: false,
-                                min_binding_size: None,
-                            },
-                            count: None,
-                        }],
-                        label: Some("camera_bind_group_layout"),
-                    });
+        let mut screensaver: Box<dyn ScreenSaver> = match screensaver_type {
+            ScreenSaverType::Snow => {
+                Box::new(screensaver::SnowScreenSaver::new(configurator.clone()))
+            }
+            ScreenSaverType::Balls => {
+                Box::new(screensaver::BallScreenSaver::new(configurator.clone()))
+            }
+            ScreenSaverType::DDDModel => {
+                Box::new(screensaver::DDDModelScreensaver::new(configurator.clone()))
+            }
+            ScreenSaverType::Clock => {
+                Box::new(screensaver::ClockScreenSaver::new(configurator.clone()))
+            }
+            ScreenSaverType::Harmonograph => Box::new(screensaver::HarmonographScreenSaver::new(
+                configurator.clone(),
+            )),
+            ScreenSaverType::CirclePacking => Box::new(screensaver::CirclePackingScreenSaver::new(
+                configurator.clone(),
+            )),
+            ScreenSaverType::Comet => {
+                Box::new(screensaver::CometScreenSaver::new(configurator.clone()))
+            }
+            ScreenSaverType::Starfield => {
+                Box::new(screensaver::StarfieldScreenSaver::new(configurator.clone()))
+            }
+            ScreenSaverType::Matrix => {
+                Box::new(screensaver::MatrixScreenSaver::new(configurator.clone()))
+            }
+            ScreenSaverType::Fireworks => {
+                Box::new(screensaver::FireworksScreenSaver::new(configurator.clone()))
+            }
+            ScreenSaverType::Boids => {
+                Box::new(screensaver::BoidsScreenSaver::new(configurator.clone()))
+            }
+            ScreenSaverType::Plasma => {
+                Box::new(screensaver::PlasmaScreenSaver::new(configurator.clone()))
+            }
+        };
 
-                let mut camera_uniform = CameraUniform::new();
-                camera_uniform.update_view_proj(&camera);
+        let campos = screensaver.get_camera_position();
+        let (znear, zfar) = screensaver.get_clip_planes();
+
+        let camera = Camera {
+            eye: campos.0,
+            target: campos.1,
+            up: cgmath::Vector3::unit_y(),
+            znear,
+            zfar,
+            ratio: config.width as f32 / config.height as f32,
+            camera_type: camera_type(&*screensaver, configurator.projection),
+        };
 
-                let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("Camera Buffer"),
-                    contents: bytemuck::cast_slice(&[camera_uniform]),
-                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-                });
+        let camera_controller = CameraController::new();
+
+        let camera_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("camera_bind_group_layout"),
+            });
 
-                let background_color = wgpu::Color {
-                    r: 0.1,
-                    g: 0.1,
-                    b: 0.1,
-                    a: 1.0,
-                };
+        let mut camera_uniform = CameraUniform::new();
+        camera_uniform.update_view_proj(&camera);
 
-                let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                    layout: &camera_bind_group_layout,
-                    entries: &[wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: camera_buffer.as_entire_binding(),
-                    }],
-                    label: Some("camera_bind_group"),
-                });
+        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Camera Buffer"),
+            contents: bytemuck::cast_slice(&[camera_uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let background_color = wgpu::Color {
+            r: 0.1,
+            g: 0.1,
+            b: 0.1,
+            a: 1.0,
+        };
+
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &camera_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+            label: Some("camera_bind_group"),
+        });
+
+        let global_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("global_bind_group_layout"),
+            });
+
+        let global_uniform = GlobalUniform::new();
+
+        let global_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Global Buffer"),
+            contents: bytemuck::cast_slice(&[global_uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let global_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &global_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: global_buffer.as_entire_binding(),
+            }],
+            label: Some("global_bind_group"),
+        });
+
+        let shadow_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Shadow Buffer"),
+            contents: bytemuck::cast_slice(&[global_uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let shadow_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &global_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: shadow_buffer.as_entire_binding(),
+            }],
+            label: Some("shadow_bind_group"),
+        });
+
+        let time_bind_group_layout = TimeUniform::bind_group_layout(&device);
+
+        let time_uniform = TimeUniform::new(size);
+
+        let time_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Time Buffer"),
+            contents: bytemuck::cast_slice(&[time_uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let time_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &time_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: time_buffer.as_entire_binding(),
+            }],
+            label: Some("time_bind_group"),
+        });
+
+        let depth_texture =
+            texture::Texture::create_depth_texture(&device, &config, sample_count, "depth_texture");
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Render Pipeline Layout"),
+                bind_group_layouts: &[
+                    &texture_bind_group_layout,
+                    &camera_bind_group_layout,
+                    &global_bind_group_layout,
+                    &time_bind_group_layout,
+                ],
+                push_constant_ranges: &[],
+            });
+
+        screensaver.setup(
+            Size::from(size),
+            configurator,
+            &device,
+            &queue,
+            &texture_bind_group_layout,
+            &render_pipeline_layout,
+            config.format,
+            Some(texture::Texture::DEPTH_FORMAT),
+            sample_count,
+        );
+
+        if let Some(snapshot) = snapshot {
+            screensaver.load_state(&snapshot.particle_systems, &device);
+        }
+
+        let background = Background::load(
+            &configurator.background_image,
+            configurator.background_fit,
+            configurator.texture_quality,
+            config.width as f32 / config.height as f32,
+            &device,
+            &queue,
+            &texture_bind_group_layout,
+            config.format,
+            Some(texture::Texture::DEPTH_FORMAT),
+            sample_count,
+        )
+        .await;
+
+        let gradient = load_gradient(
+            &configurator,
+            &device,
+            &queue,
+            &texture_bind_group_layout,
+            config.format,
+            sample_count,
+        );
+
+        let trails = configurator.trail_fade.map(|fade| {
+            Trails::new(
+                &device,
+                &config,
+                &texture_bind_group_layout,
+                Some(texture::Texture::DEPTH_FORMAT),
+                sample_count,
+                fade,
+            )
+        });
+
+        Self {
+            window,
+            target,
+            device,
+            queue,
+            config,
+            size,
+            background_color,
+            depth_texture,
+            msaa_view,
+            sample_count,
+            sample_counts,
+            msaa: configurator.msaa,
+            background,
+            gradient,
+            gradient_colors: (
+                configurator.background_color,
+                configurator.background_gradient,
+                configurator.color_interpolation,
+            ),
+            background_image: configurator.background_image.clone(),
+            texture_quality: configurator.texture_quality,
+            trails,
+            camera,
+            camera_controller,
+            camera_uniform,
+            camera_buffer,
+            camera_bind_group,
+            global_uniform,
+            global_buffer,
+            global_bind_group,
+            shadow_buffer,
+            shadow_bind_group,
+            time_uniform,
+            time_buffer,
+            time_bind_group,
+            texture_bind_group_layout,
+            render_pipeline_layout,
+            alpha_modes,
+            present_modes,
+            present_mode: configurator.present_mode,
+            screensaver,
+            screensaver_type: *screensaver_type,
+            last_updated: Instant::now(),
+            minimized: false,
+            paused: false,
+            step: false,
+            last_rendered: Instant::now(),
+            physics_accumulator: Duration::ZERO,
+            average_frame_time: 0.0,
+            adaptive_count_timer: Duration::ZERO,
+        }
+    }
 
-                let depth_texture =
-                    texture::Texture::create_depth_texture(&device, &config, "depth_texture");
-
-                let render_pipeline_layout =
-                    device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                        label: Some("Render Pipeline Layout"),
-                        bind_group_layouts: &[
-                            &texture_bind_group_layout,
-                            &camera_bind_group_layout,
-                        ],
-                        push_constant_ranges: &[],
-                    });
+    pub fn window(&self) -> &Window {
+        self.window.expect("an off-screen state has no window")
+    }
 
-                screensaver.setup(
-                    Size::from(size),
-                    configurator,
-                    &device,
-                    &queue,
-                    &texture_bind_group_layout,
-                    &render_pipeline_layout,
-                    config.format,
-                    Some(texture::Texture::DEPTH_FORMAT),
-                );
+    /// the window has no size while it's minimized, there is nothing to draw to then
+    fn is_minimized(&self) -> bool {
+        self.window.is_some_and(|window| {
+            let size = window.inner_size();
+            size.width == 0 || size.height == 0
+        })
+    }
 
-                Self {
-                    window,
-                    surface,
-                    device,
-                    queue,
-                    config,
-                    size,
-                    background_color,
-                    depth_texture,
-                    camera,
-                    camera_controller,
-                    camera_uniform,
-                    camera_buffer,
-                    camera_bind_group,
-                    texture_bind_group_layout,
-                    render_pipeline_layout,
-                    screensaver,
-                    screensaver_type: *screensaver_type,
-                    last_updated: Instant::now(),
-                }
-            }
-            None => {
-                panic!("Unable to find an appropriate graphics adapter");
+    /// whether enough time has passed since the last frame to draw a new one without going over `max_fps`
+    fn frame_due(&mut self, max_fps: Option<u32>) -> bool {
+        if let Some(max_fps) = max_fps {
+            if self.last_rendered.elapsed() < Duration::from_secs_f32(1.0 / max_fps as f32) {
+                return false;
             }
         }
+        self.last_rendered = Instant::now();
+        true
     }
 
-    pub fn window(&self) -> &Window {
-        self.window
+    /// saves the particles and the config, so they can be restored with `--load-state`
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_snapshot(&self, configurator: &Configurator, path: &Path) {
+        let snapshot = Snapshot {
+            config: configurator.to_toml(
+                std::str::from_utf8(DEFAULT_CONFIG).expect("Failed to read the default config"),
+            ),
+            particle_systems: self.screensaver.save_state(),
+        };
+        match snapshot.save(path) {
+            Ok(_) => log::info!("Saved the state to {}", path.display()),
+            Err(e) => log::error!("Failed to save the state to {}: {}", path.display(), e),
+        }
     }
 
+    /// has to be called when the window changes size, with its new inner size
     pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
-        if new_size.width > 0 && new_size.height > 0 {
-            self.size = new_size;
-            self.config.width = new_size.width;
-            self.config.height = new_size.height;
-            self.surface.configure(&self.device, &self.config);
-        }
-        self.depth_texture =
-            texture::Texture::create_depth_texture(&self.device, &self.config, "depth_texture");
+        //minimized, nothing is drawn until the window comes back with its real size
+        if new_size.width == 0 || new_size.height == 0 {
+            self.minimized = true;
+            return;
+        }
+        if self.minimized {
+            self.minimized = false;
+            self.last_updated = Instant::now();
+        }
+        self.size = new_size;
+        self.config.width = new_size.width;
+        self.config.height = new_size.height;
+        self.target.configure(&self.device, &self.config);
+        self.depth_texture = texture::Texture::create_depth_texture(
+            &self.device,
+            &self.config,
+            self.sample_count,
+            "depth_texture",
+        );
+        self.msaa_view = create_msaa_view(&self.device, &self.config, self.sample_count);
+        if let Some(trails) = &mut self.trails {
+            trails.resize(&self.device, &self.config, &self.texture_bind_group_layout);
+        }
         self.screensaver.resize(
             self.camera.ratio,
             new_size.width as f32 / new_size.height as f32,
         );
+        if let Some(background) = &mut self.background {
+            let fit = background.fit();
+            background.update_fit(
+                &self.queue,
+                fit,
+                new_size.width as f32 / new_size.height as f32,
+            );
+        }
         self.camera.ratio = new_size.width as f32 / new_size.height as f32;
     }
 
-    fn input(&mut self, event: &WindowEvent) -> bool {
-        if
-        /* !self.camera_controller.process_events(event)*/
-        true {
-            match event {
-                WindowEvent::CursorMoved { position, .. } => self.screensaver.handle_input(
+    /// lets the screensaver react to the mouse, touches and the free camera keys. returns whether it used the event
+    pub fn input(&mut self, event: &WindowEvent, config: &Configurator) -> bool {
+        if config.free_camera && self.camera_controller.process_events(event) {
+            return true;
+        }
+        match event {
+            WindowEvent::CursorMoved { position, .. } => self.screensaver.handle_input(
+                [
+                    -(position.x as f32 / self.size.width as f32) * 2.0 + 1.0,
+                    (position.y as f32 / self.size.height as f32) * 2.0 - 1.0,
+                ],
+                0,
+                true,
+            ),
+            WindowEvent::Touch(touch) => {
+                let position = touch.location;
+                self.screensaver.handle_input(
                     [
                         -(position.x as f32 / self.size.width as f32) * 2.0 + 1.0,
                         (position.y as f32 / self.size.height as f32) * 2.0 - 1.0,
                     ],
-                    0,
-                    true,
-                ),
-                WindowEvent::Touch(touch) => {
-                    let position = touch.location;
-                    self.screensaver.handle_input(
-                        [
-                            -(position.x as f32 / self.size.width as f32) * 2.0 + 1.0,
-                            (position.y as f32 / self.size.height as f32) * 2.0 - 1.0,
-                        ],
-                        touch.id + 1,
-                        matches!(touch.phase, TouchPhase::Started | TouchPhase::Moved),
-                    )
-                }
-                _ => false,
+                    touch.id + 1,
+                    matches!(touch.phase, TouchPhase::Started | TouchPhase::Moved),
+                )
+            }
+            _ => false,
+        }
+    }
+
+    /// moves everything forward by the time since the last update. the config can be changed between updates,
+    /// the adaptive count changes it too
+    pub fn update(&mut self, config: &mut Configurator) {
+        let now = Instant::now();
+        //still updated while paused, so changes to the config and the camera show up
+        let elapsed = if !self.paused {
+            now.duration_since(self.last_updated)
+        } else if self.step {
+            self.step = false;
+            if config.fixed_timestep > 0.0 {
+                Duration::from_secs_f32(1.0 / config.fixed_timestep)
+            } else {
+                PAUSED_STEP
             }
         } else {
-            true
+            Duration::ZERO
+        };
+        self.advance(config, elapsed);
+        self.last_updated = now;
+    }
+
+    /// space pauses and unpauses, `.` moves a paused simulation forward by one step.
+    /// returns whether the key was one of them
+    fn handle_pause_key(&mut self, key: &Key) -> bool {
+        match key {
+            Key::Named(NamedKey::Space) => self.paused = !self.paused,
+            Key::Character(char) if char == "." => self.step = self.paused,
+            _ => return false,
         }
+        true
     }
 
-    fn update(&mut self, config: &mut Configurator) {
-        self.camera_controller.update_camera(&mut self.camera);
+    /// updates everything by `elapsed` of real time, which is scaled by the time scale.
+    /// `update` uses the time since the last update, recordings use a fixed frame time
+    fn advance(&mut self, config: &mut Configurator, elapsed: Duration) {
+        //negative time scales are handled by the particles themselves, since a Duration can't be negative
+        let dt = elapsed.mul_f32(
+            config
+                .time_scale
+                .clamp(MIN_TIME_SCALE, MAX_TIME_SCALE)
+                .abs(),
+        );
+
         let cam_pos = self.screensaver.get_camera_position();
         self.camera.eye = cam_pos.0;
         self.camera.target = cam_pos.1;
+        //the lens can be changed in the config while running
+        self.camera.camera_type = camera_type(&*self.screensaver, config.projection);
+        (self.camera.znear, self.camera.zfar) = self.screensaver.get_clip_planes();
+        self.screensaver.update_camera(&mut self.camera, config, dt);
+        if config.free_camera {
+            self.camera_controller.update_camera(&mut self.camera);
+        } else {
+            self.camera_controller.reset();
+        }
 
         self.camera_uniform.update_view_proj(&self.camera);
         self.queue.write_buffer(
@@ -581,14 +1570,67 @@ impl<'a> State<'a> {
             0,
             bytemuck::cast_slice(&[self.camera_uniform]),
         );
-        let last_updated = Instant::now();
+        if self.texture_quality != config.texture_quality {
+            config.should_reload = true;
+        }
+        //every pipeline has to be made again for the new sample count
+        let msaa_changed = self.msaa != config.msaa;
+        if msaa_changed {
+            self.msaa = config.msaa;
+            self.sample_count = msaa_sample_count(&self.sample_counts, config.msaa);
+            self.msaa_view = create_msaa_view(&self.device, &self.config, self.sample_count);
+            self.depth_texture = texture::Texture::create_depth_texture(
+                &self.device,
+                &self.config,
+                self.sample_count,
+                "depth_texture",
+            );
+            self.trails = None;
+            config.should_reload = true;
+            //the image can't be loaded again on the web
+            #[cfg(target_arch = "wasm32")]
+            if self.background.take().is_some() {
+                log::warn!("The background image is only shown again after reloading the page");
+            }
+        }
         if self.screensaver_type != config.screensaver || config.should_reload {
             config.should_reload = false;
             self.screensaver = match config.screensaver {
-                ScreenSaverType::Snow => Box::new(screensaver::SnowScreenSaver::new(*config)),
-                ScreenSaverType::Balls => Box::new(screensaver::BallScreenSaver::new(*config)),
+                ScreenSaverType::Snow => {
+                    Box::new(screensaver::SnowScreenSaver::new(config.clone()))
+                }
+                ScreenSaverType::Balls => {
+                    Box::new(screensaver::BallScreenSaver::new(config.clone()))
+                }
                 ScreenSaverType::DDDModel => {
-                    Box::new(screensaver::DDDModelScreensaver::new(*config))
+                    Box::new(screensaver::DDDModelScreensaver::new(config.clone()))
+                }
+                ScreenSaverType::Clock => {
+                    Box::new(screensaver::ClockScreenSaver::new(config.clone()))
+                }
+                ScreenSaverType::Harmonograph => {
+                    Box::new(screensaver::HarmonographScreenSaver::new(config.clone()))
+                }
+                ScreenSaverType::CirclePacking => {
+                    Box::new(screensaver::CirclePackingScreenSaver::new(config.clone()))
+                }
+                ScreenSaverType::Comet => {
+                    Box::new(screensaver::CometScreenSaver::new(config.clone()))
+                }
+                ScreenSaverType::Starfield => {
+                    Box::new(screensaver::StarfieldScreenSaver::new(config.clone()))
+                }
+                ScreenSaverType::Matrix => {
+                    Box::new(screensaver::MatrixScreenSaver::new(config.clone()))
+                }
+                ScreenSaverType::Fireworks => {
+                    Box::new(screensaver::FireworksScreenSaver::new(config.clone()))
+                }
+                ScreenSaverType::Boids => {
+                    Box::new(screensaver::BoidsScreenSaver::new(config.clone()))
+                }
+                ScreenSaverType::Plasma => {
+                    Box::new(screensaver::PlasmaScreenSaver::new(config.clone()))
                 }
             };
             self.screensaver_type = config.screensaver;
@@ -602,18 +1644,146 @@ impl<'a> State<'a> {
                 &self.render_pipeline_layout,
                 self.config.format,
                 Some(texture::Texture::DEPTH_FORMAT),
+                self.sample_count,
+            );
+        }
+        //on the web the image can only be loaded asynchronously, so it is only loaded at startup
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.background_image != config.background_image
+            || self.texture_quality != config.texture_quality
+            || msaa_changed
+        {
+            self.background_image = config.background_image.clone();
+            self.background = pollster::block_on(Background::load(
+                &config.background_image,
+                config.background_fit,
+                config.texture_quality,
+                self.camera.ratio,
+                &self.device,
+                &self.queue,
+                &self.texture_bind_group_layout,
+                self.config.format,
+                Some(texture::Texture::DEPTH_FORMAT),
+                self.sample_count,
+            ));
+        }
+        self.texture_quality = config.texture_quality;
+        if let Some(background) = &mut self.background {
+            if background.fit() != config.background_fit {
+                background.update_fit(&self.queue, config.background_fit, self.camera.ratio);
+            }
+        }
+        let gradient_colors = (
+            config.background_color,
+            config.background_gradient,
+            config.color_interpolation,
+        );
+        if self.gradient_colors != gradient_colors || msaa_changed {
+            self.gradient_colors = gradient_colors;
+            self.gradient = load_gradient(
+                config,
+                &self.device,
+                &self.queue,
+                &self.texture_bind_group_layout,
+                self.config.format,
+                self.sample_count,
             );
+        }
+        if let Some(fade) = config.trail_fade {
+            if let Some(trails) = &self.trails {
+                trails.set_fade(&self.queue, fade);
+            } else {
+                self.trails = Some(Trails::new(
+                    &self.device,
+                    &self.config,
+                    &self.texture_bind_group_layout,
+                    Some(texture::Texture::DEPTH_FORMAT),
+                    self.sample_count,
+                    fade,
+                ));
+            }
+        } else {
+            self.trails = None;
+        }
+        self.update_adaptive_count(config, elapsed);
+        if config.fixed_timestep > 0.0 {
+            let step = Duration::from_secs_f32(1.0 / config.fixed_timestep);
+            self.physics_accumulator += dt;
+            let mut steps = 0;
+            while self.physics_accumulator >= step {
+                //if the simulation can't keep up, drop the backlog instead of falling further and further behind
+                if steps >= MAX_PHYSICS_STEPS {
+                    self.physics_accumulator = Duration::ZERO;
+                    break;
+                }
+                self.screensaver.update(
+                    Size::from(self.size),
+                    config,
+                    &self.device,
+                    &self.queue,
+                    step,
+                );
+                self.physics_accumulator -= step;
+                steps += 1;
+            }
+            if config.motion_smoothing {
+                self.screensaver.interpolate(
+                    self.physics_accumulator.as_secs_f32() / step.as_secs_f32(),
+                    &self.queue,
+                );
+            }
+        } else {
+            self.screensaver
+                .update(Size::from(self.size), config, &self.device, &self.queue, dt);
+        }
+        self.background_color = match config.background_color {
+            Some(color) => util::color::linear_from_color32(color),
+            None => self.screensaver.get_background_color(),
+        };
 
-            self.camera.camera_type = self.screensaver.get_camera_type();
+        self.global_uniform.edge_softness =
+            self.screensaver.get_edge_softness(config).unwrap_or(-1.0);
+        if self.global_uniform.opacity != config.opacity {
+            self.global_uniform.opacity = config.opacity.clamp(0.0, 1.0);
+            let alpha_mode = alpha_mode(&self.alpha_modes, config.opacity);
+            if self.config.alpha_mode != alpha_mode {
+                #[cfg(not(target_arch = "wasm32"))]
+                if let Some(window) = self.window {
+                    window.set_transparent(config.opacity < 1.0);
+                }
+                self.config.alpha_mode = alpha_mode;
+                self.target.configure(&self.device, &self.config);
+            }
         }
-        self.screensaver.update(
-            Size::from(self.size),
-            config,
-            &self.device,
-            &self.queue,
-            Instant::now().duration_since(self.last_updated),
+        if self.present_mode != config.present_mode {
+            self.present_mode = config.present_mode;
+            self.config.present_mode = present_mode(&self.present_modes, config.present_mode);
+            self.target.configure(&self.device, &self.config);
+        }
+        self.queue.write_buffer(
+            &self.global_buffer,
+            0,
+            bytemuck::cast_slice(&[self.global_uniform]),
+        );
+        self.time_uniform.dt = dt.as_secs_f32();
+        self.time_uniform.time += self.time_uniform.dt * config.time_scale.signum();
+        self.time_uniform.resolution = [self.size.width as f32, self.size.height as f32];
+        self.queue.write_buffer(
+            &self.time_buffer,
+            0,
+            bytemuck::cast_slice(&[self.time_uniform]),
         );
-        self.background_color = self.screensaver.get_background_color();
+        if let Some(offset) = self.screensaver.get_shadow_offset() {
+            self.queue.write_buffer(
+                &self.shadow_buffer,
+                0,
+                bytemuck::cast_slice(&[GlobalUniform::shadow(
+                    self.global_uniform.edge_softness,
+                    self.global_uniform.opacity,
+                    offset,
+                )]),
+            );
+        }
 
         cfg_if::cfg_if! {
             if #[cfg(target_arch = "wasm32")] {
@@ -635,29 +1805,87 @@ impl<'a> State<'a> {
 
                 //config_canvas.style().set_property("transform", &*format! {"scale({})", 1.0/scale}).unwrap();
 
-                let  _ = self.window.request_inner_size(winit::dpi::LogicalSize::new(size_x, size_y));
+                let  _ = self.window().request_inner_size(winit::dpi::LogicalSize::new(size_x, size_y));
 
                 /*
-                let _ = self.window.canvas().unwrap().style().set_property(
+                let _ = self.window().canvas().unwrap().style().set_property(
                     "transform",
                     &*format! {"scale({})", scale},
                 );*/
 
-                if self.window.fullscreen().is_some() {
-                    self.window.set_cursor_visible(false);
+                if self.window().fullscreen().is_some() {
+                    self.window().set_cursor_visible(false);
                 } else {
-                    self.window.set_cursor_visible(true);
+                    self.window().set_cursor_visible(true);
                 }
             }
         }
-        self.last_updated = last_updated;
     }
 
-    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
-        let output = self.surface.get_current_texture()?;
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+    /// lowers the particle count when the frames take longer than the target, and slowly raises it again when they don't
+    fn update_adaptive_count(&mut self, config: &mut Configurator, frame_time: Duration) {
+        //ignore hitches, like the screensaver being reloaded, and paused frames
+        if frame_time.is_zero() || frame_time > Duration::from_millis(500) {
+            return;
+        }
+        self.average_frame_time = self.average_frame_time * 0.9 + frame_time.as_secs_f32() * 0.1;
+        self.adaptive_count_timer += frame_time;
+        if !config.adaptive_count || self.adaptive_count_timer < ADAPTIVE_COUNT_INTERVAL {
+            return;
+        }
+        self.adaptive_count_timer = Duration::ZERO;
+
+        let target_frame_time = 1.0 / config.adaptive_target_fps.max(1.0);
+        let min_count = config.adaptive_min_count;
+        let max_count = config.adaptive_max_count.max(min_count);
+        let average_frame_time = self.average_frame_time;
+        if let Some(count) = config.instance_count_mut() {
+            //back off quickly, but grow slowly so it doesn't keep overshooting
+            let new_count = if average_frame_time > target_frame_time * 1.2 {
+                (*count as f32 * 0.9) as usize
+            } else if average_frame_time < target_frame_time * 1.05 {
+                (*count as f32 * 1.02) as usize + 1
+            } else {
+                *count
+            };
+            *count = new_count.clamp(min_count, max_count);
+        }
+    }
+
+    /// the background color, see-through if the opacity is lowered
+    fn clear_color(&self) -> wgpu::Color {
+        let opacity = self.global_uniform.opacity as f64;
+        let color = self.background_color;
+        match self.config.alpha_mode {
+            wgpu::CompositeAlphaMode::PreMultiplied => wgpu::Color {
+                r: color.r * opacity,
+                g: color.g * opacity,
+                b: color.b * opacity,
+                a: color.a * opacity,
+            },
+            wgpu::CompositeAlphaMode::PostMultiplied => wgpu::Color {
+                a: color.a * opacity,
+                ..color
+            },
+            _ => color,
+        }
+    }
+
+    /// renders a frame. a lost or outdated surface is set up again and the frame is tried once more right away,
+    /// instead of leaving a blank frame until the next one. only for a state with a window
+    pub fn render_frame(&mut self) -> Result<(), wgpu::SurfaceError> {
+        match self.render() {
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                self.resize(self.window().inner_size());
+                self.render()
+            }
+            result => result,
+        }
+    }
+
+    /// draws a frame. a lost or outdated surface has to be set up again with `resize`, `render_frame` does that itself
+    pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        let (view, output) = self.target.current_view()?;
         let mut encoder = self
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
@@ -665,13 +1893,22 @@ impl<'a> State<'a> {
             });
 
         {
+            //with trails the frame is drawn off-screen first, so it's still there for the next one
+            let scene_view = match &self.trails {
+                Some(trails) => trails.target(),
+                None => &view,
+            };
+            let (color_view, resolve_target) = match &self.msaa_view {
+                Some(msaa_view) => (msaa_view, Some(scene_view)),
+                None => (scene_view, None),
+            };
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Render Pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
+                    view: color_view,
+                    resolve_target,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(self.background_color),
+                        load: wgpu::LoadOp::Clear(self.clear_color()),
                         //load: wgpu::LoadOp::Load,
                         store: wgpu::StoreOp::Store,
                     },
@@ -688,28 +1925,169 @@ impl<'a> State<'a> {
                 timestamp_writes: None,
             });
 
+            if let Some(gradient) = &self.gradient {
+                gradient.render(&mut render_pass);
+            }
+            if let Some(background) = &self.background {
+                background.render(&mut render_pass);
+            }
+            if let Some(trails) = &self.trails {
+                trails.render_previous(&mut render_pass);
+            }
+
+            render_pass.set_bind_group(2, &self.global_bind_group, &[]);
+            render_pass.set_bind_group(3, &self.time_bind_group, &[]);
             self.screensaver.render(&mut render_pass, self);
         }
+        if let Some(trails) = &mut self.trails {
+            trails.finish_frame(&mut encoder, &view);
+        }
 
         // submit will accept anything that implements IntoIter
         self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
+        if let Some(output) = output {
+            output.present();
+        }
 
         Ok(())
     }
+
+    /// copies the off-screen render target back from the gpu
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read_target(&self) -> anyhow::Result<image::RgbaImage> {
+        let RenderTarget::Texture(texture) = &self.target else {
+            anyhow::bail!("only off-screen targets can be read back");
+        };
+        let (width, height) = (texture.width(), texture.height());
+        //rows have to be copied in multiples of 256 bytes
+        let unpadded_row = width * 4;
+        let padded_row = unpadded_row.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+            * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Render Target Staging Buffer"),
+            size: (padded_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Target Copy Encoder"),
+            });
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                aspect: wgpu::TextureAspect::All,
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &staging,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            texture.size(),
+        );
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = staging.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver.recv()??;
+
+        let pixels = {
+            let data = slice.get_mapped_range();
+            data.chunks(padded_row as usize)
+                .flat_map(|row| &row[..unpadded_row as usize])
+                .copied()
+                .collect::<Vec<_>>()
+        };
+        staging.unmap();
+
+        image::RgbaImage::from_raw(width, height, pixels)
+            .ok_or_else(|| anyhow::anyhow!("the render target has an unexpected size"))
+    }
+}
+
+/// renders a single frame of the screensaver without a window and saves it as a png
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn render_to_png(
+    config: Configurator,
+    width: u32,
+    height: u32,
+    path: &Path,
+) -> anyhow::Result<()> {
+    let mut config = config;
+    let mut state = State::new_headless(&config, width, height).await?;
+    state.update(&mut config);
+    state.render()?;
+    state.read_target()?.save(path)?;
+    Ok(())
+}
+
+/// the balls screensaver set up without a window, so its physics can be timed by the benchmarks
+#[cfg(not(target_arch = "wasm32"))]
+pub struct BallPhysics {
+    state: State<'static>,
+    config: Configurator,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl BallPhysics {
+    /// the particles are still made on a gpu, so this fails without a graphics adapter
+    pub async fn new(ball_count: usize) -> anyhow::Result<Self> {
+        let config = Configurator {
+            screensaver: ScreenSaverType::Balls,
+            ball_count,
+            gpu_physics: false,
+            ..Default::default()
+        };
+        let state = State::new_headless(&config, 1920, 1080).await?;
+        Ok(Self { state, config })
+    }
+
+    /// advances the balls by `dt` on the cpu, nothing is uploaded to the gpu
+    pub fn step(&mut self, dt: Duration) {
+        self.state
+            .screensaver
+            .simulate(Size::from(self.state.size), &self.config, dt);
+    }
 }
 
+/// runs the screensaver with the config from the config file (or the url parameters on the web)
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
 pub async fn run() {
-    let configurator = Configurator::from_config(get_config());
-    let configurator = Arc::new(Mutex::new(configurator));
-    run_with_config(configurator).await;
+    run_with(Configurator::from_config_or_default(get_config())).await;
+}
+
+/// runs the screensaver with the given config, without reading the config file. this is the one to use when
+/// embedding the screensaver in another program, with a config made by `Configurator::builder()`
+///
+/// ```no_run
+/// use michaels_screensaver::configurator::{Configurator, ScreenSaverType};
+///
+/// let configurator = Configurator::builder()
+///     .screensaver(ScreenSaverType::Snow)
+///     .snowflake_count(2000)
+///     .build()
+///     .unwrap();
+/// pollster::block_on(michaels_screensaver::run_with(configurator));
+/// ```
+pub async fn run_with(configurator: Configurator) {
+    run_with_config(Arc::new(Mutex::new(configurator))).await;
 }
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
 #[cfg(target_arch = "wasm32")]
 pub async fn run_with_config_window() {
-    let configurator = Configurator::from_config(get_config());
+    let configurator = Configurator::from_config_or_default(get_config());
     let configurator = Arc::new(Mutex::new(configurator));
     let config_ui = ConfigUI::new(Arc::clone(&configurator));
 
@@ -735,7 +2113,190 @@ pub async fn run_with_config_window() {
         .await;
 }
 
+/// runs the screensaver, optionally restoring a saved state and saving it again on exit
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn run_with_state(load_path: Option<PathBuf>, save_path: Option<PathBuf>) {
+    //a broken state file shouldn't keep the screensaver from starting, it just starts from scratch
+    let loaded = load_path.and_then(|path| {
+        let loaded = Snapshot::load(&path).and_then(|snapshot| {
+            let config = Config::builder()
+                .add_source(config::File::from_str(
+                    std::str::from_utf8(DEFAULT_CONFIG).expect("Failed to read the default config"),
+                    FileFormat::Toml,
+                ))
+                .add_source(config::File::from_str(&snapshot.config, FileFormat::Toml))
+                .build()?;
+            Ok((snapshot, config))
+        });
+        match loaded {
+            Ok(loaded) => Some(loaded),
+            Err(e) => {
+                log::error!(
+                    "Failed to load the state from {}, starting a new one instead: {}",
+                    path.display(),
+                    e
+                );
+                None
+            }
+        }
+    });
+    let (snapshot, config) = match loaded {
+        Some((snapshot, config)) => (Some(snapshot), config),
+        None => (None, get_config()),
+    };
+    let configurator = Configurator::from_config_or_default(config);
+    let configurator = Arc::new(Mutex::new(configurator));
+    run_with_config_and_state(configurator, snapshot, save_path).await;
+}
+
+/// the names of the connected monitors, in the order `monitor_index` counts them. this creates the event loop,
+/// so nothing else can open a window afterwards
+#[cfg(not(target_arch = "wasm32"))]
+pub fn monitor_names() -> Vec<String> {
+    match EventLoopBuilder::<()>::default()
+        .with_any_thread(true)
+        .build()
+    {
+        Ok(event_loop) => event_loop
+            .available_monitors()
+            .enumerate()
+            .map(|(index, monitor)| {
+                monitor
+                    .name()
+                    .unwrap_or_else(|| format!("Monitor {}", index))
+            })
+            .collect(),
+        Err(e) => {
+            log::error!("Failed to list the monitors: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// draws the screensaver into the preview in the windows screensaver settings
+#[cfg(target_os = "windows")]
+pub async fn run_preview(parent_window: isize) {
+    let mut configurator = Configurator::from_config_or_default(get_config());
+    if !configurator.windows_preview {
+        return;
+    }
+    configurator.preview_window = true;
+    configurator.parent_window = Some(parent_window);
+    run_with_config(Arc::new(Mutex::new(configurator))).await;
+}
+
+/// a borderless window filling the whole parent window
+#[cfg(target_os = "windows")]
+fn preview_window_builder(parent_window: isize) -> WindowBuilder {
+    use std::num::NonZeroIsize;
+    use windows_sys::Win32::Foundation::RECT;
+    use windows_sys::Win32::UI::WindowsAndMessaging::GetClientRect;
+    use winit::dpi::{PhysicalPosition, PhysicalSize};
+    use winit::raw_window_handle::{RawWindowHandle, Win32WindowHandle};
+
+    let mut rect = RECT {
+        left: 0,
+        top: 0,
+        right: 0,
+        bottom: 0,
+    };
+    //SAFETY: windows gave us this handle, and if it's invalid the rect just stays empty
+    unsafe {
+        GetClientRect(parent_window, &mut rect);
+    }
+    let handle = RawWindowHandle::Win32(Win32WindowHandle::new(
+        NonZeroIsize::new(parent_window).expect("The preview window handle is null"),
+    ));
+
+    //SAFETY: the parent window outlives ours, windows closes the preview before destroying it
+    unsafe { WindowBuilder::new().with_parent_window(Some(handle)) }
+        .with_decorations(false)
+        .with_resizable(false)
+        .with_position(PhysicalPosition::new(0, 0))
+        .with_inner_size(PhysicalSize::new(
+            (rect.right - rect.left).max(1) as u32,
+            (rect.bottom - rect.top).max(1) as u32,
+        ))
+}
+
+/// logs the error, and on the web also shows it above the screensaver, since nobody reads the console
+pub(crate) fn show_error(message: &str) {
+    log::error!("{}", message);
+    #[cfg(target_arch = "wasm32")]
+    if let Some(canvas) = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.get_element_by_id("screensaver"))
+    {
+        let message = message
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        let _ = canvas.insert_adjacent_html(
+            "beforebegin",
+            &format!("<p class=\"error\">{}</p>", message),
+        );
+    }
+}
+
+/// a window of the screensaver and the config it runs with
+struct Screen<'a> {
+    state: State<'a>,
+    configurator: Arc<Mutex<Configurator>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    last_cursor_position: Option<(f64, f64)>,
+    #[cfg(not(target_arch = "wasm32"))]
+    cursor_travel: f64,
+    #[cfg(not(target_arch = "wasm32"))]
+    last_cursor_moved: Instant,
+}
+
+/// with `[[monitor]]` settings the screensaver goes on every monitor, each window with the config for its monitor.
+/// empty when it only goes on one
+#[cfg(not(target_arch = "wasm32"))]
+fn monitor_windows(
+    event_loop: &EventLoop<()>,
+    configurator: &Configurator,
+) -> Vec<(Window, Option<Configurator>)> {
+    if configurator.monitor_overrides.is_empty()
+        || !configurator.fullscreen
+        || configurator.preview_window
+        //a chosen monitor means only that one
+        || configurator.monitor_index.is_some()
+    {
+        return Vec::new();
+    }
+    event_loop
+        .available_monitors()
+        .enumerate()
+        .map(|(index, monitor)| {
+            let config = configurator.for_monitor(index, monitor.name().as_deref());
+            let window = WindowBuilder::new()
+                .with_fullscreen(Some(Fullscreen::Borderless(Some(monitor))))
+                .with_transparent(config.opacity < 1.0)
+                .build(event_loop)
+                .unwrap();
+            (window, Some(config))
+        })
+        .collect()
+}
+
+/// how long after the screensaver starts input doesn't close it
+#[cfg(not(target_arch = "wasm32"))]
+const STARTUP_GRACE: Duration = Duration::from_millis(250);
+
+/// how long the mouse has to stay still for the distance it moved to be forgotten
+#[cfg(not(target_arch = "wasm32"))]
+const CURSOR_TRAVEL_RESET: Duration = Duration::from_millis(500);
+
 pub async fn run_with_config(configurator: Arc<Mutex<Configurator>>) {
+    run_with_config_and_state(configurator, None, None).await;
+}
+
+async fn run_with_config_and_state(
+    configurator: Arc<Mutex<Configurator>>,
+    snapshot: Option<Snapshot>,
+    save_path: Option<PathBuf>,
+) {
     #[cfg(target_arch = "wasm32")]
     {
         std::panic::set_hook(Box::new(console_error_panic_hook::hook));
@@ -751,66 +2312,169 @@ pub async fn run_with_config(configurator: Arc<Mutex<Configurator>>) {
 
         match event_loop {
             Ok(event_loop) => {
-                let window = match configurator.lock() {
-                    Ok(configurator) => {
-                        cfg_if::cfg_if! {
-                            if #[cfg(target_arch = "wasm32")] {
-                                let canvas = web_sys::window().unwrap().document().unwrap().get_element_by_id("screensaver").unwrap()
-                                    .dyn_into::<web_sys::HtmlCanvasElement>()
-                                    .map_err(|_| ())
-                                    .unwrap();
+                let windows = with_config(&configurator, |configurator| {
+                    cfg_if::cfg_if! {
+                        if #[cfg(target_arch = "wasm32")] {
+                            let canvas = web_sys::window().unwrap().document().unwrap().get_element_by_id("screensaver").unwrap()
+                                .dyn_into::<web_sys::HtmlCanvasElement>()
+                                .map_err(|_| ())
+                                .unwrap();
+                            vec![(WindowBuilder::new()
+                                .with_canvas(Some(canvas))
+                                .build(&event_loop).unwrap(), None)]
+                            }
+                        else {
+                            let monitor_windows = monitor_windows(&event_loop, configurator);
+                            if !monitor_windows.is_empty() {
+                                return monitor_windows;
+                            }
+                            let window_builder = if configurator.fullscreen && !configurator.preview_window {
+                                //None goes fullscreen on the primary monitor
+                                let monitor = configurator.monitor_index.and_then(|index| {
+                                    let monitor = event_loop.available_monitors().nth(index);
+                                    if monitor.is_none() {
+                                        log::warn!("There is no monitor {}, using the primary one", index);
+                                    }
+                                    monitor
+                                });
                                 WindowBuilder::new()
-                                    .with_canvas(Some(canvas))
-                                    .build(&event_loop).unwrap()
-                                }
+                                .with_fullscreen(Some(Fullscreen::Borderless(monitor)))
+                                    }
                             else {
-                                if configurator.fullscreen && !configurator.preview_window {
-                                    WindowBuilder::new()
-                                    .with_fullscreen(Some(Fullscreen::Borderless(None)))
-                                    .build(&event_loop).unwrap()
-                                        }
-                                else {
-                                    WindowBuilder::new()
-                                        .build(&event_loop).unwrap()
-                                }
-                                //window.set_cursor_visible(false);
+                                WindowBuilder::new()
+                            };
+                            #[cfg(target_os = "windows")]
+                            let window_builder = match configurator.parent_window {
+                                Some(parent_window) => preview_window_builder(parent_window),
+                                None => window_builder,
+                            };
+                            let window = window_builder.with_transparent(configurator.opacity < 1.0).build(&event_loop).unwrap();
+                            //window.set_cursor_visible(false);
+
+                            //use the settings for the monitor the screensaver ended up on, but not when testing from the config ui
+                            if let Some(monitor) = window.current_monitor().filter(|_| !configurator.preview_window) {
+                                let index = window
+                                    .available_monitors()
+                                    .position(|m| m == monitor)
+                                    .unwrap_or(0);
+                                *configurator = configurator.for_monitor(index, monitor.name().as_deref());
                             }
+                            vec![(window, None)]
                         }
                     }
-                    Err(e) => panic!("failed to lock configurator: {}", e),
-                };
-
-                let mut state = match configurator.lock() {
-                    Ok(configurator) => State::new(&window, &configurator).await,
-                    Err(e) => panic!("failed to lock configurator: {}", e),
-                };
+                })
+                .expect("The config is locked");
+
+                //a window on its own monitor gets its own config, otherwise it's the one that was passed in, so the
+                //config ui can change it while testing
+                let windows: Vec<(Window, Arc<Mutex<Configurator>>)> = windows
+                    .into_iter()
+                    .map(|(window, monitor_config)| {
+                        let monitor_config = match monitor_config {
+                            Some(monitor_config) => Arc::new(Mutex::new(monitor_config)),
+                            None => Arc::clone(&configurator),
+                        };
+                        (window, monitor_config)
+                    })
+                    .collect();
+
+                let mut screens = Vec::new();
+                for (i, (window, configurator)) in windows.iter().enumerate() {
+                    //cloned, so the config isn't locked across the await
+                    let config = with_config(configurator, |configurator| configurator.clone())
+                        .expect("The config is locked");
+                    //the saved state is of a single screensaver, it goes on the first window
+                    let snapshot = snapshot.as_ref().filter(|_| i == 0);
+                    screens.push(Screen {
+                        state: State::with_snapshot(window, &config, snapshot).await,
+                        configurator: Arc::clone(configurator),
+                        #[cfg(not(target_arch = "wasm32"))]
+                        last_cursor_position: None,
+                        #[cfg(not(target_arch = "wasm32"))]
+                        cursor_travel: 0.0,
+                        #[cfg(not(target_arch = "wasm32"))]
+                        last_cursor_moved: Instant::now(),
+                    });
+                }
 
+                //some systems send a key event when the window gets focused, and the cursor can jump when the
+                //window appears, neither of which should close the screensaver.
+                //measured from here, because setting up the state can take longer than the grace period
+                #[cfg(not(target_arch = "wasm32"))]
+                let created = Instant::now();
                 let result = event_loop.run(|event, control_flow| {
-                    if let Ok(mut configurator) = configurator.lock() {
-                        if let Event::WindowEvent {
-                            ref event,
-                            window_id,
-                        } = event
-                        {
-                            if !state.input(event) && window_id == state.window().id() {
+                    if let Event::WindowEvent {
+                        ref event,
+                        window_id,
+                    } = event
+                    {
+                        let Some(screen) = screens
+                            .iter_mut()
+                            .find(|screen| screen.state.window().id() == window_id)
+                        else {
+                            return;
+                        };
+                        let state = &mut screen.state;
+                        with_config(&screen.configurator, |configurator| {
+                            if !state.input(event, configurator) {
                                 match event {
                                     #[cfg(not(target_arch = "wasm32"))]
                                     WindowEvent::CloseRequested => {
                                         if !configurator.preview_window {
+                                            if let Some(path) = &save_path {
+                                                state.save_snapshot(configurator, path);
+                                            }
                                             control_flow.exit();
                                             process::exit(0);
                                         }
                                     }
+                                    //the preview window gets destroyed together with the settings dialog
+                                    #[cfg(not(target_arch = "wasm32"))]
+                                    WindowEvent::Destroyed => {
+                                        control_flow.exit();
+                                    }
                                     #[cfg(not(target_arch = "wasm32"))]
                                     WindowEvent::MouseInput {
                                         state: ElementState::Pressed,
                                         ..
                                     } => {
-                                        if configurator.fullscreen && !configurator.preview_window {
+                                        if configurator.fullscreen
+                                            && !configurator.preview_window
+                                            && created.elapsed() >= STARTUP_GRACE
+                                        {
+                                            if let Some(path) = &save_path {
+                                                state.save_snapshot(configurator, path);
+                                            }
                                             control_flow.exit();
                                             process::exit(0);
                                         }
                                     }
+                                    //only gets here if the screensaver doesn't use the mouse itself
+                                    #[cfg(not(target_arch = "wasm32"))]
+                                    WindowEvent::CursorMoved { position, .. } => {
+                                        if configurator.fullscreen && !configurator.preview_window {
+                                            //the position is still tracked during the grace period, so a jump
+                                            //when the window appears isn't counted afterwards
+                                            //a table getting bumped now and then doesn't add up to a dismissal
+                                            if screen.last_cursor_moved.elapsed() >= CURSOR_TRAVEL_RESET {
+                                                screen.cursor_travel = 0.0;
+                                            }
+                                            screen.last_cursor_moved = Instant::now();
+                                            if let Some((x, y)) = screen.last_cursor_position {
+                                                if created.elapsed() >= STARTUP_GRACE {
+                                                    screen.cursor_travel += (position.x - x).hypot(position.y - y);
+                                                }
+                                            }
+                                            screen.last_cursor_position = Some((position.x, position.y));
+                                            if screen.cursor_travel > configurator.dismiss_mouse_threshold as f64 {
+                                                if let Some(path) = &save_path {
+                                                    state.save_snapshot(configurator, path);
+                                                }
+                                                control_flow.exit();
+                                                process::exit(0);
+                                            }
+                                        }
+                                    }
                                     //#[cfg(not(debug_assertions))]
                                     #[cfg(not(target_arch = "wasm32"))]
                                     WindowEvent::KeyboardInput {
@@ -822,15 +2486,32 @@ pub async fn run_with_config(configurator: Arc<Mutex<Configurator>>) {
                                         log::debug!("{:?}", event);
 
                                         if event.state == ElementState::Pressed {
-                                            //stupid windows sending a stupid random key event at the start of the program
-                                            if cfg!(target_os = "windows") {
+                                            if event.logical_key == Key::Named(NamedKey::F6) {
+                                                configurator.debug_velocities =
+                                                    !configurator.debug_velocities;
+                                            } else if (configurator.preview_window || !configurator.fullscreen)
+                                                && state.handle_pause_key(&event.logical_key)
+                                            {
+                                                //keys don't close the window here, so they can control the simulation
+                                            } else if created.elapsed() < STARTUP_GRACE {
+                                                log::debug!("Ignoring a key pressed right after the window was created");
+                                            } else if cfg!(target_os = "windows") {
+                                                //stupid windows sending a stupid random key event at the start of the program
                                                 match event.logical_key {
                                                     Key::Named(NamedKey::AltGraph) => {}
-                                                    _ => control_flow.exit(),
+                                                    _ => {
+                                                        if let Some(path) = &save_path {
+                                                            state.save_snapshot(configurator, path);
+                                                        }
+                                                        control_flow.exit()
+                                                    }
                                                 }
                                             } else if configurator.fullscreen
                                                 && !configurator.preview_window
                                             {
+                                                if let Some(path) = &save_path {
+                                                    state.save_snapshot(configurator, path);
+                                                }
                                                 control_flow.exit();
                                                 process::exit(0);
                                             }
@@ -847,19 +2528,25 @@ pub async fn run_with_config(configurator: Arc<Mutex<Configurator>>) {
                                         ..
                                     } => match logical_key {
                                         Key::Named(NamedKey::Escape) => {
-                                            state.window.set_fullscreen(None);
+                                            state.window().set_fullscreen(None);
                                         }
                                         Key::Named(NamedKey::F11) => {
                                             state
                                                 .window
                                                 .set_fullscreen(Some(Fullscreen::Borderless(None)));
                                         }
+                                        Key::Named(NamedKey::F6) => {
+                                            configurator.debug_velocities =
+                                                !configurator.debug_velocities;
+                                        }
                                         Key::Character(char) if char == "f" => {
                                             state
                                                 .window
                                                 .set_fullscreen(Some(Fullscreen::Borderless(None)));
                                         }
-                                        _ => {}
+                                        key => {
+                                            state.handle_pause_key(key);
+                                        }
                                     },
                                     #[cfg(target_arch = "wasm32")]
                                     WindowEvent::MouseInput {
@@ -875,8 +2562,14 @@ pub async fn run_with_config(configurator: Arc<Mutex<Configurator>>) {
 
                                     WindowEvent::Resized(physical_size) => {
                                         state.resize(*physical_size);
+                                        //the frames stop while minimized, this starts them again
+                                        state.window().request_redraw();
                                     }
                                     WindowEvent::RedrawRequested => {
+                                        //nothing is updated or drawn while minimized, `Resized` asks for a frame once the window is back
+                                        if state.is_minimized() {
+                                            return;
+                                        }
                                         state.window().request_redraw();
                                         state.window().set_visible(true);
 
@@ -885,14 +2578,19 @@ pub async fn run_with_config(configurator: Arc<Mutex<Configurator>>) {
                                             return;
                                         }*/
 
-                                        state.update(&mut configurator);
-                                        match state.render() {
+                                        //the physics still get the whole time since the last update, so they don't slow down
+                                        if !state.frame_due(configurator.max_fps) {
+                                            return;
+                                        }
+
+                                        state.update(configurator);
+                                        match state.render_frame() {
                                             Ok(_) => {}
-                                            // Reconfigure the surface if it's lost or outdated
+                                            //it was set up again and still didn't work, the next frame tries again
                                             Err(
                                                 wgpu::SurfaceError::Lost
                                                 | wgpu::SurfaceError::Outdated,
-                                            ) => state.resize(window.inner_size()),
+                                            ) => log::warn!("The surface was lost, skipping a frame"),
                                             // The system is out of memory, we should probably quit
                                             Err(wgpu::SurfaceError::OutOfMemory) => {
                                                 log::error!("Out Of Memory");
@@ -911,7 +2609,7 @@ pub async fn run_with_config(configurator: Arc<Mutex<Configurator>>) {
                                     _ => {}
                                 }
                             }
-                        }
+                        });
                     }
                 });
 
@@ -928,3 +2626,65 @@ pub async fn run_with_config(configurator: Arc<Mutex<Configurator>>) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::{Point3, Vector3, Vector4};
+
+    /// a camera 5 away from the origin, where a 90 degree fov sees 5 up and 10 to the side (with the ratio of 2)
+    fn camera(camera_type: CameraType) -> Camera {
+        Camera {
+            eye: Point3::new(0.0, 0.0, 5.0),
+            target: Point3::new(0.0, 0.0, 0.0),
+            up: Vector3::unit_y(),
+            znear: 1.0,
+            zfar: 10.0,
+            ratio: 2.0,
+            camera_type,
+        }
+    }
+
+    /// where `point` ends up on the screen, x and y from -1 to 1 and the depth from 0 to 1
+    fn project(camera: &Camera, point: [f32; 3]) -> Vector3<f32> {
+        let clip =
+            camera.build_view_projection_matrix() * Vector4::new(point[0], point[1], point[2], 1.0);
+        clip.truncate() / clip.w
+    }
+
+    fn assert_projects_to(camera: &Camera, point: [f32; 3], expected: [f32; 3]) {
+        let actual = project(camera, point);
+        assert!(
+            (actual - Vector3::from(expected)).magnitude() < 1e-4,
+            "{:?} should be at {:?}, but it's at {:?}",
+            point,
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn perspective_camera() {
+        let camera = camera(CameraType::Perspective(90.0));
+        //the corners of the screen at the target
+        assert_projects_to(&camera, [10.0, 5.0, 0.0], [1.0, 1.0, 8.0 / 9.0]);
+        assert_projects_to(&camera, [-10.0, -5.0, 0.0], [-1.0, -1.0, 8.0 / 9.0]);
+        //twice as far away things are half as big
+        assert_projects_to(&camera, [10.0, 5.0, -5.0], [0.5, 0.5, 1.0]);
+        //the clip planes
+        assert_projects_to(&camera, [0.0, 0.0, 4.0], [0.0, 0.0, 0.0]);
+        assert_projects_to(&camera, [0.0, 0.0, -5.0], [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn orthographic_camera() {
+        let camera = camera(CameraType::OrthographicLookAt(90.0));
+        //the target is seen as big as with the perspective camera
+        assert_projects_to(&camera, [10.0, 5.0, 0.0], [1.0, 1.0, 4.0 / 9.0]);
+        assert_projects_to(&camera, [-10.0, -5.0, 0.0], [-1.0, -1.0, 4.0 / 9.0]);
+        //and the size doesn't change with the distance
+        assert_projects_to(&camera, [10.0, 5.0, -5.0], [1.0, 1.0, 1.0]);
+        assert_projects_to(&camera, [0.0, 0.0, 4.0], [0.0, 0.0, 0.0]);
+        assert_projects_to(&camera, [0.0, 0.0, -5.0], [0.0, 0.0, 1.0]);
+    }
+}
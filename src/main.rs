@@ -3,16 +3,16 @@
 
 use eframe::{HardwareAcceleration, Renderer};
 use michaels_screensaver::configurator::{ConfigUI, Configurator};
-use michaels_screensaver::{get_config, DEFAULT_CONFIG};
+use michaels_screensaver::{config_file, get_config, DEFAULT_CONFIG};
 use std::fs::File;
 use std::io::Write;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::{env, process};
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    let mut config_path = dirs::config_dir().unwrap().to_path_buf();
-    config_path.push("michaels-screensaver.toml");
+    let (config_path, _) = config_file();
     if !config_path.exists() {
         let file = File::create(config_path.clone());
         match file {
@@ -34,13 +34,50 @@ fn main() {
     }
     env_logger::init();
 
-    let config_app = ConfigUI::new(Arc::new(Mutex::new(
-        Configurator::from_config(get_config()),
-    )));
+    match michaels_screensaver::migrate_config_file() {
+        Ok(added) if !added.is_empty() => {
+            log::info!(
+                "Added the new settings to the config file: {}",
+                added.join(", ")
+            )
+        }
+        Ok(_) => {}
+        Err(e) => log::error!("Failed to add the new settings to the config file: {}", e),
+    }
+
+    //the path following an argument, like --load-state <file>
+    let path_arg = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|i| args.get(i + 1))
+            .map(PathBuf::from)
+    };
+
+    //used by the config ui, which can't look up the monitors itself
+    if args.contains(&"--list-monitors".to_string()) {
+        for name in michaels_screensaver::monitor_names() {
+            println!("{}", name);
+        }
+        process::exit(0);
+    }
+
+    let config_app = ConfigUI::new(Arc::new(Mutex::new(Configurator::from_config_or_default(
+        get_config(),
+    ))));
 
     //https://stackoverflow.com/questions/5165133/how-can-i-write-a-screen-saver-for-windows-in-c
     if cfg!(target_os = "windows") {
         if args.contains(&"/p".to_string()) || args.contains(&"\\p".to_string()) {
+            //the settings dialog passes the handle of the little preview window after /p
+            #[cfg(target_os = "windows")]
+            if let Some(parent_window) = args
+                .iter()
+                .skip_while(|arg| *arg != "/p" && *arg != "\\p")
+                .nth(1)
+                .and_then(|hwnd| hwnd.parse().ok())
+            {
+                pollster::block_on(michaels_screensaver::run_preview(parent_window));
+            }
             process::exit(0);
         } else if args.contains(&"/s".to_string()) || args.contains(&"\\s".to_string()) {
             pollster::block_on(michaels_screensaver::run());
@@ -73,12 +110,18 @@ fn main() {
         } else if args.contains(&"-h".to_string()) || args.contains(&"--help".to_string()) {
             println!("--help or -h: show this message");
             println!("--config or -c: open the configuration GUI");
+            println!("--save-state <file>: save the particles and the config to a file on exit");
+            println!("--load-state <file>: start from a state saved with --save-state");
+            println!("--list-monitors: print the names of the monitors, in the order monitor_index counts them");
             println!(
                 "the configuration file is located at: {}",
                 config_path.display()
             );
         } else {
-            pollster::block_on(michaels_screensaver::run());
+            pollster::block_on(michaels_screensaver::run_with_state(
+                path_arg("--load-state"),
+                path_arg("--save-state"),
+            ));
         }
     }
 }
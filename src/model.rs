@@ -1,14 +1,18 @@
 #![allow(dead_code)]
 
+use crate::configurator::Configurator;
 use crate::instance::{Instance, LayoutDescriptor, ToRaw};
+use crate::texture::TextureQuality;
 use crate::util::model::DDDModel;
-use crate::util::pos::{Position2, Position3};
+use crate::util::pos::{BoundingBox, BoundingBoxType, Position2, Position3};
 use crate::{model, texture};
 use cgmath::{Point2, Point3, Quaternion, Rotation3, Vector3};
 use downcast_rs::Downcast;
 use std::io::{BufReader, Cursor, Read};
 use std::ops::{Add, Range};
 #[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+#[cfg(not(target_arch = "wasm32"))]
 use std::time::Duration;
 #[cfg(target_arch = "wasm32")]
 use web_time::Duration;
@@ -72,96 +76,257 @@ pub struct Model {
 }
 
 impl Model {
+    /// loads every mesh of the model. each mesh has its own material, so `create_pipeline` is
+    /// called once per mesh
+    /// the box around the vertices of the mesh, before the instances move or scale it. `None` for meshes that aren't a `ModelMesh`
+    pub fn aabb(&self) -> Option<BoundingBox<f32>> {
+        self.mesh
+            .as_any()
+            .downcast_ref::<ModelMesh>()
+            .map(|mesh| mesh.bounds)
+    }
+
     pub fn load(
-        model: DDDModel,
+        model: &DDDModel,
         position: Vector3<f32>,
+        quality: TextureQuality,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         layout: &wgpu::BindGroupLayout,
-        pipeline: RenderPipeline,
-    ) -> anyhow::Result<Model> {
-        //let obj_text  =model.get().0;
-        //let obj_text = include_str!("resources/models/apple.obj");
-        let obj_text = model.get().0;
+        create_pipeline: &dyn Fn() -> RenderPipeline,
+    ) -> anyhow::Result<Vec<Model>> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let DDDModel::Custom { path } = model {
+            if model.is_gltf() {
+                return Self::load_gltf(
+                    path,
+                    position,
+                    quality,
+                    device,
+                    queue,
+                    layout,
+                    create_pipeline,
+                );
+            }
+        }
+
+        let (obj_text, texture_bytes) = model.get()?;
         let obj_cursor = Cursor::new(obj_text);
         let mut obj_reader = BufReader::new(obj_cursor);
 
-        let (models, _) = tobj::load_obj_buf(
+        //custom models can come with an .mtl file, the built-in ones don't
+        let material_dir: Option<std::path::PathBuf> = match model {
+            #[cfg(not(target_arch = "wasm32"))]
+            DDDModel::Custom { path } => path.parent().map(|dir| dir.to_path_buf()),
+            _ => None,
+        };
+        let (models, materials) = tobj::load_obj_buf(
             &mut obj_reader,
-            &tobj::LoadOptions {
-                triangulate: true,
-                single_index: true,
-                ..Default::default()
+            &obj_load_options(),
+            |mtl| match &material_dir {
+                Some(dir) => tobj::load_mtl(dir.join(mtl)),
+                None => tobj::load_mtl_buf(&mut BufReader::new(Cursor::new(""))),
             },
-            |_| tobj::load_mtl_buf(&mut BufReader::new(Cursor::new(""))),
         )?;
+        //a missing .mtl file only costs the textures
+        let materials = materials.unwrap_or_else(|e| {
+            log::warn!(
+                "Failed to load the materials of {}: {}",
+                model.to_string(),
+                e
+            );
+            vec![]
+        });
 
-        let diffuse_texture = texture::Texture::from_bytes(device, queue, &*model.get().1, "")?;
-
-        let material = Material::new(diffuse_texture, device, layout, pipeline);
-
-        let mesh = {
-            let vertices = (0..models[0].mesh.positions.len() / 3)
-                .map(|i| model::ModelVertex {
-                    position: [
-                        models[0].mesh.positions[i * 3],
-                        models[0].mesh.positions[i * 3 + 1],
-                        models[0].mesh.positions[i * 3 + 2],
-                    ],
-                    tex_coords: [
-                        models[0].mesh.texcoords[i * 2],
-                        1.0 - models[0].mesh.texcoords[i * 2 + 1],
-                    ],
+        //the .mtl texture wins over the one next to the model, meshes without either use their diffuse color
+        models
+            .iter()
+            .map(|obj_model| {
+                let material = obj_model.mesh.material_id.and_then(|id| materials.get(id));
+                let mtl_texture = material
+                    .and_then(|m| m.diffuse_texture.as_ref())
+                    .zip(material_dir.as_ref());
+                let diffuse_texture = match (mtl_texture, &texture_bytes) {
+                    (Some((file, dir)), _) => texture::Texture::from_bytes(
+                        device,
+                        queue,
+                        &std::fs::read(dir.join(file))?,
+                        file,
+                        quality,
+                    )?,
+                    (None, Some(bytes)) => {
+                        texture::Texture::from_bytes(device, queue, bytes, "", quality)?
+                    }
+                    (None, None) => {
+                        let [r, g, b] = material.and_then(|m| m.diffuse).unwrap_or([1.0; 3]);
+                        texture::Texture::from_color(device, queue, [r, g, b, 1.0], None)?
+                    }
+                };
+
+                Ok(Model {
+                    mesh: Box::new(ModelMesh::from_obj(&obj_model.mesh, position, device)),
+                    material: Material::new(diffuse_texture, device, layout, create_pipeline()),
                 })
-                .collect::<Vec<_>>();
-
-            let instances: Vec<ModelInstance> = vec![ModelInstance {
-                position,
-                ..Default::default()
-            }];
-
-            let instance_data = instances
-                .iter()
-                .map(|model_instance: &ModelInstance| model_instance.to_raw())
-                .collect::<Vec<_>>();
-
-            let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some(&"Vertex Buffer"),
-                contents: bytemuck::cast_slice(&vertices),
-                usage: wgpu::BufferUsages::VERTEX,
-            });
-            let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some(&"Index Buffer"),
-                contents: bytemuck::cast_slice(&models[0].mesh.indices),
-                usage: wgpu::BufferUsages::INDEX,
-            });
-
-            let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: None,
-                contents: bytemuck::cast_slice(&instance_data),
-                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-            });
-
-            ModelMesh {
-                vertex_buffer,
-                index_buffer,
-                instance_buffer,
-                instances,
-                num_elements: models[0].mesh.indices.len() as u32,
+            })
+            .collect()
+    }
+
+    /// loads every triangle primitive in the default scene of a .gltf or .glb file, with the node
+    /// transforms baked into the vertices. the base color texture (or color) becomes the diffuse texture
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_gltf(
+        path: &Path,
+        position: Vector3<f32>,
+        quality: TextureQuality,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        create_pipeline: &dyn Fn() -> RenderPipeline,
+    ) -> anyhow::Result<Vec<Model>> {
+        use cgmath::SquareMatrix;
+
+        let (document, buffers, images) = gltf::import(path)?;
+        let scene = document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+            .ok_or_else(|| anyhow::anyhow!("{} has no scenes", path.display()))?;
+
+        let mut models = vec![];
+        let mut nodes = scene
+            .nodes()
+            .map(|node| (node, cgmath::Matrix4::identity()))
+            .collect::<Vec<_>>();
+        while let Some((node, parent_transform)) = nodes.pop() {
+            let transform = parent_transform * cgmath::Matrix4::from(node.transform().matrix());
+
+            if let Some(mesh) = node.mesh() {
+                for primitive in mesh.primitives() {
+                    if primitive.mode() != gltf::mesh::Mode::Triangles {
+                        log::warn!(
+                            "Skipping a {:?} primitive in {}, only triangles are supported",
+                            primitive.mode(),
+                            path.display()
+                        );
+                        continue;
+                    }
+
+                    let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+                    let Some(positions) = reader.read_positions() else {
+                        continue;
+                    };
+                    let tex_coords = reader
+                        .read_tex_coords(0)
+                        .map(|tex_coords| tex_coords.into_f32().collect::<Vec<_>>())
+                        .unwrap_or_default();
+
+                    let vertices = positions
+                        .enumerate()
+                        .map(|(i, [x, y, z])| ModelVertex {
+                            position: (transform * cgmath::Vector4::new(x, y, z, 1.0))
+                                .truncate()
+                                .into(),
+                            tex_coords: tex_coords.get(i).copied().unwrap_or([0.0, 0.0]),
+                        })
+                        .collect::<Vec<_>>();
+                    let indices = match reader.read_indices() {
+                        Some(indices) => indices.into_u32().collect::<Vec<_>>(),
+                        None => (0..vertices.len() as u32).collect(),
+                    };
+
+                    let pbr = primitive.material().pbr_metallic_roughness();
+                    let diffuse_texture = match pbr.base_color_texture() {
+                        Some(info) => {
+                            let img = gltf_image(&images[info.texture().source().index()])?;
+                            texture::Texture::from_image(
+                                device,
+                                queue,
+                                &quality.downsample(img),
+                                mesh.name(),
+                            )?
+                        }
+                        None => texture::Texture::from_color(
+                            device,
+                            queue,
+                            pbr.base_color_factor(),
+                            mesh.name(),
+                        )?,
+                    };
+
+                    models.push(Model {
+                        mesh: Box::new(ModelMesh::new(&vertices, &indices, position, device)),
+                        material: Material::new(diffuse_texture, device, layout, create_pipeline()),
+                    });
+                }
             }
-        };
 
-        Ok(model::Model {
-            mesh: Box::new(mesh),
-            material,
-        })
+            nodes.extend(node.children().map(|child| (child, transform)));
+        }
+
+        if models.is_empty() {
+            return Err(anyhow::anyhow!("{} has no meshes", path.display()));
+        }
+        Ok(models)
+    }
+
+    pub(crate) fn update(&mut self, delta_t: Duration, config: &Configurator, queue: &Queue) {
+        let (motion_delta_t, age_delta_t) = time_deltas(delta_t, config);
+        self.mesh.update(motion_delta_t, age_delta_t, queue);
+    }
+
+    /// moves the mesh on the cpu, `update` also uploads it
+    pub(crate) fn step(&mut self, delta_t: Duration, config: &Configurator) {
+        let (motion_delta_t, age_delta_t) = time_deltas(delta_t, config);
+        self.mesh.step(motion_delta_t, age_delta_t);
     }
 
-    pub(crate) fn update(&mut self, delta_t: Duration, queue: &Queue) {
-        self.mesh.update(delta_t, queue);
+    pub(crate) fn interpolate(&mut self, alpha: f32, queue: &Queue) {
+        self.mesh.update_instance_buffer_interpolated(queue, alpha);
     }
 }
 
+/// how far the meshes move (negative when time runs backwards) and how much they age in `delta_t`
+pub(crate) fn time_deltas(delta_t: Duration, config: &Configurator) -> (f32, Duration) {
+    let age_delta_t = if config.freeze_age {
+        Duration::ZERO
+    } else {
+        delta_t
+    };
+    //dt is always positive, the direction of time is stored separately
+    (
+        delta_t.as_secs_f32() * config.time_scale.signum(),
+        age_delta_t,
+    )
+}
+
+fn obj_load_options() -> tobj::LoadOptions {
+    tobj::LoadOptions {
+        triangulate: true,
+        single_index: true,
+        ..Default::default()
+    }
+}
+
+/// converts a decoded gltf image into one the textures can be created from
+#[cfg(not(target_arch = "wasm32"))]
+fn gltf_image(data: &gltf::image::Data) -> anyhow::Result<image::DynamicImage> {
+    use gltf::image::Format;
+    use image::DynamicImage;
+
+    let (width, height, pixels) = (data.width, data.height, data.pixels.clone());
+    let img = match data.format {
+        Format::R8G8B8A8 => {
+            image::RgbaImage::from_raw(width, height, pixels).map(DynamicImage::from)
+        }
+        Format::R8G8B8 => image::RgbImage::from_raw(width, height, pixels).map(DynamicImage::from),
+        Format::R8G8 => {
+            image::GrayAlphaImage::from_raw(width, height, pixels).map(DynamicImage::from)
+        }
+        Format::R8 => image::GrayImage::from_raw(width, height, pixels).map(DynamicImage::from),
+        format => return Err(anyhow::anyhow!("unsupported texture format {:?}", format)),
+    };
+    img.ok_or_else(|| anyhow::anyhow!("the texture data doesn't match its size"))
+}
+
 pub struct Material {
     pub pipeline: wgpu::RenderPipeline,
     pub diffuse_texture: texture::Texture,
@@ -201,9 +366,18 @@ impl Material {
 pub trait Mesh: DrawMesh + Downcast {
     fn rebuild_instance_buffer(&mut self, device: &wgpu::Device);
     fn update_instance_buffer(&mut self, queue: &Queue);
+    /// like update_instance_buffer, but the instances are drawn `alpha` of the way between their
+    /// previous and current positions. used for smoothing out fixed timestep physics
+    fn update_instance_buffer_interpolated(&mut self, queue: &Queue, _alpha: f32) {
+        self.update_instance_buffer(queue);
+    }
     fn instance_count(&self) -> usize;
     //fn set_instances(&mut self, instances: Vec<Box<dyn Instance>>);
-    fn update(&mut self, _delta_t: Duration, _queue: &Queue);
+    /// `delta_t` is in seconds and is negative when time runs backwards.
+    /// `age_delta_t` is how much the instances age, which can differ from how far they move
+    fn update(&mut self, _delta_t: f32, _age_delta_t: Duration, _queue: &Queue);
+    /// like `update`, but without uploading the instances
+    fn step(&mut self, _delta_t: f32, _age_delta_t: Duration) {}
 }
 
 pub trait Instanced {
@@ -216,6 +390,8 @@ pub struct ModelMesh {
     pub instance_buffer: wgpu::Buffer,
     pub instances: Vec<ModelInstance>,
     pub num_elements: u32,
+    /// the box around the vertices
+    pub bounds: BoundingBox<f32>,
 }
 
 impl ModelMesh {
@@ -246,6 +422,59 @@ impl ModelMesh {
 
         let indices: &[u32] = &[0, 1, 2, 1, 3, 2];
 
+        Self::new(vertices, indices, position, device)
+    }
+
+    /// a sphere around `position`, split into `sectors` around the y axis and `stacks` from pole to pole.
+    /// the texture wraps around it once
+    pub fn create_uv_sphere(
+        radius: f32,
+        sectors: u32,
+        stacks: u32,
+        position: Vector3<f32>,
+        device: &wgpu::Device,
+    ) -> ModelMesh {
+        use std::f32::consts::{PI, TAU};
+
+        let mut vertices = vec![];
+        for i in 0..=stacks {
+            //0 is the top pole
+            let phi = PI * i as f32 / stacks as f32;
+            let (ring, y) = (phi.sin() * radius, phi.cos() * radius);
+            for j in 0..=sectors {
+                let theta = TAU * j as f32 / sectors as f32;
+                vertices.push(ModelVertex {
+                    position: [theta.cos() * ring, y, theta.sin() * ring],
+                    tex_coords: [j as f32 / sectors as f32, i as f32 / stacks as f32],
+                });
+            }
+        }
+
+        let mut indices = vec![];
+        for i in 0..stacks {
+            for j in 0..sectors {
+                let top = i * (sectors + 1) + j;
+                let bottom = top + sectors + 1;
+                //the triangles touching the poles would have no area
+                if i != 0 {
+                    indices.extend_from_slice(&[top, top + 1, bottom]);
+                }
+                if i != stacks - 1 {
+                    indices.extend_from_slice(&[top + 1, bottom + 1, bottom]);
+                }
+            }
+        }
+
+        Self::new(&vertices, &indices, position, device)
+    }
+
+    /// a mesh with a single instance at `position`
+    pub fn new(
+        vertices: &[ModelVertex],
+        indices: &[u32],
+        position: Vector3<f32>,
+        device: &wgpu::Device,
+    ) -> ModelMesh {
         let instances: Vec<ModelInstance> = vec![ModelInstance {
             position,
             ..Default::default()
@@ -257,12 +486,12 @@ impl ModelMesh {
             .collect::<Vec<_>>();
 
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: None,
-            contents: bytemuck::cast_slice(vertices.as_slice()),
+            label: Some(&"Vertex Buffer"),
+            contents: bytemuck::cast_slice(vertices),
             usage: wgpu::BufferUsages::VERTEX,
         });
         let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: None,
+            label: Some(&"Index Buffer"),
             contents: bytemuck::cast_slice(indices),
             usage: wgpu::BufferUsages::INDEX,
         });
@@ -273,14 +502,47 @@ impl ModelMesh {
             usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
         });
 
+        let bounds = vertices
+            .iter()
+            .map(|vertex| {
+                let position = Vector3::from(vertex.position);
+                BoundingBox::new(position, position, BoundingBoxType::Ignore)
+            })
+            .reduce(|a, b| a.union(&b))
+            .unwrap_or(BoundingBox::new(
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(0.0, 0.0, 0.0),
+                BoundingBoxType::Ignore,
+            ));
+
         ModelMesh {
             vertex_buffer,
             index_buffer,
             instances,
             instance_buffer,
             num_elements: indices.len() as u32,
+            bounds,
         }
     }
+
+    /// obj texture coordinates start at the bottom, so they are flipped
+    fn from_obj(mesh: &tobj::Mesh, position: Vector3<f32>, device: &wgpu::Device) -> ModelMesh {
+        let vertices = (0..mesh.positions.len() / 3)
+            .map(|i| ModelVertex {
+                position: [
+                    mesh.positions[i * 3],
+                    mesh.positions[i * 3 + 1],
+                    mesh.positions[i * 3 + 2],
+                ],
+                tex_coords: match mesh.texcoords.get(i * 2..i * 2 + 2) {
+                    Some(&[u, v]) => [u, 1.0 - v],
+                    _ => [0.0, 0.0],
+                },
+            })
+            .collect::<Vec<_>>();
+
+        Self::new(&vertices, &mesh.indices, position, device)
+    }
 }
 
 impl Mesh for ModelMesh {
@@ -315,9 +577,9 @@ impl Mesh for ModelMesh {
         self.instances.len()
     }
 
-    fn update(&mut self, delta_t: Duration, queue: &Queue) {
+    fn update(&mut self, _delta_t: f32, age_delta_t: Duration, queue: &Queue) {
         for instance in self.instances.iter_mut() {
-            instance.update(delta_t)
+            instance.update(age_delta_t)
         }
         self.update_instance_buffer(queue);
     }
@@ -343,6 +605,8 @@ pub struct ModelInstance {
     //rotation: cgmath::Quaternion<f32>,
     pub scale: f32,
     pub age: Duration,
+    /// multiplied into the texture, white leaves it as it is
+    pub color: Color,
 }
 
 impl Default for ModelInstance {
@@ -352,6 +616,7 @@ impl Default for ModelInstance {
             rotation: Quaternion::from_axis_angle(cgmath::Vector3::unit_z(), cgmath::Deg(0.0)),
             scale: 1.0,
             age: Default::default(),
+            color: Color::WHITE,
         }
     }
 }
@@ -371,6 +636,12 @@ impl ToRaw for ModelInstance {
             model: (cgmath::Matrix4::from_translation(self.position)
                 * cgmath::Matrix4::from(self.rotation))
             .into(),
+            color: [
+                self.color.r as f32,
+                self.color.g as f32,
+                self.color.b as f32,
+                self.color.a as f32,
+            ],
         }
     }
 }
@@ -398,6 +669,7 @@ pub struct ModelInstanceRaw {
     pub scale: f32,
     pub position: [f32; 3],
     pub model: [[f32; 4]; 4],
+    pub color: [f32; 4],
 }
 
 impl LayoutDescriptor for ModelInstanceRaw {
@@ -443,6 +715,12 @@ impl LayoutDescriptor for ModelInstanceRaw {
                     shader_location: 9,
                     format: wgpu::VertexFormat::Float32x4,
                 },
+                //color
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 20]>() as wgpu::BufferAddress,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
             ],
         }
     }
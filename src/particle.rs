@@ -3,24 +3,71 @@
 use crate::instance::{LayoutDescriptor, ToRaw};
 use crate::model::{DrawMesh, Mesh, ModelVertex};
 use crate::util::pos::{BoundingBox, BoundingBoxType, InstanceContainer, Position2, Position3};
-use cgmath::{Vector2, Vector3, Zero};
+use cgmath::{Vector2, Vector3, VectorSpace, Zero};
 use std::ops::{Add, Mul, Range};
 use std::time::Duration;
 use wgpu::util::DeviceExt;
 use wgpu::Queue;
 
+/// what happens to particles when the window is resized
+#[derive(Debug, Clone, PartialEq, Copy)]
+pub enum ResizeBehavior {
+    /// stretch the positions to the new width
+    Preserve,
+    /// place every particle randomly in the new domain
+    Respawn,
+}
+
+impl ToString for ResizeBehavior {
+    fn to_string(&self) -> String {
+        match self {
+            ResizeBehavior::Preserve => "preserve".to_string(),
+            ResizeBehavior::Respawn => "respawn".to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct ParticleData {
     pub velocity: Vector3<f32>,
     pub collider: Option<Vector2<f32>>,
+    /// how heavy the particle is when it collides with others
+    pub mass: f32,
+    /// how old the particle can get before it is removed, `None` lives forever
+    pub lifetime: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+/// something that happened to the particles during a step, see `ParticleSystem::collect_events`.
+/// the indices are only valid until particles get removed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParticleEvent {
+    /// the particle bounced off of one of the domain's walls along `axis`, only in the `Bounce` bounding box type
+    WallBounce { index: usize, axis: Axis },
+    /// two particles hit each other. the particle system doesn't collide them itself, whatever does reports it here
+    Collision { a: usize, b: usize },
 }
 
 pub struct ParticleSystemData {
     pub domain: BoundingBox<f32>,
+    /// how much of the velocity is kept when bouncing off of the domain's walls
+    pub restitution: f32,
+    /// whether the particles with a lifetime fade out as they get older. this sets their alpha
+    pub fade_out: bool,
 }
 impl ParticleSystemData {
     pub fn new(domain: BoundingBox<f32>) -> Self {
-        ParticleSystemData { domain }
+        ParticleSystemData {
+            domain,
+            restitution: 1.0,
+            fade_out: true,
+        }
     }
 }
 
@@ -32,6 +79,11 @@ pub struct ParticleSystem {
     pub particle_data: Vec<ParticleData>,
     pub particle_system_data: ParticleSystemData,
     pub num_elements: u32,
+    /// the particles are moved on the gpu (see `GpuPhysics`), so `update` only ages them and
+    /// the instance buffer has to be usable as a storage buffer
+    pub gpu_owned: bool,
+    /// the events since they were last drained, `None` while nobody asked for them
+    pub events: Option<Vec<ParticleEvent>>,
 }
 
 impl ParticleSystem {
@@ -67,6 +119,8 @@ impl ParticleSystem {
         let particle_data = vec![ParticleData {
             velocity: Vector3::zero(),
             collider: Option::from(Vector2::new(width, height)), //cheeky hack to transfer the width and height to the population routine
+            mass: 1.0,
+            lifetime: None,
         }];
 
         let instance_data = instances
@@ -99,11 +153,55 @@ impl ParticleSystem {
             instance_buffer,
             num_elements: indices.len() as u32,
             particle_system_data,
+            gpu_owned: false,
+            events: None,
+        }
+    }
+
+    /// starts or stops recording what happens to the particles. the events pile up until `drain_events` is called
+    pub fn collect_events(&mut self, collect: bool) {
+        match (collect, self.events.is_some()) {
+            (true, false) => self.events = Some(Vec::new()),
+            (false, true) => self.events = None,
+            _ => {}
         }
     }
-    pub fn populate_random(&mut self, instance_count: usize, device: &wgpu::Device) {
+
+    /// records an event, if they are being collected
+    pub fn push_event(&mut self, event: ParticleEvent) {
+        if let Some(events) = &mut self.events {
+            events.push(event);
+        }
+    }
+
+    /// the events since the last time they were drained
+    pub fn drain_events(&mut self) -> Vec<ParticleEvent> {
+        match &mut self.events {
+            Some(events) => std::mem::take(events),
+            None => Vec::new(),
+        }
+    }
+    /// replaces the domain (usually after the window was resized) and moves the particles into it
+    pub fn resize_domain(&mut self, domain: BoundingBox<f32>, behavior: ResizeBehavior) {
+        let old_domain = self.particle_system_data.domain;
+        self.particle_system_data.domain = domain;
+        move_into_domain(&mut self.instances, old_domain, domain, behavior);
+    }
+
+    pub fn populate_random(
+        &mut self,
+        instance_count: usize,
+        device: &wgpu::Device,
+        rng: &mut impl rand::Rng,
+    ) {
+        self.add_random(instance_count, rng);
+        self.rebuild_instance_buffer(device);
+    }
+
+    /// like `populate_random`, but the instance buffer is left for `upload` to grow
+    pub fn add_random(&mut self, instance_count: usize, rng: &mut impl rand::Rng) {
         for _ in 0..instance_count {
-            let position = self.particle_system_data.domain.random_pos();
+            let position = self.particle_system_data.domain.random_pos_with(rng);
 
             let new_color = wgpu::Color {
                 r: 1.0,
@@ -114,17 +212,115 @@ impl ParticleSystem {
 
             self.instances.push(ParticleInstance {
                 position,
+                previous_position: position,
                 color: new_color,
                 //velocity: Vector3::new(0.0, 0.0, 0.0),
                 scale: 1.0,
+                tex_offset: [0.0, 0.0],
+                tex_scale: [1.0, 1.0],
+                rotation: 0.0,
                 age: Duration::new(0, 0),
             });
             self.particle_data.push(ParticleData {
                 velocity: Vector3::zero(),
                 collider: self.particle_data[0].collider,
+                mass: 1.0,
+                lifetime: None,
+            });
+        }
+    }
+
+    /// adds `count` particles at `origin`, flying in random directions in the xy plane at up to
+    /// `velocity_spread`. `retain_alive` removes them once they reach `lifetime`. returns the indices of the new particles
+    pub fn emit(
+        &mut self,
+        count: usize,
+        origin: Vector3<f32>,
+        velocity_spread: f32,
+        color: wgpu::Color,
+        lifetime: Duration,
+        device: &wgpu::Device,
+        rng: &mut impl rand::Rng,
+    ) -> Range<usize> {
+        let start = self.instances.len();
+        for _ in 0..count {
+            let angle = rng.gen::<f32>() * std::f32::consts::TAU;
+            //the square root spreads them evenly over the circle instead of bunching them in the middle
+            let speed = velocity_spread * rng.gen::<f32>().sqrt();
+
+            //the data goes before the extra entry at the end, so instance i still pairs with data i
+            self.particle_data.insert(
+                self.instances.len(),
+                ParticleData {
+                    velocity: Vector3::new(angle.cos() * speed, angle.sin() * speed, 0.0),
+                    collider: self.particle_data[0].collider,
+                    mass: 1.0,
+                    lifetime: Some(lifetime),
+                },
+            );
+            self.instances.push(ParticleInstance {
+                position: origin,
+                previous_position: origin,
+                color,
+                scale: 1.0,
+                tex_offset: [0.0, 0.0],
+                tex_scale: [1.0, 1.0],
+                rotation: 0.0,
+                age: Duration::new(0, 0),
             });
         }
         self.rebuild_instance_buffer(device);
+        start..self.instances.len()
+    }
+
+    /// whether the particle at `index` hasn't reached its lifetime yet
+    pub fn is_alive(&self, index: usize) -> bool {
+        self.particle_data[index]
+            .lifetime
+            .is_none_or(|lifetime| self.instances[index].age < lifetime)
+    }
+
+    /// removes the particles that reached their lifetime and returns them, a lifetime of zero ends a particle right away.
+    /// the instance buffer is left as it is, it's only ever too big after this
+    pub fn reap(&mut self) -> Vec<ParticleInstance> {
+        let count = self.instances.len();
+        let mut dead = vec![];
+        let mut kept = 0;
+        for i in 0..count {
+            if !self.is_alive(i) {
+                dead.push(self.instances[i]);
+            } else {
+                //keeps the order, and the instances and their data together
+                self.instances.instances.swap(kept, i);
+                self.particle_data.swap(kept, i);
+                kept += 1;
+            }
+        }
+        self.instances.instances.truncate(kept);
+        self.particle_data.drain(kept..count);
+        dead
+    }
+
+    /// removes the particles that reached their lifetime, `update` only fades them out
+    pub fn retain_alive(&mut self, device: &wgpu::Device) {
+        //most frames nothing dies, the buffer is only made again when something did
+        if !self.reap().is_empty() {
+            self.rebuild_instance_buffer(device);
+        }
+    }
+
+    /// writes the particles into the instance buffer, making it bigger first if they don't fit anymore.
+    /// does nothing while the gpu owns them
+    pub fn upload(&mut self, device: &wgpu::Device, queue: &Queue) {
+        if self.gpu_owned {
+            return;
+        }
+        let size = (self.instances.len() * std::mem::size_of::<ParticleInstanceRaw>()) as u64;
+        if size > self.instance_buffer.size() {
+            self.rebuild_instance_buffer(device);
+        } else {
+            self.update_instance_buffer(queue);
+        }
     }
 }
 
@@ -136,10 +332,14 @@ impl Mesh for ParticleSystem {
             .map(|particle_instance: &ParticleInstance| ParticleInstance::to_raw(particle_instance))
             .collect::<Vec<_>>();
 
+        let mut usage = wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST;
+        if self.gpu_owned {
+            usage |= wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC;
+        }
         self.instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: wgpu::Label::from("Instance Buffer"),
             contents: bytemuck::cast_slice(&instance_data),
-            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            usage,
         });
     }
     fn update_instance_buffer(&mut self, queue: &Queue) {
@@ -156,80 +356,202 @@ impl Mesh for ParticleSystem {
         );
     }
 
+    fn update_instance_buffer_interpolated(&mut self, queue: &Queue, alpha: f32) {
+        //the positions on the cpu side are out of date
+        if self.gpu_owned {
+            return;
+        }
+        let instance_data = self
+            .instances
+            .iter()
+            .map(|particle_instance: &ParticleInstance| {
+                let mut raw = particle_instance.to_raw();
+                raw.position = particle_instance
+                    .previous_position
+                    .lerp(particle_instance.position, alpha)
+                    .into();
+                raw
+            })
+            .collect::<Vec<_>>();
+
+        queue.write_buffer(
+            &self.instance_buffer,
+            0,
+            bytemuck::cast_slice(&instance_data),
+        );
+    }
+
     fn instance_count(&self) -> usize {
         self.instances.len()
     }
 
-    fn update(&mut self, delta_t: Duration, queue: &Queue) {
+    fn step(&mut self, delta_t: f32, age_delta_t: Duration) {
+        if self.gpu_owned {
+            for instance in self.instances.iter_mut() {
+                instance.update(age_delta_t);
+            }
+            return;
+        }
         for i in 0..self.instances.len() {
             let instance = &mut self.instances[i];
             let data = &mut self.particle_data[i];
-            instance.update(delta_t);
+            instance.update(age_delta_t);
+            instance.previous_position = instance.position;
+
+            //dead particles are invisible until `retain_alive` removes them
+            if self.particle_system_data.fade_out {
+                if let Some(lifetime) = data.lifetime {
+                    let life_left = if lifetime.is_zero() {
+                        0.0
+                    } else {
+                        1.0 - instance.age.as_secs_f64() / lifetime.as_secs_f64()
+                    };
+                    instance.color.a = life_left.clamp(0.0, 1.0);
+                }
+            }
 
-            if !instance.position[0].is_normal() {
+            if !is_valid_position(instance.position) {
                 instance.position = self.particle_system_data.domain.random_pos();
+                instance.previous_position = instance.position;
             }
 
             match self.particle_system_data.domain.bound_type() {
                 BoundingBoxType::Clamp => {
-                    instance.position = self.particle_system_data.domain.clamp_pos(
-                        instance
-                            .position
-                            .add(data.velocity.mul(delta_t.as_secs_f32())),
-                    );
+                    instance.position = self
+                        .particle_system_data
+                        .domain
+                        .clamp_pos(instance.position.add(data.velocity.mul(delta_t)));
                 }
                 BoundingBoxType::Modulo => {
-                    instance.position = self.particle_system_data.domain.modulo_pos(
-                        instance
-                            .position
-                            .add(data.velocity.mul(delta_t.as_secs_f32())),
-                    );
+                    let new_position = instance.position.add(data.velocity.mul(delta_t));
+                    instance.position = self.particle_system_data.domain.modulo_pos(new_position);
+                    //move the previous position along with the wrap, so interpolation doesn't streak across the screen
+                    instance.previous_position += instance.position - new_position;
                 }
                 BoundingBoxType::Bounce => {
                     let collider = match data.collider {
                         None => Vector2::zero(),
                         Some(collider) => collider,
                     };
-                    if self.particle_system_data.domain.min_pos.x - instance.position.x
-                        > -instance.scale * collider.x / 2.0
-                    {
-                        data.velocity.x = data.velocity.x.abs();
-                    } else if self.particle_system_data.domain.max_pos.x - instance.position.x
-                        < instance.scale * collider.x / 2.0
-                    {
-                        data.velocity.x = -data.velocity.x.abs();
-                    }
-                    if self.particle_system_data.domain.min_pos.y - instance.position.y
-                        > -instance.scale * collider.y / 2.0
-                    {
-                        data.velocity.y = data.velocity.y.abs();
-                    } else if self.particle_system_data.domain.max_pos.y - instance.position.y
-                        < instance.scale * collider.y / 2.0
-                    {
-                        data.velocity.y = -data.velocity.y.abs();
-                    }
-                    if self.particle_system_data.domain.min_pos.z - instance.position.z > 0.0 {
-                        data.velocity.z = data.velocity.z.abs();
-                    } else if self.particle_system_data.domain.max_pos.z - instance.position.z < 0.0
-                    {
-                        data.velocity.z = -data.velocity.z.abs();
-                    }
-                    instance.position = self.particle_system_data.domain.clamp_pos(
-                        instance
-                            .position
-                            .add(data.velocity.mul(delta_t.as_secs_f32())),
+                    //when time runs backwards the velocity has to point out of the walls to move away from them
+                    let direction = delta_t.signum();
+                    let domain = self.particle_system_data.domain;
+                    let restitution = self.particle_system_data.restitution;
+                    let (position_x, velocity_x, bounced_x) = bounce_axis(
+                        instance.position.x,
+                        data.velocity.x,
+                        domain.min_pos.x,
+                        domain.max_pos.x,
+                        instance.scale * collider.x / 2.0,
+                        direction,
+                        restitution,
+                    );
+                    let (position_y, velocity_y, bounced_y) = bounce_axis(
+                        instance.position.y,
+                        data.velocity.y,
+                        domain.min_pos.y,
+                        domain.max_pos.y,
+                        instance.scale * collider.y / 2.0,
+                        direction,
+                        restitution,
                     );
+                    //the particles are round, so their depth is the same as their width
+                    let (position_z, velocity_z, bounced_z) = bounce_axis(
+                        instance.position.z,
+                        data.velocity.z,
+                        domain.min_pos.z,
+                        domain.max_pos.z,
+                        instance.scale * collider.x / 2.0,
+                        direction,
+                        restitution,
+                    );
+                    instance.position = Vector3::new(position_x, position_y, position_z);
+                    data.velocity = Vector3::new(velocity_x, velocity_y, velocity_z);
+                    if let Some(events) = &mut self.events {
+                        for (bounced, axis) in [
+                            (bounced_x, Axis::X),
+                            (bounced_y, Axis::Y),
+                            (bounced_z, Axis::Z),
+                        ] {
+                            if bounced {
+                                events.push(ParticleEvent::WallBounce { index: i, axis });
+                            }
+                        }
+                    }
+                    instance.position = self
+                        .particle_system_data
+                        .domain
+                        .clamp_pos(instance.position.add(data.velocity.mul(delta_t)));
                 }
                 BoundingBoxType::Ignore => {
-                    instance.position = instance
-                        .position
-                        .add(data.velocity.mul(delta_t.as_secs_f32()));
+                    instance.position = instance.position.add(data.velocity.mul(delta_t));
                 }
             }
-            instance.age += delta_t;
         }
-        //model.mesh.rebuild_instance_buffer(device);
-        self.update_instance_buffer(queue);
+    }
+
+    fn update(&mut self, delta_t: f32, age_delta_t: Duration, queue: &Queue) {
+        self.step(delta_t, age_delta_t);
+        if !self.gpu_owned {
+            self.update_instance_buffer(queue);
+        }
+    }
+}
+
+/// whether the position can be used, a NaN or infinite one gets the particle respawned. 0 is fine, things
+/// are put in the middle of the screen all the time
+pub(crate) fn is_valid_position(position: Vector3<f32>) -> bool {
+    position.x.is_finite() && position.y.is_finite() && position.z.is_finite()
+}
+
+/// moves the instances from `old_domain` into `domain`, see `ResizeBehavior`
+fn move_into_domain(
+    instances: &mut InstanceContainer<ParticleInstance>,
+    old_domain: BoundingBox<f32>,
+    domain: BoundingBox<f32>,
+    behavior: ResizeBehavior,
+) {
+    let old_center = (old_domain.min_pos.x + old_domain.max_pos.x) / 2.0;
+    let new_center = (domain.min_pos.x + domain.max_pos.x) / 2.0;
+    let width_ratio = domain.width() / old_domain.width();
+
+    for instance in instances.iter_mut() {
+        let position = match behavior {
+            ResizeBehavior::Preserve => Vector3::new(
+                new_center + (instance.position.x - old_center) * width_ratio,
+                instance.position.y,
+                instance.position.z,
+            ),
+            ResizeBehavior::Respawn => domain.random_pos(),
+        };
+        //make sure nothing ends up outside, even with floating point errors
+        instance.position = match domain.bound_type() {
+            BoundingBoxType::Modulo => domain.modulo_pos(position),
+            _ => domain.clamp_pos(position),
+        };
+        instance.previous_position = instance.position;
+    }
+}
+
+/// the position and velocity along one axis after bouncing off the walls at `min` and `max`, which are hit `radius`
+/// before the center reaches them. a particle that went into a wall is put back where it touches it.
+/// `direction` is -1 when time runs backwards. `restitution` is only applied when the particle is moving into the wall,
+/// so it doesn't keep slowing down while it's leaving it. also returns whether the particle bounced
+fn bounce_axis(
+    position: f32,
+    velocity: f32,
+    min: f32,
+    max: f32,
+    radius: f32,
+    direction: f32,
+    restitution: f32,
+) -> (f32, f32, bool) {
+    if position - radius < min && velocity * direction < 0.0 {
+        (position.max(min + radius), -velocity * restitution, true)
+    } else if position + radius > max && velocity * direction > 0.0 {
+        (position.min(max - radius), -velocity * restitution, true)
+    } else {
+        (position, velocity, false)
     }
 }
 
@@ -245,9 +567,16 @@ impl DrawMesh for ParticleSystem {
 #[derive(Debug, Clone, Copy)]
 pub struct ParticleInstance {
     pub(crate) position: cgmath::Vector3<f32>,
+    //position before the last update, for interpolation
+    pub(crate) previous_position: cgmath::Vector3<f32>,
     //rotation: cgmath::Quaternion<f32>,
     pub(crate) color: wgpu::Color,
     pub(crate) scale: f32,
+    //which part of the texture is used, for texture atlases
+    pub(crate) tex_offset: [f32; 2],
+    pub(crate) tex_scale: [f32; 2],
+    //around the z axis, in radians
+    pub(crate) rotation: f32,
     pub(crate) age: Duration,
 }
 
@@ -264,14 +593,27 @@ impl ToRaw for ParticleInstance {
             ],
             //velocity: self.velocity.into(),
             scale: self.scale,
+            tex_offset: self.tex_offset,
+            tex_scale: self.tex_scale,
+            rotation: self.rotation,
+            _padding: [0.0; 3],
         }
     }
 }
 
 impl ParticleInstance {
+    /// ages the particle by `delta_time`, once per step, so the lifetimes are in seconds of (scaled) time
     pub fn update(&mut self, delta_time: Duration) {
         self.age = self.age.add(delta_time);
     }
+
+    /// turns the particle so the -x side of its texture points along `velocity`, that's where the tip of the boid
+    /// dart is. a zero velocity keeps the old rotation
+    pub fn face_velocity(&mut self, velocity: Vector3<f32>) {
+        if velocity.x != 0.0 || velocity.y != 0.0 {
+            self.rotation = (-velocity.y).atan2(-velocity.x);
+        }
+    }
 }
 
 impl Position2 for ParticleInstance {
@@ -297,6 +639,11 @@ pub struct ParticleInstanceRaw {
     //velocity: [f32; 3],
     pub(crate) scale: f32,
     pub(crate) position: [f32; 3],
+    pub(crate) tex_offset: [f32; 2],
+    pub(crate) tex_scale: [f32; 2],
+    pub(crate) rotation: f32,
+    //the gpu physics use this as a storage buffer, where the size has to be a multiple of 16 bytes
+    pub(crate) _padding: [f32; 3],
 }
 
 impl LayoutDescriptor for ParticleInstanceRaw {
@@ -324,7 +671,130 @@ impl LayoutDescriptor for ParticleInstanceRaw {
                     shader_location: 5,
                     format: wgpu::VertexFormat::Float32x3,
                 },
+                //texture offset
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    shader_location: 6,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                //texture scale
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 10]>() as wgpu::BufferAddress,
+                    shader_location: 7,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                //rotation
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 12]>() as wgpu::BufferAddress,
+                    shader_location: 8,
+                    format: wgpu::VertexFormat::Float32,
+                },
             ],
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const RADIUS: f32 = 0.1;
+
+    fn instance(position: Vector3<f32>) -> ParticleInstance {
+        ParticleInstance {
+            position,
+            previous_position: position,
+            color: wgpu::Color::WHITE,
+            scale: 1.0,
+            tex_offset: [0.0, 0.0],
+            tex_scale: [1.0, 1.0],
+            rotation: 0.0,
+            age: Duration::ZERO,
+        }
+    }
+
+    fn inside(domain: &BoundingBox<f32>, position: Vector3<f32>) -> bool {
+        (domain.min_pos.x..=domain.max_pos.x).contains(&position.x)
+            && (domain.min_pos.y..=domain.max_pos.y).contains(&position.y)
+            && (domain.min_pos.z..=domain.max_pos.z).contains(&position.z)
+    }
+
+    #[test]
+    fn nothing_is_left_outside_after_a_resize() {
+        for bound_type in [
+            BoundingBoxType::Clamp,
+            BoundingBoxType::Modulo,
+            BoundingBoxType::Bounce,
+        ] {
+            for behavior in [ResizeBehavior::Preserve, ResizeBehavior::Respawn] {
+                let old_domain = BoundingBox::new(
+                    Vector3::new(-2.0, -1.0, 0.0),
+                    Vector3::new(2.0, 1.0, 1.0),
+                    bound_type,
+                );
+                let domain = BoundingBox::new(
+                    Vector3::new(-0.5, -0.5, 0.0),
+                    Vector3::new(1.0, 0.5, 0.5),
+                    bound_type,
+                );
+                let mut rng = StdRng::seed_from_u64(1);
+                let mut positions: Vec<Vector3<f32>> = (0..1000)
+                    .map(|_| old_domain.random_pos_with(&mut rng))
+                    .collect();
+                //and the corners, which are the furthest out
+                positions.push(old_domain.min_pos);
+                positions.push(old_domain.max_pos);
+                let mut instances =
+                    InstanceContainer::new(positions.into_iter().map(instance).collect(), 1, 1);
+
+                move_into_domain(&mut instances, old_domain, domain, behavior);
+
+                for instance in instances.iter() {
+                    assert!(
+                        inside(&domain, instance.position),
+                        "{:?} is outside the domain with {:?} and {:?}",
+                        instance.position,
+                        bound_type,
+                        behavior
+                    );
+                    assert_eq!(instance.previous_position, instance.position);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bounces_off_every_wall() {
+        //the min and max of the x, y and z walls
+        for (min, max) in [(-1.0, 1.0), (-0.5, 2.0), (0.0, 3.0)] {
+            let (position, velocity, bounced) =
+                bounce_axis(min + 0.05, -1.0, min, max, RADIUS, 1.0, 1.0);
+            assert_eq!(position, min + RADIUS);
+            assert_eq!(velocity, 1.0);
+            assert!(bounced);
+
+            let (position, velocity, bounced) =
+                bounce_axis(max - 0.05, 1.0, min, max, RADIUS, 1.0, 1.0);
+            assert_eq!(position, max - RADIUS);
+            assert_eq!(velocity, -1.0);
+            assert!(bounced);
+        }
+    }
+
+    #[test]
+    fn leaving_a_wall_isnt_a_bounce() {
+        let (position, velocity, bounced) = bounce_axis(-0.95, 1.0, -1.0, 1.0, RADIUS, 1.0, 0.5);
+        assert_eq!((position, velocity, bounced), (-0.95, 1.0, false));
+        //backwards in time the same particle is moving into the wall
+        let (position, velocity, bounced) = bounce_axis(-0.95, 1.0, -1.0, 1.0, RADIUS, -1.0, 0.5);
+        assert_eq!((position, velocity, bounced), (-1.0 + RADIUS, -0.5, true));
+    }
+
+    #[test]
+    fn away_from_the_walls_nothing_changes() {
+        let (position, velocity, bounced) = bounce_axis(0.0, 1.0, -1.0, 1.0, RADIUS, 1.0, 1.0);
+        assert_eq!((position, velocity, bounced), (0.0, 1.0, false));
+    }
+}
@@ -0,0 +1,121 @@
+use crate::configurator::Configurator;
+use crate::State;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+const WIDTH: u32 = 640;
+const HEIGHT: u32 = 360;
+
+/// renders `frames` frames off-screen, each exactly `1 / fps` seconds after the previous one no
+/// matter how long they take to draw, and saves them to `out`. paths ending in .mp4 are encoded
+/// with ffmpeg (which has to be installed), anything else becomes a gif.
+/// with a seed in the config every run starts out the same, but the physics run on several threads (or on the
+/// gpu), so the order floats get added up in can change and a long clip can drift apart between runs
+pub async fn record(config: Configurator, frames: u32, fps: f32, out: &Path) -> anyhow::Result<()> {
+    let mut config = config;
+    //the count would otherwise depend on how fast the frames happen to render
+    config.adaptive_count = false;
+    let fps = fps.max(1.0);
+    let frame_time = Duration::from_secs_f32(1.0 / fps);
+
+    let mut encoder: Box<dyn FrameEncoder> = match out.extension().and_then(|e| e.to_str()) {
+        Some("mp4") => Box::new(Mp4Encoder::new(out, fps)?),
+        _ => Box::new(GifEncoder::new(out, fps)?),
+    };
+
+    let mut state = State::new_headless(&config, WIDTH, HEIGHT).await?;
+    for _ in 0..frames {
+        state.advance(&mut config, frame_time);
+        state.render()?;
+        encoder.write_frame(state.read_target()?)?;
+    }
+    encoder.finish()
+}
+
+trait FrameEncoder {
+    fn write_frame(&mut self, frame: image::RgbaImage) -> anyhow::Result<()>;
+    fn finish(self: Box<Self>) -> anyhow::Result<()>;
+}
+
+struct GifEncoder {
+    encoder: gif::Encoder<File>,
+    //in hundredths of a second
+    delay: u16,
+}
+
+impl GifEncoder {
+    fn new(path: &Path, fps: f32) -> anyhow::Result<Self> {
+        let mut encoder = gif::Encoder::new(File::create(path)?, WIDTH as u16, HEIGHT as u16, &[])?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+        Ok(Self {
+            encoder,
+            delay: (100.0 / fps).round().max(1.0) as u16,
+        })
+    }
+}
+
+impl FrameEncoder for GifEncoder {
+    fn write_frame(&mut self, frame: image::RgbaImage) -> anyhow::Result<()> {
+        let (width, height) = frame.dimensions();
+        let mut pixels = frame.into_raw();
+        let mut frame = gif::Frame::from_rgba_speed(width as u16, height as u16, &mut pixels, 10);
+        frame.delay = self.delay;
+        self.encoder.write_frame(&frame)?;
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> anyhow::Result<()> {
+        //the trailer is written when the encoder is dropped
+        Ok(())
+    }
+}
+
+/// pipes the raw frames into ffmpeg
+struct Mp4Encoder {
+    ffmpeg: Child,
+}
+
+impl Mp4Encoder {
+    fn new(path: &Path, fps: f32) -> anyhow::Result<Self> {
+        let ffmpeg = Command::new("ffmpeg")
+            .args([
+                "-y",
+                "-loglevel",
+                "error",
+                "-f",
+                "rawvideo",
+                "-pix_fmt",
+                "rgba",
+            ])
+            .args(["-s", &format!("{}x{}", WIDTH, HEIGHT)])
+            .args(["-r", &fps.to_string()])
+            .args(["-i", "-", "-pix_fmt", "yuv420p"])
+            .arg(path)
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("Failed to start ffmpeg: {}", e))?;
+        Ok(Self { ffmpeg })
+    }
+}
+
+impl FrameEncoder for Mp4Encoder {
+    fn write_frame(&mut self, frame: image::RgbaImage) -> anyhow::Result<()> {
+        match self.ffmpeg.stdin.as_mut() {
+            Some(stdin) => Ok(stdin.write_all(frame.as_raw())?),
+            None => Err(anyhow::anyhow!("ffmpeg's input was already closed")),
+        }
+    }
+
+    fn finish(mut self: Box<Self>) -> anyhow::Result<()> {
+        //closing the input tells ffmpeg there are no more frames
+        drop(self.ffmpeg.stdin.take());
+        let status = self.ffmpeg.wait()?;
+        if !status.success() {
+            return Err(anyhow::anyhow!("ffmpeg exited with {}", status));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+// ball physics on the gpu: the balls are sorted into a grid, collide with the balls in the
+// surrounding cells, and then move. the same buffer is drawn as the instance buffer afterwards
+
+// the same layout as ParticleInstanceRaw. the position is split up, because a vec3 would be padded
+struct Instance {
+    color: vec4<f32>,
+    scale: f32,
+    position_x: f32,
+    position_y: f32,
+    position_z: f32,
+    tex_offset: vec2<f32>,
+    tex_scale: vec2<f32>,
+    // padded to 16 bytes, like in ParticleInstanceRaw
+    @size(16) rotation: f32,
+}
+
+struct Body {
+    // w is the mass
+    velocity: vec4<f32>,
+    // the velocity after the collisions, so the other balls still see the old one while colliding
+    resolved: vec4<f32>,
+    // how far the ball has to move to stop overlapping the others
+    correction: vec4<f32>,
+}
+
+struct Params {
+    domain_min: vec2<f32>,
+    domain_max: vec2<f32>,
+    // already multiplied by the time step
+    gravity: vec2<f32>,
+    // negative when time runs backwards
+    dt: f32,
+    restitution: f32,
+    // the diameter of a ball with a scale of 1
+    collider: f32,
+    count: u32,
+    cells_x: u32,
+    cells_y: u32,
+    // the part of the velocity that is left after friction
+    kept_speed: f32,
+}
+
+// must match CELL_CAPACITY in gpu_physics.rs. balls that don't fit into a full cell don't collide that step
+const CELL_CAPACITY: u32 = 32u;
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+@group(0) @binding(1)
+var<storage, read_write> instances: array<Instance>;
+@group(0) @binding(2)
+var<storage, read_write> bodies: array<Body>;
+@group(0) @binding(3)
+var<storage, read_write> cell_counts: array<atomic<u32>>;
+@group(0) @binding(4)
+var<storage, read_write> cell_entries: array<u32>;
+
+fn position_of(i: u32) -> vec2<f32> {
+    return vec2<f32>(instances[i].position_x, instances[i].position_y);
+}
+
+fn diameter_of(i: u32) -> f32 {
+    return instances[i].scale * params.collider;
+}
+
+fn cell_of(position: vec2<f32>) -> vec2<i32> {
+    let cells = vec2<f32>(f32(params.cells_x), f32(params.cells_y));
+    let cell = vec2<i32>((position - params.domain_min) / (params.domain_max - params.domain_min) * cells);
+    return clamp(cell, vec2<i32>(0, 0), vec2<i32>(i32(params.cells_x) - 1, i32(params.cells_y) - 1));
+}
+
+fn cell_index(cell: vec2<i32>) -> u32 {
+    return u32(cell.y) * params.cells_x + u32(cell.x);
+}
+
+// the same as bounce_axis in particle.rs
+fn bounce_axis(position: f32, velocity: f32, lower: f32, upper: f32, radius: f32, direction: f32) -> f32 {
+    let into_min = position - radius < lower && velocity * direction < 0.0;
+    let into_max = position + radius > upper && velocity * direction > 0.0;
+    if (into_min || into_max) {
+        return -velocity * params.restitution;
+    }
+    return velocity;
+}
+
+@compute @workgroup_size(64)
+fn bin(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if (i >= params.count) {
+        return;
+    }
+    let cell = cell_index(cell_of(position_of(i)));
+    let slot = atomicAdd(&cell_counts[cell], 1u);
+    if (slot < CELL_CAPACITY) {
+        cell_entries[cell * CELL_CAPACITY + slot] = i;
+    }
+}
+
+@compute @workgroup_size(64)
+fn collide(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if (i >= params.count) {
+        return;
+    }
+    let own_position = position_of(i);
+    let own_diameter = diameter_of(i);
+    let mass = bodies[i].velocity.w;
+    let velocity = bodies[i].velocity.xy;
+    var resolved = velocity;
+    var correction = vec2<f32>(0.0, 0.0);
+    let cell = cell_of(own_position);
+
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let other_cell = cell + vec2<i32>(x, y);
+            if (other_cell.x < 0 || other_cell.y < 0 || other_cell.x >= i32(params.cells_x) || other_cell.y >= i32(params.cells_y)) {
+                continue;
+            }
+            let index = cell_index(other_cell);
+            let count = min(atomicLoad(&cell_counts[index]), CELL_CAPACITY);
+            for (var k = 0u; k < count; k++) {
+                let j = cell_entries[index * CELL_CAPACITY + k];
+                if (j == i) {
+                    continue;
+                }
+                let offset = own_position - position_of(j);
+                let distance2 = dot(offset, offset);
+                // the balls touch when they are as far apart as their radii together
+                let target_distance = (own_diameter + diameter_of(j)) / 2.0;
+                if (distance2 < target_distance * target_distance && distance2 > 0.0) {
+                    let dist = sqrt(distance2);
+                    // the lighter ball moves more of the way, the other one does its part itself
+                    let other_mass = bodies[j].velocity.w;
+                    let share = other_mass / (mass + other_mass);
+                    correction += offset / dist * (target_distance - dist) * share;
+                    // https://stackoverflow.com/questions/35211114/2d-elastic-ball-collision-physics
+                    resolved -= offset * dot(velocity - bodies[j].velocity.xy, offset) / distance2 * 2.0 * share;
+                }
+            }
+        }
+    }
+
+    bodies[i].resolved = vec4<f32>(resolved, bodies[i].velocity.zw);
+    bodies[i].correction = vec4<f32>(correction, 0.0, 0.0);
+}
+
+@compute @workgroup_size(64)
+fn integrate(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if (i >= params.count) {
+        return;
+    }
+    var velocity = (bodies[i].resolved.xy + params.gravity) * params.kept_speed;
+    var new_position = position_of(i) + bodies[i].correction.xy;
+
+    let radius = diameter_of(i) / 2.0;
+    let direction = sign(params.dt);
+    velocity.x = bounce_axis(new_position.x, velocity.x, params.domain_min.x, params.domain_max.x, radius, direction);
+    velocity.y = bounce_axis(new_position.y, velocity.y, params.domain_min.y, params.domain_max.y, radius, direction);
+    new_position = clamp(new_position + velocity * params.dt, params.domain_min, params.domain_max);
+
+    bodies[i].velocity = vec4<f32>(velocity, bodies[i].resolved.zw);
+    instances[i].position_x = new_position.x;
+    instances[i].position_y = new_position.y;
+}
@@ -5,6 +5,15 @@ struct CameraUniform {
 @group(1) @binding(0)
 var<uniform> camera: CameraUniform;
 
+struct GlobalUniform {
+    edge_softness: f32,
+    opacity: f32,
+    offset: vec3<f32>,
+    tint: vec4<f32>,
+};
+@group(2) @binding(0)
+var<uniform> globals: GlobalUniform;
+
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) tex_coords: vec2<f32>,
@@ -13,11 +22,12 @@ struct VertexInput {
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) tex_coords: vec2<f32>,
-    //@location(1) color: vec4<f32>,
+    @location(1) color: vec4<f32>,
 }
 
 struct InstanceInput {
-    //@location(3) color: vec4<f32>,
+    //white unless the instance is tinted
+    @location(3) color: vec4<f32>,
     @location(4) scale: f32,
     @location(5) position: vec3<f32>,
     @location(6) model_matrix_0: vec4<f32>,
@@ -40,8 +50,10 @@ fn vs_main(
     );
 
     out.tex_coords = model.tex_coords;
+    out.color = instance.color;
     //out.clip_position[3] *= 0.01;
-    out.clip_position = camera.view_proj * model_matrix * vec4<f32>(instance.position + model.position * instance.scale, 1.0); // 2.
+    //the model matrix already moves the model to the instance's position
+    out.clip_position = camera.view_proj * model_matrix * vec4<f32>(model.position * instance.scale, 1.0); // 2.
     return out;
 }
 
@@ -54,7 +66,8 @@ var s_diffuse: sampler;
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    var out = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    var out = textureSample(t_diffuse, s_diffuse, in.tex_coords) * in.color;
+    out.a *= globals.opacity;
     if out[3] == 0 {
         discard;
     }
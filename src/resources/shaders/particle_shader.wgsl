@@ -5,6 +5,15 @@ struct CameraUniform {
 @group(1) @binding(0)
 var<uniform> camera: CameraUniform;
 
+struct GlobalUniform {
+    edge_softness: f32,
+    opacity: f32,
+    offset: vec3<f32>,
+    tint: vec4<f32>,
+};
+@group(2) @binding(0)
+var<uniform> globals: GlobalUniform;
+
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) tex_coords: vec2<f32>,
@@ -14,6 +23,8 @@ struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) tex_coords: vec2<f32>,
     @location(1) color: vec4<f32>,
+    //position on the quad, ignoring the texture atlas
+    @location(2) quad_coords: vec2<f32>,
 }
 
 struct InstanceInput {
@@ -21,6 +32,10 @@ struct InstanceInput {
     //@location(10) velocity: vec3<f32>,
     @location(4) scale: f32,
     @location(5) position: vec3<f32>,
+    @location(6) tex_offset: vec2<f32>,
+    @location(7) tex_scale: vec2<f32>,
+    //around the z axis, in radians
+    @location(8) rotation: f32,
 };
 
 @vertex
@@ -29,10 +44,14 @@ fn vs_main(
     instance: InstanceInput,
 ) -> VertexOutput {
     var out: VertexOutput;
-    out.tex_coords = model.tex_coords;
+    out.tex_coords = instance.tex_offset + model.tex_coords * instance.tex_scale;
     //out.clip_position[3] *= 0.01;
-    out.color = instance.color;
-    out.clip_position = camera.view_proj * vec4<f32>(instance.position + model.position * instance.scale, 1.0); // 2.
+    out.color = instance.color * globals.tint;
+    out.quad_coords = model.tex_coords;
+    let c = cos(instance.rotation);
+    let s = sin(instance.rotation);
+    let rotated = vec3<f32>(model.position.x * c - model.position.y * s, model.position.x * s + model.position.y * c, model.position.z);
+    out.clip_position = camera.view_proj * vec4<f32>(instance.position + globals.offset + rotated * instance.scale, 1.0); // 2.
     return out;
 }
 
@@ -47,6 +66,14 @@ var s_diffuse: sampler;
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     var out = textureSample(t_diffuse, s_diffuse, in.tex_coords);
     out *= in.color;
+    if globals.edge_softness >= 0.0 {
+        //draw a circle instead of the texture. 0 is the center of the quad, 1 is the edge
+        let dist = length(in.quad_coords - vec2<f32>(0.5, 0.5)) * 2.0;
+        let aa = fwidth(dist);
+        let alpha = 1.0 - smoothstep(1.0 - globals.edge_softness - aa, 1.0, dist);
+        out = vec4<f32>(in.color.rgb, in.color.a * alpha);
+    }
+    out.a *= globals.opacity;
     if out[3] == 0 {
         discard;
     }
@@ -0,0 +1,93 @@
+// The classic sine plasma, drawn over the whole screen without any vertex buffers
+struct PlasmaUniform {
+    //in seconds, already multiplied by the speed
+    time: f32,
+    palette: u32,
+};
+@group(0) @binding(0)
+var<uniform> plasma: PlasmaUniform;
+
+//the same time uniform as the one bound at group 3 for the other shaders
+struct TimeUniform {
+    time: f32,
+    dt: f32,
+    //in pixels
+    resolution: vec2<f32>,
+};
+@group(1) @binding(0)
+var<uniform> global_time: TimeUniform;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    //0-1 across the screen, with y going down like in textures
+    @location(0) screen_coords: vec2<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    //one triangle big enough to cover the whole screen
+    let x = f32((vertex_index << 1u) & 2u);
+    let y = f32(vertex_index & 2u);
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(x * 2.0 - 1.0, 1.0 - y * 2.0, 1.0, 1.0);
+    out.screen_coords = vec2<f32>(x, y);
+    return out;
+}
+
+// Fragment shader
+
+const TAU: f32 = 6.28318530718;
+
+//a*b*cos(tau*(c*t+d)) palettes, each one is (a, b, c, d)
+fn palette_color(t: f32, index: u32) -> vec3<f32> {
+    var a = vec3<f32>(0.5, 0.5, 0.5);
+    var b = vec3<f32>(0.5, 0.5, 0.5);
+    var c = vec3<f32>(1.0, 1.0, 1.0);
+    var d = vec3<f32>(0.0, 0.33, 0.67);
+    switch index {
+        //fire
+        case 1u: {
+            a = vec3<f32>(0.5, 0.25, 0.05);
+            b = vec3<f32>(0.5, 0.3, 0.05);
+            c = vec3<f32>(1.0, 1.0, 1.0);
+            d = vec3<f32>(0.0, 0.1, 0.2);
+        }
+        //ocean
+        case 2u: {
+            a = vec3<f32>(0.1, 0.35, 0.5);
+            b = vec3<f32>(0.1, 0.3, 0.4);
+            c = vec3<f32>(1.0, 1.0, 1.0);
+            d = vec3<f32>(0.5, 0.6, 0.7);
+        }
+        //neon
+        case 3u: {
+            a = vec3<f32>(0.5, 0.5, 0.5);
+            b = vec3<f32>(0.5, 0.5, 0.5);
+            c = vec3<f32>(2.0, 1.0, 0.0);
+            d = vec3<f32>(0.5, 0.2, 0.25);
+        }
+        //rainbow
+        default: {}
+    }
+    return a + b * cos(TAU * (c * t + d));
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    //the shorter side of the screen goes from -1 to 1, so the blobs stay round on any screen
+    let resolution = global_time.resolution;
+    let aspect = resolution / min(resolution.x, resolution.y);
+    let p = (in.screen_coords * 2.0 - 1.0) * aspect * 3.0;
+    let t = plasma.time;
+
+    var v = sin(p.x + t);
+    v += sin((p.y + t) * 0.5);
+    v += sin((p.x + p.y + t) * 0.5);
+    //a moving center the rings spread out from
+    let center = p + vec2<f32>(sin(t / 3.0), cos(t / 2.0)) * 2.0;
+    v += sin(sqrt(dot(center, center) + 1.0) + t);
+
+    //v is between -4 and 4
+    let color = palette_color(v * 0.125 + 0.5 + t * 0.05, plasma.palette);
+    return vec4<f32>(color, 1.0);
+}
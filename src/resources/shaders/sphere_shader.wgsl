@@ -0,0 +1,81 @@
+// Vertex shader
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+};
+@group(1) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct GlobalUniform {
+    edge_softness: f32,
+    opacity: f32,
+    offset: vec3<f32>,
+    tint: vec4<f32>,
+};
+@group(2) @binding(0)
+var<uniform> globals: GlobalUniform;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) normal: vec3<f32>,
+}
+
+struct InstanceInput {
+    @location(3) color: vec4<f32>,
+    @location(4) scale: f32,
+    @location(5) position: vec3<f32>,
+    //spheres look the same however they are turned, so the model matrix isn't used
+};
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+    instance: InstanceInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    //the ball camera looks towards +z, so the sphere is mirrored to have its outside face the camera
+    let position = model.position * vec3<f32>(1.0, 1.0, -1.0);
+    out.tex_coords = model.tex_coords;
+    out.color = instance.color * globals.tint;
+    out.normal = normalize(position);
+    out.clip_position = camera.view_proj * vec4<f32>(instance.position + globals.offset + position * instance.scale, 1.0);
+    return out;
+}
+
+// Fragment Shader
+
+@group(0) @binding(0)
+var t_diffuse: texture_2d<f32>;
+@group(0) @binding(1)
+var s_diffuse: sampler;
+
+//pointing from the balls towards the light, which is up, left and in front of the screen.
+//the camera is mirrored, so that is +x, -y and -z
+const LIGHT_DIRECTION: vec3<f32> = vec3<f32>(0.4, -0.5, -0.77);
+const AMBIENT: f32 = 0.25;
+const SPECULAR: f32 = 0.4;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let normal = normalize(in.normal);
+    let light = normalize(LIGHT_DIRECTION);
+    let diffuse = max(dot(normal, light), 0.0);
+    //blinn-phong, with the camera straight in front of the screen
+    let half_dir = normalize(light + vec3<f32>(0.0, 0.0, -1.0));
+    let specular = pow(max(dot(normal, half_dir), 0.0), 32.0) * SPECULAR;
+
+    var out = textureSample(t_diffuse, s_diffuse, in.tex_coords) * in.color;
+    //the highlight is tinted too, so the shadows stay dark
+    out = vec4<f32>(out.rgb * (AMBIENT + (1.0 - AMBIENT) * diffuse) + specular * globals.tint.rgb, out.a);
+    out.a *= globals.opacity;
+    if out[3] == 0 {
+        discard;
+    }
+    return out;
+}
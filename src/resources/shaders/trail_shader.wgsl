@@ -0,0 +1,40 @@
+// Draws a previous frame over the whole screen, faded out for the motion trails
+struct TrailUniform {
+    // the alpha the frame is drawn with
+    alpha: f32,
+    // taken off of every color channel, so faint trails end instead of getting stuck
+    cutoff: f32,
+    _padding: vec2<f32>,
+};
+@group(1) @binding(0)
+var<uniform> trail: TrailUniform;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    //0-1 across the screen, with y going down like in textures
+    @location(0) screen_coords: vec2<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    //one triangle big enough to cover the whole screen
+    let x = f32((vertex_index << 1u) & 2u);
+    let y = f32(vertex_index & 2u);
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(x * 2.0 - 1.0, 1.0 - y * 2.0, 1.0, 1.0);
+    out.screen_coords = vec2<f32>(x, y);
+    return out;
+}
+
+// Fragment shader
+
+@group(0) @binding(0)
+var t_diffuse: texture_2d<f32>;
+@group(0) @binding(1)
+var s_diffuse: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_diffuse, s_diffuse, in.screen_coords);
+    return vec4<f32>(max(color.rgb - vec3<f32>(trail.cutoff), vec3<f32>(0.0)), color.a * trail.alpha);
+}
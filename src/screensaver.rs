@@ -1,23 +1,33 @@
 use crate::configurator::Configurator;
+use crate::gpu_physics::GpuPhysics;
 use crate::instance::LayoutDescriptor;
 use crate::model::{
     DrawModel, Material, Mesh, Model, ModelInstance, ModelInstanceRaw, ModelMesh, Vertex,
 };
-use crate::particle::{ParticleInstance, ParticleInstanceRaw, ParticleSystem, ParticleSystemData};
+use crate::particle::{
+    Axis, ParticleData, ParticleEvent, ParticleInstance, ParticleInstanceRaw, ParticleSystem,
+    ParticleSystemData,
+};
+use crate::snapshot::ParticleSnapshot;
+use crate::util::glyphs;
+use crate::util::lines::{LineVertex, Lines, RibbonPoint, Ribbons};
+use crate::util::model::DDDModel;
 use crate::util::pos::{BoundingBox, BoundingBoxType};
 use crate::util::render::create_render_pipeline;
-use crate::{model, shaders, texture, util, CameraType, State};
+use crate::{model, shaders, snapshot, texture, util, Camera, CameraType, State};
 use cgmath::num_traits::FloatConst;
-use cgmath::{InnerSpace, MetricSpace, Point3, Quaternion, Rotation3, Vector3};
-use prisma::{Hsv, Rgb};
+use cgmath::{InnerSpace, MetricSpace, Point3, Quaternion, Rotation3, Vector3, VectorSpace};
 use rand::prelude::SliceRandom;
-use rand::random;
-use std::ops::{AddAssign, MulAssign};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::VecDeque;
+use std::ops::{Add, AddAssign, MulAssign};
 use std::path::PathBuf;
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::{Duration, Instant};
 #[cfg(target_arch = "wasm32")]
 use web_time::{Duration, Instant};
+use wgpu::util::DeviceExt;
 use wgpu::{
     BindGroupLayout, Color, Device, PipelineLayout, Queue, RenderPass, RenderPipelineDescriptor,
     TextureFormat,
@@ -26,10 +36,19 @@ use winit::dpi::Size;
 
 #[derive(Debug, Clone, PartialEq, Copy)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
-pub(crate) enum ScreenSaverType {
+pub enum ScreenSaverType {
     Snow,
     Balls,
     DDDModel, //can't do 3DModel
+    Clock,
+    Harmonograph,
+    CirclePacking,
+    Comet,
+    Starfield,
+    Matrix,
+    Fireworks,
+    Boids,
+    Plasma,
 }
 
 impl ToString for ScreenSaverType {
@@ -38,6 +57,15 @@ impl ToString for ScreenSaverType {
             ScreenSaverType::Snow => "snow".to_string(),
             ScreenSaverType::Balls => "balls".to_string(),
             ScreenSaverType::DDDModel => "3d_model".to_string(),
+            ScreenSaverType::Clock => "clock".to_string(),
+            ScreenSaverType::Harmonograph => "harmonograph".to_string(),
+            ScreenSaverType::CirclePacking => "packing".to_string(),
+            ScreenSaverType::Comet => "comet".to_string(),
+            ScreenSaverType::Starfield => "starfield".to_string(),
+            ScreenSaverType::Matrix => "matrix".to_string(),
+            ScreenSaverType::Fireworks => "fireworks".to_string(),
+            ScreenSaverType::Boids => "boids".to_string(),
+            ScreenSaverType::Plasma => "plasma".to_string(),
         }
     }
 }
@@ -64,7 +92,12 @@ pub trait ScreenSaver {
         pipeline_layout: &wgpu::PipelineLayout,
         color_format: wgpu::TextureFormat,
         depth_format: Option<wgpu::TextureFormat>,
+        sample_count: u32,
     );
+    /// moves the screensaver forward by `dt` on the cpu, without touching the gpu
+    fn simulate(&mut self, _size: Size, _config: &Configurator, _dt: Duration) {}
+    /// writes what `simulate` changed into the gpu buffers
+    fn upload(&mut self, _device: &wgpu::Device, _queue: &wgpu::Queue) {}
     fn update(
         &mut self,
         size: Size,
@@ -72,19 +105,152 @@ pub trait ScreenSaver {
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         dt: Duration,
-    );
+    ) {
+        self.simulate(size, config, dt);
+        self.upload(device, queue);
+    }
     fn resize(&mut self, old_ratio: f32, new_ratio: f32);
     fn get_background_color(&self) -> wgpu::Color;
     fn handle_input(&mut self, position: [f32; 2], id: u64, active: bool) -> bool;
     fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, state: &State<'_>);
     fn get_camera_type(&self) -> CameraType;
+    /// how close and how far from the perspective camera things are still drawn
+    fn get_clip_planes(&self) -> (f32, f32) {
+        (0.1, 100.0)
+    }
     fn get_camera_position(&self) -> (Point3<f32>, Point3<f32>);
+    /// moves the camera further after `get_camera_position` placed it, for screensavers where it moves on its own
+    fn update_camera(&mut self, _camera: &mut Camera, _config: &Configurator, _dt: Duration) {}
+    /// called after the fixed timestep updates with how far (0-1) into the next step the frame is
+    fn interpolate(&mut self, _alpha: f32, _queue: &wgpu::Queue) {}
+    /// if set, particles are drawn as procedural circles with this edge softness (0-1) instead of using their texture
+    fn get_edge_softness(&self, _config: &Configurator) -> Option<f32> {
+        None
+    }
+    /// if set, the screensaver draws a shadow pass (with the shadow bind group) offset by this much
+    fn get_shadow_offset(&self) -> Option<[f32; 2]> {
+        None
+    }
+    /// the particles of the screensaver, for saving the simulation state
+    fn save_state(&self) -> Vec<Vec<ParticleSnapshot>> {
+        vec![]
+    }
+    /// replaces the particles set up by `setup` with saved ones
+    fn load_state(&mut self, _particle_systems: &[Vec<ParticleSnapshot>], _device: &Device) {}
+}
+
+/// how far from the middle the copies of the model can be, when there's more than one
+const MODEL_SPREAD: f32 = 1.5;
+
+/// where a copy of the model is, and how far into the bounce and the spin it is ahead of the others
+#[derive(Debug, Clone, Copy)]
+struct ModelPlacement {
+    position: Vector3<f32>,
+    bounce_offset: f32,
+    spin_offset: f32,
+}
+
+impl Default for ModelPlacement {
+    fn default() -> Self {
+        Self {
+            position: Vector3::new(0.0, 0.0, 0.0),
+            bounce_offset: 0.0,
+            spin_offset: 0.0,
+        }
+    }
 }
 
+/// where the camera looks at the model from, when it isn't orbiting and the model fits
+const CAMERA_POSITION: Point3<f32> = Point3::new(3.0, 2.0, 3.0);
+/// how much room is left around the model when the camera is fit around it
+const CAMERA_FIT_MARGIN: f32 = 1.1;
+/// how many seconds apart the afterimages of the model are
+const TRAIL_SPACING: f32 = 0.06;
+/// how see-through the newest afterimage is, the older ones fade out from there
+const TRAIL_OPACITY: f32 = 0.5;
+
 pub struct DDDModelScreensaver {
     models: Vec<Model>,
+    //every mesh of the model gets an instance at every placement
+    placements: Vec<ModelPlacement>,
     rotation: f32,
     bounce_phase: f32,
+    //in degrees, where the orbiting camera is around the model
+    orbit_angle: f32,
+    //the box around every mesh of the model, for fitting the camera around it
+    bounds: Option<BoundingBox<f32>>,
+    //how much further than usual the camera is, so big models fit on the screen
+    camera_zoom: f32,
+    //the lens from the config, kept so the camera can be set up without it
+    fov: f32,
+    clip_planes: (f32, f32),
+    //seeded from the config, so the same seed scatters the models the same way
+    rng: StdRng,
+}
+
+impl DDDModelScreensaver {
+    /// how much further away the camera has to be than usual to see every copy of the model, however high it bounces.
+    /// never closer than usual, small models keep the usual framing
+    fn fit_camera(&self, config: &Configurator) -> f32 {
+        let Some(bounds) = self.bounds else {
+            return 1.0;
+        };
+        //the model is moved so the middle of the box is where its origin would be
+        let (min, max) = (
+            bounds.min_pos - bounds.center(),
+            bounds.max_pos - bounds.center(),
+        );
+        //the model spins around y, so how far out it reaches is the furthest corner from the axis
+        let reach = [min.x, max.x]
+            .iter()
+            .flat_map(|x| [min.z, max.z].map(|z| x.hypot(z)))
+            .fold(0.0, f32::max)
+            * config.model_scale;
+        let spread = self
+            .placements
+            .iter()
+            .map(|placement| placement.position.x.hypot(placement.position.z))
+            .fold(0.0, f32::max);
+        let (lowest, highest) =
+            self.placements
+                .iter()
+                .fold((0.0f32, 0.0f32), |(lowest, highest), placement| {
+                    (
+                        lowest.min(placement.position.y),
+                        highest.max(placement.position.y),
+                    )
+                });
+        let bottom = min.y * config.model_scale + config.bounce_floor + lowest;
+        let top = max.y * config.model_scale + config.bounce_floor + config.bounce_height + highest;
+        let radius = (reach + spread).hypot(bottom.abs().max(top.abs()));
+
+        //far enough for a sphere around all of it to fit in the lens, with a bit of room around it
+        let distance = radius * CAMERA_FIT_MARGIN / (config.fov.to_radians() / 2.0).sin();
+        let usual_distance = CAMERA_POSITION.distance(Point3::new(0.0, 0.0, 0.0));
+        (distance / usual_distance).max(1.0)
+    }
+
+    /// adds or removes copies of the model until there are `count` of them. a single one is in the middle
+    fn place_models(&mut self, count: usize) {
+        if count <= 1 {
+            self.placements = vec![ModelPlacement::default()];
+            return;
+        }
+        self.placements.truncate(count);
+        while self.placements.len() < count {
+            let rng = &mut self.rng;
+            self.placements.push(ModelPlacement {
+                position: Vector3::new(
+                    rng.gen_range(-MODEL_SPREAD..MODEL_SPREAD),
+                    rng.gen_range(-MODEL_SPREAD..MODEL_SPREAD) / 2.0,
+                    rng.gen_range(-MODEL_SPREAD..MODEL_SPREAD),
+                ),
+                //a bounce takes 2 and a spin takes 4
+                bounce_offset: rng.gen_range(0.0..2.0),
+                spin_offset: rng.gen_range(0.0..4.0),
+            });
+        }
+    }
 }
 
 impl ScreenSaver for DDDModelScreensaver {
@@ -94,8 +260,16 @@ impl ScreenSaver for DDDModelScreensaver {
     {
         Self {
             models: vec![],
+            placements: vec![],
             rotation: 0.0,
             bounce_phase: 0.0,
+            //the same direction as the camera that doesn't orbit
+            orbit_angle: 45.0,
+            bounds: None,
+            camera_zoom: 1.0,
+            fov: config.fov,
+            clip_planes: (config.znear, config.zfar),
+            rng: config.rng(),
         }
     }
 
@@ -109,32 +283,49 @@ impl ScreenSaver for DDDModelScreensaver {
         pipeline_layout: &PipelineLayout,
         color_format: TextureFormat,
         depth_format: Option<TextureFormat>,
+        sample_count: u32,
     ) {
-        let shader = wgpu::ShaderModuleDescriptor {
-            label: Some("Mesh Shader"),
-            source: shaders::get(shaders::ShaderType::MeshShader),
-        };
-
-        let mut model = Model::load(
-            config.ddd_model,
-            Vector3::new(0.0, 0.0, 0.0),
-            device,
-            queue,
-            layout,
+        let create_pipeline = || {
             create_render_pipeline(
                 device,
                 pipeline_layout,
                 color_format,
                 depth_format,
+                sample_count,
                 &[model::ModelVertex::desc(), ModelInstanceRaw::desc()],
-                shader,
-            ),
-        )
-        .unwrap();
+                wgpu::ShaderModuleDescriptor {
+                    label: Some("Mesh Shader"),
+                    source: shaders::get(shaders::ShaderType::MeshShader),
+                },
+            )
+        };
+        let load = |ddd_model: &DDDModel| {
+            Model::load(
+                ddd_model,
+                Vector3::new(0.0, 0.0, 0.0),
+                config.texture_quality,
+                device,
+                queue,
+                layout,
+                &create_pipeline,
+            )
+        };
 
-        model.mesh.update_instance_buffer(queue);
+        //a broken custom model shouldn't take the whole screensaver down with it
+        let models = load(&config.ddd_model).unwrap_or_else(|e| {
+            log::error!("Failed to load {}: {}", config.ddd_model.to_string(), e);
+            load(&DDDModel::Apple).unwrap()
+        });
 
-        self.models.push(model);
+        for mut model in models {
+            model.mesh.update_instance_buffer(queue);
+            self.models.push(model);
+        }
+        self.bounds = self
+            .models
+            .iter()
+            .filter_map(|model| model.aabb())
+            .reduce(|a, b| a.union(&b));
     }
 
     fn update(
@@ -145,20 +336,65 @@ impl ScreenSaver for DDDModelScreensaver {
         queue: &Queue,
         dt: Duration,
     ) {
+        self.fov = config.fov;
+        self.clip_planes = (config.znear, config.zfar);
         self.bounce_phase += dt.as_secs_f32() * config.bounce_speed;
-        self.rotation += dt.as_secs_f32() * config.spin_speed;
+        //the model holds still while the camera moves around it
+        let spin_speed = match config.orbit_camera {
+            true => 0.0,
+            false => config.spin_speed,
+        };
+        self.rotation += dt.as_secs_f32() * spin_speed;
+        if self.placements.len() != config.model_count {
+            self.place_models(config.model_count);
+        }
+        self.camera_zoom = self.fit_camera(config);
         for model in &mut self.models {
-            model.update(dt, queue);
+            model.update(dt, config, queue);
             //get (ParticleSystem)(Object) idiot
             if let Some(model) = model.mesh.as_any_mut().downcast_mut::<ModelMesh>() {
-                for instance in &mut model.instances {
-                    instance.position.y =
-                        f32::sin(self.bounce_phase * f32::PI()) * config.bounce_height;
+                let old_count = model.instances.len();
+                let placement_count = self.placements.len();
+                let center = self
+                    .bounds
+                    .map_or(Vector3::new(0.0, 0.0, 0.0), |bounds| bounds.center());
+                model.instances.resize(
+                    placement_count * (config.model_trail_count + 1),
+                    ModelInstance::default(),
+                );
+                //the models come first and the oldest afterimages last, so the depth keeps the afterimages behind the models
+                for (i, instance) in model.instances.iter_mut().enumerate() {
+                    let placement = &self.placements[i % placement_count];
+                    let age = (i / placement_count) as f32;
+                    //the poses come from the phases, so the past ones are found by going back in time
+                    let time_back = age * TRAIL_SPACING;
+                    let bounce_phase = self.bounce_phase - time_back * config.bounce_speed;
+                    let rotation = self.rotation - time_back * spin_speed;
                     instance.rotation = Quaternion::from_axis_angle(
                         cgmath::Vector3::unit_y(),
-                        cgmath::Deg(self.rotation * 90.0),
+                        cgmath::Deg((rotation + placement.spin_offset) * 90.0),
                     );
+                    let position = placement.position
+                        + Vector3::unit_y()
+                            * (config.bounce_floor
+                                + (f32::sin((bounce_phase + placement.bounce_offset) * f32::PI())
+                                    + 1.0)
+                                    / 2.0
+                                    * config.bounce_height);
+                    //the model spins around the middle of its box, wherever its origin happens to be
+                    instance.position =
+                        position - instance.rotation * (center * config.model_scale);
                     instance.scale = config.model_scale;
+                    instance.color.a = if age == 0.0 {
+                        1.0
+                    } else {
+                        (TRAIL_OPACITY * (1.0 - age / (config.model_trail_count + 1) as f32)) as f64
+                    };
+                }
+                //the buffer only has room for the old instances
+                if model.instances.len() != old_count {
+                    model.rebuild_instance_buffer(device);
+                    continue;
                 }
             }
             model.mesh.update_instance_buffer(queue);
@@ -193,20 +429,42 @@ impl ScreenSaver for DDDModelScreensaver {
     }
 
     fn get_camera_type(&self) -> CameraType {
-        CameraType::Perspective(30.0)
+        CameraType::Perspective(self.fov)
+    }
+
+    fn get_clip_planes(&self) -> (f32, f32) {
+        self.clip_planes
     }
 
     fn get_camera_position(&self) -> (Point3<f32>, Point3<f32>) {
-        (Point3::new(3.0, 2.0, 3.0), Point3::new(0.0, 0.0, 0.0))
+        (
+            CAMERA_POSITION * self.camera_zoom,
+            Point3::new(0.0, 0.0, 0.0),
+        )
+    }
+
+    fn update_camera(&mut self, camera: &mut Camera, config: &Configurator, dt: Duration) {
+        if !config.orbit_camera {
+            return;
+        }
+        self.orbit_angle = (self.orbit_angle + dt.as_secs_f32() * config.orbit_speed) % 360.0;
+        let angle = self.orbit_angle.to_radians();
+        camera.eye = Point3::new(
+            angle.cos() * config.orbit_radius,
+            config.orbit_height,
+            angle.sin() * config.orbit_radius,
+        ) * self.camera_zoom;
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Copy)]
-pub(crate) enum BallColorMode {
+pub enum BallColorMode {
     Random,
     Color,
     Infection,
     Temperature,
+    /// like the dvd logo, a ball changes color every time it bounces off of a wall and flashes when it hits a corner
+    DvdBounce,
 }
 
 impl ToString for BallColorMode {
@@ -216,260 +474,281 @@ impl ToString for BallColorMode {
             BallColorMode::Color => "color".to_string(),
             BallColorMode::Infection => "infection".to_string(),
             BallColorMode::Temperature => "temperature".to_string(),
+            BallColorMode::DvdBounce => "dvd_bounce".to_string(),
+        }
+    }
+}
+
+/// how the balls are drawn
+#[derive(Debug, Clone, PartialEq, Copy)]
+pub enum BallRenderMode {
+    /// flat circles
+    Sprite,
+    /// lit 3d spheres
+    Sphere,
+}
+
+impl ToString for BallRenderMode {
+    fn to_string(&self) -> String {
+        match self {
+            BallRenderMode::Sprite => "sprite".to_string(),
+            BallRenderMode::Sphere => "sphere".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Copy)]
+pub enum MouseMode {
+    /// moves the balls along with the cursor
+    Push,
+    /// pulls the balls towards the cursor
+    Attract,
+    /// pushes the balls away from the cursor
+    Repel,
+}
+
+impl ToString for MouseMode {
+    fn to_string(&self) -> String {
+        match self {
+            MouseMode::Push => "push".to_string(),
+            MouseMode::Attract => "attract".to_string(),
+            MouseMode::Repel => "repel".to_string(),
         }
     }
 }
 
+//the impulse the attract and repel modes give a ball one unit away from the cursor, per mouse movement
+const MOUSE_FORCE: f32 = 0.002;
+const MAX_MOUSE_IMPULSE: f32 = 0.05;
+
+//blue, cyan, green, yellow and red, from the slowest balls to the fastest ones
+const TEMPERATURE_PALETTE: [Color; 5] = [
+    Color {
+        r: 0.0,
+        g: 0.0,
+        b: 1.0,
+        a: 1.0,
+    },
+    Color {
+        r: 0.0,
+        g: 1.0,
+        b: 1.0,
+        a: 1.0,
+    },
+    Color {
+        r: 0.0,
+        g: 1.0,
+        b: 0.0,
+        a: 1.0,
+    },
+    Color {
+        r: 1.0,
+        g: 1.0,
+        b: 0.0,
+        a: 1.0,
+    },
+    Color {
+        r: 1.0,
+        g: 0.0,
+        b: 0.0,
+        a: 1.0,
+    },
+];
+//how many seconds the temperature scale takes to follow the slowest and fastest ball
+const TEMPERATURE_SMOOTHING: f32 = 1.0;
+
+/// the color of a ball in the temperature color mode. `heat` is 0 for the slowest balls and 1 for the fastest
+fn temperature_color(heat: f32, space: util::color::ColorSpace) -> Color {
+    let position = heat.clamp(0.0, 1.0) * (TEMPERATURE_PALETTE.len() - 1) as f32;
+    let index = (position as usize).min(TEMPERATURE_PALETTE.len() - 2);
+    util::color::lerp_color(
+        TEMPERATURE_PALETTE[index],
+        TEMPERATURE_PALETTE[index + 1],
+        (position - index as f32) as f64,
+        space,
+    )
+}
+/// how many seconds of movement the velocity debug lines show
+const VELOCITY_LINE_LENGTH: f32 = 0.25;
+/// how long the balls flash for once all of them are infected
+const INFECTION_PULSE: Duration = Duration::from_millis(500);
+/// how long a ball flashes for after hitting a corner in the dvd bounce color mode
+const CORNER_FLASH: Duration = Duration::from_millis(1000);
+
+/// a ball that hit a corner, fading from white to its new color
+struct CornerFlash {
+    ball: usize,
+    remaining: Duration,
+    color: Color,
+}
+
 pub struct BallScreenSaver {
     balls: Vec<Model>,
     inputs: [Option<[f32; 2]>; 6],
     first_input_handled: bool,
     actual_ball_speed: f32,
+    //the slowest and fastest ball, smoothed over time, for the temperature color mode
+    temperature_range: (f32, f32),
+    velocity_lines: Option<Lines>,
+    //how long the balls have been (almost) still, for the auto re-toss
+    low_energy_time: Duration,
+    //how much is left of the flash after every ball got infected
+    infection_pulse: Option<Duration>,
+    //every ball is infected and the infection doesn't restart
+    infection_finished: bool,
+    //the balls that hit a corner recently, for the dvd bounce color mode
+    corner_flashes: Vec<CornerFlash>,
+    //only while the sound is on
+    #[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+    sounds: Option<crate::audio::BallSounds>,
+    //set while the balls are simulated on the gpu
+    gpu_physics: Option<GpuPhysics>,
+    //drawn in place of the balls in the sphere render mode
+    spheres: Option<Model>,
+    //how many balls are around every ball, only counted while the density is shown
+    densities: Vec<f32>,
+    //seeded from the config, so the same seed sets up the same balls
+    rng: StdRng,
     //config
     color: Color,
     old_config: Configurator,
 }
-impl ScreenSaver for BallScreenSaver {
-    fn new(config: Configurator) -> BallScreenSaver
-    where
-        Self: Sized,
-    {
-        Self {
-            balls: vec![],
-            inputs: [None; 6],
-            first_input_handled: false,
-            color: util::color::color_from_hex(config.color.to_hex()).unwrap(),
-            actual_ball_speed: config.ball_speed,
-            old_config: config,
-        }
+impl BallScreenSaver {
+    /// a random color from the configured range, for the random color mode
+    fn random_ball_color(config: &Configurator, rng: &mut impl Rng) -> Color {
+        util::color::random_color_in_range(
+            (config.hue_min, config.hue_max),
+            config.saturation,
+            config.value,
+            rng,
+        )
     }
-    fn setup(
-        &mut self,
-        size: Size,
-        config: &Configurator,
-        device: &wgpu::Device,
-        queue: &wgpu::Queue,
-        layout: &wgpu::BindGroupLayout,
-        pipeline_layout: &wgpu::PipelineLayout,
-        color_format: wgpu::TextureFormat,
-        depth_format: Option<wgpu::TextureFormat>,
-    ) {
-        let ratio = if size.to_logical::<f32>(1.0).width > 1.0 {
-            size.to_logical::<f32>(1.0).width / size.to_logical::<f32>(1.0).height
-        } else {
-            1.0
-        };
-
-        let circle_texture = include_bytes!("resources/textures/circle16.png");
-        let diffuse_texture =
-            texture::Texture::from_bytes(device, queue, circle_texture, "circle16.png").unwrap();
-        let mut particle_system = ParticleSystem::create_billboard(
-            0.16,
-            0.16,
-            Vector3::new(0.0, 0.0, 0.0),
-            ParticleSystemData::new(BoundingBox::new_with_size(
-                //Vector3::new(0.0, 0.0, 0.55),
-                Vector3::new(0.0, 0.0, 0.0),
-                2.0 * ratio,
-                2.0,
-                //1.00,
-                0.0,
-                BoundingBoxType::Bounce,
-            )),
-            device,
-        );
 
-        let shader = wgpu::ShaderModuleDescriptor {
-            label: Some("Ball Shader"),
-            source: shaders::get(shaders::ShaderType::ParticleShader),
-        };
+    /// a random velocity going up, faster than the ball speed so the balls fly around for a while
+    fn toss_velocity(config: &Configurator, rng: &mut impl Rng) -> Vector3<f32> {
+        //the camera is mirrored, so -y is up
+        Vector3::new(rng.gen::<f32>() - 0.5, -1.0, 0.0).normalize()
+            * config.ball_speed
+            * (1.0 + rng.gen::<f32>())
+    }
 
-        let material = Material::new(
-            diffuse_texture,
-            device,
-            layout,
-            util::render::create_render_pipeline(
-                device,
-                pipeline_layout,
-                color_format,
-                depth_format,
-                &[model::ModelVertex::desc(), ParticleInstanceRaw::desc()],
-                shader,
-            ),
-        );
+    /// a random direction at the ball speed
+    fn random_velocity(config: &Configurator, rng: &mut impl Rng) -> Vector3<f32> {
+        Vector3::new(rng.gen::<f32>() - 0.5, rng.gen::<f32>() - 0.5, 0.0).normalize()
+            * config.ball_speed
+    }
 
-        particle_system.populate_random(config.ball_count, device);
+    /// a random size between the smallest and the largest ball size
+    fn random_ball_size(config: &Configurator, rng: &mut impl Rng) -> f32 {
+        match config.ball_size_max {
+            Some(max) if max > config.ball_size => rng.gen_range(config.ball_size..max),
+            _ => config.ball_size,
+        }
+    }
 
-        let infection_starting_color = util::color::random_color();
+    /// the biggest a ball can get, the regions have to fit it
+    fn largest_ball_size(config: &Configurator) -> f32 {
+        config
+            .ball_size_max
+            .map_or(config.ball_size, |max| max.max(config.ball_size))
+    }
 
-        for i in 0..particle_system.instances.len() {
-            let instance = &mut particle_system.instances[i];
-            let data = &mut particle_system.particle_data[i];
+    /// the spheres are moved on the cpu, so they can't be used with the gpu physics,
+    /// and neither can anything that needs the particle events
+    fn uses_gpu_physics(config: &Configurator, device: &wgpu::Device) -> bool {
+        config.gpu_physics
+            && config.ball_render_mode == BallRenderMode::Sprite
+            && !Self::needs_events(config)
+            && GpuPhysics::supported(device)
+    }
 
-            let mut move_vector = Vector3::new(
-                random::<f32>() - 0.5,
-                random::<f32>() - 0.5,
-                //random::<f32>() - 0.5,
-                0.0,
-            );
-            move_vector = move_vector.normalize() * config.ball_speed;
+    /// the dvd bounce colors and the sound react to the balls hitting the walls and each other
+    fn needs_events(config: &Configurator) -> bool {
+        config.color_mode == BallColorMode::DvdBounce || Self::plays_sounds(config)
+    }
 
-            data.velocity = move_vector;
+    fn plays_sounds(config: &Configurator) -> bool {
+        cfg!(all(feature = "audio", not(target_arch = "wasm32"))) && config.sound
+    }
 
-            match config.color_mode {
-                BallColorMode::Random => {
-                    instance.color = util::color::random_color();
-                }
-                BallColorMode::Color => {
-                    instance.color = self.color;
-                }
-                BallColorMode::Infection => {
-                    if i == 0 {
-                        self.color = util::color::random_distinct_color(infection_starting_color);
-                        instance.color = self.color;
-                    } else {
-                        instance.color = infection_starting_color;
-                    }
-                }
-                _ => {
-                    instance.color = Color {
-                        r: 1.0,
-                        g: 1.0,
-                        b: 1.0,
-                        a: 1.0,
-                    };
-                }
+    /// puts a sphere on every ball, `alpha` of the way between their previous and current positions
+    fn sync_spheres(balls: &[Model], spheres: &mut ModelMesh, alpha: f32) {
+        spheres.instances.clear();
+        for model in balls {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any().downcast_ref::<ParticleSystem>() {
+                spheres
+                    .instances
+                    .extend(
+                        particle_system
+                            .instances
+                            .iter()
+                            .map(|instance| ModelInstance {
+                                position: instance.previous_position.lerp(instance.position, alpha),
+                                scale: instance.scale,
+                                color: instance.color,
+                                ..Default::default()
+                            }),
+                    );
             }
-            instance.scale = config.ball_size;
         }
-
-        let balls = Model {
-            mesh: Box::new(particle_system),
-            material,
-        };
-
-        self.balls.push(balls);
     }
 
-    fn update(
-        &mut self,
-        size: Size,
-        config: &Configurator,
-        device: &wgpu::Device,
-        queue: &wgpu::Queue,
-        dt: Duration,
-    ) {
-        let ratio = size.to_logical::<f32>(1.0).width / size.to_logical::<f32>(1.0).height;
-        //Note: this only is non-zero later if self.correct_ball_velocity is true
-        let mut total_velocity = 0.0;
+    /// a ball is as heavy as it is big
+    fn set_ball_size(instance: &mut ParticleInstance, data: &mut ParticleData, size: f32) {
+        instance.scale = size;
+        data.mass = size * size;
+    }
 
-        let mut infected_balls = 0;
-        let infection_starting_color = util::color::random_color();
+    /// applies the changes to the config to the balls. only on the cpu, `upload` makes room for new balls
+    fn apply_config(&mut self, config: &Configurator) {
+        if *config == self.old_config {
+            return;
+        }
+        println!("config changed");
+        let infection_starting_color = util::color::random_color_with(&mut self.rng);
 
         for model in &mut self.balls {
             //get (ParticleSystem)(Object) idiot
             if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
             {
-                if *config != self.old_config {
-                    println!("config changed");
-                    let mut should_rebuild_instance_buffer = false;
-
-                    if config.ball_speed != self.old_config.ball_speed {
-                        //redo the calculation because i am not sure if actual_ball_velocity is always calculated
-                        let mut total_v = 0.0;
-                        for data in particle_system.particle_data.iter() {
-                            total_v += data.velocity.magnitude();
-                        }
-                        let avg_v: f32 = total_v / particle_system.particle_data.len() as f32;
-                        if avg_v.is_normal() {
-                            for data in particle_system.particle_data.iter_mut() {
-                                data.velocity.mul_assign(config.ball_speed / avg_v);
-                            }
-                        }
+                if config.ball_speed != self.old_config.ball_speed {
+                    //redo the calculation because i am not sure if actual_ball_velocity is always calculated
+                    let mut total_v = 0.0;
+                    for data in particle_system.particle_data.iter() {
+                        total_v += data.velocity.magnitude();
                     }
-
-                    if config.ball_count != self.old_config.ball_count {
-                        let delta = config.ball_count as i32 - self.old_config.ball_count as i32;
-                        if delta > 0 {
-                            particle_system.populate_random(delta.try_into().unwrap(), device);
-
-                            for i in self.old_config.ball_count..particle_system.instances.len() {
-                                let instance = &mut particle_system.instances[i];
-                                let data = &mut particle_system.particle_data[i];
-
-                                let mut move_vector = Vector3::new(
-                                    random::<f32>() - 0.5,
-                                    random::<f32>() - 0.5,
-                                    //random::<f32>() - 0.5,
-                                    0.0,
-                                );
-                                move_vector = move_vector.normalize() * config.ball_speed;
-
-                                data.velocity = move_vector;
-
-                                match config.color_mode {
-                                    BallColorMode::Random => {
-                                        instance.color =
-                                            util::color::random_distinct_color(self.color);
-                                    }
-                                    BallColorMode::Color => {
-                                        instance.color = self.color;
-                                    }
-                                    BallColorMode::Infection => {
-                                        if i == 0 {
-                                            instance.color = self.color;
-                                        } else {
-                                            instance.color = infection_starting_color;
-                                        }
-                                    }
-                                    _ => {
-                                        instance.color = Color {
-                                            r: 1.0,
-                                            g: 1.0,
-                                            b: 1.0,
-                                            a: 1.0,
-                                        };
-                                    }
-                                }
-                                instance.scale = config.ball_size;
-                            }
-                        } else {
-                            particle_system
-                                .instances
-                                .instances
-                                .truncate(config.ball_count);
-                            particle_system.particle_data.truncate(config.ball_count);
+                    let avg_v: f32 = total_v / particle_system.particle_data.len() as f32;
+                    if avg_v.is_normal() {
+                        for data in particle_system.particle_data.iter_mut() {
+                            data.velocity.mul_assign(config.ball_speed / avg_v);
                         }
-
-                        should_rebuild_instance_buffer = true;
                     }
+                }
 
-                    if config.ball_size != self.old_config.ball_size {
-                        for instance in particle_system.instances.instances.iter_mut() {
-                            instance.scale = config.ball_size;
-                        }
-                    }
+                if config.ball_count != self.old_config.ball_count {
+                    let delta = config.ball_count as i32 - self.old_config.ball_count as i32;
+                    if delta > 0 {
+                        particle_system.add_random(delta.try_into().unwrap(), &mut self.rng);
 
-                    if config.color_mode != self.old_config.color_mode
-                        || config.color != self.old_config.color
-                    {
-                        self.color = util::color::color_from_hex(config.color.to_hex()).unwrap();
-                        let infection_starting_color = util::color::random_color();
-                        for i in 0..particle_system.instances.instances.len() {
+                        for i in self.old_config.ball_count..particle_system.instances.len() {
                             let instance = &mut particle_system.instances[i];
+                            let data = &mut particle_system.particle_data[i];
+
+                            data.velocity = Self::random_velocity(config, &mut self.rng);
+
                             match config.color_mode {
-                                BallColorMode::Random => {
-                                    instance.color = util::color::random_distinct_color(self.color);
+                                BallColorMode::Random | BallColorMode::DvdBounce => {
+                                    instance.color = Self::random_ball_color(config, &mut self.rng);
                                 }
                                 BallColorMode::Color => {
                                     instance.color = self.color;
                                 }
                                 BallColorMode::Infection => {
                                     if i == 0 {
-                                        self.color = util::color::random_color();
                                         instance.color = self.color;
                                     } else {
-                                        instance.color = infection_starting_color
+                                        instance.color = infection_starting_color;
                                     }
                                 }
                                 _ => {
@@ -481,325 +760,4297 @@ impl ScreenSaver for BallScreenSaver {
                                     };
                                 }
                             }
+                            Self::set_ball_size(
+                                instance,
+                                data,
+                                Self::random_ball_size(config, &mut self.rng),
+                            );
                         }
+                    } else {
+                        particle_system
+                            .instances
+                            .instances
+                            .truncate(config.ball_count);
+                        particle_system.particle_data.truncate(config.ball_count);
+                    }
+                }
+
+                if config.ball_size != self.old_config.ball_size
+                    || config.ball_size_max != self.old_config.ball_size_max
+                {
+                    for i in 0..particle_system.instances.len() {
+                        Self::set_ball_size(
+                            &mut particle_system.instances[i],
+                            &mut particle_system.particle_data[i],
+                            Self::random_ball_size(config, &mut self.rng),
+                        );
                     }
+                }
 
-                    if config.show_density != self.old_config.show_density {
-                        for instance in particle_system.instances.iter_mut() {
-                            if !config.show_density {
-                                instance.color.a = 1.0;
+                if config.color_mode != self.old_config.color_mode
+                    || config.color != self.old_config.color
+                    || config.hue_min != self.old_config.hue_min
+                    || config.hue_max != self.old_config.hue_max
+                    || config.saturation != self.old_config.saturation
+                    || config.value != self.old_config.value
+                {
+                    self.color = util::color::color_from_hex(config.color.to_hex()).unwrap();
+                    self.infection_pulse = None;
+                    self.infection_finished = false;
+                    self.corner_flashes.clear();
+                    let infection_starting_color = util::color::random_color_with(&mut self.rng);
+                    for i in 0..particle_system.instances.instances.len() {
+                        let instance = &mut particle_system.instances[i];
+                        match config.color_mode {
+                            BallColorMode::Random | BallColorMode::DvdBounce => {
+                                instance.color = Self::random_ball_color(config, &mut self.rng);
+                            }
+                            BallColorMode::Color => {
+                                instance.color = self.color;
+                            }
+                            BallColorMode::Infection => {
+                                if i == 0 {
+                                    self.color = util::color::random_color_with(&mut self.rng);
+                                    instance.color = self.color;
+                                } else {
+                                    instance.color = infection_starting_color
+                                }
+                            }
+                            _ => {
+                                instance.color = Color {
+                                    r: 1.0,
+                                    g: 1.0,
+                                    b: 1.0,
+                                    a: 1.0,
+                                };
                             }
                         }
                     }
+                }
 
-                    if should_rebuild_instance_buffer {
-                        particle_system.rebuild_instance_buffer(device);
+                //the collisions don't touch the opacity while the density isn't shown, so it's reset once here
+                if !config.show_density && self.old_config.show_density {
+                    for instance in particle_system.instances.iter_mut() {
+                        instance.color.a = 1.0;
                     }
-
-                    self.old_config = *config;
                 }
+            }
+        }
 
-                particle_system.instances.regions_x =
-                    (1.0 * ratio / (0.16 * config.ball_size * config.region_size)).ceil() as usize;
-                particle_system.instances.regions_y =
-                    (1.0 / (0.16 * config.ball_size * config.region_size)).ceil() as usize;
+        #[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+        if config.sound != self.old_config.sound {
+            self.sounds = match Self::plays_sounds(config) {
+                true => crate::audio::BallSounds::new(),
+                false => None,
+            };
+        }
+        self.old_config = config.clone();
+    }
 
-                if particle_system.instances.regions_x == 0
-                    || particle_system.instances.regions_y == 0
-                {
-                    return;
-                }
+    /// moves the balls on the cpu, after `apply_config`
+    fn step(&mut self, dt: Duration, config: &Configurator) {
+        //Note: this only is non-zero later if self.correct_ball_velocity is true
+        let mut total_velocity = 0.0;
+        //it would fight the energy that gravity adds and the bounces and friction take away
+        let correct_ball_velocity =
+            config.correct_ball_velocity && config.gravity == 0.0 && config.friction == 0.0;
+        let kept_speed = Self::kept_speed(config, dt);
 
-                particle_system.instances.bounding_box =
-                    particle_system.particle_system_data.domain;
+        let mut infected_balls = 0;
+        let mut speed_range = None;
+
+        //taken out for the loop, so the color updates can borrow the rest of the screensaver
+        let mut ball_models = std::mem::take(&mut self.balls);
+        for model in &mut ball_models {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                if !Self::size_regions(particle_system, config) {
+                    continue;
+                }
                 particle_system.instances.rebuild_regions();
 
-                for x in 0..particle_system.instances.regions_x {
-                    for y in 0..particle_system.instances.regions_y {
-                        for a in 0..particle_system.instances.get_region_mut(x, y).len() {
-                            let i = particle_system.instances.get_region_mut(x, y)[a];
-                            let mut density = 0;
-                            let instance = particle_system.instances[i];
-                            let mut velocity_if_correcting_it = 0.0;
-
-                            if config.correct_ball_velocity {
-                                velocity_if_correcting_it =
-                                    particle_system.particle_data[i].velocity.magnitude();
-
-                                if velocity_if_correcting_it.is_normal() {
-                                    let scalar = (config.ball_speed / self.actual_ball_speed - 1.0)
-                                        * dt.as_secs_f32()
-                                        / 10.0
-                                        + 1.0;
-                                    if scalar.is_normal() {
-                                        particle_system.particle_data[i]
-                                            .velocity
-                                            .mul_assign(scalar.clamp(0.5, 2.0));
-                                    }
+                particle_system.collect_events(Self::needs_events(config));
+                let collisions = BallCollisions {
+                    config,
+                    correct_ball_velocity,
+                    color: self.color,
+                    actual_ball_speed: self.actual_ball_speed,
+                    temperature_range: (
+                        config
+                            .temperature_min_speed
+                            .unwrap_or(self.temperature_range.0),
+                        config
+                            .temperature_max_speed
+                            .unwrap_or(self.temperature_range.1),
+                    ),
+                    dt,
+                    regions_x: particle_system.instances.regions_x,
+                    regions_y: particle_system.instances.regions_y,
+                    report_collisions: particle_system.events.is_some(),
+                    seed: self.rng.gen(),
+                };
+                if config.show_density {
+                    self.densities.resize(particle_system.instances.len(), 0.0);
+                }
+                let balls = SharedBalls {
+                    instances: particle_system.instances.instances.as_mut_ptr(),
+                    data: particle_system.particle_data.as_mut_ptr(),
+                    densities: self.densities.as_mut_ptr(),
+                };
+                let mut stats = collisions.run(balls, &particle_system.instances.regions);
+                if config.show_density {
+                    Self::show_density(particle_system, &self.densities, config);
+                }
+                total_velocity += stats.total_velocity;
+                infected_balls += stats.infected_balls;
+                speed_range = stats.speed_range;
+                for event in stats.collisions.drain(..) {
+                    particle_system.push_event(event);
+                }
 
-                                    total_velocity += velocity_if_correcting_it;
-                                } else {
-                                    log::warn!("Velocity is not normal. Resetting it to new random velocity. (velocity: {:?}, index: {})", particle_system.particle_data[i].velocity, i);
-                                    let mut move_vector = Vector3::new(
-                                        random::<f32>() - 0.5,
-                                        random::<f32>() - 0.5,
-                                        //random::<f32>() - 0.5,
-                                        0.0,
-                                    );
-                                    move_vector = move_vector.normalize() * config.ball_speed;
-                                    particle_system.particle_data[i].velocity = move_vector;
-                                }
+                if config.gravity != 0.0 {
+                    //the camera is mirrored, so positive y is down. when time runs backwards gravity is undone
+                    let gravity = Vector3::new(0.0, config.gravity, 0.0)
+                        * config.time_scale.signum()
+                        * dt.as_secs_f32();
+                    for data in particle_system.particle_data.iter_mut() {
+                        data.velocity += gravity;
+                    }
+                }
+                if config.friction != 0.0 {
+                    for data in particle_system.particle_data.iter_mut() {
+                        data.velocity *= kept_speed;
+                    }
+                }
+                particle_system.particle_system_data.restitution = config.restitution;
+
+                if config.color_mode == BallColorMode::Infection {
+                    self.update_infection(particle_system, infected_balls, dt, config);
+                }
+
+                if config.auto_retoss && config.ball_speed > 0.0 {
+                    //kinetic energy, relative to the energy at the target speed (the mass doesn't matter)
+                    let energy = particle_system
+                        .particle_data
+                        .iter()
+                        .map(|data| data.velocity.magnitude2())
+                        .sum::<f32>()
+                        / particle_system.particle_data.len().max(1) as f32
+                        / (config.ball_speed * config.ball_speed);
+                    if energy < config.retoss_energy {
+                        self.low_energy_time += dt;
+                        if self.low_energy_time.as_secs_f32() >= config.retoss_delay {
+                            self.low_energy_time = Duration::ZERO;
+                            for data in particle_system.particle_data.iter_mut() {
+                                data.velocity = Self::toss_velocity(config, &mut self.rng);
                             }
+                        }
+                    } else {
+                        self.low_energy_time = Duration::ZERO;
+                    }
+                }
 
-                            //particle_system.particle_data[i].velocity.add_assign(GRAVITY.mul(dt.as_secs_f32()));
-
-                            particle_system
-                                .instances
-                                .get_regions_in_range(x, y, 1)
-                                .iter()
-                                .for_each(|&j| {
-                                    density += 1;
-                                    if i > j {
-                                        let other_instance = particle_system.instances[j];
-                                        let other_data = particle_system.particle_data[j];
-                                        let data = particle_system.particle_data[i];
-
-                                        //check if the bals collide
-                                        if (instance.position.x - other_instance.position.x)
-                                            * (instance.position.x - other_instance.position.x)
-                                            + (instance.position.y - other_instance.position.y)
-                                                * (instance.position.y - other_instance.position.y)
-                                            < instance.scale
-                                                * data.collider.unwrap().x
-                                                * instance.scale
-                                                * data.collider.unwrap().y
-                                        {
-                                            let distance =
-                                                instance.position.distance(other_instance.position);
-                                            let target_distance =
-                                                config.ball_size * data.collider.unwrap().x;
-
-                                            let n = (instance.position - other_instance.position)
-                                                .normalize();
-                                            particle_system.instances[i]
-                                                .position
-                                                .add_assign(n * (target_distance - distance) / 2.0);
-                                            particle_system.instances[j].position.add_assign(
-                                                -n * (target_distance - distance) / 2.0,
-                                            );
-                                            let v1 = -data.velocity;
-                                            let v2 = -other_data.velocity;
-                                            let c1 = instance.position;
-                                            let c2 = other_instance.position;
-
-                                            //https://stackoverflow.com/questions/35211114/2d-elastic-ball-collision-physics
-                                            particle_system.particle_data[i].velocity = -v1
-                                                + (c1 - c2) * (v1 - v2).dot(c1 - c2)
-                                                    / (c1 - c2).magnitude2();
-                                            particle_system.particle_data[j].velocity = -v2
-                                                + (c2 - c1) * (v2 - v1).dot(c2 - c1)
-                                                    / (c2 - c1).magnitude2();
-
-                                            match config.color_mode {
-                                                BallColorMode::Random => {
-                                                    let col = util::color::random_color();
-
-                                                    particle_system.instances[i].color = col;
-                                                    particle_system.instances[j].color = col;
-                                                }
-                                                BallColorMode::Infection => {
-                                                    if (util::color::compare_colors_ignoring_alpha(
-                                                        other_instance.color,
-                                                        self.color,
-                                                    ) || util::color::compare_colors_ignoring_alpha(
-                                                        instance.color,
-                                                        self.color,
-                                                    )) && !util::color::compare_colors_ignoring_alpha(
-                                                        instance.color,
-                                                        other_instance.color,
-                                                    ) {
-                                                        particle_system.instances[i].color =
-                                                            self.color;
-                                                        particle_system.instances[j].color =
-                                                            self.color;
-                                                    }
-                                                }
-                                                _ => {}
-                                            }
-                                            //particle_system.instances[i].age = Duration::new(0, 0);
-                                        }
-                                    }
-                                });
+                let (motion_delta_t, age_delta_t) = model::time_deltas(dt, config);
+                particle_system.step(motion_delta_t, age_delta_t);
+
+                let events = particle_system.drain_events();
+                if config.color_mode == BallColorMode::DvdBounce {
+                    self.update_dvd_bounce(particle_system, &events, dt, config);
+                }
+                #[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+                if let Some(sounds) = &mut self.sounds {
+                    sounds.queue(&events, particle_system, config.ball_speed);
+                }
+            }
+        }
+        self.balls = ball_models;
+
+        self.actual_ball_speed = total_velocity / config.ball_count as f32;
+        if let Some((min, max)) = speed_range {
+            let follow = (dt.as_secs_f32() / TEMPERATURE_SMOOTHING).min(1.0);
+            self.temperature_range.0 += (min - self.temperature_range.0) * follow;
+            self.temperature_range.1 += (max - self.temperature_range.1) * follow;
+        }
+        /*
+        println!(
+            "a/average velocity: {},\ttarget: {}",
+            total_velocity / self.ball_count as f32,
+            self.ball_speed
+        )*/
+    }
+
+    /// sets the opacity of every ball from how many balls are around it
+    fn show_density(
+        particle_system: &mut ParticleSystem,
+        densities: &[f32],
+        config: &Configurator,
+    ) {
+        for (instance, density) in particle_system.instances.iter_mut().zip(densities) {
+            let density = (*density as f64 / config.target_display_density).clamp(0.0, 1.0);
+            instance.color.a = density.powf(config.density_power as f64);
+        }
+    }
+
+    /// flashes the balls once every one of them is infected, and then starts the next round,
+    /// or leaves them that color if the infection doesn't restart
+    fn update_infection(
+        &mut self,
+        particle_system: &mut ParticleSystem,
+        infected_balls: usize,
+        dt: Duration,
+        config: &Configurator,
+    ) {
+        if let Some(remaining) = self.infection_pulse {
+            let remaining = remaining.saturating_sub(dt);
+            if remaining.is_zero() {
+                self.infection_pulse = None;
+                //back to the exact infection color, the collisions compare it
+                for instance in particle_system.instances.iter_mut() {
+                    instance.color = Color {
+                        a: instance.color.a,
+                        ..self.color
+                    };
+                }
+                if config.infection_restart {
+                    self.reinfect(particle_system, config);
+                } else {
+                    self.infection_finished = true;
+                }
+            } else {
+                self.infection_pulse = Some(remaining);
+                //brightens up and fades back
+                let progress = 1.0 - remaining.as_secs_f64() / INFECTION_PULSE.as_secs_f64();
+                let white = Color {
+                    r: 1.0,
+                    g: 1.0,
+                    b: 1.0,
+                    a: 1.0,
+                };
+                let pulse = util::color::lerp_color(
+                    self.color,
+                    white,
+                    (progress * std::f64::consts::PI).sin() * 0.7,
+                    util::color::ColorSpace::Rgb,
+                );
+                for instance in particle_system.instances.iter_mut() {
+                    instance.color = Color {
+                        a: instance.color.a,
+                        ..pulse
+                    };
+                }
+            }
+            return;
+        }
+
+        if self.infection_finished {
+            //the restart was turned back on
+            if config.infection_restart {
+                self.infection_finished = false;
+                self.reinfect(particle_system, config);
+            }
+        } else if infected_balls >= config.ball_count {
+            self.infection_pulse = Some(INFECTION_PULSE);
+        }
+    }
+
+    /// gives every ball that bounced off of a wall a new color, and flashes the ones that hit a corner
+    fn update_dvd_bounce(
+        &mut self,
+        particle_system: &mut ParticleSystem,
+        events: &[ParticleEvent],
+        dt: Duration,
+        config: &Configurator,
+    ) {
+        let ball_count = particle_system.instances.len();
+        //the balls could have been removed since they started flashing
+        self.corner_flashes.retain(|flash| flash.ball < ball_count);
+
+        //a ball's bounces are next to each other, which sides it hit are collected for the corners
+        let mut bounces: Vec<(usize, bool, bool)> = Vec::new();
+        for event in events {
+            if let ParticleEvent::WallBounce { index, axis } = *event {
+                if bounces.last().map(|bounce| bounce.0) != Some(index) {
+                    bounces.push((index, false, false));
+                }
+                let bounce = bounces.last_mut().unwrap();
+                match axis {
+                    Axis::X => bounce.1 = true,
+                    Axis::Y => bounce.2 = true,
+                    Axis::Z => {}
+                }
+            }
+        }
+
+        for (i, hit_x, hit_y) in bounces {
+            if i >= ball_count {
+                continue;
+            }
+            let corner = hit_x && hit_y;
+            let color = Self::random_ball_color(config, &mut self.rng);
+            let instance = &mut particle_system.instances[i];
+            instance.color = Color {
+                a: instance.color.a,
+                ..color
+            };
+            match self.corner_flashes.iter_mut().find(|flash| flash.ball == i) {
+                Some(flash) => {
+                    flash.color = color;
+                    if corner {
+                        flash.remaining = CORNER_FLASH;
+                    }
+                }
+                None if corner => {
+                    self.corner_flashes.push(CornerFlash {
+                        ball: i,
+                        remaining: CORNER_FLASH,
+                        color,
+                    });
+                }
+                None => {}
+            }
+        }
+
+        let white = Color {
+            r: 1.0,
+            g: 1.0,
+            b: 1.0,
+            a: 1.0,
+        };
+        for flash in self.corner_flashes.iter_mut() {
+            flash.remaining = flash.remaining.saturating_sub(dt);
+            //starts out white and fades into the new color
+            let progress = 1.0 - flash.remaining.as_secs_f64() / CORNER_FLASH.as_secs_f64();
+            let color = util::color::lerp_color(
+                white,
+                flash.color,
+                progress * progress,
+                util::color::ColorSpace::Rgb,
+            );
+            let instance = &mut particle_system.instances[flash.ball];
+            instance.color = Color {
+                a: instance.color.a,
+                ..color
+            };
+        }
+        self.corner_flashes
+            .retain(|flash| !flash.remaining.is_zero());
+    }
+
+    /// picks a new infection color and infects `reinfection_count` random balls with it
+    fn reinfect(&mut self, particle_system: &mut ParticleSystem, config: &Configurator) {
+        let ball_count = particle_system.instances.len();
+        if ball_count == 0 {
+            return;
+        }
+        self.color = util::color::random_distinct_color(self.color, 0.2, &mut self.rng);
+        let seeds = config.reinfection_count.clamp(1, ball_count);
+        for i in rand::seq::index::sample(&mut self.rng, ball_count, seeds) {
+            let instance = &mut particle_system.instances[i];
+            instance.color = Color {
+                a: instance.color.a,
+                ..self.color
+            };
+        }
+    }
+
+    /// sizes the regions so the biggest ball fits in one, false if there are none
+    fn size_regions(particle_system: &mut ParticleSystem, config: &Configurator) -> bool {
+        let domain = particle_system.particle_system_data.domain;
+        let ratio = domain.width() / domain.height();
+        let ball_size = Self::largest_ball_size(config);
+        particle_system.instances.regions_x =
+            (1.0 * ratio / (0.16 * ball_size * config.region_size)).ceil() as usize;
+        particle_system.instances.regions_y =
+            (1.0 / (0.16 * ball_size * config.region_size)).ceil() as usize;
+        particle_system.instances.bounding_box = domain;
+
+        particle_system.instances.regions_x != 0 && particle_system.instances.regions_y != 0
+    }
+
+    /// how much of their speed the balls keep after `dt` of friction.
+    /// when time runs backwards the lost speed is given back. full friction couldn't be undone
+    fn kept_speed(config: &Configurator, dt: Duration) -> f32 {
+        (1.0 - config.friction)
+            .max(0.01)
+            .powf(dt.as_secs_f32() * config.time_scale.signum())
+    }
+}
+
+/// the balls of a particle system, shared between the threads of the collision pass
+#[derive(Clone, Copy)]
+struct SharedBalls {
+    instances: *mut ParticleInstance,
+    data: *mut ParticleData,
+    //only as long as the balls while the density is shown
+    densities: *mut f32,
+}
+
+// SAFETY: the threads only ever touch balls in regions that are far enough apart, see `BallCollisions::run`
+unsafe impl Send for SharedBalls {}
+unsafe impl Sync for SharedBalls {}
+
+impl SharedBalls {
+    /// # Safety
+    /// nothing else may be using ball `i` while the reference is alive
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn instance(&self, i: usize) -> &mut ParticleInstance {
+        &mut *self.instances.add(i)
+    }
+
+    /// # Safety
+    /// nothing else may be using ball `i` while the reference is alive
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn data(&self, i: usize) -> &mut ParticleData {
+        &mut *self.data.add(i)
+    }
+
+    /// # Safety
+    /// nothing else may be using ball `i` while the reference is alive, and the densities have to be counted
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn density(&self, i: usize) -> &mut f32 {
+        &mut *self.densities.add(i)
+    }
+}
+
+/// what the collision pass counted, summed over every region
+#[derive(Debug, Default, Clone)]
+struct CollisionStats {
+    total_velocity: f32,
+    infected_balls: usize,
+    //the slowest and fastest ball, only counted in the temperature color mode
+    speed_range: Option<(f32, f32)>,
+    //only reported when the particle system collects events
+    collisions: Vec<ParticleEvent>,
+}
+
+impl Add for CollisionStats {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self.collisions.extend(other.collisions);
+        Self {
+            total_velocity: self.total_velocity + other.total_velocity,
+            infected_balls: self.infected_balls + other.infected_balls,
+            speed_range: match (self.speed_range, other.speed_range) {
+                (Some((min, max)), Some((other_min, other_max))) => {
+                    Some((min.min(other_min), max.max(other_max)))
+                }
+                (range, None) | (None, range) => range,
+            },
+            collisions: self.collisions,
+        }
+    }
+}
+
+/// collides the balls with the ones in the surrounding regions
+struct BallCollisions<'a> {
+    config: &'a Configurator,
+    correct_ball_velocity: bool,
+    color: Color,
+    actual_ball_speed: f32,
+    //the speeds that get the coldest and the hottest color
+    temperature_range: (f32, f32),
+    dt: Duration,
+    regions_x: usize,
+    regions_y: usize,
+    report_collisions: bool,
+    //taken from the screensaver's rng every step, each column makes its own rng from it, so a seeded run
+    //stays the same no matter which thread gets which column
+    seed: u64,
+}
+
+impl BallCollisions<'_> {
+    /// collides every ball. a region touches the balls of the regions next to it, so columns of regions that are
+    /// at least 3 apart never share balls and can run at the same time. every third column runs in parallel,
+    /// in 3 rounds
+    fn run(&self, balls: SharedBalls, regions: &[Vec<usize>]) -> CollisionStats {
+        cfg_if::cfg_if! {
+            if #[cfg(target_arch = "wasm32")] {
+                //no threads on the web
+                let mut neighbours = Vec::new();
+                (0..self.regions_x)
+                    .map(|x| unsafe { self.collide_column(balls, regions, x, &mut neighbours) })
+                    .fold(CollisionStats::default(), |a, b| a + b)
+            } else {
+                use rayon::prelude::*;
+                (0..3)
+                    .map(|first_column| {
+                        (first_column..self.regions_x)
+                            .into_par_iter()
+                            .step_by(3)
+                            .map_init(Vec::new, |neighbours, x| {
+                                // SAFETY: the columns of this round are 3 apart
+                                unsafe { self.collide_column(balls, regions, x, neighbours) }
+                            })
+                            .reduce(CollisionStats::default, |a, b| a + b)
+                    })
+                    .fold(CollisionStats::default(), |a, b| a + b)
+            }
+        }
+    }
+
+    /// # Safety
+    /// nothing else may be touching the balls in columns `x - 1` to `x + 1`
+    unsafe fn collide_column(
+        &self,
+        balls: SharedBalls,
+        regions: &[Vec<usize>],
+        x: usize,
+        neighbours: &mut Vec<usize>,
+    ) -> CollisionStats {
+        let config = self.config;
+        let mut stats = CollisionStats::default();
+        let mut rng = StdRng::seed_from_u64(self.seed.wrapping_add(x as u64));
+        for y in 0..self.regions_y {
+            util::pos::regions_in_range(
+                regions,
+                self.regions_x,
+                self.regions_y,
+                x,
+                y,
+                1,
+                neighbours,
+            );
+            for &i in &regions[y * self.regions_x + x] {
+                let mut density = 0;
+                let instance = *balls.instance(i);
+                let mut velocity_if_correcting_it = 0.0;
+
+                if self.correct_ball_velocity {
+                    velocity_if_correcting_it = balls.data(i).velocity.magnitude();
+
+                    if velocity_if_correcting_it.is_normal() {
+                        let scalar = (config.ball_speed / self.actual_ball_speed - 1.0)
+                            * self.dt.as_secs_f32()
+                            / 10.0
+                            + 1.0;
+                        if scalar.is_normal() {
+                            balls.data(i).velocity.mul_assign(scalar.clamp(0.5, 2.0));
+                        }
+
+                        stats.total_velocity += velocity_if_correcting_it;
+                    } else {
+                        log::warn!("Velocity is not normal. Resetting it to new random velocity. (velocity: {:?}, index: {})", balls.data(i).velocity, i);
+                        let mut move_vector = Vector3::new(
+                            rng.gen::<f32>() - 0.5,
+                            rng.gen::<f32>() - 0.5,
+                            //rng.gen::<f32>() - 0.5,
+                            0.0,
+                        );
+                        move_vector = move_vector.normalize() * config.ball_speed;
+                        balls.data(i).velocity = move_vector;
+                    }
+                }
+
+                for &j in neighbours.iter() {
+                    density += 1;
+                    if i > j {
+                        let other_instance = *balls.instance(j);
+                        let other_data = *balls.data(j);
+                        let data = *balls.data(i);
+
+                        //the balls touch when they are as far apart as their radii together
+                        let target_distance = (instance.scale + other_instance.scale) / 2.0
+                            * data.collider.unwrap().x;
+
+                        //check if the bals collide
+                        if instance.position.distance2(other_instance.position)
+                            < target_distance * target_distance
+                        {
+                            let distance = instance.position.distance(other_instance.position);
+                            //the lighter ball gets pushed further and bounces off harder
+                            let share = other_data.mass / (data.mass + other_data.mass);
+
+                            let n = (instance.position - other_instance.position).normalize();
+                            balls
+                                .instance(i)
+                                .position
+                                .add_assign(n * (target_distance - distance) * share);
+                            balls
+                                .instance(j)
+                                .position
+                                .add_assign(-n * (target_distance - distance) * (1.0 - share));
+                            let v1 = -data.velocity;
+                            let v2 = -other_data.velocity;
+                            let c1 = instance.position;
+                            let c2 = other_instance.position;
+
+                            //https://stackoverflow.com/questions/35211114/2d-elastic-ball-collision-physics
+                            balls.data(i).velocity = -v1
+                                + (c1 - c2) * (v1 - v2).dot(c1 - c2) / (c1 - c2).magnitude2()
+                                    * 2.0
+                                    * share;
+                            balls.data(j).velocity = -v2
+                                + (c2 - c1) * (v2 - v1).dot(c2 - c1) / (c2 - c1).magnitude2()
+                                    * 2.0
+                                    * (1.0 - share);
+                            if self.report_collisions {
+                                stats
+                                    .collisions
+                                    .push(ParticleEvent::Collision { a: i, b: j });
+                            }
 
                             match config.color_mode {
-                                BallColorMode::Temperature => {
-                                    let hsv = Hsv::new(
-                                        angular_units::Turns(
-                                            (((if config.correct_ball_velocity {
-                                                velocity_if_correcting_it
-                                            } else {
-                                                particle_system.particle_data[i]
-                                                    .velocity
-                                                    .magnitude()
-                                            }) / config.ball_speed
-                                                - 0.5)
-                                                .max(0.0)
-                                                / 50.0)
-                                                .clamp(0.0, 0.9),
-                                        ),
-                                        1.0,
-                                        1.0,
-                                    );
-                                    let rgb = Rgb::from(hsv);
-                                    particle_system.instances[i].color = Color {
-                                        r: rgb.red(),
-                                        g: rgb.green(),
-                                        b: rgb.blue(),
-                                        a: 1.0,
-                                    }
+                                BallColorMode::Random => {
+                                    let col = BallScreenSaver::random_ball_color(config, &mut rng);
+
+                                    balls.instance(i).color = col;
+                                    balls.instance(j).color = col;
                                 }
                                 BallColorMode::Infection => {
-                                    if util::color::compare_colors_ignoring_alpha(
+                                    if (util::color::compare_colors_ignoring_alpha(
+                                        other_instance.color,
+                                        self.color,
+                                    ) || util::color::compare_colors_ignoring_alpha(
                                         instance.color,
                                         self.color,
+                                    )) && !util::color::compare_colors_ignoring_alpha(
+                                        instance.color,
+                                        other_instance.color,
                                     ) {
-                                        infected_balls += 1;
+                                        balls.instance(i).color = self.color;
+                                        balls.instance(j).color = self.color;
                                     }
                                 }
                                 _ => {}
                             }
-                            if config.show_density {
-                                let density = f64::clamp(
-                                    density as f64 / config.target_display_density,
-                                    0.0,
-                                    1.0,
-                                );
-                                particle_system.instances.instances[i].color.a = density * density;
-                            }
                         }
                     }
                 }
 
-                if infected_balls >= config.ball_count {
-                    self.color = util::color::random_color();
-                    particle_system
-                        .instances
-                        .instances
-                        .choose_mut(&mut rand::thread_rng())
-                        .unwrap()
-                        .color = self.color;
+                match config.color_mode {
+                    BallColorMode::Temperature => {
+                        let speed = if self.correct_ball_velocity {
+                            velocity_if_correcting_it
+                        } else {
+                            balls.data(i).velocity.magnitude()
+                        };
+                        stats.speed_range = Some(match stats.speed_range {
+                            Some((min, max)) => (min.min(speed), max.max(speed)),
+                            None => (speed, speed),
+                        });
+                        let (min, max) = self.temperature_range;
+                        let heat = (speed - min) / (max - min).max(f32::EPSILON);
+                        balls.instance(i).color =
+                            temperature_color(heat, config.color_interpolation);
+                    }
+                    BallColorMode::Infection => {
+                        if util::color::compare_colors_ignoring_alpha(instance.color, self.color) {
+                            stats.infected_balls += 1;
+                        }
+                    }
+                    _ => {}
+                }
+                if config.show_density {
+                    *balls.density(i) = density as f32;
                 }
+            }
+        }
+        stats
+    }
+}
 
-                particle_system.update_instance_buffer(queue);
-            };
-            model.update(dt, queue);
+impl ScreenSaver for BallScreenSaver {
+    fn new(config: Configurator) -> BallScreenSaver
+    where
+        Self: Sized,
+    {
+        Self {
+            balls: vec![],
+            inputs: [None; 6],
+            first_input_handled: false,
+            color: util::color::color_from_hex(config.color.to_hex()).unwrap(),
+            actual_ball_speed: config.ball_speed,
+            temperature_range: (0.0, 2.0 * config.ball_speed),
+            velocity_lines: None,
+            low_energy_time: Duration::ZERO,
+            infection_pulse: None,
+            infection_finished: false,
+            corner_flashes: Vec::new(),
+            #[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+            sounds: None,
+            gpu_physics: None,
+            spheres: None,
+            densities: Vec::new(),
+            rng: config.rng(),
+            old_config: config,
         }
+    }
+    fn setup(
+        &mut self,
+        size: Size,
+        config: &Configurator,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        pipeline_layout: &wgpu::PipelineLayout,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        sample_count: u32,
+    ) {
+        let ratio = if size.to_logical::<f32>(1.0).width > 1.0 {
+            size.to_logical::<f32>(1.0).width / size.to_logical::<f32>(1.0).height
+        } else {
+            1.0
+        };
 
-        self.actual_ball_speed = total_velocity / config.ball_count as f32;
-        /*
-        println!(
-            "a/average velocity: {},\ttarget: {}",
-            total_velocity / self.ball_count as f32,
-            self.ball_speed
-        )*/
+        let circle_texture = include_bytes!("resources/textures/circle16.png");
+        let diffuse_texture = texture::Texture::from_bytes(
+            device,
+            queue,
+            circle_texture,
+            "circle16.png",
+            config.texture_quality,
+        )
+        .unwrap();
+        let mut particle_system = ParticleSystem::create_billboard(
+            0.16,
+            0.16,
+            Vector3::new(0.0, 0.0, 0.0),
+            ParticleSystemData::new(BoundingBox::new_with_size(
+                //Vector3::new(0.0, 0.0, 0.55),
+                Vector3::new(0.0, 0.0, 0.0),
+                2.0 * ratio,
+                2.0,
+                //1.00,
+                0.0,
+                BoundingBoxType::Bounce,
+            )),
+            device,
+        );
+
+        let shader = wgpu::ShaderModuleDescriptor {
+            label: Some("Ball Shader"),
+            source: shaders::get(shaders::ShaderType::ParticleShader),
+        };
+
+        let material = Material::new(
+            diffuse_texture,
+            device,
+            layout,
+            util::render::create_render_pipeline(
+                device,
+                pipeline_layout,
+                color_format,
+                depth_format,
+                sample_count,
+                &[model::ModelVertex::desc(), ParticleInstanceRaw::desc()],
+                shader,
+            ),
+        );
+
+        particle_system.populate_random(config.ball_count, device, &mut self.rng);
+
+        let infection_starting_color = util::color::random_color_with(&mut self.rng);
+
+        for i in 0..particle_system.instances.len() {
+            let instance = &mut particle_system.instances[i];
+            let data = &mut particle_system.particle_data[i];
+
+            data.velocity = Self::random_velocity(config, &mut self.rng);
+
+            match config.color_mode {
+                BallColorMode::Random | BallColorMode::DvdBounce => {
+                    instance.color = Self::random_ball_color(config, &mut self.rng);
+                }
+                BallColorMode::Color => {
+                    instance.color = self.color;
+                }
+                BallColorMode::Infection => {
+                    if i == 0 {
+                        self.color = util::color::random_distinct_color(
+                            infection_starting_color,
+                            0.2,
+                            &mut self.rng,
+                        );
+                        instance.color = self.color;
+                    } else {
+                        instance.color = infection_starting_color;
+                    }
+                }
+                _ => {
+                    instance.color = Color {
+                        r: 1.0,
+                        g: 1.0,
+                        b: 1.0,
+                        a: 1.0,
+                    };
+                }
+            }
+            Self::set_ball_size(
+                instance,
+                data,
+                Self::random_ball_size(config, &mut self.rng),
+            );
+        }
+
+        let balls = Model {
+            mesh: Box::new(particle_system),
+            material,
+        };
+
+        if config.ball_render_mode == BallRenderMode::Sphere {
+            let shader = wgpu::ShaderModuleDescriptor {
+                label: Some("Sphere Shader"),
+                source: shaders::ShaderType::SphereShader.get_source(),
+            };
+            let material = Material::new(
+                texture::Texture::from_color(device, queue, [1.0; 4], Some("sphere")).unwrap(),
+                device,
+                layout,
+                util::render::create_render_pipeline(
+                    device,
+                    pipeline_layout,
+                    color_format,
+                    depth_format,
+                    sample_count,
+                    &[model::ModelVertex::desc(), ModelInstanceRaw::desc()],
+                    shader,
+                ),
+            );
+            //the same size as the circle texture
+            let mut mesh =
+                ModelMesh::create_uv_sphere(0.08, 24, 16, Vector3::new(0.0, 0.0, 0.0), device);
+            Self::sync_spheres(std::slice::from_ref(&balls), &mut mesh, 1.0);
+            mesh.rebuild_instance_buffer(device);
+            self.spheres = Some(Model {
+                mesh: Box::new(mesh),
+                material,
+            });
+        }
+
+        if config.gpu_physics && config.ball_render_mode == BallRenderMode::Sphere {
+            log::warn!(
+                "The spheres need the balls on the cpu, so the physics won't run on the gpu"
+            );
+        } else if config.gpu_physics && Self::needs_events(config) {
+            log::warn!("The dvd bounce colors and the sound need the balls on the cpu, so the physics won't run on the gpu");
+        } else if config.gpu_physics && !GpuPhysics::supported(device) {
+            log::warn!("This device can't run the physics on the gpu, using the cpu instead");
+        }
+
+        #[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+        if Self::plays_sounds(config) {
+            self.sounds = crate::audio::BallSounds::new();
+        }
+
+        self.balls.push(balls);
+        self.velocity_lines = Some(Lines::new(
+            device,
+            pipeline_layout,
+            color_format,
+            depth_format,
+            sample_count,
+        ));
+    }
+
+    fn update(
+        &mut self,
+        size: Size,
+        config: &Configurator,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        dt: Duration,
+    ) {
+        //the config changes are applied to the cpu side, so it has to catch up first
+        if *config != self.old_config {
+            if let Some(gpu_physics) = &self.gpu_physics {
+                for model in &mut self.balls {
+                    //get (ParticleSystem)(Object) idiot
+                    if let Some(particle_system) =
+                        model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+                    {
+                        gpu_physics.download(device, queue, particle_system);
+                    }
+                }
+            }
+        }
+
+        if !Self::uses_gpu_physics(config, device) {
+            if let Some(mut gpu_physics) = self.gpu_physics.take() {
+                for model in &mut self.balls {
+                    //get (ParticleSystem)(Object) idiot
+                    if let Some(particle_system) =
+                        model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+                    {
+                        gpu_physics.release(device, particle_system);
+                    }
+                }
+            }
+            self.simulate(size, config, dt);
+            self.upload(device, queue);
+            return;
+        }
+
+        let config_changed = *config != self.old_config;
+        self.apply_config(config);
+        //the mouse, the velocity correction and the color modes that react to collisions need the cpu
+        let gpu_physics = self
+            .gpu_physics
+            .get_or_insert_with(|| GpuPhysics::new(device));
+        for model in &mut self.balls {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                if config_changed || !particle_system.gpu_owned {
+                    gpu_physics.upload(device, particle_system);
+                }
+                if !Self::size_regions(particle_system, config) {
+                    continue;
+                }
+                particle_system.particle_system_data.restitution = config.restitution;
+                gpu_physics.step(
+                    device,
+                    queue,
+                    particle_system,
+                    dt.as_secs_f32() * config.time_scale.signum(),
+                    config.gravity,
+                    Self::kept_speed(config, dt),
+                );
+            }
+            model.update(dt, config, queue);
+        }
+        //the balls are owned by the gpu, so this only updates the velocity lines
+        self.upload(device, queue);
+    }
+
+    fn simulate(&mut self, _size: Size, config: &Configurator, dt: Duration) {
+        self.apply_config(config);
+        self.step(dt, config);
+    }
+
+    fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        for model in &mut self.balls {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                particle_system.upload(device, queue);
+            }
+        }
+
+        //the hits of all the steps of this frame are played together
+        #[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+        if let Some(sounds) = &mut self.sounds {
+            sounds.play();
+        }
+
+        if let Some(spheres) = &mut self.spheres {
+            if let Some(mesh) = spheres.mesh.as_any_mut().downcast_mut::<ModelMesh>() {
+                let old_count = mesh.instances.len();
+                Self::sync_spheres(&self.balls, mesh, 1.0);
+                //the buffer only has room for the old spheres
+                if mesh.instances.len() != old_count {
+                    mesh.rebuild_instance_buffer(device);
+                } else {
+                    mesh.update_instance_buffer(queue);
+                }
+            }
+        }
+
+        if let Some(velocity_lines) = &mut self.velocity_lines {
+            if self.old_config.debug_velocities {
+                let mut vertices = vec![];
+                for model in &self.balls {
+                    //get (ParticleSystem)(Object) idiot
+                    if let Some(particle_system) =
+                        model.mesh.as_any().downcast_ref::<ParticleSystem>()
+                    {
+                        for (instance, data) in particle_system
+                            .instances
+                            .iter()
+                            .zip(particle_system.particle_data.iter())
+                        {
+                            let end = instance.position + data.velocity * VELOCITY_LINE_LENGTH;
+                            vertices.push(LineVertex {
+                                position: instance.position.into(),
+                                color: [1.0, 1.0, 1.0, 1.0],
+                            });
+                            vertices.push(LineVertex {
+                                position: end.into(),
+                                color: [1.0, 0.2, 0.2, 1.0],
+                            });
+                        }
+                    }
+                }
+                velocity_lines.set_lines(device, queue, &vertices);
+            } else {
+                velocity_lines.clear();
+            }
+        }
+    }
+
+    fn resize(&mut self, _old_ratio: f32, new_ratio: f32) {
+        for model in &mut self.balls {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                particle_system.resize_domain(
+                    BoundingBox::new_with_size(
+                        //Vector3::new(0.0, 0.0, 0.55),
+                        Vector3::new(0.0, 0.0, 0.0),
+                        2.0 * new_ratio,
+                        2.0,
+                        //1.00,
+                        0.0,
+                        BoundingBoxType::Bounce,
+                    ),
+                    self.old_config.resize_behavior,
+                );
+            }
+        }
+    }
+
+    fn get_background_color(&self) -> wgpu::Color {
+        wgpu::Color {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            a: 1.0,
+        }
+    }
+
+    fn handle_input(&mut self, position: [f32; 2], id: u64, enabled: bool) -> bool {
+        let mut id = id;
+        //dumb chromium doing touch IDs in a dumb way
+        if id > 0 {
+            id = (id - 1) % 5 + 1
+        }
+
+        if !self.first_input_handled {
+            self.first_input_handled = true;
+            return false;
+        }
+        if id > 5 {
+            return false;
+        }
+        if !enabled {
+            self.inputs[id as usize] = None;
+            return false;
+        }
+
+        let old_input = self.inputs[id as usize];
+        let brush_size = self.old_config.brush_size;
+        let mouse_mode = self.old_config.mouse_mode;
+        for model in &mut self.balls {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                let x: f32 = position[0] / 2.0 + 0.5;
+                let y: f32 = position[1] / 2.0 + 0.5;
+                //the input is in screen space, the balls aren't
+                let domain = particle_system.particle_system_data.domain;
+                let cursor = Vector3::new(
+                    position[0] * domain.width() / 2.0,
+                    position[1] * domain.height() / 2.0,
+                    0.0,
+                );
+                for i in particle_system.instances.get_regions_in_range(
+                    usize::clamp(
+                        (x * particle_system.instances.regions_x as f32) as usize,
+                        0,
+                        particle_system.instances.regions_x - 1,
+                    ),
+                    usize::clamp(
+                        (y * particle_system.instances.regions_y as f32) as usize,
+                        0,
+                        particle_system.instances.regions_y - 1,
+                    ),
+                    (particle_system.instances.regions_y as f32 / 2.0 * brush_size).ceil() as u32,
+                ) {
+                    let impulse = match mouse_mode {
+                        MouseMode::Push => match old_input {
+                            Some(old_input) => Vector3::new(
+                                position[0] - old_input[0],
+                                position[1] - old_input[1],
+                                0.0,
+                            ),
+                            None => continue,
+                        },
+                        MouseMode::Attract | MouseMode::Repel => {
+                            let mut offset = cursor - particle_system.instances[i].position;
+                            offset.z = 0.0;
+                            let distance = offset.magnitude();
+                            if distance > brush_size || distance < f32::EPSILON {
+                                continue;
+                            }
+                            //stronger the closer the ball is, but not so strong it shoots off
+                            let strength = (MOUSE_FORCE / distance).min(MAX_MOUSE_IMPULSE);
+                            let impulse = offset / distance * strength;
+                            if mouse_mode == MouseMode::Repel {
+                                -impulse
+                            } else {
+                                impulse
+                            }
+                        }
+                    };
+                    particle_system.particle_data[i]
+                        .velocity
+                        .add_assign(impulse);
+                }
+            }
+        }
+        self.inputs[id as usize] = Some(position);
+        false
+    }
+
+    fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, state: &State<'_>) {
+        // lib.rmesh.in
+        //render_pass.set_pipeline(&state.render_pipeline);
+        render_pass.set_bind_group(1, &state.camera_bind_group, &[]);
+
+        for model in &self.balls {
+            render_pass.set_pipeline(&model.material.pipeline);
+            render_pass.set_bind_group(0, &model.material.bind_group, &[]);
+            //the same instances again, but dark and offset. the spheres get the flat shadows too
+            if self.old_config.shadow {
+                render_pass.set_bind_group(2, &state.shadow_bind_group, &[]);
+                render_pass
+                    .draw_mesh_instanced(&*model.mesh, 0..model.mesh.instance_count() as u32);
+                render_pass.set_bind_group(2, &state.global_bind_group, &[]);
+            }
+            if self.spheres.is_none() {
+                render_pass
+                    .draw_mesh_instanced(&*model.mesh, 0..model.mesh.instance_count() as u32);
+            }
+        }
+
+        if let Some(spheres) = &self.spheres {
+            render_pass.set_pipeline(&spheres.material.pipeline);
+            render_pass.set_bind_group(0, &spheres.material.bind_group, &[]);
+            render_pass
+                .draw_mesh_instanced(&*spheres.mesh, 0..spheres.mesh.instance_count() as u32);
+        }
+
+        if let Some(velocity_lines) = &self.velocity_lines {
+            velocity_lines.render(render_pass);
+        }
+    }
+
+    fn get_camera_type(&self) -> CameraType {
+        CameraType::Orthographic()
+    }
+
+    fn get_camera_position(&self) -> (Point3<f32>, Point3<f32>) {
+        (Point3::new(0.0, 0.0, 0.0), Point3::new(0.0, 0.0, 0.0))
+    }
+
+    fn interpolate(&mut self, alpha: f32, queue: &wgpu::Queue) {
+        for model in &mut self.balls {
+            model.interpolate(alpha, queue);
+        }
+        if let Some(spheres) = &mut self.spheres {
+            if let Some(mesh) = spheres.mesh.as_any_mut().downcast_mut::<ModelMesh>() {
+                let old_count = mesh.instances.len();
+                Self::sync_spheres(&self.balls, mesh, alpha);
+                //the buffer is only made bigger in upload
+                if mesh.instances.len() == old_count {
+                    mesh.update_instance_buffer(queue);
+                }
+            }
+        }
+    }
+
+    fn get_edge_softness(&self, config: &Configurator) -> Option<f32> {
+        //at 0 the balls keep their texture
+        if config.edge_softness > 0.0 {
+            Some(config.edge_softness)
+        } else {
+            None
+        }
+    }
+
+    fn get_shadow_offset(&self) -> Option<[f32; 2]> {
+        if self.old_config.shadow {
+            //the camera is mirrored, so this is down and to the right
+            Some([
+                -self.old_config.shadow_offset,
+                self.old_config.shadow_offset,
+            ])
+        } else {
+            None
+        }
+    }
+
+    fn save_state(&self) -> Vec<Vec<ParticleSnapshot>> {
+        snapshot::capture_particles(&self.balls)
+    }
+
+    fn load_state(&mut self, particle_systems: &[Vec<ParticleSnapshot>], device: &Device) {
+        snapshot::restore_particles(&mut self.balls, particle_systems, device);
+        //the first ball is the one that started the infection, so it has the infection color
+        if let Some(first) = particle_systems.first().and_then(|balls| balls.first()) {
+            self.color = Color {
+                r: first.color[0],
+                g: first.color[1],
+                b: first.color[2],
+                a: first.color[3],
+            };
+        }
+    }
+}
+
+pub struct SnowScreenSaver {
+    pub(crate) models: Vec<Model>,
+    old_config: Configurator,
+    touch_pos: [f32; 2],
+    ratio: f32,
+    //how long the snow has been building up for
+    buildup_time: Duration,
+    //seeded from the config, so the same seed sets up the same flakes
+    rng: StdRng,
+    //how long the wind has been blowing for, and how hard it blew in the last update
+    wind_time: Duration,
+    wind: f32,
+    //the flakes that landed on the ground. they never move, so they are kept out of `models`
+    pile: Option<Model>,
+    //how high the snow is piled up in each of PILE_BUCKETS columns across the domain
+    pile_heights: Vec<f32>,
+}
+
+impl SnowScreenSaver {
+    //how much of the snow there is when it starts building up
+    const BUILDUP_START: f32 = 0.1;
+    //how much of the snow has to be missing before more is added, so the instance buffer isn't recreated every frame
+    const BUILDUP_STEP: f32 = 0.01;
+    //the width and height of a flake at the front
+    const FLAKE_SIZE: f32 = 0.03;
+    //where the snow lands, a bit above the bottom of the screen (the camera is mirrored, so down is +y)
+    const GROUND_Y: f32 = 0.9;
+    //how many columns the pile is split into across the domain
+    const PILE_BUCKETS: usize = 64;
+    //how likely a flake passing the top of the pile is to land on it, so the pile grows slowly
+    const LANDING_CHANCE: f32 = 0.02;
+    //how much a landed flake raises its column, relative to its size
+    const PILE_GROWTH: f32 = 0.3;
+    //the depth layout, from the front (lower z is closer): the flakes and the pile fill z = 0 to snow_depth,
+    //then the ground layers start GROUND_GAP behind the snow, GROUND_SPACING apart. the ranges don't overlap,
+    //so the depth buffer keeps every flake in front of the ground no matter what order they're drawn in
+    const GROUND_GAP: f32 = 0.1;
+    const GROUND_SPACING: f32 = 0.4;
+    //how many ground layers there are, the first model is the front one
+    const GROUND_LAYERS: usize = 3;
+
+    /// how far back the ground layer `layer` is (0 is the front one), behind all of the snow
+    fn ground_z(depth: f32, layer: usize) -> f32 {
+        depth + Self::GROUND_GAP + layer as f32 * Self::GROUND_SPACING
+    }
+
+    /// the domain only covers what the camera can see. the camera can be moved by up to a quarter
+    /// of the depth in each direction (see get_camera_position), so there is a margin for that too
+    pub(crate) fn snow_domain(depth: f32, ratio: f32) -> BoundingBox<f32> {
+        let margin = depth / 2.0 + 0.1;
+        BoundingBox::new_with_size(
+            Vector3::new(0.0, 0.0, depth / 2.0),
+            2.0 * ratio + margin,
+            2.0 + margin,
+            depth,
+            BoundingBoxType::Modulo,
+        )
+    }
+
+    /// sets the size and opacity of a flake based on how deep it is
+    fn apply_depth(particle: &mut ParticleInstance, depth: f32) {
+        let z = (particle.position.z / depth).clamp(0.0, 1.0);
+        particle.scale = 1.0 - z * 0.8;
+        particle.color.a = 1.0 - z as f64;
+    }
+
+    /// the wind after blowing for `time` seconds. a few sine waves with unrelated periods are layered,
+    /// so the gusts don't visibly repeat
+    fn wind(config: &Configurator, time: f32) -> f32 {
+        let gusts = (time * 0.31).sin() * 0.5
+            + (time * 0.73 + 1.3).sin() * 0.3
+            + (time * 1.57 + 4.1).sin() * 0.2;
+        config.snow_wind_strength * (1.0 + config.snow_wind_variability * gusts)
+    }
+
+    /// how many flakes there should be after building up for `time`
+    fn buildup_count(config: &Configurator, time: Duration) -> usize {
+        if config.snow_buildup_secs <= 0.0 {
+            return config.snowflake_count;
+        }
+        let progress = (time.as_secs_f32() / config.snow_buildup_secs).min(1.0);
+        let count = config.snowflake_count as f32;
+        (count * (Self::BUILDUP_START + (1.0 - Self::BUILDUP_START) * progress)) as usize
+    }
+
+    /// adds or removes flakes until there are `count` of them
+    fn set_flake_count(
+        particle_system: &mut ParticleSystem,
+        count: usize,
+        config: &Configurator,
+        wind: f32,
+        rng: &mut impl Rng,
+    ) {
+        let current = particle_system.instances.len();
+        if count > current {
+            particle_system.add_random(count - current, rng);
+            for i in current..count {
+                Self::init_flake(
+                    &mut particle_system.instances.instances[i],
+                    &mut particle_system.particle_data[i],
+                    config,
+                    wind,
+                    rng,
+                );
+            }
+        } else {
+            particle_system.instances.instances.truncate(count);
+            particle_system.particle_data.truncate(count);
+        }
+    }
+
+    /// removes all the snow from the ground, and fits the pile to `domain`
+    fn clear_pile(&mut self, domain: BoundingBox<f32>) {
+        self.pile_heights = vec![0.0; Self::PILE_BUCKETS];
+        //get (ParticleSystem)(Object) idiot
+        if let Some(pile) = self
+            .pile
+            .as_mut()
+            .and_then(|pile| pile.mesh.as_any_mut().downcast_mut::<ParticleSystem>())
+        {
+            pile.particle_system_data.domain = domain;
+            //nothing past the instance count is drawn, so the buffer can wait until more snow lands
+            pile.instances.clear();
+            //the first one only holds the flake size (see ParticleSystem::create_billboard)
+            pile.particle_data.truncate(1);
+        }
+    }
+
+    /// some of the flakes that passed the top of the pile since the last update land on it.
+    /// the falling flake keeps going, the one on the ground is a copy of it
+    fn land_flakes(&mut self, config: &Configurator) {
+        //get (ParticleSystem)(Object) idiot
+        let Some(pile) = self
+            .pile
+            .as_mut()
+            .and_then(|pile| pile.mesh.as_any_mut().downcast_mut::<ParticleSystem>())
+        else {
+            return;
+        };
+        let domain = pile.particle_system_data.domain;
+        let bucket_width = domain.width() / Self::PILE_BUCKETS as f32;
+
+        for model in &self.models {
+            //get (ParticleSystem)(Object) idiot
+            let Some(particle_system) = model.mesh.as_any().downcast_ref::<ParticleSystem>() else {
+                continue;
+            };
+            for flake in particle_system.instances.iter() {
+                if pile.instances.len() >= config.snow_max_accumulation {
+                    break;
+                }
+                let bucket = (((flake.position.x - domain.min_pos.x) / bucket_width).max(0.0)
+                    as usize)
+                    .min(Self::PILE_BUCKETS - 1);
+                let top = Self::GROUND_Y - self.pile_heights[bucket];
+                if flake.previous_position.y < top
+                    && flake.position.y >= top
+                    && self.rng.gen::<f32>() < Self::LANDING_CHANCE
+                {
+                    let position = Vector3::new(flake.position.x, top, flake.position.z);
+                    pile.instances.push(ParticleInstance {
+                        position,
+                        previous_position: position,
+                        age: Duration::ZERO,
+                        ..*flake
+                    });
+                    pile.particle_data.push(ParticleData {
+                        velocity: Vector3::new(0.0, 0.0, 0.0),
+                        collider: None,
+                        mass: 1.0,
+                        lifetime: None,
+                    });
+                    self.pile_heights[bucket] += Self::FLAKE_SIZE * flake.scale * Self::PILE_GROWTH;
+                }
+            }
+        }
+    }
+
+    /// expects the particle to be at a random position in the domain. `wind` is the wind blowing on the other flakes
+    fn init_flake(
+        particle: &mut ParticleInstance,
+        data: &mut ParticleData,
+        config: &Configurator,
+        wind: f32,
+        rng: &mut impl Rng,
+    ) {
+        let z = particle.position.z / config.snow_depth;
+        //there should be more flakes in the front
+        particle.position.z = (1.0 - z * z) * config.snow_depth;
+        Self::apply_depth(particle, config.snow_depth);
+        data.velocity = Vector3::new(
+            (rng.gen::<f32>() * 0.1 - 0.4 - wind) * particle.scale,
+            (rng.gen::<f32>() * 0.1 + 0.5) * particle.scale,
+            (rng.gen::<f32>() * 2.0 - 1.0) * config.snow_z_drift,
+        )
+    }
+}
+
+impl ScreenSaver for SnowScreenSaver {
+    fn new(config: Configurator) -> SnowScreenSaver
+    where
+        Self: Sized,
+    {
+        Self {
+            models: vec![],
+            rng: config.rng(),
+            old_config: config,
+            touch_pos: [0.0, 0.0],
+            ratio: 1.0,
+            buildup_time: Duration::ZERO,
+            wind_time: Duration::ZERO,
+            wind: 0.0,
+            pile: None,
+            pile_heights: vec![0.0; Self::PILE_BUCKETS],
+        }
+    }
+
+    fn setup(
+        &mut self,
+        size: Size,
+        config: &Configurator,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        pipeline_layout: &wgpu::PipelineLayout,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        sample_count: u32,
+    ) {
+        self.ratio = if size.to_logical::<f32>(1.0).width > 1.0 {
+            size.to_logical::<f32>(1.0).width / size.to_logical::<f32>(1.0).height
+        } else {
+            1.0
+        };
+
+        let shader = wgpu::ShaderModuleDescriptor {
+            label: Some("Ground1 Shader"),
+            source: shaders::get(shaders::ShaderType::MeshShader),
+        };
+        let pipeline = create_render_pipeline(
+            device,
+            pipeline_layout,
+            color_format,
+            depth_format,
+            sample_count,
+            &[model::ModelVertex::desc(), ModelInstanceRaw::desc()],
+            shader,
+        );
+
+        //ground defined first so the snow is blended over it. the depth keeps it behind the snow either way
+        let ground1 = include_bytes!("resources/textures/ground1.png");
+        let diffuse_texture = texture::Texture::from_bytes(
+            device,
+            queue,
+            ground1,
+            "ground1.png",
+            config.texture_quality,
+        )
+        .unwrap();
+        let billboard = util::mesh::create_billboard(
+            6.0,
+            3.0,
+            Vector3::new(0.0, 0.0, Self::ground_z(config.snow_depth, 0)),
+            diffuse_texture,
+            &device,
+            &layout,
+            pipeline,
+        )
+        .unwrap();
+        self.models.push(billboard);
+
+        let shader = wgpu::ShaderModuleDescriptor {
+            label: Some("Ground2 Shader"),
+            source: shaders::get(shaders::ShaderType::MeshShader),
+        };
+        let pipeline = create_render_pipeline(
+            device,
+            pipeline_layout,
+            color_format,
+            depth_format,
+            sample_count,
+            &[model::ModelVertex::desc(), ModelInstanceRaw::desc()],
+            shader,
+        );
+
+        let ground2 = include_bytes!("resources/textures/ground2.png");
+        let diffuse_texture = texture::Texture::from_bytes(
+            device,
+            queue,
+            ground2,
+            "ground2.png",
+            config.texture_quality,
+        )
+        .unwrap();
+        let billboard = util::mesh::create_billboard(
+            6.0,
+            3.0,
+            Vector3::new(0.0, 0.0, Self::ground_z(config.snow_depth, 1)),
+            diffuse_texture,
+            &device,
+            &layout,
+            pipeline,
+        )
+        .unwrap();
+        self.models.push(billboard);
+
+        let shader = wgpu::ShaderModuleDescriptor {
+            label: Some("Ground3 Shader"),
+            source: shaders::get(shaders::ShaderType::MeshShader),
+        };
+        let pipeline = create_render_pipeline(
+            device,
+            pipeline_layout,
+            color_format,
+            depth_format,
+            sample_count,
+            &[model::ModelVertex::desc(), ModelInstanceRaw::desc()],
+            shader,
+        );
+        let ground3 = include_bytes!("resources/textures/ground3.png");
+        let diffuse_texture = texture::Texture::from_bytes(
+            device,
+            queue,
+            ground3,
+            "ground3.png",
+            config.texture_quality,
+        )
+        .unwrap();
+        let billboard = util::mesh::create_billboard(
+            6.0,
+            3.0,
+            Vector3::new(0.0, 0.0, Self::ground_z(config.snow_depth, 2)),
+            diffuse_texture,
+            &device,
+            &layout,
+            pipeline,
+        )
+        .unwrap();
+        self.models.push(billboard);
+
+        let snow1 = include_bytes!("resources/textures/snow1.png");
+        let snow2 = include_bytes!("resources/textures/snow2.png");
+        let diffuse_textures = [
+            texture::Texture::from_bytes(device, queue, snow1, "snow1.png", config.texture_quality)
+                .unwrap(),
+            texture::Texture::from_bytes(device, queue, snow2, "snow2.png", config.texture_quality)
+                .unwrap(),
+        ];
+        for diffuse_texture in diffuse_textures {
+            let mut snow_particle_system = ParticleSystem::create_billboard(
+                Self::FLAKE_SIZE,
+                Self::FLAKE_SIZE,
+                Vector3::new(0.0, 0.0, 0.0),
+                ParticleSystemData::new(Self::snow_domain(config.snow_depth, self.ratio)),
+                device,
+            );
+
+            let shader = wgpu::ShaderModuleDescriptor {
+                label: Some("Snow Shader"),
+                source: shaders::get(shaders::ShaderType::ParticleShader),
+            };
+
+            let snow_material = Material::new(
+                diffuse_texture,
+                device,
+                layout,
+                create_render_pipeline(
+                    device,
+                    pipeline_layout,
+                    color_format,
+                    depth_format,
+                    sample_count,
+                    &[model::ModelVertex::desc(), ParticleInstanceRaw::desc()],
+                    shader,
+                ),
+            );
+
+            snow_particle_system.populate_random(
+                Self::buildup_count(config, self.buildup_time),
+                device,
+                &mut self.rng,
+            );
+            for i in 0..snow_particle_system.instances.len() {
+                Self::init_flake(
+                    &mut snow_particle_system.instances[i],
+                    &mut snow_particle_system.particle_data[i],
+                    config,
+                    self.wind,
+                    &mut self.rng,
+                );
+            }
+
+            let snow = Model {
+                mesh: Box::new(snow_particle_system),
+                material: snow_material,
+            };
+
+            self.models.push(snow);
+        }
+
+        let shader = wgpu::ShaderModuleDescriptor {
+            label: Some("Snow Pile Shader"),
+            source: shaders::get(shaders::ShaderType::ParticleShader),
+        };
+        let pile_material = Material::new(
+            texture::Texture::from_bytes(device, queue, snow1, "snow1.png", config.texture_quality)
+                .unwrap(),
+            device,
+            layout,
+            create_render_pipeline(
+                device,
+                pipeline_layout,
+                color_format,
+                depth_format,
+                sample_count,
+                &[model::ModelVertex::desc(), ParticleInstanceRaw::desc()],
+                shader,
+            ),
+        );
+        self.pile = Some(Model {
+            mesh: Box::new(ParticleSystem::create_billboard(
+                Self::FLAKE_SIZE,
+                Self::FLAKE_SIZE,
+                Vector3::new(0.0, 0.0, 0.0),
+                ParticleSystemData::new(Self::snow_domain(config.snow_depth, self.ratio)),
+                device,
+            )),
+            material: pile_material,
+        });
+        self.pile_heights = vec![0.0; Self::PILE_BUCKETS];
+
+        /*
+        let moon = include_bytes!("resources/textures/moon.png");
+        let diffuse_texture =
+            texture::Texture::from_bytes(&device, &queue, moon, "moon.png", config.texture_quality).unwrap();
+        let billboard = resource::create_billboard(
+            0.32,
+            0.32,
+            Vector3::new(-1.0, -1.0, 1.0),
+            diffuse_texture,
+            &device,
+            &layout,
+        )
+            .unwrap();
+        self.models.push(billboard);*/
+    }
+
+    fn simulate(&mut self, _size: winit::dpi::Size, config: &Configurator, dt: Duration) {
+        if self.old_config != *config {
+            for model in &mut self.models {
+                //get (ParticleSystem)(Object) idiot
+                if let Some(particle_system) =
+                    model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+                {
+                    if config.snowflake_count != self.old_config.snowflake_count
+                        || config.snow_buildup_secs != self.old_config.snow_buildup_secs
+                    {
+                        Self::set_flake_count(
+                            particle_system,
+                            Self::buildup_count(config, self.buildup_time),
+                            config,
+                            self.wind,
+                            &mut self.rng,
+                        );
+                    }
+
+                    if config.snow_depth != self.old_config.snow_depth
+                        || config.snow_z_drift != self.old_config.snow_z_drift
+                    {
+                        //the domain changed, so the flakes have to be redistributed inside of it
+                        particle_system.particle_system_data.domain =
+                            Self::snow_domain(config.snow_depth, self.ratio);
+                        for i in 0..particle_system.instances.len() {
+                            particle_system.instances[i].position = particle_system
+                                .particle_system_data
+                                .domain
+                                .random_pos_with(&mut self.rng);
+                            Self::init_flake(
+                                &mut particle_system.instances.instances[i],
+                                &mut particle_system.particle_data[i],
+                                config,
+                                self.wind,
+                                &mut self.rng,
+                            );
+                        }
+                    }
+                }
+            }
+            if config.snow_depth != self.old_config.snow_depth {
+                //the ground has to stay behind the deeper (or in front of the shallower) snow
+                for (layer, ground) in self
+                    .models
+                    .iter_mut()
+                    .take(Self::GROUND_LAYERS)
+                    .filter_map(|model| model.mesh.as_any_mut().downcast_mut::<ModelMesh>())
+                    .enumerate()
+                {
+                    for instance in &mut ground.instances {
+                        instance.position.z = Self::ground_z(config.snow_depth, layer);
+                    }
+                }
+            }
+            //the pile could be in the wrong place, or bigger than it's allowed to be now
+            self.clear_pile(Self::snow_domain(config.snow_depth, self.ratio));
+            self.old_config = config.clone();
+        }
+
+        if config.snow_buildup_secs > 0.0
+            && self.buildup_time.as_secs_f32() < config.snow_buildup_secs
+        {
+            self.buildup_time += dt;
+            let count = Self::buildup_count(config, self.buildup_time);
+            let step = ((config.snowflake_count as f32 * Self::BUILDUP_STEP) as usize).max(1);
+            for model in &mut self.models {
+                //get (ParticleSystem)(Object) idiot
+                if let Some(particle_system) =
+                    model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+                {
+                    let current = particle_system.instances.len();
+                    if count >= current + step
+                        || (count == config.snowflake_count && count > current)
+                    {
+                        Self::set_flake_count(
+                            particle_system,
+                            count,
+                            config,
+                            self.wind,
+                            &mut self.rng,
+                        );
+                    }
+                }
+            }
+        }
+
+        self.wind_time += dt;
+        let wind = Self::wind(config, self.wind_time.as_secs_f32());
+        let wind_change = wind - self.wind;
+        self.wind = wind;
+
+        for model in &mut self.models {
+            if config.snow_z_drift > 0.0 || wind_change != 0.0 {
+                //get (ParticleSystem)(Object) idiot
+                if let Some(particle_system) =
+                    model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+                {
+                    let domain = particle_system.particle_system_data.domain;
+                    for i in 0..particle_system.instances.len() {
+                        let particle = &mut particle_system.instances.instances[i];
+                        let data = &mut particle_system.particle_data[i];
+
+                        //the flakes in the front are bigger, so they get blown around more
+                        data.velocity.x -= wind_change * particle.scale;
+                        if config.snow_z_drift <= 0.0 {
+                            continue;
+                        }
+
+                        //bounce off of the front and back of the domain instead of wrapping, otherwise flakes would pop in and out of existence
+                        let next_z = particle.position.z + data.velocity.z * dt.as_secs_f32();
+                        if next_z < domain.min_pos.z {
+                            data.velocity.z = data.velocity.z.abs();
+                        } else if next_z > domain.max_pos.z {
+                            data.velocity.z = -data.velocity.z.abs();
+                        }
+
+                        let old_scale = particle.scale;
+                        Self::apply_depth(particle, config.snow_depth);
+                        //keep the parallax consistent, further flakes move slower
+                        if old_scale.is_normal() {
+                            data.velocity.x *= particle.scale / old_scale;
+                            data.velocity.y *= particle.scale / old_scale;
+                        }
+                    }
+                }
+            }
+            model.step(dt, config);
+        }
+
+        if config.snow_max_accumulation > 0 {
+            self.land_flakes(config);
+        }
+    }
+
+    fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        for model in self.models.iter_mut().chain(&mut self.pile) {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                particle_system.upload(device, queue);
+            } else {
+                //the ground, which moves when the depth changes
+                model.mesh.update_instance_buffer(queue);
+            }
+        }
+    }
+
+    fn resize(&mut self, _old_ratio: f32, new_ratio: f32) {
+        self.ratio = new_ratio;
+        for model in &mut self.models {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                particle_system.resize_domain(
+                    Self::snow_domain(self.old_config.snow_depth, new_ratio),
+                    self.old_config.resize_behavior,
+                );
+                //respawned flakes are at a different depth, so their size has to match it
+                for instance in particle_system.instances.iter_mut() {
+                    Self::apply_depth(instance, self.old_config.snow_depth);
+                }
+            }
+        }
+        self.clear_pile(Self::snow_domain(self.old_config.snow_depth, new_ratio));
+    }
+
+    fn get_background_color(&self) -> wgpu::Color {
+        wgpu::Color {
+            r: 0.0,
+            g: 0.0,
+            b: 0.01,
+            a: 1.0,
+        }
+    }
+
+    fn handle_input(&mut self, position: [f32; 2], _id: u64, _enabled: bool) -> bool {
+        self.touch_pos = position;
+        false
+    }
+
+    fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, state: &State<'_>) {
+        // lib.rmesh.in
+        //render_pass.set_pipeline(&state.render_pipeline);
+        render_pass.set_bind_group(1, &state.camera_bind_group, &[]);
+
+        for model in self.models.iter().chain(&self.pile) {
+            render_pass.set_pipeline(&model.material.pipeline);
+            render_pass.set_bind_group(0, &model.material.bind_group, &[]);
+            render_pass.draw_mesh_instanced(&*model.mesh, 0..model.mesh.instance_count() as u32);
+        }
+    }
+
+    fn get_camera_type(&self) -> CameraType {
+        CameraType::Orthographic()
+    }
+
+    fn get_camera_position(&self) -> (Point3<f32>, Point3<f32>) {
+        (
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(self.touch_pos[0] / 4.0, self.touch_pos[1] / 4.0, 0.0),
+        )
+    }
+
+    fn interpolate(&mut self, alpha: f32, queue: &wgpu::Queue) {
+        for model in &mut self.models {
+            model.interpolate(alpha, queue);
+        }
+    }
+
+    fn save_state(&self) -> Vec<Vec<ParticleSnapshot>> {
+        snapshot::capture_particles(&self.models)
+    }
+
+    fn load_state(&mut self, particle_systems: &[Vec<ParticleSnapshot>], device: &Device) {
+        snapshot::restore_particles(&mut self.models, particle_systems, device);
+        //the saved snow already built up
+        self.buildup_time = Duration::from_secs_f32(self.old_config.snow_buildup_secs.max(0.0));
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Copy)]
+pub enum ClockFormat {
+    TwentyFourHour,
+    TwelveHour,
+}
+
+impl ToString for ClockFormat {
+    fn to_string(&self) -> String {
+        match self {
+            ClockFormat::TwentyFourHour => "24h".to_string(),
+            ClockFormat::TwelveHour => "12h".to_string(),
+        }
+    }
+}
+
+pub struct ClockScreenSaver {
+    digits: Vec<Model>,
+    text: String,
+    ratio: f32,
+    rng: StdRng,
+    old_config: Configurator,
+}
+
+impl ClockScreenSaver {
+    //width of a digit relative to its height
+    const GLYPH_ASPECT: f32 = glyphs::GLYPH_WIDTH as f32 / glyphs::GLYPH_HEIGHT as f32;
+
+    fn current_text(config: &Configurator) -> String {
+        let format = match (config.clock_format, config.clock_show_seconds) {
+            (ClockFormat::TwentyFourHour, true) => "%H:%M:%S",
+            (ClockFormat::TwentyFourHour, false) => "%H:%M",
+            //%l is the hour padded with a space instead of a zero, %p is am or pm
+            (ClockFormat::TwelveHour, true) => "%l:%M:%S %p",
+            (ClockFormat::TwelveHour, false) => "%l:%M %p",
+        };
+        let text = chrono::Local::now().format(format).to_string();
+        //there is no seven segment M, so it's just A or P
+        text.trim_end_matches('M').to_string()
+    }
+
+    /// where the `i`th of `char_count` characters goes, so the text is centered
+    fn glyph_x(i: usize, char_count: usize, glyph_width: f32) -> f32 {
+        //the camera is mirrored, so positive x is on the left
+        char_count as f32 * glyph_width / 2.0 - (i as f32 + 0.5) * glyph_width
+    }
+
+    /// lays the digits out centered on the screen, one instance per character
+    fn layout_digits(&mut self, config: &Configurator, device: &wgpu::Device) {
+        let color = util::color::color_from_hex(config.clock_color.to_hex()).unwrap();
+        let char_count = self.text.chars().count();
+        //shrink the clock if it wouldn't fit on the screen
+        let scale = f32::min(
+            config.clock_font_size,
+            2.0 * self.ratio * 0.9 / (char_count as f32 * Self::GLYPH_ASPECT),
+        );
+        let glyph_width = scale * Self::GLYPH_ASPECT;
+
+        for model in &mut self.digits {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                if particle_system.instances.len() != char_count {
+                    if particle_system.instances.len() < char_count {
+                        particle_system.populate_random(
+                            char_count - particle_system.instances.len(),
+                            device,
+                            &mut self.rng,
+                        );
+                    } else {
+                        particle_system.instances.instances.truncate(char_count);
+                        particle_system.particle_data.truncate(char_count);
+                        particle_system.rebuild_instance_buffer(device);
+                    }
+                }
+
+                for (i, c) in self.text.chars().enumerate() {
+                    let instance = &mut particle_system.instances[i];
+                    let (tex_offset, tex_scale) = glyphs::glyph_tex_coords(c);
+                    instance.position =
+                        Vector3::new(Self::glyph_x(i, char_count, glyph_width), 0.0, 0.0);
+                    instance.tex_offset = tex_offset;
+                    instance.tex_scale = tex_scale;
+                    instance.scale = scale;
+                    instance.color = color;
+                }
+            }
+        }
+    }
+}
+
+impl ScreenSaver for ClockScreenSaver {
+    fn new(config: Configurator) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            digits: vec![],
+            text: String::new(),
+            ratio: 1.0,
+            rng: config.rng(),
+            old_config: config,
+        }
+    }
+
+    fn setup(
+        &mut self,
+        size: Size,
+        config: &Configurator,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        pipeline_layout: &wgpu::PipelineLayout,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        sample_count: u32,
+    ) {
+        self.ratio = if size.to_logical::<f32>(1.0).width > 1.0 {
+            size.to_logical::<f32>(1.0).width / size.to_logical::<f32>(1.0).height
+        } else {
+            1.0
+        };
+
+        let atlas = image::DynamicImage::ImageRgba8(glyphs::seven_segment_atlas());
+        let diffuse_texture =
+            texture::Texture::from_image(device, queue, &atlas, Some("glyph atlas")).unwrap();
+
+        let particle_system = ParticleSystem::create_billboard(
+            Self::GLYPH_ASPECT,
+            1.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            ParticleSystemData::new(BoundingBox::new_with_size(
+                Vector3::new(0.0, 0.0, 0.0),
+                2.0 * self.ratio,
+                2.0,
+                0.0,
+                BoundingBoxType::Ignore,
+            )),
+            device,
+        );
+
+        let shader = wgpu::ShaderModuleDescriptor {
+            label: Some("Clock Shader"),
+            source: shaders::get(shaders::ShaderType::ParticleShader),
+        };
+
+        let material = Material::new(
+            diffuse_texture,
+            device,
+            layout,
+            create_render_pipeline(
+                device,
+                pipeline_layout,
+                color_format,
+                depth_format,
+                sample_count,
+                &[model::ModelVertex::desc(), ParticleInstanceRaw::desc()],
+                shader,
+            ),
+        );
+
+        self.digits.push(Model {
+            mesh: Box::new(particle_system),
+            material,
+        });
+
+        self.text = Self::current_text(config);
+        self.layout_digits(config, device);
+    }
+
+    fn update(
+        &mut self,
+        _size: Size,
+        config: &Configurator,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        dt: Duration,
+    ) {
+        let text = Self::current_text(config);
+        if text != self.text || *config != self.old_config {
+            self.text = text;
+            self.layout_digits(config, device);
+            self.old_config = config.clone();
+        }
+
+        for model in &mut self.digits {
+            model.update(dt, config, queue);
+        }
+    }
+
+    fn resize(&mut self, _old_ratio: f32, new_ratio: f32) {
+        self.ratio = new_ratio;
+        //force the digits to be laid out again
+        self.text.clear();
+    }
+
+    fn get_background_color(&self) -> wgpu::Color {
+        wgpu::Color {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            a: 1.0,
+        }
+    }
+
+    fn handle_input(&mut self, _position: [f32; 2], _id: u64, _active: bool) -> bool {
+        false
+    }
+
+    fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, state: &State<'_>) {
+        render_pass.set_bind_group(1, &state.camera_bind_group, &[]);
+
+        for model in &self.digits {
+            render_pass.set_pipeline(&model.material.pipeline);
+            render_pass.set_bind_group(0, &model.material.bind_group, &[]);
+            render_pass.draw_mesh_instanced(&*model.mesh, 0..model.mesh.instance_count() as u32);
+        }
+    }
+
+    fn get_camera_type(&self) -> CameraType {
+        CameraType::Orthographic()
+    }
+
+    fn get_camera_position(&self) -> (Point3<f32>, Point3<f32>) {
+        (Point3::new(0.0, 0.0, 0.0), Point3::new(0.0, 0.0, 0.0))
+    }
+}
+
+pub struct HarmonographScreenSaver {
+    trail: Vec<Model>,
+    //how long the pen has been swinging, in seconds
+    time: f32,
+    //index of the point in the trail that will be overwritten next
+    head: usize,
+    //how many points were drawn since the trail was cleared
+    drawn: usize,
+    //random offsets added to the phases every time the pen is restarted, so every swing is a bit different
+    phase_offset: [f32; 2],
+    ratio: f32,
+    rng: StdRng,
+    old_config: Configurator,
+}
+
+impl HarmonographScreenSaver {
+    //when the swing gets this small the pen is restarted
+    const MIN_AMPLITUDE: f32 = 0.02;
+    //the most points that can be added to the trail in a single update
+    const MAX_POINTS_PER_UPDATE: usize = 256;
+
+    fn pen_position(&self, time: f32, config: &Configurator) -> Vector3<f32> {
+        let amplitude = (-config.harmonograph_damping * time).exp() * 0.9;
+        Vector3::new(
+            amplitude
+                * self.ratio
+                * (f32::TAU()
+                    * (config.harmonograph_frequency_x * time
+                        + config.harmonograph_phase_x
+                        + self.phase_offset[0]))
+                    .sin(),
+            amplitude
+                * (f32::TAU()
+                    * (config.harmonograph_frequency_y * time
+                        + config.harmonograph_phase_y
+                        + self.phase_offset[1]))
+                    .sin(),
+            0.0,
+        )
+    }
+
+    fn restart(&mut self) {
+        self.time = 0.0;
+        self.phase_offset = [
+            (self.rng.gen::<f32>() - 0.5) * 0.1,
+            (self.rng.gen::<f32>() - 0.5) * 0.1,
+        ];
+    }
+
+    /// resizes the trail to the configured length and hides every point
+    fn clear_trail(&mut self, config: &Configurator, device: &wgpu::Device) {
+        let color = util::color::color_from_hex(config.harmonograph_color.to_hex()).unwrap();
+        for model in &mut self.trail {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                let length = config.harmonograph_trail_length;
+                if particle_system.instances.len() < length {
+                    particle_system.populate_random(
+                        length - particle_system.instances.len(),
+                        device,
+                        &mut self.rng,
+                    );
+                } else {
+                    particle_system.instances.instances.truncate(length);
+                    particle_system.particle_data.truncate(length);
+                    particle_system.rebuild_instance_buffer(device);
+                }
+
+                for instance in particle_system.instances.iter_mut() {
+                    instance.color = Color { a: 0.0, ..color };
+                    instance.scale = config.harmonograph_line_width;
+                }
+            }
+        }
+        self.head = 0;
+        self.drawn = 0;
+    }
+}
+
+impl ScreenSaver for HarmonographScreenSaver {
+    fn new(config: Configurator) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            trail: vec![],
+            time: 0.0,
+            head: 0,
+            drawn: 0,
+            phase_offset: [0.0, 0.0],
+            ratio: 1.0,
+            rng: config.rng(),
+            old_config: config,
+        }
+    }
+
+    fn setup(
+        &mut self,
+        size: Size,
+        config: &Configurator,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        pipeline_layout: &wgpu::PipelineLayout,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        sample_count: u32,
+    ) {
+        self.ratio = if size.to_logical::<f32>(1.0).width > 1.0 {
+            size.to_logical::<f32>(1.0).width / size.to_logical::<f32>(1.0).height
+        } else {
+            1.0
+        };
+
+        let circle_texture = include_bytes!("resources/textures/circle16.png");
+        let diffuse_texture = texture::Texture::from_bytes(
+            device,
+            queue,
+            circle_texture,
+            "circle16.png",
+            config.texture_quality,
+        )
+        .unwrap();
+
+        let particle_system = ParticleSystem::create_billboard(
+            1.0,
+            1.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            ParticleSystemData::new(BoundingBox::new_with_size(
+                Vector3::new(0.0, 0.0, 0.0),
+                2.0 * self.ratio,
+                2.0,
+                0.0,
+                BoundingBoxType::Ignore,
+            )),
+            device,
+        );
+
+        let shader = wgpu::ShaderModuleDescriptor {
+            label: Some("Harmonograph Shader"),
+            source: shaders::get(shaders::ShaderType::ParticleShader),
+        };
+
+        let material = Material::new(
+            diffuse_texture,
+            device,
+            layout,
+            create_render_pipeline(
+                device,
+                pipeline_layout,
+                color_format,
+                depth_format,
+                sample_count,
+                &[model::ModelVertex::desc(), ParticleInstanceRaw::desc()],
+                shader,
+            ),
+        );
+
+        self.trail.push(Model {
+            mesh: Box::new(particle_system),
+            material,
+        });
+
+        self.restart();
+        self.clear_trail(config, device);
+    }
+
+    fn update(
+        &mut self,
+        _size: Size,
+        config: &Configurator,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        dt: Duration,
+    ) {
+        if *config != self.old_config {
+            if config.harmonograph_trail_length != self.old_config.harmonograph_trail_length
+                || config.harmonograph_line_width != self.old_config.harmonograph_line_width
+                || config.harmonograph_color != self.old_config.harmonograph_color
+            {
+                self.clear_trail(config, device);
+            }
+            if config.harmonograph_frequency_x != self.old_config.harmonograph_frequency_x
+                || config.harmonograph_frequency_y != self.old_config.harmonograph_frequency_y
+                || config.harmonograph_phase_x != self.old_config.harmonograph_phase_x
+                || config.harmonograph_phase_y != self.old_config.harmonograph_phase_y
+                || config.harmonograph_damping != self.old_config.harmonograph_damping
+            {
+                self.restart();
+            }
+            self.old_config = config.clone();
+        }
+
+        let step = dt.as_secs_f32() * config.harmonograph_speed;
+        let distance = (self.pen_position(self.time + step, config)
+            - self.pen_position(self.time, config))
+        .magnitude();
+        //add enough points that the trail looks like a continuous line
+        let points = ((distance / (config.harmonograph_line_width * 0.5)).ceil() as usize)
+            .clamp(1, Self::MAX_POINTS_PER_UPDATE);
+
+        let mut positions = Vec::with_capacity(points);
+        for i in 1..=points {
+            positions.push(self.pen_position(self.time + step * i as f32 / points as f32, config));
+        }
+        self.time += step;
+
+        for model in &mut self.trail {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                let length = particle_system.instances.len();
+                if length == 0 {
+                    continue;
+                }
+
+                for position in &positions {
+                    particle_system.instances[self.head].position = *position;
+                    self.head = (self.head + 1) % length;
+                    self.drawn = usize::min(self.drawn + 1, length);
+                }
+
+                for i in 0..length {
+                    //0 is the newest point
+                    let age = (self.head + length - 1 - i) % length;
+                    let instance = &mut particle_system.instances[i];
+                    instance.color.a = if age < self.drawn {
+                        1.0 - age as f64 / length as f64
+                    } else {
+                        0.0
+                    };
+                    //older points go further back, so the newest part of the trail is drawn on top
+                    instance.position.z = age as f32 / length as f32 * 0.5;
+                }
+            }
+            model.update(dt, config, queue);
+        }
+
+        if (-config.harmonograph_damping * self.time).exp() < Self::MIN_AMPLITUDE {
+            self.restart();
+        }
+    }
+
+    fn resize(&mut self, _old_ratio: f32, new_ratio: f32) {
+        self.ratio = new_ratio;
+    }
+
+    fn get_background_color(&self) -> wgpu::Color {
+        wgpu::Color {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            a: 1.0,
+        }
+    }
+
+    fn handle_input(&mut self, _position: [f32; 2], _id: u64, _active: bool) -> bool {
+        false
+    }
+
+    fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, state: &State<'_>) {
+        render_pass.set_bind_group(1, &state.camera_bind_group, &[]);
+
+        for model in &self.trail {
+            render_pass.set_pipeline(&model.material.pipeline);
+            render_pass.set_bind_group(0, &model.material.bind_group, &[]);
+            render_pass.draw_mesh_instanced(&*model.mesh, 0..model.mesh.instance_count() as u32);
+        }
+    }
+
+    fn get_camera_type(&self) -> CameraType {
+        CameraType::Orthographic()
+    }
+
+    fn get_camera_position(&self) -> (Point3<f32>, Point3<f32>) {
+        (Point3::new(0.0, 0.0, 0.0), Point3::new(0.0, 0.0, 0.0))
+    }
+}
+
+pub struct CirclePackingScreenSaver {
+    circles: Vec<Model>,
+    //the first `count` instances are circles on the screen, the rest are hidden
+    count: usize,
+    growing: Vec<bool>,
+    palette: Vec<Color>,
+    //fractional seeds left over from the last update
+    seeds_to_spawn: f32,
+    //how long the picture has been finished, it starts over after RESTART_DELAY
+    finished_time: Option<Duration>,
+    ratio: f32,
+    rng: StdRng,
+    old_config: Configurator,
+}
+
+impl CirclePackingScreenSaver {
+    const SEEDS_PER_SECOND: f32 = 20.0;
+    //how many random places are tried for every seed before giving up
+    const SEED_ATTEMPTS: usize = 30;
+    const MIN_RADIUS: f32 = 0.003;
+    const MAX_RADIUS: f32 = 0.3;
+    //space left between touching circles
+    const GAP: f32 = 0.004;
+    const RESTART_DELAY: Duration = Duration::from_secs(10);
+    //size of the regions used for finding nearby circles
+    const REGION_SIZE: f32 = 0.1;
+
+    fn parse_palette(palette: &str) -> Vec<Color> {
+        palette
+            .split(',')
+            .map(|hex| hex.trim())
+            //an empty palette (or a comma at the end) isn't a mistake
+            .filter(|hex| !hex.is_empty())
+            .filter_map(|hex| match util::color::color_from_hex(hex.to_string()) {
+                Ok(color) => Some(color),
+                Err(e) => {
+                    log::warn!("Skipping a color in the palette: {}", e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn random_color(&mut self) -> Color {
+        match self.palette.choose(&mut self.rng) {
+            Some(color) => *color,
+            None => util::color::random_color_with(&mut self.rng),
+        }
+    }
+
+    fn region_of(particle_system: &ParticleSystem, position: Vector3<f32>) -> (usize, usize) {
+        let instances = &particle_system.instances;
+        let x = position.x / instances.bounding_box.width() + 0.5;
+        let y = position.y / instances.bounding_box.height() + 0.5;
+        (
+            usize::clamp(
+                (x * instances.regions_x as f32) as usize,
+                0,
+                instances.regions_x - 1,
+            ),
+            usize::clamp(
+                (y * instances.regions_y as f32) as usize,
+                0,
+                instances.regions_y - 1,
+            ),
+        )
+    }
+
+    /// how many regions around a circle have to be checked to find everything it could touch
+    fn region_range(radius: f32) -> u32 {
+        ((radius + Self::MAX_RADIUS) / Self::REGION_SIZE).ceil() as u32
+    }
+
+    /// resizes the circles to the configured count and hides all of them
+    fn clear(&mut self, config: &Configurator, device: &wgpu::Device) {
+        for model in &mut self.circles {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                let length = config.packing_max_circles;
+                if particle_system.instances.len() < length {
+                    particle_system.populate_random(
+                        length - particle_system.instances.len(),
+                        device,
+                        &mut self.rng,
+                    );
+                } else {
+                    particle_system.instances.instances.truncate(length);
+                    particle_system.particle_data.truncate(length);
+                    particle_system.rebuild_instance_buffer(device);
+                }
+            }
+        }
+        self.hide_all();
+        self.growing = vec![false; config.packing_max_circles];
+    }
+
+    fn hide_all(&mut self) {
+        for model in &mut self.circles {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                for instance in particle_system.instances.iter_mut() {
+                    instance.color.a = 0.0;
+                    instance.scale = 0.0;
+                }
+            }
+        }
+        self.growing.fill(false);
+        self.count = 0;
+        self.seeds_to_spawn = 0.0;
+        self.finished_time = None;
+    }
+
+    /// tries to find a free spot for a new circle. returns false if the screen seems to be full
+    fn spawn_seed(&mut self, particle_system: &mut ParticleSystem) -> bool {
+        let domain = particle_system.particle_system_data.domain;
+        for _ in 0..Self::SEED_ATTEMPTS {
+            let position = Vector3::new(
+                (self.rng.gen::<f32>() - 0.5) * (domain.width() - 2.0 * Self::MIN_RADIUS),
+                (self.rng.gen::<f32>() - 0.5) * (domain.height() - 2.0 * Self::MIN_RADIUS),
+                0.0,
+            );
+            let (x, y) = Self::region_of(particle_system, position);
+            let free = particle_system
+                .instances
+                .get_regions_in_range(x, y, Self::region_range(Self::MIN_RADIUS))
+                .iter()
+                .filter(|&&j| j < self.count)
+                .all(|&j| {
+                    let other = &particle_system.instances[j];
+                    other.position.distance(position)
+                        > other.scale / 2.0 + Self::MIN_RADIUS + Self::GAP
+                });
+            if free {
+                let color = self.random_color();
+                let instance = &mut particle_system.instances[self.count];
+                instance.position = position;
+                instance.previous_position = position;
+                instance.scale = Self::MIN_RADIUS * 2.0;
+                instance.color = color;
+                //so seeds spawned in the same update don't end up on top of each other
+                particle_system
+                    .instances
+                    .get_region_mut(x, y)
+                    .push(self.count);
+                self.growing[self.count] = true;
+                self.count += 1;
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl ScreenSaver for CirclePackingScreenSaver {
+    fn new(config: Configurator) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            circles: vec![],
+            count: 0,
+            growing: vec![],
+            palette: Self::parse_palette(&config.packing_palette),
+            seeds_to_spawn: 0.0,
+            finished_time: None,
+            ratio: 1.0,
+            rng: config.rng(),
+            old_config: config,
+        }
+    }
+
+    fn setup(
+        &mut self,
+        size: Size,
+        config: &Configurator,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        pipeline_layout: &wgpu::PipelineLayout,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        sample_count: u32,
+    ) {
+        self.ratio = if size.to_logical::<f32>(1.0).width > 1.0 {
+            size.to_logical::<f32>(1.0).width / size.to_logical::<f32>(1.0).height
+        } else {
+            1.0
+        };
+
+        let circle_texture = include_bytes!("resources/textures/circle16.png");
+        let diffuse_texture = texture::Texture::from_bytes(
+            device,
+            queue,
+            circle_texture,
+            "circle16.png",
+            config.texture_quality,
+        )
+        .unwrap();
+
+        let particle_system = ParticleSystem::create_billboard(
+            1.0,
+            1.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            ParticleSystemData::new(BoundingBox::new_with_size(
+                Vector3::new(0.0, 0.0, 0.0),
+                2.0 * self.ratio,
+                2.0,
+                0.0,
+                BoundingBoxType::Ignore,
+            )),
+            device,
+        );
+
+        let shader = wgpu::ShaderModuleDescriptor {
+            label: Some("Circle Packing Shader"),
+            source: shaders::get(shaders::ShaderType::ParticleShader),
+        };
+
+        let material = Material::new(
+            diffuse_texture,
+            device,
+            layout,
+            create_render_pipeline(
+                device,
+                pipeline_layout,
+                color_format,
+                depth_format,
+                sample_count,
+                &[model::ModelVertex::desc(), ParticleInstanceRaw::desc()],
+                shader,
+            ),
+        );
+
+        self.circles.push(Model {
+            mesh: Box::new(particle_system),
+            material,
+        });
+
+        self.clear(config, device);
+    }
+
+    fn update(
+        &mut self,
+        _size: Size,
+        config: &Configurator,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        dt: Duration,
+    ) {
+        if *config != self.old_config {
+            if config.packing_palette != self.old_config.packing_palette {
+                self.palette = Self::parse_palette(&config.packing_palette);
+            }
+            if config.packing_max_circles != self.old_config.packing_max_circles
+                || config.packing_palette != self.old_config.packing_palette
+            {
+                self.clear(config, device);
+            }
+            self.old_config = config.clone();
+        }
+
+        if let Some(finished_time) = self.finished_time {
+            let finished_time = finished_time + dt;
+            if finished_time >= Self::RESTART_DELAY {
+                self.hide_all();
+            } else {
+                self.finished_time = Some(finished_time);
+            }
+        }
+
+        let mut models = std::mem::take(&mut self.circles);
+        for model in &mut models {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                let domain = particle_system.particle_system_data.domain;
+                particle_system.instances.bounding_box = domain;
+                particle_system.instances.regions_x =
+                    ((domain.width() / Self::REGION_SIZE).ceil() as usize).max(1);
+                particle_system.instances.regions_y =
+                    ((domain.height() / Self::REGION_SIZE).ceil() as usize).max(1);
+                particle_system.instances.rebuild_regions();
+
+                if self.finished_time.is_none() {
+                    //grow every circle until it touches a wall or another circle
+                    for i in 0..self.count {
+                        if !self.growing[i] {
+                            continue;
+                        }
+                        let instance = particle_system.instances[i];
+                        let radius = (instance.scale / 2.0
+                            + config.packing_growth_rate * dt.as_secs_f32())
+                        .min(Self::MAX_RADIUS);
+                        let (x, y) = Self::region_of(particle_system, instance.position);
+                        let touches_wall = instance.position.x.abs() + radius + Self::GAP
+                            >= domain.width() / 2.0
+                            || instance.position.y.abs() + radius + Self::GAP
+                                >= domain.height() / 2.0;
+                        let touches_circle = particle_system
+                            .instances
+                            .get_regions_in_range(x, y, Self::region_range(radius))
+                            .iter()
+                            .filter(|&&j| j != i && j < self.count)
+                            .any(|&j| {
+                                let other = &particle_system.instances[j];
+                                other.position.distance(instance.position)
+                                    <= other.scale / 2.0 + radius + Self::GAP
+                            });
+
+                        if touches_wall || touches_circle || radius >= Self::MAX_RADIUS {
+                            self.growing[i] = false;
+                        } else {
+                            particle_system.instances[i].scale = radius * 2.0;
+                        }
+                    }
+
+                    self.seeds_to_spawn += Self::SEEDS_PER_SECOND * dt.as_secs_f32();
+                    let mut full = self.count >= config.packing_max_circles;
+                    while self.seeds_to_spawn >= 1.0 && !full {
+                        self.seeds_to_spawn -= 1.0;
+                        full = !self.spawn_seed(particle_system)
+                            || self.count >= config.packing_max_circles;
+                    }
+                    self.seeds_to_spawn = self.seeds_to_spawn.min(1.0);
+
+                    if full && !self.growing[..self.count].contains(&true) {
+                        self.finished_time = Some(Duration::ZERO);
+                    }
+                }
+
+                particle_system.update_instance_buffer(queue);
+            }
+            model.update(dt, config, queue);
+        }
+        self.circles = models;
+    }
+
+    fn resize(&mut self, _old_ratio: f32, new_ratio: f32) {
+        self.ratio = new_ratio;
+        for model in &mut self.circles {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                particle_system.particle_system_data.domain = BoundingBox::new_with_size(
+                    Vector3::new(0.0, 0.0, 0.0),
+                    2.0 * new_ratio,
+                    2.0,
+                    0.0,
+                    BoundingBoxType::Ignore,
+                );
+            }
+        }
+        //the circles wouldn't fit anymore, so just start over
+        self.hide_all();
+    }
+
+    fn get_background_color(&self) -> wgpu::Color {
+        wgpu::Color {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            a: 1.0,
+        }
+    }
+
+    fn handle_input(&mut self, _position: [f32; 2], _id: u64, _active: bool) -> bool {
+        false
+    }
+
+    fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, state: &State<'_>) {
+        render_pass.set_bind_group(1, &state.camera_bind_group, &[]);
+
+        for model in &self.circles {
+            render_pass.set_pipeline(&model.material.pipeline);
+            render_pass.set_bind_group(0, &model.material.bind_group, &[]);
+            render_pass.draw_mesh_instanced(&*model.mesh, 0..model.mesh.instance_count() as u32);
+        }
+    }
+
+    fn get_camera_type(&self) -> CameraType {
+        CameraType::Orthographic()
+    }
+
+    fn get_camera_position(&self) -> (Point3<f32>, Point3<f32>) {
+        (Point3::new(0.0, 0.0, 0.0), Point3::new(0.0, 0.0, 0.0))
+    }
+
+    fn get_edge_softness(&self, _config: &Configurator) -> Option<f32> {
+        //the circles get big, so the texture would look blurry
+        Some(0.0)
+    }
+}
+
+pub struct CometScreenSaver {
+    heads: Vec<Model>,
+    //where every comet has been, newest first, with how many seconds ago it was there
+    tails: Vec<VecDeque<(Vector3<f32>, f32)>>,
+    //how fast every comet is turning, in radians per second
+    turn_rates: Vec<f32>,
+    ribbons: Option<Ribbons>,
+    rng: StdRng,
+    old_config: Configurator,
+}
+
+impl CometScreenSaver {
+    const HEAD_SIZE: f32 = 0.05;
+    //width of the tail right behind the head
+    const TAIL_WIDTH: f32 = 0.03;
+    //how many times wider than the tail the glow is with the glow at 1
+    const GLOW_WIDTH: f32 = 4.0;
+    const MAX_TURN_RATE: f32 = 1.0;
+    //points closer together than this are merged, so slow comets don't make huge tails
+    const MIN_POINT_DISTANCE: f32 = 0.002;
+    const MAX_TAIL_POINTS: usize = 2048;
+
+    fn tail_color(config: &Configurator) -> Color {
+        util::color::color_from_hex(config.comet_color.to_hex()).unwrap()
+    }
+
+    /// the tail color, but closer to white, so the heads look hot
+    fn head_color(config: &Configurator) -> Color {
+        let color = Self::tail_color(config);
+        Color {
+            r: (color.r + 1.0) / 2.0,
+            g: (color.g + 1.0) / 2.0,
+            b: (color.b + 1.0) / 2.0,
+            a: 1.0,
+        }
+    }
+
+    /// places the configured number of comets randomly, with no tails
+    fn respawn(&mut self, config: &Configurator, device: &wgpu::Device) {
+        let color = Self::head_color(config);
+        for model in &mut self.heads {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                let count = config.comet_count;
+                if particle_system.instances.len() < count {
+                    particle_system.populate_random(
+                        count - particle_system.instances.len(),
+                        device,
+                        &mut self.rng,
+                    );
+                } else {
+                    particle_system.instances.instances.truncate(count);
+                    particle_system.particle_data.truncate(count);
+                    particle_system.rebuild_instance_buffer(device);
+                }
+
+                let domain = particle_system.particle_system_data.domain;
+                for i in 0..particle_system.instances.len() {
+                    let instance = &mut particle_system.instances[i];
+                    instance.position = domain.random_pos_with(&mut self.rng);
+                    instance.previous_position = instance.position;
+                    instance.color = color;
+                    instance.scale = Self::HEAD_SIZE;
+
+                    let angle = self.rng.gen::<f32>() * f32::TAU();
+                    particle_system.particle_data[i].velocity =
+                        Vector3::new(angle.cos(), angle.sin(), 0.0) * config.comet_speed;
+                }
+            }
+        }
+        self.tails = vec![VecDeque::new(); config.comet_count];
+        self.turn_rates = vec![0.0; config.comet_count];
+    }
+
+    /// every tail as a core ribbon that gets thinner and fainter with age, and a wider, fainter glow around it
+    fn tail_ribbons(&self, config: &Configurator) -> Vec<Vec<RibbonPoint>> {
+        let color = Self::tail_color(config);
+        let mut ribbons = vec![];
+        for tail in &self.tails {
+            let mut core = Vec::with_capacity(tail.len());
+            let mut glow = Vec::with_capacity(tail.len());
+            for (position, age) in tail {
+                let fade = (1.0 - age / config.comet_tail_length).max(0.0);
+                core.push(RibbonPoint {
+                    position: *position,
+                    width: Self::TAIL_WIDTH * fade,
+                    color: [color.r as f32, color.g as f32, color.b as f32, fade * fade],
+                });
+                glow.push(RibbonPoint {
+                    position: *position,
+                    width: Self::TAIL_WIDTH * (1.0 + Self::GLOW_WIDTH * config.comet_glow) * fade,
+                    color: [
+                        color.r as f32,
+                        color.g as f32,
+                        color.b as f32,
+                        0.25 * config.comet_glow * fade,
+                    ],
+                });
+            }
+            if config.comet_glow > 0.0 {
+                ribbons.push(glow);
+            }
+            ribbons.push(core);
+        }
+        ribbons
+    }
+}
+
+impl ScreenSaver for CometScreenSaver {
+    fn new(config: Configurator) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            heads: vec![],
+            tails: vec![],
+            turn_rates: vec![],
+            ribbons: None,
+            rng: config.rng(),
+            old_config: config,
+        }
+    }
+
+    fn setup(
+        &mut self,
+        size: Size,
+        config: &Configurator,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        pipeline_layout: &wgpu::PipelineLayout,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        sample_count: u32,
+    ) {
+        let ratio = if size.to_logical::<f32>(1.0).width > 1.0 {
+            size.to_logical::<f32>(1.0).width / size.to_logical::<f32>(1.0).height
+        } else {
+            1.0
+        };
+
+        let circle_texture = include_bytes!("resources/textures/circle16.png");
+        let diffuse_texture = texture::Texture::from_bytes(
+            device,
+            queue,
+            circle_texture,
+            "circle16.png",
+            config.texture_quality,
+        )
+        .unwrap();
+
+        let particle_system = ParticleSystem::create_billboard(
+            1.0,
+            1.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            ParticleSystemData::new(BoundingBox::new_with_size(
+                Vector3::new(0.0, 0.0, 0.0),
+                2.0 * ratio,
+                2.0,
+                0.0,
+                BoundingBoxType::Bounce,
+            )),
+            device,
+        );
+
+        let shader = wgpu::ShaderModuleDescriptor {
+            label: Some("Comet Shader"),
+            source: shaders::get(shaders::ShaderType::ParticleShader),
+        };
+
+        let material = Material::new(
+            diffuse_texture,
+            device,
+            layout,
+            create_render_pipeline(
+                device,
+                pipeline_layout,
+                color_format,
+                depth_format,
+                sample_count,
+                &[model::ModelVertex::desc(), ParticleInstanceRaw::desc()],
+                shader,
+            ),
+        );
+
+        self.heads.push(Model {
+            mesh: Box::new(particle_system),
+            material,
+        });
+        self.ribbons = Some(Ribbons::new(
+            device,
+            pipeline_layout,
+            color_format,
+            depth_format,
+            sample_count,
+        ));
+
+        self.respawn(config, device);
+    }
+
+    fn update(
+        &mut self,
+        _size: Size,
+        config: &Configurator,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        dt: Duration,
+    ) {
+        if *config != self.old_config {
+            if config.comet_count != self.old_config.comet_count
+                || config.comet_color != self.old_config.comet_color
+            {
+                self.respawn(config, device);
+            }
+            self.old_config = config.clone();
+        }
+
+        let dt_secs = dt.as_secs_f32();
+        for model in &mut self.heads {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                //slowly and randomly change how much every comet turns, so they fly in gentle curves
+                for (i, turn_rate) in self.turn_rates.iter_mut().enumerate() {
+                    *turn_rate = (*turn_rate + (self.rng.gen::<f32>() - 0.5) * 2.0 * dt_secs)
+                        .clamp(-Self::MAX_TURN_RATE, Self::MAX_TURN_RATE);
+                    let (sin, cos) = (*turn_rate * dt_secs * config.time_scale.signum()).sin_cos();
+                    let velocity = particle_system.particle_data[i].velocity;
+                    particle_system.particle_data[i].velocity = Vector3::new(
+                        velocity.x * cos - velocity.y * sin,
+                        velocity.x * sin + velocity.y * cos,
+                        0.0,
+                    )
+                    .normalize()
+                        * config.comet_speed;
+                }
+            }
+
+            model.update(dt, config, queue);
+
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any().downcast_ref::<ParticleSystem>() {
+                for (instance, tail) in particle_system.instances.iter().zip(&mut self.tails) {
+                    for (_, age) in tail.iter_mut() {
+                        *age += dt_secs;
+                    }
+                    while tail
+                        .back()
+                        .is_some_and(|(_, age)| *age > config.comet_tail_length)
+                    {
+                        tail.pop_back();
+                    }
+                    //the newest point always follows the head, and stays behind once the head is far enough
+                    if tail.len() >= 2
+                        && tail[1].0.distance(instance.position) < Self::MIN_POINT_DISTANCE
+                    {
+                        tail[0] = (instance.position, 0.0);
+                    } else {
+                        tail.push_front((instance.position, 0.0));
+                        tail.truncate(Self::MAX_TAIL_POINTS);
+                    }
+                }
+            }
+        }
+
+        let ribbons = self.tail_ribbons(config);
+        if let Some(tail_ribbons) = &mut self.ribbons {
+            tail_ribbons.set_ribbons(device, queue, &ribbons);
+        }
+    }
+
+    fn resize(&mut self, _old_ratio: f32, new_ratio: f32) {
+        for model in &mut self.heads {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                particle_system.resize_domain(
+                    BoundingBox::new_with_size(
+                        Vector3::new(0.0, 0.0, 0.0),
+                        2.0 * new_ratio,
+                        2.0,
+                        0.0,
+                        BoundingBoxType::Bounce,
+                    ),
+                    self.old_config.resize_behavior,
+                );
+            }
+        }
+        //the tails would cut across the screen
+        for tail in &mut self.tails {
+            tail.clear();
+        }
+    }
+
+    fn get_background_color(&self) -> wgpu::Color {
+        wgpu::Color {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            a: 1.0,
+        }
+    }
+
+    fn handle_input(&mut self, _position: [f32; 2], _id: u64, _active: bool) -> bool {
+        false
+    }
+
+    fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, state: &State<'_>) {
+        render_pass.set_bind_group(1, &state.camera_bind_group, &[]);
+
+        for model in &self.heads {
+            //the tails don't use the texture, but every bind group in the layout has to be set
+            render_pass.set_bind_group(0, &model.material.bind_group, &[]);
+            //drawn first, so the heads are on top of them
+            if let Some(ribbons) = &self.ribbons {
+                ribbons.render(render_pass);
+            }
+            render_pass.set_pipeline(&model.material.pipeline);
+            render_pass.draw_mesh_instanced(&*model.mesh, 0..model.mesh.instance_count() as u32);
+        }
+    }
+
+    fn get_camera_type(&self) -> CameraType {
+        CameraType::Orthographic()
+    }
+
+    fn get_camera_position(&self) -> (Point3<f32>, Point3<f32>) {
+        (Point3::new(0.0, 0.0, 0.0), Point3::new(0.0, 0.0, 0.0))
+    }
+
+    fn get_edge_softness(&self, _config: &Configurator) -> Option<f32> {
+        //a soft edge makes the heads glow a bit
+        Some(0.8)
+    }
+}
+
+pub struct StarfieldScreenSaver {
+    stars: Vec<Model>,
+    streaks: Option<Lines>,
+    ratio: f32,
+    rng: StdRng,
+    old_config: Configurator,
+}
+
+impl StarfieldScreenSaver {
+    const FOV: f32 = 60.0;
+    //how far away the stars appear
+    const DEPTH: f32 = 20.0;
+    const STAR_SIZE: f32 = 0.03;
+
+    /// everything the camera can see, from the camera to DEPTH away
+    fn star_domain(ratio: f32) -> BoundingBox<f32> {
+        let height = 2.0 * Self::DEPTH * (Self::FOV.to_radians() / 2.0).tan();
+        BoundingBox::new_with_size(
+            Vector3::new(0.0, 0.0, -Self::DEPTH / 2.0),
+            height * ratio,
+            height,
+            Self::DEPTH,
+            BoundingBoxType::Modulo,
+        )
+    }
+
+    /// expects the star to be at a random position in the domain
+    fn init_star(data: &mut ParticleData, instance: &mut ParticleInstance, config: &Configurator) {
+        instance.scale = Self::STAR_SIZE;
+        instance.color = Color {
+            r: 1.0,
+            g: 1.0,
+            b: 1.0,
+            a: 1.0,
+        };
+        //towards the camera
+        data.velocity = Vector3::new(0.0, 0.0, config.star_speed);
+    }
+
+    /// far away stars are dimmer, so they fade in instead of popping in
+    fn apply_distance(instance: &mut ParticleInstance, domain: &BoundingBox<f32>) {
+        instance.color.a =
+            ((instance.position.z - domain.min_pos.z) / domain.depth()).clamp(0.0, 1.0) as f64;
+    }
+
+    /// adds or removes stars until there are `count` of them
+    fn set_star_count(
+        particle_system: &mut ParticleSystem,
+        count: usize,
+        config: &Configurator,
+        device: &wgpu::Device,
+        rng: &mut impl Rng,
+    ) {
+        let current = particle_system.instances.len();
+        if count > current {
+            particle_system.populate_random(count - current, device, rng);
+            for i in current..count {
+                Self::init_star(
+                    &mut particle_system.particle_data[i],
+                    &mut particle_system.instances.instances[i],
+                    config,
+                );
+            }
+        } else {
+            particle_system.instances.instances.truncate(count);
+            particle_system.particle_data.truncate(count);
+        }
+
+        particle_system.rebuild_instance_buffer(device);
+    }
+}
+
+impl ScreenSaver for StarfieldScreenSaver {
+    fn new(config: Configurator) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            stars: vec![],
+            streaks: None,
+            ratio: 1.0,
+            rng: config.rng(),
+            old_config: config,
+        }
+    }
+
+    fn setup(
+        &mut self,
+        size: Size,
+        config: &Configurator,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        pipeline_layout: &wgpu::PipelineLayout,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        sample_count: u32,
+    ) {
+        self.ratio = if size.to_logical::<f32>(1.0).width > 1.0 {
+            size.to_logical::<f32>(1.0).width / size.to_logical::<f32>(1.0).height
+        } else {
+            1.0
+        };
+
+        let circle_texture = include_bytes!("resources/textures/circle16.png");
+        let diffuse_texture = texture::Texture::from_bytes(
+            device,
+            queue,
+            circle_texture,
+            "circle16.png",
+            config.texture_quality,
+        )
+        .unwrap();
+
+        let mut particle_system = ParticleSystem::create_billboard(
+            1.0,
+            1.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            ParticleSystemData::new(Self::star_domain(self.ratio)),
+            device,
+        );
+
+        let shader = wgpu::ShaderModuleDescriptor {
+            label: Some("Starfield Shader"),
+            source: shaders::get(shaders::ShaderType::ParticleShader),
+        };
+
+        let material = Material::new(
+            diffuse_texture,
+            device,
+            layout,
+            create_render_pipeline(
+                device,
+                pipeline_layout,
+                color_format,
+                depth_format,
+                sample_count,
+                &[model::ModelVertex::desc(), ParticleInstanceRaw::desc()],
+                shader,
+            ),
+        );
+
+        Self::set_star_count(
+            &mut particle_system,
+            config.star_count,
+            config,
+            device,
+            &mut self.rng,
+        );
+
+        self.stars.push(Model {
+            mesh: Box::new(particle_system),
+            material,
+        });
+        self.streaks = Some(Lines::new(
+            device,
+            pipeline_layout,
+            color_format,
+            depth_format,
+            sample_count,
+        ));
+    }
+
+    fn update(
+        &mut self,
+        _size: Size,
+        config: &Configurator,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        dt: Duration,
+    ) {
+        if *config != self.old_config {
+            for model in &mut self.stars {
+                //get (ParticleSystem)(Object) idiot
+                if let Some(particle_system) =
+                    model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+                {
+                    if config.star_count != self.old_config.star_count {
+                        Self::set_star_count(
+                            particle_system,
+                            config.star_count,
+                            config,
+                            device,
+                            &mut self.rng,
+                        );
+                    }
+                    if config.star_speed != self.old_config.star_speed {
+                        for data in &mut particle_system.particle_data {
+                            data.velocity = Vector3::new(0.0, 0.0, config.star_speed);
+                        }
+                    }
+                }
+            }
+            self.old_config = config.clone();
+        }
+
+        let step = dt.as_secs_f32() * config.time_scale.signum();
+        let mut streaks = vec![];
+        for model in &mut self.stars {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                let domain = particle_system.particle_system_data.domain;
+                for i in 0..particle_system.instances.len() {
+                    let velocity = particle_system.particle_data[i].velocity;
+                    let instance = &mut particle_system.instances[i];
+                    //the star flew past the camera, so a new one appears far away
+                    if instance.position.z + velocity.z * step > domain.max_pos.z {
+                        instance.position = Vector3 {
+                            z: domain.min_pos.z,
+                            ..domain.random_pos_with(&mut self.rng)
+                        };
+                        instance.previous_position = instance.position;
+                    }
+                    Self::apply_distance(instance, &domain);
+                }
+            }
+
+            model.update(dt, config, queue);
+
+            if config.warp_streak_length > 0.0 {
+                //get (ParticleSystem)(Object) idiot
+                if let Some(particle_system) = model.mesh.as_any().downcast_ref::<ParticleSystem>()
+                {
+                    //a line behind every star along its path, which the perspective turns into a streak from the center
+                    for (instance, data) in particle_system
+                        .instances
+                        .iter()
+                        .zip(&particle_system.particle_data)
+                    {
+                        let alpha = instance.color.a as f32;
+                        streaks.push(LineVertex {
+                            position: instance.position.into(),
+                            color: [1.0, 1.0, 1.0, alpha],
+                        });
+                        streaks.push(LineVertex {
+                            position: (instance.position
+                                - data.velocity * config.warp_streak_length)
+                                .into(),
+                            color: [1.0, 1.0, 1.0, 0.0],
+                        });
+                    }
+                }
+            }
+        }
+
+        if let Some(lines) = &mut self.streaks {
+            if streaks.is_empty() {
+                lines.clear();
+            } else {
+                lines.set_lines(device, queue, &streaks);
+            }
+        }
+    }
+
+    fn resize(&mut self, _old_ratio: f32, new_ratio: f32) {
+        self.ratio = new_ratio;
+        for model in &mut self.stars {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                particle_system.resize_domain(
+                    Self::star_domain(new_ratio),
+                    self.old_config.resize_behavior,
+                );
+            }
+        }
+    }
+
+    fn get_background_color(&self) -> wgpu::Color {
+        wgpu::Color {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            a: 1.0,
+        }
+    }
+
+    fn handle_input(&mut self, _position: [f32; 2], _id: u64, _active: bool) -> bool {
+        false
+    }
+
+    fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, state: &State<'_>) {
+        render_pass.set_bind_group(1, &state.camera_bind_group, &[]);
+
+        for model in &self.stars {
+            render_pass.set_pipeline(&model.material.pipeline);
+            render_pass.set_bind_group(0, &model.material.bind_group, &[]);
+            render_pass.draw_mesh_instanced(&*model.mesh, 0..model.mesh.instance_count() as u32);
+        }
+
+        if let Some(streaks) = &self.streaks {
+            streaks.render(render_pass);
+        }
+    }
+
+    fn get_camera_type(&self) -> CameraType {
+        CameraType::Perspective(Self::FOV)
+    }
+
+    fn get_camera_position(&self) -> (Point3<f32>, Point3<f32>) {
+        (Point3::new(0.0, 0.0, 0.0), Point3::new(0.0, 0.0, -1.0))
+    }
+
+    fn interpolate(&mut self, alpha: f32, queue: &wgpu::Queue) {
+        for model in &mut self.stars {
+            model.interpolate(alpha, queue);
+        }
+    }
+
+    fn get_edge_softness(&self, _config: &Configurator) -> Option<f32> {
+        Some(0.5)
+    }
+}
+
+pub struct MatrixScreenSaver {
+    glyphs: Vec<Model>,
+    //set when the columns have to be laid out again
+    needs_layout: bool,
+    ratio: f32,
+    rng: StdRng,
+    old_config: Configurator,
+}
+
+impl MatrixScreenSaver {
+    const GLYPH_HEIGHT: f32 = 0.06;
+    const GLYPH_ASPECT: f32 = glyphs::GLYPH_WIDTH as f32 / glyphs::GLYPH_HEIGHT as f32;
+    const MIN_STREAM_LENGTH: usize = 6;
+    const MAX_STREAM_LENGTH: usize = 30;
+    //how many times per second a glyph changes into a different one, on average
+    const GLYPH_CHANGE_RATE: f32 = 0.5;
+
+    /// the screen, plus enough space above it for the longest stream to be hidden before it wraps around
+    fn matrix_domain(ratio: f32) -> BoundingBox<f32> {
+        let margin = Self::MAX_STREAM_LENGTH as f32 * Self::GLYPH_HEIGHT;
+        BoundingBox::new_with_size(
+            Vector3::new(0.0, -margin / 2.0, 0.0),
+            2.0 * ratio,
+            2.0 + margin,
+            0.0,
+            BoundingBoxType::Modulo,
+        )
+    }
+
+    fn random_glyph(instance: &mut ParticleInstance, rng: &mut impl Rng) {
+        let (tex_offset, tex_scale) = glyphs::matrix_glyph_tex_coords(
+            (rng.gen::<f32>() * glyphs::MATRIX_GLYPH_COUNT as f32) as usize,
+        );
+        instance.tex_offset = tex_offset;
+        instance.tex_scale = tex_scale;
+    }
+
+    /// fills some of the columns with streams of glyphs, each one falling at its own speed
+    fn layout_columns(&mut self, config: &Configurator, device: &wgpu::Device) {
+        let color = util::color::color_from_hex(config.glyph_color.to_hex()).unwrap();
+        //the first glyph of every stream is brighter
+        let head_color = Color {
+            r: (color.r + 1.0) / 2.0,
+            g: (color.g + 1.0) / 2.0,
+            b: (color.b + 1.0) / 2.0,
+            a: 1.0,
+        };
+        let glyph_width = Self::GLYPH_HEIGHT * Self::GLYPH_ASPECT;
+        let domain = Self::matrix_domain(self.ratio);
+        let column_count = (domain.width() / glyph_width) as usize;
+
+        //(x, y of the head, length, speed) of every stream
+        let mut streams = vec![];
+        for column in 0..column_count {
+            if self.rng.gen::<f32>() < config.column_density {
+                streams.push((
+                    domain.min_pos.x + (column as f32 + 0.5) * glyph_width,
+                    domain.min_pos.y + self.rng.gen::<f32>() * domain.height(),
+                    Self::MIN_STREAM_LENGTH
+                        + (self.rng.gen::<f32>()
+                            * (Self::MAX_STREAM_LENGTH - Self::MIN_STREAM_LENGTH) as f32)
+                            as usize,
+                    config.fall_speed * (0.5 + self.rng.gen::<f32>()),
+                ));
+            }
+        }
+        let count = streams
+            .iter()
+            .map(|(_, _, length, _)| length)
+            .sum::<usize>();
+
+        for model in &mut self.glyphs {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                particle_system.particle_system_data.domain = domain;
+                if particle_system.instances.len() < count {
+                    particle_system.populate_random(
+                        count - particle_system.instances.len(),
+                        device,
+                        &mut self.rng,
+                    );
+                } else {
+                    particle_system.instances.instances.truncate(count);
+                    particle_system.particle_data.truncate(count);
+                    particle_system.rebuild_instance_buffer(device);
+                }
+
+                let mut i = 0;
+                for (x, head_y, length, speed) in &streams {
+                    for k in 0..*length {
+                        let instance = &mut particle_system.instances[i];
+                        //the camera is mirrored, so positive y is down and the tail is above the head
+                        instance.position = domain.modulo_pos(Vector3::new(
+                            *x,
+                            head_y - k as f32 * Self::GLYPH_HEIGHT,
+                            0.0,
+                        ));
+                        instance.previous_position = instance.position;
+                        instance.scale = Self::GLYPH_HEIGHT;
+                        instance.color = if k == 0 {
+                            head_color
+                        } else {
+                            Color {
+                                a: 1.0 - k as f64 / *length as f64,
+                                ..color
+                            }
+                        };
+                        Self::random_glyph(instance, &mut self.rng);
+                        particle_system.particle_data[i].velocity = Vector3::new(0.0, *speed, 0.0);
+                        i += 1;
+                    }
+                }
+            }
+        }
+        self.needs_layout = false;
+    }
+}
+
+impl ScreenSaver for MatrixScreenSaver {
+    fn new(config: Configurator) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            glyphs: vec![],
+            needs_layout: true,
+            ratio: 1.0,
+            rng: config.rng(),
+            old_config: config,
+        }
+    }
+
+    fn setup(
+        &mut self,
+        size: Size,
+        config: &Configurator,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        pipeline_layout: &wgpu::PipelineLayout,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        sample_count: u32,
+    ) {
+        self.ratio = if size.to_logical::<f32>(1.0).width > 1.0 {
+            size.to_logical::<f32>(1.0).width / size.to_logical::<f32>(1.0).height
+        } else {
+            1.0
+        };
+
+        let atlas = image::DynamicImage::ImageRgba8(glyphs::matrix_atlas());
+        let diffuse_texture =
+            texture::Texture::from_image(device, queue, &atlas, Some("matrix glyph atlas"))
+                .unwrap();
+
+        let particle_system = ParticleSystem::create_billboard(
+            Self::GLYPH_ASPECT,
+            1.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            ParticleSystemData::new(Self::matrix_domain(self.ratio)),
+            device,
+        );
+
+        let shader = wgpu::ShaderModuleDescriptor {
+            label: Some("Matrix Shader"),
+            source: shaders::get(shaders::ShaderType::ParticleShader),
+        };
+
+        let material = Material::new(
+            diffuse_texture,
+            device,
+            layout,
+            create_render_pipeline(
+                device,
+                pipeline_layout,
+                color_format,
+                depth_format,
+                sample_count,
+                &[model::ModelVertex::desc(), ParticleInstanceRaw::desc()],
+                shader,
+            ),
+        );
+
+        self.glyphs.push(Model {
+            mesh: Box::new(particle_system),
+            material,
+        });
+
+        self.layout_columns(config, device);
+    }
+
+    fn update(
+        &mut self,
+        _size: Size,
+        config: &Configurator,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        dt: Duration,
+    ) {
+        if self.needs_layout || *config != self.old_config {
+            self.layout_columns(config, device);
+            self.old_config = config.clone();
+        }
+
+        for model in &mut self.glyphs {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                let chance = Self::GLYPH_CHANGE_RATE * dt.as_secs_f32();
+                for instance in particle_system.instances.iter_mut() {
+                    if self.rng.gen::<f32>() < chance {
+                        Self::random_glyph(instance, &mut self.rng);
+                    }
+                }
+            }
+            model.update(dt, config, queue);
+        }
+    }
+
+    fn resize(&mut self, _old_ratio: f32, new_ratio: f32) {
+        self.ratio = new_ratio;
+        self.needs_layout = true;
+    }
+
+    fn get_background_color(&self) -> wgpu::Color {
+        wgpu::Color {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            a: 1.0,
+        }
+    }
+
+    fn handle_input(&mut self, _position: [f32; 2], _id: u64, _active: bool) -> bool {
+        false
+    }
+
+    fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, state: &State<'_>) {
+        render_pass.set_bind_group(1, &state.camera_bind_group, &[]);
+
+        for model in &self.glyphs {
+            render_pass.set_pipeline(&model.material.pipeline);
+            render_pass.set_bind_group(0, &model.material.bind_group, &[]);
+            render_pass.draw_mesh_instanced(&*model.mesh, 0..model.mesh.instance_count() as u32);
+        }
+    }
+
+    fn get_camera_type(&self) -> CameraType {
+        CameraType::Orthographic()
+    }
+
+    fn get_camera_position(&self) -> (Point3<f32>, Point3<f32>) {
+        (Point3::new(0.0, 0.0, 0.0), Point3::new(0.0, 0.0, 0.0))
+    }
+
+    fn interpolate(&mut self, alpha: f32, queue: &wgpu::Queue) {
+        for model in &mut self.glyphs {
+            model.interpolate(alpha, queue);
+        }
+    }
+}
+
+pub struct FireworksScreenSaver {
+    rockets: Option<Model>,
+    sparks: Option<Model>,
+    //seconds until the next rocket is launched
+    next_launch: f32,
+    ratio: f32,
+    rng: StdRng,
+}
+
+impl FireworksScreenSaver {
+    const ROCKET_SIZE: f32 = 0.015;
+    const SPARK_SIZE: f32 = 0.012;
+    const SPARKS_PER_BURST: usize = 120;
+    //how fast the sparks fly away from the burst, in screen heights per second
+    const BURST_SPEED: f32 = 0.6;
+    const SPARK_LIFETIME: Duration = Duration::from_millis(1800);
+    //how much of their speed the sparks keep after a second
+    const SPARK_DRAG: f32 = 0.3;
+
+    fn domain(ratio: f32) -> BoundingBox<f32> {
+        BoundingBox::new_with_size(
+            Vector3::new(0.0, 0.0, 0.0),
+            2.0 * ratio,
+            2.0,
+            0.0,
+            BoundingBoxType::Ignore,
+        )
+    }
+
+    /// launches a rocket from the bottom of the screen, fast enough to stop somewhere in the upper half
+    fn launch(&mut self, config: &Configurator, device: &wgpu::Device) {
+        let Some(model) = &mut self.rockets else {
+            return;
+        };
+        //get (ParticleSystem)(Object) idiot
+        if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>() {
+            let gravity = config.fireworks_gravity.max(0.01);
+            let x = self.rng.gen_range(-0.8..0.8) * self.ratio;
+            //up is -y, so it starts at 1
+            let rise = 1.0 + self.rng.gen_range(0.2..0.8);
+            let speed = (2.0 * gravity * rise).sqrt();
+            //the rocket bursts when it stops rising, and it already has the color of the burst
+            let color = util::color::random_color_with(&mut self.rng);
+            let new = particle_system.emit(
+                1,
+                Vector3::new(x, 1.0, 0.0),
+                0.0,
+                color,
+                Duration::from_secs_f32(speed / gravity),
+                device,
+                &mut self.rng,
+            );
+            for i in new {
+                particle_system.instances[i].scale = Self::ROCKET_SIZE;
+                particle_system.particle_data[i].velocity =
+                    Vector3::new(self.rng.gen_range(-0.1..0.1), -speed, 0.0);
+            }
+        }
+    }
+
+    /// the sparks of a rocket that reached the top of its flight
+    fn burst(&mut self, rocket: &ParticleInstance, device: &wgpu::Device) {
+        let Some(model) = &mut self.sparks else {
+            return;
+        };
+        //get (ParticleSystem)(Object) idiot
+        if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>() {
+            let new = particle_system.emit(
+                Self::SPARKS_PER_BURST,
+                rocket.position,
+                Self::BURST_SPEED,
+                rocket.color,
+                Self::SPARK_LIFETIME,
+                device,
+                &mut self.rng,
+            );
+            for i in new {
+                particle_system.instances[i].scale = Self::SPARK_SIZE;
+                //so they don't all go out at once
+                particle_system.particle_data[i].lifetime =
+                    Some(Self::SPARK_LIFETIME.mul_f32(self.rng.gen_range(0.6..1.0)));
+            }
+        }
+    }
+
+    /// pulls the particles down, slows them down by `drag` every second and ends the ones that fell off the bottom of the screen
+    fn apply_forces(
+        particle_system: &mut ParticleSystem,
+        config: &Configurator,
+        drag: f32,
+        dt: Duration,
+    ) {
+        //when time runs backwards, the forces have to be undone
+        let dt_secs = dt.as_secs_f32() * config.time_scale.signum();
+        let bottom = particle_system.particle_system_data.domain.max_pos.y;
+        let kept_speed = drag.powf(dt_secs);
+        for (instance, data) in particle_system
+            .instances
+            .iter_mut()
+            .zip(&mut particle_system.particle_data)
+        {
+            data.velocity *= kept_speed;
+            data.velocity.y += config.fireworks_gravity * dt_secs;
+            if instance.position.y - instance.scale > bottom {
+                data.lifetime = Some(Duration::ZERO);
+            }
+        }
+    }
+}
+
+impl ScreenSaver for FireworksScreenSaver {
+    fn new(config: Configurator) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            rockets: None,
+            sparks: None,
+            next_launch: 0.0,
+            ratio: 1.0,
+            rng: config.rng(),
+        }
+    }
+
+    fn setup(
+        &mut self,
+        size: Size,
+        config: &Configurator,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        pipeline_layout: &wgpu::PipelineLayout,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        sample_count: u32,
+    ) {
+        self.ratio = if size.to_logical::<f32>(1.0).width > 1.0 {
+            size.to_logical::<f32>(1.0).width / size.to_logical::<f32>(1.0).height
+        } else {
+            1.0
+        };
+
+        let domain = Self::domain(self.ratio);
+        let create_model = |label: &str, fade_out: bool| {
+            let circle_texture = include_bytes!("resources/textures/circle16.png");
+            let diffuse_texture = texture::Texture::from_bytes(
+                device,
+                queue,
+                circle_texture,
+                "circle16.png",
+                config.texture_quality,
+            )
+            .unwrap();
+
+            let mut particle_system_data = ParticleSystemData::new(domain);
+            particle_system_data.fade_out = fade_out;
+            let particle_system = ParticleSystem::create_billboard(
+                1.0,
+                1.0,
+                Vector3::new(0.0, 0.0, 0.0),
+                particle_system_data,
+                device,
+            );
+
+            let shader = wgpu::ShaderModuleDescriptor {
+                label: Some(label),
+                source: shaders::get(shaders::ShaderType::ParticleShader),
+            };
+
+            let material = Material::new(
+                diffuse_texture,
+                device,
+                layout,
+                create_render_pipeline(
+                    device,
+                    pipeline_layout,
+                    color_format,
+                    depth_format,
+                    sample_count,
+                    &[model::ModelVertex::desc(), ParticleInstanceRaw::desc()],
+                    shader,
+                ),
+            );
+
+            Model {
+                mesh: Box::new(particle_system),
+                material,
+            }
+        };
+
+        //the rockets would be invisible by the time they burst
+        self.rockets = Some(create_model("Rocket Shader", false));
+        self.sparks = Some(create_model("Spark Shader", true));
+    }
+
+    fn update(
+        &mut self,
+        _size: Size,
+        config: &Configurator,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        dt: Duration,
+    ) {
+        if config.fireworks_launch_rate > 0.0 {
+            self.next_launch -= dt.as_secs_f32();
+            while self.next_launch <= 0.0 {
+                self.launch(config, device);
+                self.next_launch += self.rng.gen_range(0.5..1.5) / config.fireworks_launch_rate;
+            }
+        }
+
+        let mut bursts = vec![];
+        if let Some(model) = &mut self.rockets {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                //the ones that ran out of time are at the top of their flight
+                bursts = particle_system.reap();
+                Self::apply_forces(particle_system, config, 1.0, dt);
+            }
+            model.update(dt, config, queue);
+        }
+        for rocket in &bursts {
+            //falling off the screen ends them too, but they shouldn't burst down there
+            if rocket.position.y < 1.0 {
+                self.burst(rocket, device);
+            }
+        }
+
+        if let Some(model) = &mut self.sparks {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                particle_system.retain_alive(device);
+                Self::apply_forces(particle_system, config, Self::SPARK_DRAG, dt);
+            }
+            model.update(dt, config, queue);
+        }
+    }
+
+    fn resize(&mut self, _old_ratio: f32, new_ratio: f32) {
+        self.ratio = new_ratio;
+        for model in self.rockets.iter_mut().chain(&mut self.sparks) {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                //the domain is only used to find the bottom of the screen, so nothing has to be moved
+                particle_system.particle_system_data.domain = Self::domain(new_ratio);
+            }
+        }
+    }
+
+    fn get_background_color(&self) -> wgpu::Color {
+        wgpu::Color {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            a: 1.0,
+        }
+    }
+
+    fn handle_input(&mut self, _position: [f32; 2], _id: u64, _active: bool) -> bool {
+        false
+    }
+
+    fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, state: &State<'_>) {
+        render_pass.set_bind_group(1, &state.camera_bind_group, &[]);
+
+        for model in self.sparks.iter().chain(&self.rockets) {
+            render_pass.set_pipeline(&model.material.pipeline);
+            render_pass.set_bind_group(0, &model.material.bind_group, &[]);
+            render_pass.draw_mesh_instanced(&*model.mesh, 0..model.mesh.instance_count() as u32);
+        }
+    }
+
+    fn get_camera_type(&self) -> CameraType {
+        CameraType::Orthographic()
+    }
+
+    fn get_camera_position(&self) -> (Point3<f32>, Point3<f32>) {
+        (Point3::new(0.0, 0.0, 0.0), Point3::new(0.0, 0.0, 0.0))
+    }
+
+    fn interpolate(&mut self, alpha: f32, queue: &wgpu::Queue) {
+        for model in self.rockets.iter_mut().chain(&mut self.sparks) {
+            model.interpolate(alpha, queue);
+        }
+    }
+
+    fn get_edge_softness(&self, _config: &Configurator) -> Option<f32> {
+        Some(0.6)
+    }
+}
+
+pub struct BoidsScreenSaver {
+    boids: Vec<Model>,
+    //reused between the boids, so the neighbour search doesn't allocate
+    neighbours: Vec<usize>,
+    ratio: f32,
+    rng: StdRng,
+    old_config: Configurator,
+}
+
+impl BoidsScreenSaver {
+    const BOID_SIZE: f32 = 0.04;
+    //how far a boid can see the others, also the size of the regions
+    const VIEW_RADIUS: f32 = 0.15;
+    //the others closer than this are pushed away from
+    const SEPARATION_RADIUS: f32 = 0.05;
+    //how fast the boids can change their velocity, in max speeds per second
+    const STEERING: f32 = 2.0;
+    //they don't stop, even with nobody around to follow
+    const MIN_SPEED: f32 = 0.5;
+
+    fn domain(ratio: f32) -> BoundingBox<f32> {
+        BoundingBox::new_with_size(
+            Vector3::new(0.0, 0.0, 0.0),
+            2.0 * ratio,
+            2.0,
+            0.0,
+            BoundingBoxType::Modulo,
+        )
+    }
+
+    /// adds or removes boids until there are as many as configured. new ones fly in random directions
+    fn set_boid_count(&mut self, config: &Configurator, device: &wgpu::Device) {
+        for model in &mut self.boids {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                let count = config.boid_count;
+                let old_count = particle_system.instances.len();
+                if old_count < count {
+                    particle_system.populate_random(count - old_count, device, &mut self.rng);
+                    for i in old_count..count {
+                        let angle = self.rng.gen::<f32>() * f32::TAU();
+                        let velocity =
+                            Vector3::new(angle.cos(), angle.sin(), 0.0) * config.boid_max_speed;
+                        let instance = &mut particle_system.instances[i];
+                        instance.scale = Self::BOID_SIZE;
+                        instance.face_velocity(velocity);
+                        instance.color = util::color::random_color_in_range(
+                            (0.45, 0.65),
+                            0.6,
+                            1.0,
+                            &mut self.rng,
+                        );
+                        particle_system.particle_data[i].velocity = velocity;
+                    }
+                } else {
+                    particle_system.instances.instances.truncate(count);
+                    particle_system.particle_data.truncate(count);
+                    particle_system.rebuild_instance_buffer(device);
+                }
+            }
+        }
+    }
+
+    /// steers every boid away from the ones that are too close, towards the heading and the center of the ones it sees
+    fn steer(
+        particle_system: &mut ParticleSystem,
+        neighbours: &mut Vec<usize>,
+        config: &Configurator,
+        dt: f32,
+    ) {
+        let regions_x = particle_system.instances.regions_x;
+        let regions_y = particle_system.instances.regions_y;
+        let domain = particle_system.particle_system_data.domain;
+
+        let mut new_velocities = Vec::with_capacity(particle_system.instances.len());
+        for i in 0..particle_system.instances.len() {
+            let position = particle_system.instances[i].position;
+            let velocity = particle_system.particle_data[i].velocity;
+
+            //the same mapping as `rebuild_regions`
+            let x = position.x / domain.width() + 0.5;
+            let y = position.y / domain.height() + 0.5;
+            //the regions don't wrap around, so the boids don't see each other across the edges
+            particle_system.instances.fill_regions_in_range(
+                usize::clamp((x * regions_x as f32) as usize, 0, regions_x - 1),
+                usize::clamp((y * regions_y as f32) as usize, 0, regions_y - 1),
+                1,
+                neighbours,
+            );
+
+            let mut separation = Vector3::new(0.0, 0.0, 0.0);
+            let mut average_velocity = Vector3::new(0.0, 0.0, 0.0);
+            let mut average_position = Vector3::new(0.0, 0.0, 0.0);
+            let mut seen = 0;
+            for &j in neighbours.iter() {
+                if j == i {
+                    continue;
+                }
+                let offset = particle_system.instances[j].position - position;
+                let distance = offset.magnitude();
+                if distance > Self::VIEW_RADIUS || distance == 0.0 {
+                    continue;
+                }
+                seen += 1;
+                average_velocity += particle_system.particle_data[j].velocity;
+                average_position += particle_system.instances[j].position;
+                if distance < Self::SEPARATION_RADIUS {
+                    //pushes harder the closer they are
+                    separation -= offset / distance * (1.0 - distance / Self::SEPARATION_RADIUS);
+                }
+            }
+
+            let mut steering = separation * config.boid_separation;
+            if seen > 0 {
+                average_velocity /= seen as f32;
+                average_position /= seen as f32;
+                //all three are roughly between 0 and 1, so the weights are comparable
+                steering += (average_velocity - velocity) / config.boid_max_speed.max(0.01)
+                    * config.boid_alignment;
+                steering +=
+                    (average_position - position) / Self::VIEW_RADIUS * config.boid_cohesion;
+            }
+
+            let mut new_velocity =
+                velocity + steering * config.boid_max_speed * Self::STEERING * dt;
+            new_velocity.z = 0.0;
+            let speed = new_velocity.magnitude();
+            if speed > config.boid_max_speed {
+                new_velocity *= config.boid_max_speed / speed;
+            } else if speed > 0.0 && speed < config.boid_max_speed * Self::MIN_SPEED {
+                new_velocity *= config.boid_max_speed * Self::MIN_SPEED / speed;
+            }
+            new_velocities.push(new_velocity);
+        }
+
+        for (i, velocity) in new_velocities.into_iter().enumerate() {
+            particle_system.particle_data[i].velocity = velocity;
+            particle_system.instances[i].face_velocity(velocity);
+        }
+    }
+}
+
+impl ScreenSaver for BoidsScreenSaver {
+    fn new(config: Configurator) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            boids: vec![],
+            neighbours: vec![],
+            ratio: 1.0,
+            rng: config.rng(),
+            old_config: config,
+        }
+    }
+
+    fn setup(
+        &mut self,
+        size: Size,
+        config: &Configurator,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        pipeline_layout: &wgpu::PipelineLayout,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        sample_count: u32,
+    ) {
+        self.ratio = if size.to_logical::<f32>(1.0).width > 1.0 {
+            size.to_logical::<f32>(1.0).width / size.to_logical::<f32>(1.0).height
+        } else {
+            1.0
+        };
+
+        let dart = image::DynamicImage::ImageRgba8(glyphs::dart());
+        let diffuse_texture =
+            texture::Texture::from_image(device, queue, &dart, Some("boid dart")).unwrap();
+
+        let particle_system = ParticleSystem::create_billboard(
+            1.0,
+            1.0,
+            Vector3::new(0.0, 0.0, 0.0),
+            ParticleSystemData::new(Self::domain(self.ratio)),
+            device,
+        );
+
+        let shader = wgpu::ShaderModuleDescriptor {
+            label: Some("Boids Shader"),
+            source: shaders::get(shaders::ShaderType::ParticleShader),
+        };
+
+        let material = Material::new(
+            diffuse_texture,
+            device,
+            layout,
+            create_render_pipeline(
+                device,
+                pipeline_layout,
+                color_format,
+                depth_format,
+                sample_count,
+                &[model::ModelVertex::desc(), ParticleInstanceRaw::desc()],
+                shader,
+            ),
+        );
+
+        self.boids.push(Model {
+            mesh: Box::new(particle_system),
+            material,
+        });
+
+        self.set_boid_count(config, device);
+    }
+
+    fn update(
+        &mut self,
+        _size: Size,
+        config: &Configurator,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        dt: Duration,
+    ) {
+        if *config != self.old_config {
+            if config.boid_count != self.old_config.boid_count {
+                self.set_boid_count(config, device);
+            }
+            self.old_config = config.clone();
+        }
+
+        for model in &mut self.boids {
+            //get (ParticleSystem)(Object) idiot
+            if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
+            {
+                particle_system.instances.regions_x =
+                    ((2.0 * self.ratio / Self::VIEW_RADIUS).ceil() as usize).max(1);
+                particle_system.instances.regions_y =
+                    ((2.0 / Self::VIEW_RADIUS).ceil() as usize).max(1);
+                particle_system.instances.bounding_box =
+                    particle_system.particle_system_data.domain;
+                particle_system.instances.rebuild_regions();
+
+                Self::steer(
+                    particle_system,
+                    &mut self.neighbours,
+                    config,
+                    dt.as_secs_f32(),
+                );
+            }
+            model.update(dt, config, queue);
+        }
     }
 
-    fn resize(&mut self, old_ratio: f32, new_ratio: f32) {
-        for model in &mut self.balls {
+    fn resize(&mut self, _old_ratio: f32, new_ratio: f32) {
+        self.ratio = new_ratio;
+        for model in &mut self.boids {
             //get (ParticleSystem)(Object) idiot
             if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
             {
-                for instance in particle_system.instances.iter_mut() {
-                    instance.position.x *= new_ratio / old_ratio;
-                }
-                particle_system.particle_system_data.domain = BoundingBox::new_with_size(
-                    //Vector3::new(0.0, 0.0, 0.55),
-                    Vector3::new(0.0, 0.0, 0.0),
-                    2.0 * new_ratio,
-                    2.0,
-                    //1.00,
-                    0.0,
-                    BoundingBoxType::Bounce,
-                );
+                particle_system
+                    .resize_domain(Self::domain(new_ratio), self.old_config.resize_behavior);
             }
         }
     }
 
     fn get_background_color(&self) -> wgpu::Color {
         wgpu::Color {
-            r: 0.0,
-            g: 0.0,
-            b: 0.0,
+            r: 0.02,
+            g: 0.03,
+            b: 0.06,
             a: 1.0,
         }
     }
 
-    fn handle_input(&mut self, position: [f32; 2], id: u64, enabled: bool) -> bool {
-        let mut id = id;
-        //dumb chromium doing touch IDs in a dumb way
-        if id > 0 {
-            id = (id - 1) % 5 + 1
-        }
-
-        if !self.first_input_handled {
-            self.first_input_handled = true;
-            return false;
-        }
-        if id > 5 {
-            return false;
-        }
-        if !enabled {
-            self.inputs[id as usize] = None;
-            return false;
-        }
-
-        let old_input = self.inputs[id as usize];
-        const BRUSH_SIZE: f32 = 0.15;
-        if let Some(old_input) = old_input {
-            for model in &mut self.balls {
-                //get (ParticleSystem)(Object) idiot
-                if let Some(particle_system) =
-                    model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
-                {
-                    let x: f32 = position[0] / 2.0 + 0.5;
-                    let y: f32 = position[1] / 2.0 + 0.5;
-                    for i in particle_system.instances.get_regions_in_range(
-                        usize::clamp(
-                            (x * particle_system.instances.regions_x as f32) as usize,
-                            0,
-                            particle_system.instances.regions_x - 1,
-                        ),
-                        usize::clamp(
-                            (y * particle_system.instances.regions_y as f32) as usize,
-                            0,
-                            particle_system.instances.regions_y - 1,
-                        ),
-                        (particle_system.instances.regions_y as f32 / 2.0 * BRUSH_SIZE).ceil()
-                            as u32,
-                    ) {
-                        //if particle_system.instances[i].position.distance2(Vector3::new(position[0], position[1], 0.0)) < BRUSH_SIZE * BRUSH_SIZE {
-                        particle_system.particle_data[i]
-                            .velocity
-                            .add_assign(Vector3::new(
-                                position[0] - old_input[0],
-                                position[1] - old_input[1],
-                                0.0,
-                            ));
-                        //}
-                    }
-                }
-            }
-        }
-        self.inputs[id as usize] = Some(position);
+    fn handle_input(&mut self, _position: [f32; 2], _id: u64, _active: bool) -> bool {
         false
     }
 
     fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, state: &State<'_>) {
-        // lib.rmesh.in
-        //render_pass.set_pipeline(&state.render_pipeline);
         render_pass.set_bind_group(1, &state.camera_bind_group, &[]);
 
-        for model in &self.balls {
+        for model in &self.boids {
             render_pass.set_pipeline(&model.material.pipeline);
             render_pass.set_bind_group(0, &model.material.bind_group, &[]);
             render_pass.draw_mesh_instanced(&*model.mesh, 0..model.mesh.instance_count() as u32);
@@ -813,282 +5064,201 @@ impl ScreenSaver for BallScreenSaver {
     fn get_camera_position(&self) -> (Point3<f32>, Point3<f32>) {
         (Point3::new(0.0, 0.0, 0.0), Point3::new(0.0, 0.0, 0.0))
     }
+
+    fn interpolate(&mut self, alpha: f32, queue: &wgpu::Queue) {
+        for model in &mut self.boids {
+            model.interpolate(alpha, queue);
+        }
+    }
 }
 
-pub struct SnowScreenSaver {
-    pub(crate) models: Vec<Model>,
-    old_config: Configurator,
-    touch_pos: [f32; 2],
+#[derive(Debug, Clone, PartialEq, Copy)]
+pub enum PlasmaPalette {
+    Rainbow,
+    Fire,
+    Ocean,
+    Neon,
 }
 
-impl ScreenSaver for SnowScreenSaver {
-    fn new(config: Configurator) -> SnowScreenSaver
+impl ToString for PlasmaPalette {
+    fn to_string(&self) -> String {
+        match self {
+            PlasmaPalette::Rainbow => "rainbow".to_string(),
+            PlasmaPalette::Fire => "fire".to_string(),
+            PlasmaPalette::Ocean => "ocean".to_string(),
+            PlasmaPalette::Neon => "neon".to_string(),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct PlasmaUniform {
+    time: f32,
+    //the index of the palette in the shader
+    palette: u32,
+    _padding: [f32; 2],
+}
+
+/// no particles at all, the whole screen is a single triangle colored by the fragment shader
+pub struct PlasmaScreenSaver {
+    uniform: PlasmaUniform,
+    uniform_buffer: Option<wgpu::Buffer>,
+    bind_group: Option<wgpu::BindGroup>,
+    pipeline: Option<wgpu::RenderPipeline>,
+}
+
+impl ScreenSaver for PlasmaScreenSaver {
+    fn new(config: Configurator) -> Self
     where
         Self: Sized,
     {
         Self {
-            models: vec![],
-            old_config: config,
-            touch_pos: [0.0, 0.0],
+            uniform: PlasmaUniform {
+                time: 0.0,
+                palette: config.plasma_palette as u32,
+                _padding: [0.0; 2],
+            },
+            uniform_buffer: None,
+            bind_group: None,
+            pipeline: None,
         }
     }
 
     fn setup(
         &mut self,
         _size: Size,
-        config: &Configurator,
+        _config: &Configurator,
         device: &wgpu::Device,
-        queue: &wgpu::Queue,
-        layout: &wgpu::BindGroupLayout,
-        pipeline_layout: &wgpu::PipelineLayout,
+        _queue: &wgpu::Queue,
+        _layout: &wgpu::BindGroupLayout,
+        _pipeline_layout: &wgpu::PipelineLayout,
         color_format: wgpu::TextureFormat,
         depth_format: Option<wgpu::TextureFormat>,
+        sample_count: u32,
     ) {
-        let shader = wgpu::ShaderModuleDescriptor {
-            label: Some("Ground1 Shader"),
-            source: shaders::get(shaders::ShaderType::MeshShader),
-        };
-        let pipeline = create_render_pipeline(
-            device,
-            pipeline_layout,
-            color_format,
-            depth_format,
-            &[model::ModelVertex::desc(), ModelInstanceRaw::desc()],
-            shader,
-        );
-
-        //ground defined first so it gets drawn first and doesn't get occluded by the snow
-        let ground1 = include_bytes!("resources/textures/ground1.png");
-        let diffuse_texture =
-            texture::Texture::from_bytes(device, queue, ground1, "ground1.png").unwrap();
-        let billboard = util::mesh::create_billboard(
-            6.0,
-            3.0,
-            Vector3::new(0.0, 0.0, 0.1),
-            diffuse_texture,
-            &device,
-            &layout,
-            pipeline,
-        )
-        .unwrap();
-        self.models.push(billboard);
-
-        let shader = wgpu::ShaderModuleDescriptor {
-            label: Some("Ground2 Shader"),
-            source: shaders::get(shaders::ShaderType::MeshShader),
-        };
-        let pipeline = create_render_pipeline(
-            device,
-            pipeline_layout,
-            color_format,
-            depth_format,
-            &[model::ModelVertex::desc(), ModelInstanceRaw::desc()],
-            shader,
-        );
-
-        let ground2 = include_bytes!("resources/textures/ground2.png");
-        let diffuse_texture =
-            texture::Texture::from_bytes(device, queue, ground2, "ground2.png").unwrap();
-        let billboard = util::mesh::create_billboard(
-            6.0,
-            3.0,
-            Vector3::new(0.0, 0.0, 0.3),
-            diffuse_texture,
-            &device,
-            &layout,
-            pipeline,
-        )
-        .unwrap();
-        self.models.push(billboard);
-
-        let shader = wgpu::ShaderModuleDescriptor {
-            label: Some("Ground3 Shader"),
-            source: shaders::get(shaders::ShaderType::MeshShader),
-        };
-        let pipeline = create_render_pipeline(
-            device,
-            pipeline_layout,
-            color_format,
-            depth_format,
-            &[model::ModelVertex::desc(), ModelInstanceRaw::desc()],
-            shader,
-        );
-        let ground3 = include_bytes!("resources/textures/ground3.png");
-        let diffuse_texture =
-            texture::Texture::from_bytes(device, queue, ground3, "ground3.png").unwrap();
-        let billboard = util::mesh::create_billboard(
-            6.0,
-            3.0,
-            Vector3::new(0.0, 0.0, 0.5),
-            diffuse_texture,
-            &device,
-            &layout,
-            pipeline,
-        )
-        .unwrap();
-        self.models.push(billboard);
-
-        let snow1 = include_bytes!("resources/textures/snow1.png");
-        let snow2 = include_bytes!("resources/textures/snow2.png");
-        let diffuse_textures = [
-            texture::Texture::from_bytes(device, queue, snow1, "snow1.png").unwrap(),
-            texture::Texture::from_bytes(device, queue, snow2, "snow2.png").unwrap(),
-        ];
-        for diffuse_texture in diffuse_textures {
-            let mut snow_particle_system = ParticleSystem::create_billboard(
-                0.03,
-                0.03,
-                Vector3::new(0.0, 0.0, 0.0),
-                ParticleSystemData::new(BoundingBox::new_with_size(
-                    Vector3::new(0.0, 0.0, 0.5),
-                    6.0,
-                    3.0,
-                    1.0,
-                    BoundingBoxType::Modulo,
-                )),
-                device,
-            );
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Plasma Buffer"),
+            contents: bytemuck::cast_slice(&[self.uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
 
-            let shader = wgpu::ShaderModuleDescriptor {
-                label: Some("Snow Shader"),
-                source: shaders::get(shaders::ShaderType::ParticleShader),
-            };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("plasma_bind_group_layout"),
+        });
 
-            let snow_material = Material::new(
-                diffuse_texture,
-                device,
-                layout,
-                create_render_pipeline(
-                    device,
-                    pipeline_layout,
-                    color_format,
-                    depth_format,
-                    &[model::ModelVertex::desc(), ParticleInstanceRaw::desc()],
-                    shader,
-                ),
-            );
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+            label: Some("plasma_bind_group"),
+        });
 
-            snow_particle_system.populate_random(config.snowflake_count, device);
-            for i in 0..snow_particle_system.instances.len() {
-                let particle = &mut snow_particle_system.instances[i];
-                let data = &mut snow_particle_system.particle_data[i];
-                particle.position.z = 1.0 - particle.position.z * particle.position.z;
-                particle.scale = 1.0 - particle.position.z * 0.8;
-                particle.color.a = 1.0 - particle.position.z as f64;
-                data.velocity = Vector3::new(
-                    (random::<f32>() * 0.1 - 0.4) * particle.scale,
-                    (random::<f32>() * 0.1 + 0.5) * particle.scale,
-                    0.0,
-                )
-            }
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Plasma Pipeline Layout"),
+            //without the texture and the camera, the time bind group comes right after the plasma's own
+            bind_group_layouts: &[
+                &bind_group_layout,
+                &crate::TimeUniform::bind_group_layout(device),
+            ],
+            push_constant_ranges: &[],
+        });
 
-            let snow = Model {
-                mesh: Box::new(snow_particle_system),
-                material: snow_material,
-            };
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Plasma Shader"),
+            source: shaders::ShaderType::PlasmaShader.get_source(),
+        });
 
-            self.models.push(snow);
-        }
+        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("Plasma Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Option::from("vs_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Option::from("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                cull_mode: None,
+                ..Default::default()
+            },
+            //covers the whole screen, so it has nothing to be in front of or behind
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
+            multiview: None,
+            cache: None,
+        });
 
-        /*
-        let moon = include_bytes!("resources/textures/moon.png");
-        let diffuse_texture =
-            texture::Texture::from_bytes(&device, &queue, moon, "moon.png").unwrap();
-        let billboard = resource::create_billboard(
-            0.32,
-            0.32,
-            Vector3::new(-1.0, -1.0, 1.0),
-            diffuse_texture,
-            &device,
-            &layout,
-        )
-            .unwrap();
-        self.models.push(billboard);*/
+        self.uniform_buffer = Some(uniform_buffer);
+        self.bind_group = Some(bind_group);
+        self.pipeline = Some(pipeline);
     }
 
-    fn update(
-        &mut self,
-        _size: winit::dpi::Size,
-        config: &Configurator,
-        device: &wgpu::Device,
-        queue: &wgpu::Queue,
-        dt: Duration,
-    ) {
-        if self.old_config != *config {
-            for model in &mut self.models {
-                //get (ParticleSystem)(Object) idiot
-                if let Some(particle_system) =
-                    model.mesh.as_any_mut().downcast_mut::<ParticleSystem>()
-                {
-                    if config.snowflake_count != self.old_config.snowflake_count {
-                        let delta =
-                            config.snowflake_count as i32 - self.old_config.snowflake_count as i32;
-                        if delta > 0 {
-                            particle_system.populate_random(delta.try_into().unwrap(), device);
-                            for i in
-                                self.old_config.snowflake_count..particle_system.instances.len()
-                            {
-                                let particle = &mut particle_system.instances.instances[i];
-                                let data = &mut particle_system.particle_data[i];
-                                particle.position.z =
-                                    1.0 - particle.position.z * particle.position.z;
-                                particle.scale = 1.0 - particle.position.z * 0.8;
-                                particle.color.a = 1.0 - particle.position.z as f64;
-                                data.velocity = Vector3::new(
-                                    (random::<f32>() * 0.1 - 0.4) * particle.scale,
-                                    (random::<f32>() * 0.1 + 0.5) * particle.scale,
-                                    0.0,
-                                )
-                            }
-                        } else {
-                            particle_system
-                                .instances
-                                .instances
-                                .truncate(config.snowflake_count);
-                            particle_system
-                                .particle_data
-                                .truncate(config.snowflake_count);
-                        }
-
-                        model.mesh.rebuild_instance_buffer(device);
-                    }
-                }
-            }
-            self.old_config = *config;
-        }
+    fn simulate(&mut self, _size: Size, config: &Configurator, dt: Duration) {
+        //the plasma has no direction, a negative time scale just plays it backwards
+        self.uniform.time += dt.as_secs_f32() * config.plasma_speed * config.time_scale.signum();
+        self.uniform.palette = config.plasma_palette as u32;
+    }
 
-        for model in &mut self.models {
-            model.update(dt, queue);
+    fn upload(&mut self, _device: &wgpu::Device, queue: &wgpu::Queue) {
+        if let Some(uniform_buffer) = &self.uniform_buffer {
+            queue.write_buffer(uniform_buffer, 0, bytemuck::cast_slice(&[self.uniform]));
         }
     }
 
-    fn resize(&mut self, _old_ratio: f32, _new_ratio: f32) {
-        //no need to do nothin'
-    }
+    fn resize(&mut self, _old_ratio: f32, _new_ratio: f32) {}
 
     fn get_background_color(&self) -> wgpu::Color {
         wgpu::Color {
             r: 0.0,
             g: 0.0,
-            b: 0.01,
+            b: 0.0,
             a: 1.0,
         }
     }
 
-    fn handle_input(&mut self, position: [f32; 2], _id: u64, _enabled: bool) -> bool {
-        self.touch_pos = position;
+    fn handle_input(&mut self, _position: [f32; 2], _id: u64, _active: bool) -> bool {
         false
     }
 
     fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, state: &State<'_>) {
-        // lib.rmesh.in
-        //render_pass.set_pipeline(&state.render_pipeline);
-        render_pass.set_bind_group(1, &state.camera_bind_group, &[]);
-
-        for model in &self.models {
-            render_pass.set_pipeline(&model.material.pipeline);
-            render_pass.set_bind_group(0, &model.material.bind_group, &[]);
-            render_pass.draw_mesh_instanced(&*model.mesh, 0..model.mesh.instance_count() as u32);
+        if let (Some(pipeline), Some(bind_group)) = (&self.pipeline, &self.bind_group) {
+            render_pass.set_pipeline(pipeline);
+            render_pass.set_bind_group(0, bind_group, &[]);
+            render_pass.set_bind_group(1, &state.time_bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
         }
     }
 
@@ -1097,9 +5267,68 @@ impl ScreenSaver for SnowScreenSaver {
     }
 
     fn get_camera_position(&self) -> (Point3<f32>, Point3<f32>) {
-        (
-            Point3::new(0.0, 0.0, 0.0),
-            Point3::new(self.touch_pos[0] / 4.0, self.touch_pos[1] / 4.0, 0.0),
-        )
+        (Point3::new(0.0, 0.0, 0.0), Point3::new(0.0, 0.0, 0.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn palette_takes_every_hex_form() {
+        let palette = CirclePackingScreenSaver::parse_palette("#264653, fff, #ff000080, nope, ");
+        assert_eq!(palette.len(), 3);
+        assert_eq!(palette[1], Color::WHITE);
+        assert_eq!(palette[2].a, 128.0 / 255.0);
+        assert!(CirclePackingScreenSaver::parse_palette("").is_empty());
+    }
+
+    #[test]
+    fn clock_glyphs_stay_put() {
+        let text = "12:34";
+        let glyph_width = 0.5;
+        let positions: Vec<Vector3<f32>> = (0..text.len())
+            .map(|i| {
+                Vector3::new(
+                    ClockScreenSaver::glyph_x(i, text.len(), glyph_width),
+                    0.0,
+                    0.0,
+                )
+            })
+            .collect();
+        //the colon is the middle one, right in the middle of the screen
+        assert_eq!(positions[2].x, 0.0);
+        //so the particle step mustn't think it's broken and move it somewhere random
+        for position in positions {
+            assert!(crate::particle::is_valid_position(position));
+        }
+        assert!(!crate::particle::is_valid_position(Vector3::new(
+            f32::NAN,
+            0.0,
+            0.0
+        )));
+        assert!(!crate::particle::is_valid_position(Vector3::new(
+            0.0,
+            f32::INFINITY,
+            0.0
+        )));
+    }
+
+    #[test]
+    fn snow_domain_follows_the_aspect_ratio() {
+        let depth = 1.0;
+        let square = SnowScreenSaver::snow_domain(depth, 1.0);
+        for ratio in [0.5, 16.0 / 9.0, 21.0 / 9.0] {
+            let domain = SnowScreenSaver::snow_domain(depth, ratio);
+            //the screen is 2 high and 2 * ratio wide, so the width grows with the ratio and the height doesn't
+            assert!((domain.width() - square.width() - 2.0 * (ratio - 1.0)).abs() < 1e-5);
+            assert!((domain.height() - square.height()).abs() < 1e-5);
+            //and it still covers the whole screen, centered on it
+            assert!(domain.min_pos.x < -ratio && domain.max_pos.x > ratio);
+            assert!(domain.min_pos.y < -1.0 && domain.max_pos.y > 1.0);
+            assert!((domain.min_pos.x + domain.max_pos.x).abs() < 1e-5);
+            assert_eq!((domain.min_pos.z, domain.max_pos.z), (0.0, depth));
+        }
     }
 }
@@ -4,6 +4,12 @@ use wgpu::ShaderSource;
 pub enum ShaderType {
     ParticleShader,
     MeshShader,
+    BackgroundShader,
+    LineShader,
+    BallPhysicsShader,
+    TrailShader,
+    PlasmaShader,
+    SphereShader,
 }
 
 impl ShaderType {
@@ -15,6 +21,24 @@ impl ShaderType {
             ShaderType::MeshShader => ShaderSource::Wgsl(Cow::Borrowed(include_str!(
                 "resources/shaders/model_shader.wgsl"
             ))),
+            ShaderType::BackgroundShader => ShaderSource::Wgsl(Cow::Borrowed(include_str!(
+                "resources/shaders/background_shader.wgsl"
+            ))),
+            ShaderType::LineShader => ShaderSource::Wgsl(Cow::Borrowed(include_str!(
+                "resources/shaders/line_shader.wgsl"
+            ))),
+            ShaderType::BallPhysicsShader => ShaderSource::Wgsl(Cow::Borrowed(include_str!(
+                "resources/shaders/ball_physics.wgsl"
+            ))),
+            ShaderType::TrailShader => ShaderSource::Wgsl(Cow::Borrowed(include_str!(
+                "resources/shaders/trail_shader.wgsl"
+            ))),
+            ShaderType::PlasmaShader => ShaderSource::Wgsl(Cow::Borrowed(include_str!(
+                "resources/shaders/plasma_shader.wgsl"
+            ))),
+            ShaderType::SphereShader => ShaderSource::Wgsl(Cow::Borrowed(include_str!(
+                "resources/shaders/sphere_shader.wgsl"
+            ))),
         }
     }
 }
@@ -28,5 +52,23 @@ pub fn get(t: ShaderType) -> ShaderSource<'static> {
         ShaderType::MeshShader => ShaderSource::Wgsl(Cow::Borrowed(include_str!(
             "resources/shaders/model_shader.wgsl"
         ))),
+        ShaderType::BackgroundShader => ShaderSource::Wgsl(Cow::Borrowed(include_str!(
+            "resources/shaders/background_shader.wgsl"
+        ))),
+        ShaderType::LineShader => ShaderSource::Wgsl(Cow::Borrowed(include_str!(
+            "resources/shaders/line_shader.wgsl"
+        ))),
+        ShaderType::BallPhysicsShader => ShaderSource::Wgsl(Cow::Borrowed(include_str!(
+            "resources/shaders/ball_physics.wgsl"
+        ))),
+        ShaderType::TrailShader => ShaderSource::Wgsl(Cow::Borrowed(include_str!(
+            "resources/shaders/trail_shader.wgsl"
+        ))),
+        ShaderType::PlasmaShader => ShaderSource::Wgsl(Cow::Borrowed(include_str!(
+            "resources/shaders/plasma_shader.wgsl"
+        ))),
+        ShaderType::SphereShader => ShaderSource::Wgsl(Cow::Borrowed(include_str!(
+            "resources/shaders/sphere_shader.wgsl"
+        ))),
     }
 }
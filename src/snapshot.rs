@@ -0,0 +1,121 @@
+use crate::model::Model;
+use crate::particle::ParticleSystem;
+use serde::{Deserialize, Serialize};
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs::File;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::{BufReader, BufWriter};
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ParticleSnapshot {
+    pub position: [f32; 3],
+    pub velocity: [f32; 3],
+    pub color: [f64; 4],
+    pub scale: f32,
+    //older snapshots don't have it
+    #[serde(default)]
+    pub mass: Option<f32>,
+}
+
+/// the state of a running screensaver, so a nice arrangement can be saved and loaded again later
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// the config, in the same format as the config file
+    pub config: String,
+    /// the particles of every particle system in the screensaver, in the order they are drawn
+    pub particle_systems: Vec<Vec<ParticleSnapshot>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Snapshot {
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(writer, self)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
+/// saves the particles of every model that is a particle system
+pub(crate) fn capture_particles(models: &[Model]) -> Vec<Vec<ParticleSnapshot>> {
+    models
+        .iter()
+        .filter_map(|model| model.mesh.as_any().downcast_ref::<ParticleSystem>())
+        .map(|particle_system| {
+            particle_system
+                .instances
+                .iter()
+                .zip(particle_system.particle_data.iter())
+                .map(|(instance, data)| ParticleSnapshot {
+                    position: instance.position.into(),
+                    velocity: data.velocity.into(),
+                    color: [
+                        instance.color.r,
+                        instance.color.g,
+                        instance.color.b,
+                        instance.color.a,
+                    ],
+                    scale: instance.scale,
+                    mass: Some(data.mass),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// replaces the particles of every particle system with the saved ones.
+/// if the snapshot has a different number of particle systems, the extra ones are left alone
+pub(crate) fn restore_particles(
+    models: &mut [Model],
+    particle_systems: &[Vec<ParticleSnapshot>],
+    device: &wgpu::Device,
+) {
+    let mut saved = particle_systems.iter();
+    for model in models.iter_mut() {
+        //get (ParticleSystem)(Object) idiot
+        if let Some(particle_system) = model.mesh.as_any_mut().downcast_mut::<ParticleSystem>() {
+            let Some(particles) = saved.next() else {
+                return;
+            };
+
+            if particles.len() > particle_system.instances.len() {
+                particle_system.populate_random(
+                    particles.len() - particle_system.instances.len(),
+                    device,
+                    &mut rand::thread_rng(),
+                );
+            } else {
+                particle_system
+                    .instances
+                    .instances
+                    .truncate(particles.len());
+                particle_system.particle_data.truncate(particles.len());
+            }
+
+            for (i, particle) in particles.iter().enumerate() {
+                let instance = &mut particle_system.instances[i];
+                instance.position = particle.position.into();
+                instance.previous_position = instance.position;
+                instance.color = wgpu::Color {
+                    r: particle.color[0],
+                    g: particle.color[1],
+                    b: particle.color[2],
+                    a: particle.color[3],
+                };
+                instance.scale = particle.scale;
+                particle_system.particle_data[i].velocity = particle.velocity.into();
+                //the balls are as heavy as they are big
+                particle_system.particle_data[i].mass =
+                    particle.mass.unwrap_or(particle.scale * particle.scale);
+            }
+
+            particle_system.rebuild_instance_buffer(device);
+        }
+    }
+}
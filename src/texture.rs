@@ -1,6 +1,52 @@
 use anyhow::*;
 use image::GenericImageView;
 
+/// lower quality textures use less video memory, which matters on weak devices
+#[derive(Debug, Clone, PartialEq, Copy)]
+pub enum TextureQuality {
+    Full,
+    Half,
+    Quarter,
+}
+
+impl ToString for TextureQuality {
+    fn to_string(&self) -> String {
+        match self {
+            TextureQuality::Full => "full".to_string(),
+            TextureQuality::Half => "half".to_string(),
+            TextureQuality::Quarter => "quarter".to_string(),
+        }
+    }
+}
+
+impl TextureQuality {
+    //textures aren't shrunk below this, otherwise small ones (like the circles) lose their shape entirely
+    const MIN_SIZE: u32 = 8;
+
+    pub fn divisor(&self) -> u32 {
+        match self {
+            TextureQuality::Full => 1,
+            TextureQuality::Half => 2,
+            TextureQuality::Quarter => 4,
+        }
+    }
+
+    /// shrinks the image according to the quality
+    pub fn downsample(&self, img: image::DynamicImage) -> image::DynamicImage {
+        let divisor = self.divisor();
+        if divisor == 1 {
+            return img;
+        }
+        let (width, height) = img.dimensions();
+        let new_width = (width / divisor).max(Self::MIN_SIZE.min(width));
+        let new_height = (height / divisor).max(Self::MIN_SIZE.min(height));
+        if new_width == width && new_height == height {
+            return img;
+        }
+        img.resize_exact(new_width, new_height, image::imageops::FilterType::Triangle)
+    }
+}
+
 pub struct Texture {
     #[allow(unused)]
     pub texture: wgpu::Texture,
@@ -14,6 +60,7 @@ impl Texture {
     pub fn create_depth_texture(
         device: &wgpu::Device,
         config: &wgpu::SurfaceConfiguration,
+        sample_count: u32,
         label: &str,
     ) -> Self {
         let size = wgpu::Extent3d {
@@ -26,7 +73,8 @@ impl Texture {
             label: Some(label),
             size,
             mip_level_count: 1,
-            sample_count: 1,
+            //has to match the color target when using msaa
+            sample_count,
             dimension: wgpu::TextureDimension::D2,
             format: Self::DEPTH_FORMAT,
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT // 3.
@@ -62,11 +110,24 @@ impl Texture {
         queue: &wgpu::Queue,
         bytes: &[u8],
         label: &str,
+        quality: TextureQuality,
     ) -> Result<Self> {
-        let img = image::load_from_memory(bytes)?;
+        let img = quality.downsample(image::load_from_memory(bytes)?);
         Self::from_image(device, queue, &img, Some(label))
     }
 
+    /// a 1x1 texture of a single color, for meshes that don't have an image
+    pub fn from_color(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        color: [f32; 4],
+        label: Option<&str>,
+    ) -> Result<Self> {
+        let pixel = image::Rgba(color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8));
+        let img = image::DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(1, 1, pixel));
+        Self::from_image(device, queue, &img, label)
+    }
+
     pub fn from_image(
         device: &wgpu::Device,
         queue: &wgpu::Queue,
@@ -0,0 +1,284 @@
+//! the motion trails: instead of clearing the screen, every frame is drawn over a faded copy of the one before it
+
+use crate::shaders::ShaderType;
+use wgpu::util::DeviceExt;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct TrailUniform {
+    alpha: f32,
+    cutoff: f32,
+    _padding: [f32; 2],
+}
+
+impl TrailUniform {
+    fn new(alpha: f32, cutoff: f32) -> Self {
+        Self {
+            alpha,
+            cutoff,
+            _padding: [0.0; 2],
+        }
+    }
+}
+
+//8 bit colors that are faded a little bit round back to the same value, so the trails would never quite go away
+const FADE_CUTOFF: f32 = 1.5 / 255.0;
+
+/// two textures the size of the screen, one with the previous frame and one the current frame is drawn into
+pub(crate) struct Trails {
+    views: [wgpu::TextureView; 2],
+    texture_bind_groups: [wgpu::BindGroup; 2],
+    //which of the two the current frame is drawn into
+    current: usize,
+    sampler: wgpu::Sampler,
+    fade_buffer: wgpu::Buffer,
+    fade_bind_group: wgpu::BindGroup,
+    //draws the finished frame to the screen as it is
+    copy_bind_group: wgpu::BindGroup,
+    fade_pipeline: wgpu::RenderPipeline,
+    copy_pipeline: wgpu::RenderPipeline,
+}
+
+impl Trails {
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        texture_bind_group_layout: &wgpu::BindGroupLayout,
+        depth_format: Option<wgpu::TextureFormat>,
+        sample_count: u32,
+        fade: f32,
+    ) -> Self {
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let uniform_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("trail_uniform_bind_group_layout"),
+            });
+
+        let uniform_bind_group = |label: &str, uniform: TrailUniform| {
+            let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents: bytemuck::cast_slice(&[uniform]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &uniform_bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }],
+                label: Some(label),
+            });
+            (buffer, bind_group)
+        };
+        let (fade_buffer, fade_bind_group) =
+            uniform_bind_group("Trail Fade Buffer", TrailUniform::new(fade, FADE_CUTOFF));
+        let (_, copy_bind_group) =
+            uniform_bind_group("Trail Copy Buffer", TrailUniform::new(1.0, 0.0));
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Trail Pipeline Layout"),
+            bind_group_layouts: &[texture_bind_group_layout, &uniform_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Trail Shader"),
+            source: ShaderType::TrailShader.get_source(),
+        });
+
+        let create_pipeline = |label: &str,
+                               blend: wgpu::BlendState,
+                               depth_format: Option<wgpu::TextureFormat>,
+                               sample_count: u32| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Option::from("vs_main"),
+                    buffers: &[],
+                    compilation_options: Default::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Option::from("fs_main"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: config.format,
+                        blend: Some(blend),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: Default::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    cull_mode: None,
+                    ..Default::default()
+                },
+                //like the background, the old frame is drawn first and must never hide anything
+                depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                    format,
+                    depth_write_enabled: false,
+                    depth_compare: wgpu::CompareFunction::Always,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState {
+                    count: sample_count,
+                    ..Default::default()
+                },
+                multiview: None,
+                cache: None,
+            })
+        };
+        let fade_pipeline = create_pipeline(
+            "Trail Fade Pipeline",
+            wgpu::BlendState::ALPHA_BLENDING,
+            depth_format,
+            sample_count,
+        );
+        //the frame already has the right alpha, blending it again would darken see-through windows.
+        //it's drawn straight to the screen in a pass of its own, without msaa or a depth buffer
+        let copy_pipeline =
+            create_pipeline("Trail Copy Pipeline", wgpu::BlendState::REPLACE, None, 1);
+
+        let (views, texture_bind_groups) =
+            Self::create_frames(device, config, texture_bind_group_layout, &sampler);
+
+        Self {
+            views,
+            texture_bind_groups,
+            current: 0,
+            sampler,
+            fade_buffer,
+            fade_bind_group,
+            copy_bind_group,
+            fade_pipeline,
+            copy_pipeline,
+        }
+    }
+
+    fn create_frames(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        texture_bind_group_layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+    ) -> ([wgpu::TextureView; 2], [wgpu::BindGroup; 2]) {
+        let create_frame = |label: &str| {
+            let texture = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width: config.width.max(1),
+                    height: config.height.max(1),
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: config.format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            });
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: texture_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(sampler),
+                    },
+                ],
+                label: Some(label),
+            });
+            (view, bind_group)
+        };
+        let (view_a, bind_group_a) = create_frame("trail_frame_a");
+        let (view_b, bind_group_b) = create_frame("trail_frame_b");
+        ([view_a, view_b], [bind_group_a, bind_group_b])
+    }
+
+    /// the frames have to be the size of the screen. the trails start over afterwards
+    pub(crate) fn resize(
+        &mut self,
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        texture_bind_group_layout: &wgpu::BindGroupLayout,
+    ) {
+        (self.views, self.texture_bind_groups) =
+            Self::create_frames(device, config, texture_bind_group_layout, &self.sampler);
+    }
+
+    /// how much of the previous frame is left, 0 to 1
+    pub(crate) fn set_fade(&self, queue: &wgpu::Queue, fade: f32) {
+        queue.write_buffer(
+            &self.fade_buffer,
+            0,
+            bytemuck::cast_slice(&[TrailUniform::new(fade, FADE_CUTOFF)]),
+        );
+    }
+
+    /// the texture the current frame is drawn into, instead of the screen
+    pub(crate) fn target(&self) -> &wgpu::TextureView {
+        &self.views[self.current]
+    }
+
+    /// draws the faded previous frame over whatever was drawn so far
+    pub(crate) fn render_previous<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.fade_pipeline);
+        render_pass.set_bind_group(0, &self.texture_bind_groups[1 - self.current], &[]);
+        render_pass.set_bind_group(1, &self.fade_bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    /// copies the finished frame to the screen, and keeps it around as the previous frame
+    pub(crate) fn finish_frame(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+    ) {
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Trail Copy Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+            render_pass.set_pipeline(&self.copy_pipeline);
+            render_pass.set_bind_group(0, &self.texture_bind_groups[self.current], &[]);
+            render_pass.set_bind_group(1, &self.copy_bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+        self.current = 1 - self.current;
+    }
+}
@@ -1,6 +1,8 @@
 #![allow(dead_code)]
 
 pub mod color;
+pub mod glyphs;
+pub mod lines;
 pub mod mesh;
 pub mod model;
 pub mod pos;
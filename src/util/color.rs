@@ -1,13 +1,55 @@
 use angular_units::{Angle, Turns};
 use prisma::{Hsv, Rgb};
+use rand::Rng;
 use wgpu::Color;
 
+/// which color space colors get blended in
+#[derive(Debug, Clone, PartialEq, Copy)]
+pub enum ColorSpace {
+    Rgb,
+    Hsv,
+}
+
+impl ToString for ColorSpace {
+    fn to_string(&self) -> String {
+        match self {
+            ColorSpace::Rgb => "rgb".to_string(),
+            ColorSpace::Hsv => "hsv".to_string(),
+        }
+    }
+}
+
 pub fn compare_colors_ignoring_alpha(left: Color, right: Color) -> bool {
     left.r == right.r && left.g == right.g && left.b == right.b
 }
 
 pub fn random_color() -> Color {
-    let hsv = Hsv::new(angular_units::Turns(rand::random::<f32>()), 1.0, 1.0);
+    random_color_with(&mut rand::thread_rng())
+}
+
+/// a random fully saturated color from the given generator
+pub fn random_color_with(rng: &mut impl Rng) -> Color {
+    random_color_in_range((0.0, 1.0), 1.0, 1.0, rng)
+}
+
+/// a random color with a hue between the two ends of `hue_range` (in turns).
+/// if the start is bigger than the end, the range wraps around through red
+pub fn random_color_in_range(
+    hue_range: (f32, f32),
+    saturation: f32,
+    value: f32,
+    rng: &mut impl Rng,
+) -> Color {
+    let mut span = hue_range.1 - hue_range.0;
+    if span < 0.0 {
+        span += 1.0;
+    }
+    let hue = (hue_range.0 + rng.gen::<f32>() * span).rem_euclid(1.0);
+    let hsv = Hsv::new(
+        angular_units::Turns(hue),
+        saturation.clamp(0.0, 1.0) as f64,
+        value.clamp(0.0, 1.0) as f64,
+    );
     let rgb = Rgb::from(hsv);
     Color {
         r: rgb.red(),
@@ -17,25 +59,31 @@ pub fn random_color() -> Color {
     }
 }
 
-pub fn random_distinct_color(other_color: Color) -> Color {
+/// how many random hues are tried before giving up and taking the most distinct one
+const MAX_DISTINCT_COLOR_ATTEMPTS: usize = 32;
+
+/// a random fully saturated color with a hue at least `min_delta` (in turns, at most 0.5) away from `other_color`'s
+pub fn random_distinct_color(other_color: Color, min_delta: f64, rng: &mut impl Rng) -> Color {
     let old_rgb = Rgb::new(other_color.r, other_color.g, other_color.b);
     let old_hsv: Hsv<f64, Turns<f64>> = Hsv::from(old_rgb);
 
-    let mut new_hsv = old_hsv.clone();
+    //the distance around the hue wheel
+    let hue_delta = |hue: f64| {
+        let delta = (old_hsv.hue().scalar() - hue).rem_euclid(1.0);
+        delta.min(1.0 - delta)
+    };
 
-    loop {
-        new_hsv = Hsv::new(angular_units::Turns(rand::random::<f64>()), 1.0, 1.0);
-        let mut delta = old_hsv.hue().scalar() - new_hsv.hue().scalar();
-        if delta > 0.5 {
-            delta -= 1.0
-        } else if delta < -0.5 {
-            delta += 1.0
-        }
-        if delta > 0.2 {
+    let mut best_hue = rng.gen::<f64>();
+    for _ in 1..MAX_DISTINCT_COLOR_ATTEMPTS {
+        if hue_delta(best_hue) >= min_delta {
             break;
         }
+        let hue = rng.gen::<f64>();
+        if hue_delta(hue) > hue_delta(best_hue) {
+            best_hue = hue;
+        }
     }
-    let rgb = Rgb::from(new_hsv);
+    let rgb = Rgb::from(Hsv::new(angular_units::Turns(best_hue), 1.0, 1.0));
     Color {
         r: rgb.red(),
         g: rgb.green(),
@@ -44,16 +92,280 @@ pub fn random_distinct_color(other_color: Color) -> Color {
     }
 }
 
+/// parses #rgb, #rgba, #rrggbb and #rrggbbaa colors
 pub fn color_from_hex(color_hex: String) -> Result<Color, anyhow::Error> {
-    if color_hex.starts_with("#") {
-        let color_hex: String = color_hex[1..7].parse()?;
-        Ok(wgpu::Color {
-            r: i64::from_str_radix(&color_hex[0..2], 16)? as f64 / 255.0,
-            g: i64::from_str_radix(&color_hex[2..4], 16)? as f64 / 255.0,
-            b: i64::from_str_radix(&color_hex[4..6], 16)? as f64 / 255.0,
-            a: 1.0,
-        })
-    } else {
-        Err(anyhow::anyhow!("Invalid color hex: {}", color_hex))
+    let digits = color_hex.strip_prefix('#').unwrap_or(&color_hex);
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(anyhow::anyhow!("Invalid color hex: {}", color_hex));
+    }
+    //expand the short forms, so every channel is two digits
+    let digits: String = match digits.len() {
+        3 | 4 => digits.chars().flat_map(|c| [c, c]).collect(),
+        6 | 8 => digits.to_string(),
+        len => {
+            return Err(anyhow::anyhow!(
+                "Invalid color hex: {} (expected 3, 4, 6 or 8 digits, got {})",
+                color_hex,
+                len
+            ))
+        }
+    };
+    let channel = |i: usize| -> Result<f64, anyhow::Error> {
+        Ok(u8::from_str_radix(&digits[i * 2..i * 2 + 2], 16)? as f64 / 255.0)
+    };
+    Ok(Color {
+        r: channel(0)?,
+        g: channel(1)?,
+        b: channel(2)?,
+        a: if digits.len() == 8 { channel(3)? } else { 1.0 },
+    })
+}
+
+/// blends between two colors. RGB can go through muddy grays, HSV stays vivid and takes the shorter way around the hue wheel
+pub fn lerp_color(a: Color, b: Color, t: f64, space: ColorSpace) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    match space {
+        ColorSpace::Rgb => Color {
+            r: a.r + (b.r - a.r) * t,
+            g: a.g + (b.g - a.g) * t,
+            b: a.b + (b.b - a.b) * t,
+            a: a.a + (b.a - a.a) * t,
+        },
+        ColorSpace::Hsv => {
+            let a_hsv: Hsv<f64, Turns<f64>> = Hsv::from(Rgb::new(a.r, a.g, a.b));
+            let b_hsv: Hsv<f64, Turns<f64>> = Hsv::from(Rgb::new(b.r, b.g, b.b));
+
+            //grays don't have a hue, so use the other color's hue instead of blending through red
+            let a_hue = if a_hsv.saturation() == 0.0 {
+                b_hsv.hue().scalar()
+            } else {
+                a_hsv.hue().scalar()
+            };
+            let b_hue = if b_hsv.saturation() == 0.0 {
+                a_hue
+            } else {
+                b_hsv.hue().scalar()
+            };
+
+            let mut delta = b_hue - a_hue;
+            if delta > 0.5 {
+                delta -= 1.0
+            } else if delta < -0.5 {
+                delta += 1.0
+            }
+
+            let hsv = Hsv::new(
+                Turns((a_hue + delta * t).rem_euclid(1.0)),
+                a_hsv.saturation() + (b_hsv.saturation() - a_hsv.saturation()) * t,
+                a_hsv.value() + (b_hsv.value() - a_hsv.value()) * t,
+            );
+            let rgb = Rgb::from(hsv);
+            Color {
+                r: rgb.red(),
+                g: rgb.green(),
+                b: rgb.blue(),
+                a: a.a + (b.a - a.a) * t,
+            }
+        }
+    }
+}
+
+/// the linear color the gpu works with, so a color from the color picker looks the same on the (srgb) screen
+pub fn linear_from_color32(color: egui::Color32) -> Color {
+    let rgba = egui::Rgba::from(color);
+    Color {
+        r: rgba.r() as f64,
+        g: rgba.g() as f64,
+        b: rgba.b() as f64,
+        a: rgba.a() as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{RngCore, SeedableRng};
+
+    fn hex(color_hex: &str) -> Color {
+        color_from_hex(color_hex.to_string()).unwrap()
+    }
+
+    #[test]
+    fn short_form() {
+        assert_eq!(hex("#f80"), hex("#ff8800"));
+        assert_eq!(
+            hex("#f00"),
+            Color {
+                r: 1.0,
+                g: 0.0,
+                b: 0.0,
+                a: 1.0
+            }
+        );
+    }
+
+    #[test]
+    fn alpha_forms() {
+        assert_eq!(hex("#f008"), hex("#ff000088"));
+        assert_eq!(hex("#ff000080").a, 128.0 / 255.0);
+        assert_eq!(hex("#ff0000").a, 1.0);
+    }
+
+    #[test]
+    fn long_form() {
+        let color = hex("#3366cc");
+        assert_eq!(color.r, 0x33 as f64 / 255.0);
+        assert_eq!(color.g, 0x66 as f64 / 255.0);
+        assert_eq!(color.b, 0xcc as f64 / 255.0);
+    }
+
+    #[test]
+    fn hash_is_optional() {
+        assert_eq!(hex("3366cc"), hex("#3366cc"));
+        assert_eq!(hex("3c6"), hex("#3c6"));
+        assert_eq!(hex("3366CC80"), hex("#3366cc80"));
+    }
+
+    #[test]
+    fn malformed_colors_are_rejected() {
+        for color_hex in [
+            "",
+            "#",
+            "#f",
+            "#ff",
+            "#ff000",
+            "#ff0000f",
+            "#ff0000fff",
+            "#gg0000",
+            "#12345z",
+            "#f 0",
+            "##f00",
+        ] {
+            assert!(
+                color_from_hex(color_hex.to_string()).is_err(),
+                "{:?} was accepted",
+                color_hex
+            );
+        }
+    }
+
+    fn hue(color: Color) -> f64 {
+        let hsv: Hsv<f64, Turns<f64>> = Hsv::from(Rgb::new(color.r, color.g, color.b));
+        hsv.hue().scalar()
+    }
+
+    fn hue_delta(a: f64, b: f64) -> f64 {
+        let delta = (a - b).rem_euclid(1.0);
+        delta.min(1.0 - delta)
+    }
+
+    #[test]
+    fn distinct_colors_are_far_enough_away() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for other in ["#ff0000", "#00ff00", "#3366cc", "#ffff00"] {
+            let other = hex(other);
+            for min_delta in [0.1, 0.2, 0.3] {
+                let color = random_distinct_color(other, min_delta, &mut rng);
+                //a little leeway for the round trip through rgb
+                assert!(
+                    hue_delta(hue(color), hue(other)) >= min_delta - 1e-9,
+                    "{:?} is too close to {:?}",
+                    color,
+                    other
+                );
+            }
+        }
+    }
+
+    fn assert_close(a: Color, b: Color) {
+        for (x, y) in [(a.r, b.r), (a.g, b.g), (a.b, b.b), (a.a, b.a)] {
+            assert!((x - y).abs() < 1e-6, "{:?} isn't {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn rgb_midpoint() {
+        assert_close(
+            lerp_color(hex("#000000"), hex("#ffffff80"), 0.5, ColorSpace::Rgb),
+            Color {
+                r: 0.5,
+                g: 0.5,
+                b: 0.5,
+                a: (1.0 + 128.0 / 255.0) / 2.0,
+            },
+        );
+    }
+
+    #[test]
+    fn hsv_midpoint() {
+        //red to blue goes through magenta, not through the muddy purple rgb gives
+        assert_close(
+            lerp_color(hex("#ff0000"), hex("#0000ff"), 0.5, ColorSpace::Hsv),
+            hex("#ff00ff"),
+        );
+    }
+
+    #[test]
+    fn hsv_wraps_around_the_hue_wheel() {
+        //pink and orange are on either side of red, the other way around would go through cyan
+        let color = lerp_color(hex("#ff0080"), hex("#ff8000"), 0.5, ColorSpace::Hsv);
+        assert_close(color, hex("#ff0000"));
+        assert_close(
+            lerp_color(hex("#ff8000"), hex("#ff0080"), 0.5, ColorSpace::Hsv),
+            color,
+        );
+    }
+
+    #[test]
+    fn gray_takes_the_other_hue() {
+        let red = hex("#ff0000");
+        for gray in ["#000000", "#808080", "#ffffff"] {
+            for t in [0.25, 0.5, 0.75] {
+                let color = lerp_color(hex(gray), red, t, ColorSpace::Hsv);
+                assert!(
+                    hue_delta(hue(color), hue(red)) < 1e-9,
+                    "{:?} isn't a shade of red",
+                    color
+                );
+                assert_close(color, lerp_color(red, hex(gray), 1.0 - t, ColorSpace::Hsv));
+            }
+        }
+    }
+
+    /// counts how many random numbers were taken
+    struct CountingRng {
+        rng: StdRng,
+        calls: usize,
+    }
+
+    impl RngCore for CountingRng {
+        fn next_u32(&mut self) -> u32 {
+            self.calls += 1;
+            self.rng.next_u32()
+        }
+        fn next_u64(&mut self) -> u64 {
+            self.calls += 1;
+            self.rng.next_u64()
+        }
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            self.calls += 1;
+            self.rng.fill_bytes(dest)
+        }
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+            self.calls += 1;
+            self.rng.try_fill_bytes(dest)
+        }
+    }
+
+    #[test]
+    fn impossible_delta_gives_up() {
+        let mut rng = CountingRng {
+            rng: StdRng::seed_from_u64(1),
+            calls: 0,
+        };
+        //no hue is more than half a turn away
+        random_distinct_color(hex("#ff0000"), 0.75, &mut rng);
+        assert_eq!(rng.calls, MAX_DISTINCT_COLOR_ATTEMPTS);
     }
 }
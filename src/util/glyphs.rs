@@ -0,0 +1,173 @@
+use image::{Rgba, RgbaImage};
+
+pub const GLYPH_WIDTH: u32 = 64;
+pub const GLYPH_HEIGHT: u32 = 96;
+const SEGMENT_THICKNESS: u32 = 10;
+const PADDING: u32 = 8;
+
+/// the characters in the atlas, in order. A and P are for am and pm, M can't be drawn with seven segments
+pub const GLYPHS: &str = "0123456789:AP ";
+
+//    a
+//  f   b
+//    g
+//  e   c
+//    d
+const SEGMENTS: [[bool; 7]; 10] = [
+    //a, b, c, d, e, f, g
+    [true, true, true, true, true, true, false],     //0
+    [false, true, true, false, false, false, false], //1
+    [true, true, false, true, true, false, true],    //2
+    [true, true, true, true, false, false, true],    //3
+    [false, true, true, false, false, true, true],   //4
+    [true, false, true, true, false, true, true],    //5
+    [true, false, true, true, true, true, true],     //6
+    [true, true, true, false, false, false, false],  //7
+    [true, true, true, true, true, true, true],      //8
+    [true, true, true, true, false, true, true],     //9
+];
+
+//extra letter-like shapes for the matrix rain, on top of the digits and their mirror images
+const EXTRA_SEGMENTS: [[bool; 7]; 8] = [
+    //a, b, c, d, e, f, g
+    [true, true, true, false, true, true, true],   //A
+    [true, false, false, true, true, true, false], //C
+    [false, true, true, true, true, false, true],  //d
+    [true, false, false, false, true, true, true], //F
+    [false, true, true, false, true, true, true],  //H
+    [false, false, true, true, true, false, true], //o
+    [true, true, false, false, true, true, true],  //P
+    [false, false, false, true, true, true, true], //t
+];
+
+/// how many glyphs there are in the matrix atlas
+pub const MATRIX_GLYPH_COUNT: usize = SEGMENTS.len() * 2 + EXTRA_SEGMENTS.len();
+
+/// index of a character in the atlas. unknown characters are blank
+pub fn glyph_index(c: char) -> usize {
+    GLYPHS.find(c).unwrap_or(GLYPHS.len() - 1)
+}
+
+/// texture coordinate offset and scale of a character in the atlas
+pub fn glyph_tex_coords(c: char) -> ([f32; 2], [f32; 2]) {
+    let count = GLYPHS.len() as f32;
+    ([glyph_index(c) as f32 / count, 0.0], [1.0 / count, 1.0])
+}
+
+/// texture coordinate offset and scale of a glyph in the matrix atlas
+pub fn matrix_glyph_tex_coords(index: usize) -> ([f32; 2], [f32; 2]) {
+    let count = MATRIX_GLYPH_COUNT as f32;
+    (
+        [(index % MATRIX_GLYPH_COUNT) as f32 / count, 0.0],
+        [1.0 / count, 1.0],
+    )
+}
+
+fn fill_rect(img: &mut RgbaImage, x: u32, y: u32, width: u32, height: u32) {
+    for px in x..(x + width).min(img.width()) {
+        for py in y..(y + height).min(img.height()) {
+            img.put_pixel(px, py, Rgba([255, 255, 255, 255]));
+        }
+    }
+}
+
+/// draws the lit segments of a glyph into the cell starting at `x`
+fn draw_segments(img: &mut RgbaImage, x: u32, segments: [bool; 7]) {
+    let left = PADDING;
+    let right = GLYPH_WIDTH - PADDING - SEGMENT_THICKNESS;
+    let top = PADDING;
+    let middle = (GLYPH_HEIGHT - SEGMENT_THICKNESS) / 2;
+    let bottom = GLYPH_HEIGHT - PADDING - SEGMENT_THICKNESS;
+    let horizontal_length = GLYPH_WIDTH - 2 * PADDING;
+    let vertical_length = middle - top + SEGMENT_THICKNESS;
+
+    #[rustfmt::skip]
+    let rects = [
+        (left, top, horizontal_length, SEGMENT_THICKNESS), //a
+        (right, top, SEGMENT_THICKNESS, vertical_length), //b
+        (right, middle, SEGMENT_THICKNESS, vertical_length), //c
+        (left, bottom, horizontal_length, SEGMENT_THICKNESS), //d
+        (left, middle, SEGMENT_THICKNESS, vertical_length), //e
+        (left, top, SEGMENT_THICKNESS, vertical_length), //f
+        (left, middle, horizontal_length, SEGMENT_THICKNESS), //g
+    ];
+    for (segment, (rx, ry, width, height)) in segments.iter().zip(rects) {
+        if *segment {
+            fill_rect(img, x + rx, ry, width, height);
+        }
+    }
+}
+
+/// generates a white seven-segment font on a transparent background, one glyph per cell
+pub fn seven_segment_atlas() -> RgbaImage {
+    let mut img = RgbaImage::new(GLYPH_WIDTH * GLYPHS.len() as u32, GLYPH_HEIGHT);
+
+    for (i, c) in GLYPHS.chars().enumerate() {
+        let x = i as u32 * GLYPH_WIDTH;
+        match c {
+            '0'..='9' => draw_segments(&mut img, x, SEGMENTS[c as usize - '0' as usize]),
+            //the same letters as in the matrix rain
+            'A' => draw_segments(&mut img, x, EXTRA_SEGMENTS[0]),
+            'P' => draw_segments(&mut img, x, EXTRA_SEGMENTS[6]),
+            ':' => {
+                let dot_x = x + (GLYPH_WIDTH - SEGMENT_THICKNESS) / 2;
+                fill_rect(
+                    &mut img,
+                    dot_x,
+                    GLYPH_HEIGHT / 3 - SEGMENT_THICKNESS / 2,
+                    SEGMENT_THICKNESS,
+                    SEGMENT_THICKNESS,
+                );
+                fill_rect(
+                    &mut img,
+                    dot_x,
+                    GLYPH_HEIGHT * 2 / 3 - SEGMENT_THICKNESS / 2,
+                    SEGMENT_THICKNESS,
+                    SEGMENT_THICKNESS,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    img
+}
+
+/// the digits, their mirror images and a few letters, in the same style as the clock
+pub fn matrix_atlas() -> RgbaImage {
+    let mut img = RgbaImage::new(GLYPH_WIDTH * MATRIX_GLYPH_COUNT as u32, GLYPH_HEIGHT);
+
+    //swaps the left and right segments
+    let mirrored = SEGMENTS.map(|[a, b, c, d, e, f, g]| [a, f, e, d, c, b, g]);
+    for (i, segments) in SEGMENTS
+        .iter()
+        .chain(mirrored.iter())
+        .chain(EXTRA_SEGMENTS.iter())
+        .enumerate()
+    {
+        draw_segments(&mut img, i as u32 * GLYPH_WIDTH, *segments);
+    }
+
+    img
+}
+
+/// a white dart pointing to the right, for things that show which way they're going
+pub fn dart() -> RgbaImage {
+    const SIZE: u32 = 64;
+    let mut img = RgbaImage::new(SIZE, SIZE);
+
+    for px in 0..SIZE {
+        for py in 0..SIZE {
+            //0 at the back, 1 at the tip
+            let u = (px as f32 + 0.5) / SIZE as f32;
+            //-1 to 1, 0 in the middle
+            let v = ((py as f32 + 0.5) / SIZE as f32) * 2.0 - 1.0;
+            //gets narrower towards the tip, with a notch cut out of the back
+            if v.abs() <= 0.75 * (1.0 - u) && u >= 0.4 * v.abs() {
+                img.put_pixel(px, py, Rgba([255, 255, 255, 255]));
+            }
+        }
+    }
+
+    img
+}
@@ -0,0 +1,285 @@
+use crate::model::Vertex;
+use crate::shaders::ShaderType;
+use cgmath::{InnerSpace, Vector2, Vector3};
+use std::ops::Range;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct LineVertex {
+    pub position: [f32; 3],
+    pub color: [f32; 4],
+}
+
+impl Vertex for LineVertex {
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        use std::mem;
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<LineVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+            ],
+        }
+    }
+}
+
+/// a pipeline for flat colored vertices, drawn on top of everything.
+/// the pipeline layout has to have the camera bind group at index 1
+fn create_pipeline(
+    device: &wgpu::Device,
+    pipeline_layout: &wgpu::PipelineLayout,
+    color_format: wgpu::TextureFormat,
+    depth_format: Option<wgpu::TextureFormat>,
+    sample_count: u32,
+    topology: wgpu::PrimitiveTopology,
+    blend: wgpu::BlendState,
+) -> wgpu::RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Line Shader"),
+        source: ShaderType::LineShader.get_source(),
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Line Pipeline"),
+        layout: Some(pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: Option::from("vs_main"),
+            buffers: &[LineVertex::desc()],
+            compilation_options: Default::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: Option::from("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format: color_format,
+                blend: Some(blend),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: Default::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology,
+            cull_mode: None,
+            ..Default::default()
+        },
+        //overlays are drawn last and always on top
+        depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+            format,
+            depth_write_enabled: false,
+            depth_compare: wgpu::CompareFunction::Always,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState {
+            count: sample_count,
+            ..Default::default()
+        },
+        multiview: None,
+        cache: None,
+    })
+}
+
+/// a vertex buffer that is rewritten every frame, and only recreated when it's too small
+struct DynamicVertexBuffer {
+    buffer: wgpu::Buffer,
+    //in vertices
+    capacity: usize,
+}
+
+impl DynamicVertexBuffer {
+    fn new(device: &wgpu::Device) -> Self {
+        let capacity = 2;
+        Self {
+            buffer: Self::create_buffer(device, capacity),
+            capacity,
+        }
+    }
+
+    fn create_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Line Vertex Buffer"),
+            size: (capacity * std::mem::size_of::<LineVertex>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn write(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, vertices: &[LineVertex]) {
+        if vertices.len() > self.capacity {
+            self.capacity = vertices.len().next_power_of_two();
+            self.buffer = Self::create_buffer(device, self.capacity);
+        }
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(vertices));
+    }
+}
+
+/// one pixel wide lines that are rebuilt every frame, for debug overlays.
+/// every two vertices make up a line
+pub struct Lines {
+    pipeline: wgpu::RenderPipeline,
+    vertex_buffer: DynamicVertexBuffer,
+    vertex_count: u32,
+}
+
+impl Lines {
+    /// the pipeline layout has to have the camera bind group at index 1
+    pub fn new(
+        device: &wgpu::Device,
+        pipeline_layout: &wgpu::PipelineLayout,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        sample_count: u32,
+    ) -> Self {
+        Self {
+            pipeline: create_pipeline(
+                device,
+                pipeline_layout,
+                color_format,
+                depth_format,
+                sample_count,
+                wgpu::PrimitiveTopology::LineList,
+                wgpu::BlendState::ALPHA_BLENDING,
+            ),
+            vertex_buffer: DynamicVertexBuffer::new(device),
+            vertex_count: 0,
+        }
+    }
+
+    /// replaces the lines
+    pub fn set_lines(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        vertices: &[LineVertex],
+    ) {
+        self.vertex_buffer.write(device, queue, vertices);
+        self.vertex_count = vertices.len() as u32;
+    }
+
+    pub fn clear(&mut self) {
+        self.vertex_count = 0;
+    }
+
+    /// expects the camera bind group to be set already
+    pub fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if self.vertex_count == 0 {
+            return;
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.buffer.slice(..));
+        render_pass.draw(0..self.vertex_count, 0..1);
+    }
+}
+
+/// a point along a ribbon
+#[derive(Copy, Clone, Debug)]
+pub struct RibbonPoint {
+    pub position: Vector3<f32>,
+    pub width: f32,
+    pub color: [f32; 4],
+}
+
+/// wide, glowing (additively blended) strips along a list of points, rebuilt every frame
+pub struct Ribbons {
+    pipeline: wgpu::RenderPipeline,
+    vertex_buffer: DynamicVertexBuffer,
+    //which vertices belong to which ribbon
+    strips: Vec<Range<u32>>,
+}
+
+impl Ribbons {
+    /// the pipeline layout has to have the camera bind group at index 1
+    pub fn new(
+        device: &wgpu::Device,
+        pipeline_layout: &wgpu::PipelineLayout,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        sample_count: u32,
+    ) -> Self {
+        Self {
+            pipeline: create_pipeline(
+                device,
+                pipeline_layout,
+                color_format,
+                depth_format,
+                sample_count,
+                wgpu::PrimitiveTopology::TriangleStrip,
+                wgpu::BlendState {
+                    color: wgpu::BlendComponent {
+                        src_factor: wgpu::BlendFactor::SrcAlpha,
+                        dst_factor: wgpu::BlendFactor::One,
+                        operation: wgpu::BlendOperation::Add,
+                    },
+                    alpha: wgpu::BlendComponent::OVER,
+                },
+            ),
+            vertex_buffer: DynamicVertexBuffer::new(device),
+            strips: vec![],
+        }
+    }
+
+    /// replaces the ribbons, each one is a list of points from one end to the other
+    pub fn set_ribbons(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        ribbons: &[Vec<RibbonPoint>],
+    ) {
+        let mut vertices = vec![];
+        self.strips.clear();
+        for ribbon in ribbons {
+            let start = vertices.len() as u32;
+            Self::build_strip(ribbon, &mut vertices);
+            self.strips.push(start..vertices.len() as u32);
+        }
+        self.vertex_buffer.write(device, queue, &vertices);
+    }
+
+    /// two vertices per point, on both sides of the line going through the points
+    fn build_strip(points: &[RibbonPoint], vertices: &mut Vec<LineVertex>) {
+        if points.len() < 2 {
+            return;
+        }
+        let mut normal = Vector2::new(0.0, 1.0);
+        for (i, point) in points.iter().enumerate() {
+            let previous = points[i.saturating_sub(1)].position;
+            let next = points[usize::min(i + 1, points.len() - 1)].position;
+            let direction = Vector2::new(next.x - previous.x, next.y - previous.y);
+            //keep the last normal if the points are on top of each other
+            if direction.magnitude2() > 0.0 {
+                let direction = direction.normalize();
+                normal = Vector2::new(-direction.y, direction.x);
+            }
+            let offset = Vector3::new(normal.x, normal.y, 0.0) * point.width / 2.0;
+            vertices.push(LineVertex {
+                position: (point.position + offset).into(),
+                color: point.color,
+            });
+            vertices.push(LineVertex {
+                position: (point.position - offset).into(),
+                color: point.color,
+            });
+        }
+    }
+
+    /// expects the camera bind group to be set already
+    pub fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.buffer.slice(..));
+        for strip in &self.strips {
+            if strip.end - strip.start >= 4 {
+                render_pass.draw(strip.clone(), 0..1);
+            }
+        }
+    }
+}
@@ -2,13 +2,20 @@ use crate::{model, texture};
 use std::io::{BufReader, Cursor};
 
 use cfg_if::cfg_if;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::{Path, PathBuf};
 use wgpu::util::DeviceExt;
 
-#[derive(Debug, Clone, PartialEq, Copy)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum DDDModel {
     Apple,
     Shark,
     KimKitsuragi,
+    /// an .obj, .gltf or .glb file on disk
+    #[cfg(not(target_arch = "wasm32"))]
+    Custom {
+        path: PathBuf,
+    },
 }
 
 impl ToString for DDDModel {
@@ -17,31 +24,131 @@ impl ToString for DDDModel {
             DDDModel::Apple => "apple".to_string(),
             DDDModel::Shark => "shark".to_string(),
             DDDModel::KimKitsuragi => "kim_kitsuragi".to_string(),
+            #[cfg(not(target_arch = "wasm32"))]
+            DDDModel::Custom { path } => path.display().to_string(),
         }
     }
 }
 
 impl DDDModel {
-    pub(crate) fn get(&self) -> (String, Vec<u8>) {
+    pub(crate) const BUILT_IN: [DDDModel; 3] =
+        [DDDModel::Apple, DDDModel::Shark, DDDModel::KimKitsuragi];
+
+    /// the name shown in the config ui
+    pub(crate) fn display_name(&self) -> String {
         match self {
+            DDDModel::Apple => "Apple".to_string(),
+            DDDModel::Shark => "Shark".to_string(),
+            DDDModel::KimKitsuragi => "Kim Kitsuragi".to_string(),
+            #[cfg(not(target_arch = "wasm32"))]
+            DDDModel::Custom { path } => path
+                .file_name()
+                .unwrap_or(path.as_os_str())
+                .to_string_lossy()
+                .to_string(),
+        }
+    }
+
+    /// the built-in models, followed by the custom ones in the models directory
+    pub(crate) fn available() -> Vec<DDDModel> {
+        let mut models = Vec::from(Self::BUILT_IN);
+        #[cfg(not(target_arch = "wasm32"))]
+        models.extend(scan_custom_models());
+        models
+    }
+
+    /// whether the file has an extension a custom model can be loaded from
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn is_model_file(path: &Path) -> bool {
+        matches!(extension(path).as_str(), "obj" | "gltf" | "glb")
+    }
+
+    /// whether the model has to be loaded with Model::load_gltf instead of from obj text
+    pub(crate) fn is_gltf(&self) -> bool {
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            DDDModel::Custom { path } => matches!(extension(path).as_str(), "gltf" | "glb"),
+            _ => false,
+        }
+    }
+
+    /// the obj text and texture of the model. custom models are read from disk, with the texture
+    /// being the .png next to the .obj, if there is one
+    pub(crate) fn get(&self) -> anyhow::Result<(String, Option<Vec<u8>>)> {
+        Ok(match self {
             DDDModel::Apple => (
                 include_str!("../resources/models/apple.obj")
                     .parse()
                     .unwrap(),
-                Vec::from(include_bytes!("../resources/textures/apple.png")),
+                Some(Vec::from(include_bytes!("../resources/textures/apple.png"))),
             ),
             DDDModel::Shark => (
                 include_str!("../resources/models/shark.obj")
                     .parse()
                     .unwrap(),
-                Vec::from(include_bytes!("../resources/textures/shark.png")),
+                Some(Vec::from(include_bytes!("../resources/textures/shark.png"))),
             ),
             DDDModel::KimKitsuragi => (
                 include_str!("../resources/models/kim_kitsuragi.obj")
                     .parse()
                     .unwrap(),
-                Vec::from(include_bytes!("../resources/textures/kim_kitsuragi.png")),
+                Some(Vec::from(include_bytes!(
+                    "../resources/textures/kim_kitsuragi.png"
+                ))),
             ),
-        }
+            #[cfg(not(target_arch = "wasm32"))]
+            DDDModel::Custom { path } => {
+                if extension(path) != "obj" {
+                    anyhow::bail!("{} is not an .obj file", path.display());
+                }
+                let obj_text = std::fs::read_to_string(path)
+                    .map_err(|e| anyhow::anyhow!("failed to read {}: {}", path.display(), e))?;
+                let texture_path = path.with_extension("png");
+                let texture = if texture_path.exists() {
+                    Some(std::fs::read(&texture_path)?)
+                } else {
+                    None
+                };
+                (obj_text, texture)
+            }
+        })
     }
 }
+
+#[cfg(not(target_arch = "wasm32"))]
+fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// where custom models are picked up from
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn custom_models_dir() -> Option<PathBuf> {
+    let mut path = dirs::config_dir()?;
+    path.push("michaels-screensaver");
+    path.push("models");
+    Some(path)
+}
+
+/// every model file in the models directory, sorted by name. a missing directory just means
+/// there are no custom models
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn scan_custom_models() -> Vec<DDDModel> {
+    let Some(entries) = custom_models_dir().and_then(|dir| std::fs::read_dir(dir).ok()) else {
+        return vec![];
+    };
+
+    let mut paths = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && DDDModel::is_model_file(path))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| DDDModel::Custom { path })
+        .collect()
+}
@@ -11,6 +11,10 @@ pub struct InstanceContainer<T: Position2> {
     pub regions_x: usize,
     pub regions_y: usize,
     pub regions: Vec<Vec<usize>>,
+    //the region every instance was put in by the last rebuild, so only the ones that moved have to be sorted again
+    instance_regions: Vec<usize>,
+    //the region counts the regions were built with
+    built_regions: (usize, usize),
     //pub regions_new: Vec<usize>,
     //pub instances_sorted: Vec<usize>,
 }
@@ -27,6 +31,9 @@ impl<T: Position2> InstanceContainer<T> {
             regions_x,
             regions_y,
             regions: vec![vec![]; regions_x * regions_y],
+            instance_regions: vec![],
+            //no instance is in a region yet, so the first rebuild has to sort all of them
+            built_regions: (0, 0),
             //regions_new: vec![0; regions_x * regions_y + 1],
             //instances_sorted: vec![0; instances.len()],
         }
@@ -89,48 +96,111 @@ impl<T: Position2> InstanceContainer<T> {
     }
 
     pub fn get_regions_in_range(&self, x: usize, y: usize, range: u32) -> Vec<usize> {
-        debug_assert!(x < self.regions_x);
-        debug_assert!(y < self.regions_y);
-        let range = range as i32;
         let mut instances = vec![];
-        for x1 in -range..1 + range {
-            for y1 in -range..1 + range {
-                let x2 = x as i32 + x1;
-                let y2 = y as i32 + y1;
-                if (x2 >= 0 && x2 < self.regions_x as i32)
-                    && (y2 >= 0 && y2 < self.regions_y as i32)
-                {
-                    instances.extend(self.get_region(x2 as usize, y2 as usize));
-                }
-            }
-        }
+        self.fill_regions_in_range(x, y, range, &mut instances);
         instances
     }
 
+    /// like `get_regions_in_range`, but reuses `instances` instead of allocating a new vec every time
+    pub fn fill_regions_in_range(
+        &self,
+        x: usize,
+        y: usize,
+        range: u32,
+        instances: &mut Vec<usize>,
+    ) {
+        regions_in_range(
+            &self.regions,
+            self.regions_x,
+            self.regions_y,
+            x,
+            y,
+            range,
+            instances,
+        );
+    }
+
+    /// the index of the region the instance is in
+    fn region_index(&self, instance: &T) -> usize {
+        let x: f32 = instance.x().into()
+            / (2.0 * self.bounding_box.width() / self.bounding_box.height())
+            + 0.5;
+        let y: f32 = instance.y().into() / 2.0 + 0.5;
+        let x = usize::clamp((x * self.regions_x as f32) as usize, 0, self.regions_x - 1);
+        let y = usize::clamp((y * self.regions_y as f32) as usize, 0, self.regions_y - 1);
+        y * self.regions_x + x
+    }
+
+    /// sorts the instances into the regions. only the instances that changed regions are moved,
+    /// unless the region counts or the number of instances changed since the last time
     pub fn rebuild_regions(&mut self) {
-        //self.regions = vec![; self.regions_x * self.regions_y];
-        let len = self.regions.len();
+        //there is nowhere to put the instances (and no region to clamp them to)
+        if self.regions_x == 0 || self.regions_y == 0 {
+            self.regions.clear();
+            self.instance_regions.clear();
+            self.built_regions = (0, 0);
+            return;
+        }
+        if self.built_regions != (self.regions_x, self.regions_y)
+            || self.instance_regions.len() != self.instances.len()
+        {
+            self.sort_all_into_regions();
+            return;
+        }
+        for i in 0..self.instances.len() {
+            let old_region = self.instance_regions[i];
+            let new_region = self.region_index(&self.instances[i]);
+            if old_region == new_region {
+                continue;
+            }
+            let bucket = &mut self.regions[old_region];
+            if let Some(position) = bucket.iter().position(|&index| index == i) {
+                bucket.swap_remove(position);
+            }
+            self.regions[new_region].push(i);
+            self.instance_regions[i] = new_region;
+        }
+    }
 
+    fn sort_all_into_regions(&mut self) {
+        //the old regions keep their capacity, so this only allocates when there are more of them
         self.regions.iter_mut().for_each(|region| {
             region.clear();
         });
-        //self.regions.fill(Vec::with_capacity((self.instances.len() / len) * 2));
-        self.regions.resize(
-            self.regions_x * self.regions_y,
-            Vec::with_capacity((self.instances.len() / len) * 2),
-        );
+        self.regions
+            .resize_with(self.regions_x * self.regions_y, Vec::new);
+        self.instance_regions.clear();
         for i in 0..self.instances.len() {
-            let instance = &mut self.instances[i];
-
-            let x: f32 = instance.x().into()
-                / (2.0 * self.bounding_box.width() / self.bounding_box.height())
-                + 0.5;
-            let y: f32 = instance.y().into() / 2.0 + 0.5;
-            self.get_region_mut(
-                usize::clamp((x * self.regions_x as f32) as usize, 0, self.regions_x - 1),
-                usize::clamp((y * self.regions_y as f32) as usize, 0, self.regions_y - 1),
-            )
-            .push(i);
+            let region = self.region_index(&self.instances[i]);
+            self.regions[region].push(i);
+            self.instance_regions.push(region);
+        }
+        self.built_regions = (self.regions_x, self.regions_y);
+    }
+}
+
+/// the instances in the regions around (x, y), for when the instances themselves are borrowed
+/// somewhere else (like the threads of the ball collisions)
+pub fn regions_in_range(
+    regions: &[Vec<usize>],
+    regions_x: usize,
+    regions_y: usize,
+    x: usize,
+    y: usize,
+    range: u32,
+    instances: &mut Vec<usize>,
+) {
+    debug_assert!(x < regions_x);
+    debug_assert!(y < regions_y);
+    let range = range as i32;
+    instances.clear();
+    for x1 in -range..1 + range {
+        for y1 in -range..1 + range {
+            let x2 = x as i32 + x1;
+            let y2 = y as i32 + y1;
+            if (x2 >= 0 && x2 < regions_x as i32) && (y2 >= 0 && y2 < regions_y as i32) {
+                instances.extend(&regions[y2 as usize * regions_x + x2 as usize]);
+            }
         }
     }
 }
@@ -210,7 +280,34 @@ impl<T: Num + FloatCore> BoundingBox<T> {
     }
 }
 
+impl<T: Num + FloatCore> BoundingBox<T> {
+    /// the smallest box around both boxes, with this box's bound type
+    pub fn union(&self, other: &BoundingBox<T>) -> BoundingBox<T> {
+        BoundingBox::new(
+            Vector3::new(
+                T::min(self.min_pos.x, other.min_pos.x),
+                T::min(self.min_pos.y, other.min_pos.y),
+                T::min(self.min_pos.z, other.min_pos.z),
+            ),
+            Vector3::new(
+                T::max(self.max_pos.x, other.max_pos.x),
+                T::max(self.max_pos.y, other.max_pos.y),
+                T::max(self.max_pos.z, other.max_pos.z),
+            ),
+            self.bound_type,
+        )
+    }
+}
+
 impl<T: Num + From<f32> + Copy> BoundingBox<T> {
+    pub fn center(&self) -> Vector3<T> {
+        Vector3::new(
+            (self.min_pos.x + self.max_pos.x).div(2.0.into()),
+            (self.min_pos.y + self.max_pos.y).div(2.0.into()),
+            (self.min_pos.z + self.max_pos.z).div(2.0.into()),
+        )
+    }
+
     pub fn new_with_size(
         pos: Vector3<T>,
         size_x: T,
@@ -245,35 +342,28 @@ impl<T: Num + From<f32> + std::cmp::PartialOrd + Copy> BoundingBox<T> {
 }
 
 impl<T: Num + From<f32> + AddAssign + SubAssign + FloatCore> BoundingBox<T> {
+    /// wraps the position around to the other side of the box on every axis, no matter how far
+    /// outside it is. a point just past the max ends up just past the min and the other way around
     pub fn modulo_pos(&self, pos: Vector3<T>) -> Vector3<T> {
-        /*
         Vector3::new(
-            //((pos.x - self.min_pos.x) % (self.max_pos.x - self.min_pos.x).abs()) + self.min_pos.x,
-            (pos.x - self.min_pos.x) % (self.max_pos.x - self.min_pos.x) + self.min_pos.x,
-            (pos.y - self.min_pos.y) % (self.max_pos.y - self.min_pos.y) + self.min_pos.y,
-            (pos.z - self.min_pos.z) % (self.max_pos.z - self.min_pos.z) + self.min_pos.z,
+            wrap_axis(pos.x, self.min_pos.x, self.max_pos.x),
+            wrap_axis(pos.y, self.min_pos.y, self.max_pos.y),
+            wrap_axis(pos.z, self.min_pos.z, self.max_pos.z),
         )
-         */
-        let mut new_pos = pos;
-
-        if pos.x < self.min_pos.x {
-            new_pos.x += self.width().into()
-        } else if pos.x > self.max_pos.x {
-            new_pos.x -= self.width().into()
-        }
-        if pos.y < self.min_pos.y {
-            new_pos.y += self.height().into()
-        } else if pos.y > self.max_pos.y {
-            new_pos.y -= self.height().into()
-        }
-        if pos.z < self.min_pos.z {
-            new_pos.z += self.depth().into()
-        } else if pos.z > self.max_pos.z {
-            new_pos.z -= self.depth().into()
-        }
+    }
+}
 
-        new_pos
+fn wrap_axis<T: Num + AddAssign + FloatCore>(pos: T, min: T, max: T) -> T {
+    let size = max - min;
+    //flat axes (like z in 2d screensavers) have nothing to wrap around
+    if size <= T::zero() || (pos >= min && pos <= max) {
+        return pos;
+    }
+    let mut offset = (pos - min) % size;
+    if offset < T::zero() {
+        offset += size;
     }
+    min + offset
 }
 
 impl<
@@ -281,10 +371,82 @@ impl<
     > BoundingBox<T>
 {
     pub fn random_pos(&self) -> Vector3<T> {
+        self.random_pos_with(&mut rand::thread_rng())
+    }
+
+    pub fn random_pos_with(&self, rng: &mut impl Rng) -> Vector3<T> {
         Vector3::new(
-            rand::thread_rng().gen_range(self.min_pos.x..=self.max_pos.x),
-            rand::thread_rng().gen_range(self.min_pos.y..=self.max_pos.y),
-            rand::thread_rng().gen_range(self.min_pos.z..=self.max_pos.z),
+            rng.gen_range(self.min_pos.x..=self.max_pos.x),
+            rng.gen_range(self.min_pos.y..=self.max_pos.y),
+            rng.gen_range(self.min_pos.z..=self.max_pos.z),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Point(f32, f32);
+
+    impl Position2 for Point {
+        fn x(&self) -> impl FloatCore + Into<f32> {
+            self.0
+        }
+        fn y(&self) -> impl FloatCore + Into<f32> {
+            self.1
+        }
+    }
+
+    #[test]
+    fn rebuilding_without_regions_doesnt_panic() {
+        for (regions_x, regions_y) in [(0, 0), (0, 3), (3, 0)] {
+            let mut container = InstanceContainer::new(
+                vec![Point(0.0, 0.0), Point(0.5, -0.5)],
+                regions_x,
+                regions_y,
+            );
+            container.rebuild_regions();
+            assert!(container.regions().is_empty());
+            //and again, now that it thinks it already sorted them
+            container.rebuild_regions();
+        }
+    }
+
+    fn wrapping_box() -> BoundingBox<f32> {
+        BoundingBox::new(
+            Vector3::new(-1.0, -1.0, 0.0),
+            Vector3::new(1.0, 1.0, 0.0),
+            BoundingBoxType::Modulo,
         )
     }
+
+    #[test]
+    fn just_past_max_wraps_to_just_past_min() {
+        let wrapped = wrapping_box().modulo_pos(Vector3::new(1.25, 0.0, 0.0));
+        assert_eq!(wrapped, Vector3::new(-0.75, 0.0, 0.0));
+    }
+
+    #[test]
+    fn just_past_min_wraps_to_just_past_max() {
+        let wrapped = wrapping_box().modulo_pos(Vector3::new(0.0, -1.25, 0.0));
+        assert_eq!(wrapped, Vector3::new(0.0, 0.75, 0.0));
+    }
+
+    #[test]
+    fn wraps_from_several_widths_outside() {
+        let domain = wrapping_box();
+        for widths in [1.0, 3.0, 10.0] {
+            let past_max = domain.modulo_pos(Vector3::new(1.25 + 2.0 * widths, 0.0, 0.0));
+            assert_eq!(past_max.x, -0.75, "{} widths past the max", widths);
+            let past_min = domain.modulo_pos(Vector3::new(-1.25 - 2.0 * widths, 0.0, 0.0));
+            assert_eq!(past_min.x, 0.75, "{} widths past the min", widths);
+        }
+    }
+
+    #[test]
+    fn inside_and_flat_axes_are_left_alone() {
+        let position = Vector3::new(0.5, -1.0, 3.0);
+        assert_eq!(wrapping_box().modulo_pos(position), position);
+    }
 }
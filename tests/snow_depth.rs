@@ -0,0 +1,71 @@
+//! The snow has to be drawn in front of the ground, however deep the flakes are.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use michaels_screensaver::configurator::{Configurator, ScreenSaverType};
+use std::ops::Range;
+
+const WIDTH: u32 = 320;
+const HEIGHT: u32 = 180;
+
+/// renders the first frame of the snow with `snowflake_count` flakes
+fn render_snow(snowflake_count: usize, name: &str) -> image::RgbaImage {
+    let config = Configurator::builder()
+        .screensaver(ScreenSaverType::Snow)
+        .snowflake_count(snowflake_count)
+        .snow_max_accumulation(0)
+        .seed(Some(1))
+        .build()
+        .unwrap();
+    let path = std::env::temp_dir().join(format!("michaels-screensaver-{}.png", name));
+    pollster::block_on(michaels_screensaver::render_to_png(
+        config, WIDTH, HEIGHT, &path,
+    ))
+    .expect("Failed to render the snow");
+    let image = image::open(&path).unwrap().to_rgba8();
+    std::fs::remove_file(&path).ok();
+    image
+}
+
+/// how much of `rows` the (white) flakes cover, from how much whiter they got than without the flakes
+fn coverage(snow: &image::RgbaImage, empty: &image::RgbaImage, rows: Range<u32>) -> f32 {
+    //the target is srgb, the blending happens in linear space
+    let linear = |c: u8| (c as f32 / 255.0).powf(2.2);
+    let mut total = 0.0;
+    let mut count = 0;
+    for y in rows {
+        for x in 0..WIDTH {
+            let before = linear(empty.get_pixel(x, y)[1]);
+            let after = linear(snow.get_pixel(x, y)[1]);
+            total += (after - before) / (1.0 - before).max(0.01);
+            count += 1;
+        }
+    }
+    total / count as f32
+}
+
+#[test]
+fn snow_is_in_front_of_the_ground() {
+    let adapter = pollster::block_on(
+        wgpu::Instance::default().request_adapter(&wgpu::RequestAdapterOptions::default()),
+    );
+    if adapter.is_none() {
+        eprintln!("Skipping, there is no graphics card to render with");
+        return;
+    }
+
+    //there has to be at least one flake
+    let empty = render_snow(1, "empty");
+    let snow = render_snow(100_000, "snow");
+
+    //the top of the screen is the sky, the bottom is covered by the ground
+    let sky = coverage(&snow, &empty, 0..HEIGHT / 10);
+    let ground = coverage(&snow, &empty, HEIGHT - HEIGHT / 10..HEIGHT);
+    assert!(sky > 0.01, "the snow isn't drawn at all");
+    assert!(
+        ground > sky * 0.5,
+        "the ground hides the snow, it covers {} of the sky but only {} of the ground",
+        sky,
+        ground
+    );
+}
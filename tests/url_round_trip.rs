@@ -0,0 +1,68 @@
+//! A link made with "Generate URL" has to open the same scene it was made from.
+
+use michaels_screensaver::configurator::{
+    BallColorMode, ClockFormat, Configurator, DDDModel, PlasmaPalette, ScreenSaverType,
+};
+
+fn assert_round_trip(configurator: Configurator) {
+    let url = configurator.generate_url();
+    let parsed = Configurator::from_url(&url).expect("Failed to read the generated url");
+    assert_eq!(parsed, configurator, "the url was {}", url);
+}
+
+#[test]
+fn default_config() {
+    let configurator = Configurator::default();
+    assert!(configurator.generate_url().ends_with("?screensaver=balls"));
+    assert_round_trip(configurator);
+}
+
+#[test]
+fn every_table() {
+    assert_round_trip(
+        Configurator::builder()
+            .screensaver(ScreenSaverType::Balls)
+            .fullscreen(false)
+            .monitor_index(Some(1))
+            .time_scale(1.5)
+            .seed(Some(42))
+            .max_fps(Some(30))
+            .background_color(Some(egui::Color32::from_rgb(10, 20, 30)))
+            .background_image("my pictures/cat & dog #1.png".to_string())
+            .snowflake_count(1234)
+            .snow_depth(0.3)
+            .ball_count(500)
+            .ball_speed(0.25)
+            .ball_size_max(Some(0.1))
+            .color_mode(BallColorMode::DvdBounce)
+            .color(egui::Color32::from_rgb(255, 0, 128))
+            .gravity(0.3)
+            .target_display_density(12.3)
+            .temperature_max_speed(Some(0.7))
+            .ddd_model(DDDModel::Shark)
+            .model_count(3)
+            .bounce_floor(-0.5)
+            .clock_format(ClockFormat::TwelveHour)
+            .comet_count(7)
+            .plasma_palette(PlasmaPalette::Fire)
+            .build()
+            .unwrap(),
+    );
+}
+
+#[test]
+fn keys_in_more_than_one_table() {
+    //`count` and `speed` are in both [balls] and [comet], each has to end up in its own table
+    assert_round_trip(
+        Configurator::builder()
+            .screensaver(ScreenSaverType::Comet)
+            .comet_count(5)
+            .comet_speed(0.8)
+            .ball_count(200)
+            .ball_speed(0.3)
+            .star_count(100)
+            .boid_count(50)
+            .build()
+            .unwrap(),
+    );
+}